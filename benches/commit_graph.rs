@@ -0,0 +1,94 @@
+//! Compare `--find-deleted --first-parent` with and without a commit-graph
+//!
+//! Run with `cargo bench --bench commit_graph`; requires `git`.
+
+const COMMITS: usize = 50_000;
+const RUNS: usize = 3;
+
+fn main() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    for args in [&["--no-commit-graph"][..], &[]] {
+        let mut best = std::time::Duration::MAX;
+        for _ in 0..RUNS {
+            let start = std::time::Instant::now();
+            let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-dive"))
+                .args(args)
+                .args(["--no-pager", "--find-deleted", "--first-parent"])
+                .arg(format!("--max-commits={}", COMMITS + 10))
+                .arg("deleted.txt")
+                .current_dir(root_path)
+                .output()
+                .unwrap();
+            best = best.min(start.elapsed());
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let name = if args.is_empty() {
+            "commit-graph"
+        } else {
+            "no commit-graph"
+        };
+        println!("{name:>16}: {best:?} (best of {RUNS}, {COMMITS} commits)");
+    }
+
+    root.close().unwrap();
+}
+
+/// A linear history where `deleted.txt` is removed right after the root commit
+fn fixture(root_path: &std::path::Path) {
+    git(root_path, &["init", "--quiet"]);
+
+    let mut import = std::process::Command::new("git")
+        .args(["fast-import", "--quiet"])
+        .current_dir(root_path)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .expect("`git` is required");
+    {
+        let mut stdin = std::io::BufWriter::new(import.stdin.take().unwrap());
+        let commit = |stdin: &mut dyn std::io::Write, i: usize, change: &str| {
+            let message = format!("Commit {i}");
+            write!(
+                stdin,
+                "commit refs/heads/main\n\
+                 committer Test User <test@example.com> {i} +0000\n\
+                 data {}\n{message}\n\
+                 {change}\n",
+                message.len()
+            )
+            .unwrap();
+        };
+        commit(&mut stdin, 0, "M 644 inline deleted.txt\ndata 4\none\n");
+        commit(&mut stdin, 1, "D deleted.txt");
+        for i in 2..COMMITS {
+            let content = format!("{i}\n");
+            commit(
+                &mut stdin,
+                i,
+                &format!(
+                    "M 644 inline counter.txt\ndata {}\n{content}",
+                    content.len()
+                ),
+            );
+        }
+    }
+    assert!(import.wait().unwrap().success());
+
+    git(root_path, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+    git(root_path, &["commit-graph", "write", "--reachable"]);
+}
+
+fn git(root_path: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(root_path)
+        .status()
+        .expect("`git` is required");
+    assert!(status.success());
+}
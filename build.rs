@@ -0,0 +1,291 @@
+//! Embed what `--build-info` and `--diagnostic` report about how the binary was built, and the
+//! licenses `--acknowledgements` reports for the crates built into it
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let manifest_dir = std::path::PathBuf::from(env("CARGO_MANIFEST_DIR"));
+    let commit = git_commit(&manifest_dir).unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=GIT_DIVE_BUILD_COMMIT={commit}");
+    println!("cargo:rustc-env=GIT_DIVE_BUILD_DATE={}", build_date());
+
+    let rustc = std::process::Command::new(env("RUSTC"))
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=GIT_DIVE_BUILD_RUSTC={rustc}");
+    println!("cargo:rustc-env=GIT_DIVE_BUILD_TARGET={}", env("TARGET"));
+
+    let mut features = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+    println!(
+        "cargo:rustc-env=GIT_DIVE_BUILD_FEATURES={}",
+        features.join(",")
+    );
+
+    for (name, file) in [("SYNTAXES", "syntaxes.bin"), ("THEMES", "themes.bin")] {
+        let path = manifest_dir.join("assets").join(file);
+        println!("cargo:rerun-if-changed={}", path.display());
+        let digest = std::fs::read(&path)
+            .map(|bytes| format!("{:016x}", fnv1a(&bytes)))
+            .unwrap_or_else(|_| "unknown".to_owned());
+        println!("cargo:rustc-env=GIT_DIVE_BUILD_{name}={digest}");
+    }
+
+    let out_dir = std::path::PathBuf::from(env("OUT_DIR"));
+    let crates = if features.iter().any(|f| f == "skip-acknowledgements") {
+        None
+    } else {
+        println!(
+            "cargo:rerun-if-changed={}",
+            manifest_dir.join("Cargo.lock").display()
+        );
+        let crates = dependencies(&manifest_dir, &features);
+        if crates.is_none() {
+            println!("cargo:warning=couldn't list dependencies for `--acknowledgements`");
+        }
+        crates
+    };
+    write_acknowledgements(&out_dir, crates.as_deref().unwrap_or_default());
+}
+
+fn env(key: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| panic!("cargo sets `{key}`"))
+}
+
+/// The commit checked out in the source's repo, when built from one
+///
+/// Only a repo of git-dive's own counts, not one the source was unpacked into, like a workspace
+/// vendoring the crate.
+fn git_commit(manifest_dir: &std::path::Path) -> Option<String> {
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(manifest_dir)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).ok())
+            .flatten()
+            .map(|stdout| stdout.trim().to_owned())
+    };
+    let toplevel = git(&["rev-parse", "--show-toplevel"])?;
+    let canonical = |path: &std::path::Path| std::fs::canonicalize(path).ok();
+    if canonical(std::path::Path::new(&toplevel))? != canonical(manifest_dir)? {
+        return None;
+    }
+    let git_dir = manifest_dir.join(git(&["rev-parse", "--git-dir"])?);
+    println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+    if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        println!(
+            "cargo:rerun-if-changed={}",
+            git_dir.join(head_ref).display()
+        );
+        println!(
+            "cargo:rerun-if-changed={}",
+            git_dir.join("packed-refs").display()
+        );
+    }
+    git(&["rev-parse", "HEAD"])
+}
+
+/// `YYYY-MM-DD` in UTC, per `SOURCE_DATE_EPOCH` for reproducible builds
+fn build_date() -> String {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|since| since.as_secs() as i64)
+                .unwrap_or_default()
+        });
+    // From Howard Hinnant's `civil_from_days`
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// A crate built into the binary, from `cargo metadata`
+struct Crate {
+    name: String,
+    version: String,
+    /// An SPDX expression
+    license: Option<String>,
+    /// File names and contents, like `LICENSE-MIT`
+    license_texts: Vec<(String, String)>,
+}
+
+/// The normal dependencies, direct and not, of the package being built with `features`
+fn dependencies(manifest_dir: &std::path::Path, features: &[String]) -> Option<Vec<Crate>> {
+    let features = features
+        .iter()
+        .filter(|feature| *feature != "default")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(",");
+    let output = std::process::Command::new(env("CARGO"))
+        .args(["metadata", "--format-version", "1", "--offline"])
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .args(["--filter-platform", &env("TARGET")])
+        .args(["--no-default-features", "--features", &features])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let resolve = &metadata["resolve"];
+    let root = resolve["root"].as_str()?;
+    let nodes = resolve["nodes"]
+        .as_array()?
+        .iter()
+        .filter_map(|node| Some((node["id"].as_str()?, node)))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    let mut seen = std::collections::BTreeSet::new();
+    let mut queue = vec![root];
+    while let Some(id) = queue.pop() {
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+        for dep in node["deps"].as_array().into_iter().flatten() {
+            // Dev and build dependencies aren't in the binary
+            let normal = dep["dep_kinds"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|kind| kind["kind"].is_null());
+            if let Some(pkg) = dep["pkg"].as_str().filter(|_| normal) {
+                if seen.insert(pkg) {
+                    queue.push(pkg);
+                }
+            }
+        }
+    }
+
+    let mut crates = metadata["packages"]
+        .as_array()?
+        .iter()
+        .filter(|package| package["id"].as_str().is_some_and(|id| seen.contains(id)))
+        .filter_map(|package| {
+            let manifest_path = std::path::Path::new(package["manifest_path"].as_str()?);
+            let dir = manifest_path.parent()?;
+            let license_file = package["license_file"].as_str();
+            Some(Crate {
+                name: package["name"].as_str()?.to_owned(),
+                version: package["version"].as_str()?.to_owned(),
+                license: package["license"].as_str().map(ToOwned::to_owned),
+                license_texts: license_texts(dir, license_file),
+            })
+        })
+        .collect::<Vec<_>>();
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    Some(crates)
+}
+
+/// Files like `LICENSE-MIT`, `COPYING`, and `NOTICE` at the top of a crate
+fn license_texts(dir: &std::path::Path, license_file: Option<&str>) -> Vec<(String, String)> {
+    let mut names = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| {
+            let name = name.to_lowercase();
+            ["license", "licence", "copying", "notice"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .collect::<Vec<_>>();
+    if let Some(license_file) = license_file {
+        if !names.iter().any(|name| name == license_file) {
+            names.push(license_file.to_owned());
+        }
+    }
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let text = std::fs::read_to_string(dir.join(&name)).ok()?;
+            Some((name, text))
+        })
+        .collect()
+}
+
+/// `crates.json` for `--format json` and `crates.md`, a section per license for people
+fn write_acknowledgements(out_dir: &std::path::Path, crates: &[Crate]) {
+    let records = crates
+        .iter()
+        .map(|krate| {
+            serde_json::json!({
+                "name": krate.name,
+                "version": krate.version,
+                "license": krate.license,
+            })
+        })
+        .collect::<Vec<_>>();
+    let records = serde_json::to_string(&records).expect("JSON values always serialize");
+    std::fs::write(out_dir.join("crates.json"), records).expect("`OUT_DIR` is writable");
+
+    let mut by_license = std::collections::BTreeMap::<&str, Vec<&Crate>>::new();
+    for krate in crates {
+        by_license
+            .entry(krate.license.as_deref().unwrap_or("unknown"))
+            .or_default()
+            .push(krate);
+    }
+    let mut text = String::new();
+    for (license, crates) in by_license {
+        text.push_str(&format!("## {license}\n\n"));
+        for krate in &crates {
+            text.push_str(&format!("- {} {}\n", krate.name, krate.version));
+        }
+        // Many crates share a license text word for word
+        let mut shown = std::collections::HashSet::new();
+        for krate in &crates {
+            for (name, license_text) in &krate.license_texts {
+                let license_text = license_text.trim();
+                if !shown.insert(license_text) {
+                    continue;
+                }
+                text.push_str(&format!(
+                    "\n### {} {}: {name}\n\n{license_text}\n",
+                    krate.name, krate.version
+                ));
+            }
+        }
+        text.push('\n');
+    }
+    std::fs::write(out_dir.join("crates.md"), text).expect("`OUT_DIR` is writable");
+}
+
+/// A stable digest, unlike `std`'s hashers, to tell embedded assets apart
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,72 @@
+//! `--acknowledgements`, the licenses of what's built into the binary
+//!
+//! The embedded syntaxes and themes come with their licenses in `assets/`, while the crates'
+//! are gathered by `build.rs` from the dependency tree being built.
+
+use proc_exit::WithCodeResultExt;
+
+use crate::args::Args;
+use crate::args::OutputFormat;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::git_pager::Pager;
+
+/// A section per license for people, or `[{"name", "version", "license"}]` with `--format json`
+pub(crate) fn acknowledgements(config: &mut Config, args: &Args) -> proc_exit::ExitResult {
+    let mut pager = Pager::resolve(config, args)?;
+    let mut pager = pager.start();
+    let pager = pager.as_writer().with_code(exit_code::IO)?;
+    if args.format == OutputFormat::Json {
+        let crates =
+            serde_json::from_str::<Vec<Crate>>(CRATES_JSON).expect("`build.rs` writes valid JSON");
+        let crates = serde_json::to_string_pretty(&crates).with_code(proc_exit::Code::FAILURE)?;
+        let _ = writeln!(pager, "{crates}");
+    } else {
+        let _ = writeln!(pager, "{}", text());
+    }
+    Ok(())
+}
+
+/// The embedded assets' licenses, then the crates'
+pub(crate) fn text() -> String {
+    let crates = if CRATES_MD.is_empty() {
+        "Left out of this build by the `skip-acknowledgements` feature.\n"
+    } else {
+        CRATES_MD
+    };
+    format!(
+        "# Syntaxes and themes\n\n{}\n\n# Crates\n\n{crates}",
+        crate::assets::get_acknowledgements().trim_end()
+    )
+}
+
+/// A crate built into the binary, for compliance tooling
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Crate {
+    name: String,
+    version: String,
+    /// An SPDX expression, `null` when the crate doesn't say
+    license: Option<String>,
+}
+
+const CRATES_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/crates.json"));
+const CRATES_MD: &str = include_str!(concat!(env!("OUT_DIR"), "/crates.md"));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crates_are_listed() {
+        let crates = serde_json::from_str::<Vec<Crate>>(CRATES_JSON).unwrap();
+        if cfg!(feature = "skip-acknowledgements") {
+            assert!(crates.is_empty());
+            return;
+        }
+        let git2 = crates.iter().find(|c| c.name == "git2").unwrap();
+        assert_eq!(git2.license.as_deref(), Some("MIT OR Apache-2.0"));
+        // Only what's in the binary
+        assert!(!crates.iter().any(|c| c.name == "snapbox"));
+        assert!(text().contains("\n## MIT OR Apache-2.0\n"));
+    }
+}
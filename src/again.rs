@@ -0,0 +1,135 @@
+//! Remember the last run's arguments in `$GIT_DIR/dive-last`, to replay with `--again`
+//!
+//! The git dir is per-worktree, so each worktree remembers its own run.  The file is JSON with
+//! a `version`, and files from other versions are ignored rather than misread.
+
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// Bumped whenever `State` changes shape
+const VERSION: u32 = 1;
+
+const FILE_NAME: &str = "dive-last";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct State {
+    version: u32,
+    /// Where git-dive was run from, before `-C`
+    cwd: std::path::PathBuf,
+    /// Without the binary name or profile expansion
+    args: Vec<String>,
+}
+
+/// Just enough of any version of `State` to tell whether the rest can be read
+#[derive(serde::Deserialize)]
+struct Versioned {
+    version: u32,
+}
+
+/// Record a successful run of `argv`, started from `cwd`
+pub(crate) fn remember(
+    repo: &git2::Repository,
+    cwd: &std::path::Path,
+    argv: &[std::ffi::OsString],
+) {
+    let args = argv
+        .iter()
+        .skip(1)
+        .map(|arg| arg.to_str().map(str::to_owned))
+        .collect::<Option<Vec<_>>>();
+    let Some(args) = args else {
+        log::debug!("not remembering arguments that aren't UTF-8");
+        return;
+    };
+    let state = State {
+        version: VERSION,
+        cwd: cwd.to_owned(),
+        args,
+    };
+    let path = repo.path().join(FILE_NAME);
+    let result = serde_json::to_string(&state)
+        .map_err(anyhow::Error::from)
+        .and_then(|state| std::fs::write(&path, state).map_err(anyhow::Error::from));
+    if let Err(err) = result {
+        log::debug!("could not write {}: {err:#}", path.display());
+    }
+}
+
+/// The arguments to replay `argv`'s `--again` with and where to run them from
+///
+/// Options from `argv` take precedence over the remembered ones, per [`crate::profile::merge`].
+pub(crate) fn replay(
+    repo: &git2::Repository,
+    argv: &[std::ffi::OsString],
+) -> Option<(std::path::PathBuf, Vec<std::ffi::OsString>)> {
+    let state = load(&repo.path().join(FILE_NAME))?;
+    let remembered = state
+        .args
+        .into_iter()
+        .map(std::ffi::OsString::from)
+        .collect();
+    let given = argv
+        .iter()
+        .skip(1)
+        .filter(|arg| *arg != "--again")
+        .cloned()
+        .collect();
+    let mut replayed = argv.iter().take(1).cloned().collect::<Vec<_>>();
+    replayed.extend(crate::profile::merge(&[remembered, given]));
+    Some((state.cwd, replayed))
+}
+
+fn load(path: &std::path::Path) -> Option<State> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let version = match serde_json::from_str::<Versioned>(&content) {
+        Ok(versioned) => versioned.version,
+        Err(err) => {
+            log::debug!("ignoring {}: {err}", path.display());
+            return None;
+        }
+    };
+    if version != VERSION {
+        log::debug!(
+            "ignoring {} from version {version} rather than {VERSION}",
+            path.display()
+        );
+        return None;
+    }
+    match serde_json::from_str::<State>(&content) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            log::debug!("ignoring {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+pub(crate) const REMEMBER: DefaultField<bool> =
+    RawField::<bool>::new("dive.remember").default_value(|| true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn other_versions_are_ignored() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let path = root.path().unwrap().join(FILE_NAME);
+
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "cwd": "/repo", "args": ["lib.rs"]}"#,
+        )
+        .unwrap();
+        let state = load(&path).unwrap();
+        assert_eq!(state.args, ["lib.rs"]);
+
+        std::fs::write(&path, r#"{"version": 2, "argv": ["lib.rs"]}"#).unwrap();
+        assert!(load(&path).is_none());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load(&path).is_none());
+
+        root.close().unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(clap::Parser)]
+#[derive(Clone, clap::Parser)]
 #[command(about, author, version)]
 #[command(allow_missing_positional = true)]
 #[command(group = clap::ArgGroup::new("mode").multiple(false).required(true))]
@@ -6,12 +6,23 @@ pub(crate) struct Args {
     #[arg(default_value = "HEAD")]
     pub(crate) rev: String,
 
+    /// Whether `REV` was on the command line rather than defaulted, per [`Args::parse_argv`]
+    #[arg(skip)]
+    pub(crate) rev_given: bool,
+
     #[arg(required = true, group = "mode")]
     pub(crate) file: Option<std::path::PathBuf>,
 
     #[arg(long, value_name = "PATH", group = "mode")]
     pub(crate) dump_config: Option<std::path::PathBuf>,
 
+    /// Show how config `KEY` is resolved: its type, default, and the value from each source
+    ///
+    /// Sources are listed highest priority first, marking the value that is used and any that
+    /// are ignored for not parsing.
+    #[arg(long, value_name = "KEY", group = "mode")]
+    pub(crate) explain: Option<String>,
+
     /// Display all supported languages
     #[arg(long, group = "mode")]
     pub(crate) list_languages: bool,
@@ -20,7 +31,10 @@ pub(crate) struct Args {
     #[arg(long, group = "mode")]
     pub(crate) list_themes: bool,
 
-    /// Display acknowledgements
+    /// Display the licenses of the embedded syntaxes and themes, and of the crates built in
+    ///
+    /// With `--format json`, a list of the crates' names, versions, and SPDX license
+    /// expressions.
     #[arg(long, hide_short_help = true, group = "mode")]
     pub(crate) acknowledgements: bool,
 
@@ -28,6 +42,50 @@ pub(crate) struct Args {
     #[arg(long, hide_short_help = true, group = "mode")]
     pub(crate) diagnostic: bool,
 
+    /// Display the version along with how it was built: commit, date, compiler, and features
+    #[arg(long, hide_short_help = true, group = "mode")]
+    pub(crate) build_info: bool,
+
+    /// Answer `--format json` requests over a unix socket, keeping repositories open
+    ///
+    /// The socket is `$XDG_RUNTIME_DIR/git-dive/daemon.sock`, or `git-dive/daemon.sock` in the
+    /// git dir without `XDG_RUNTIME_DIR`.  Each request is a line of JSON like `{"cwd": DIR,
+    /// "args": [ARG, ...], "rev": REV, "path": FILE}`, answered by a line with the `--format
+    /// json` output or `{"error": MESSAGE, "code": EXIT_CODE}`.  Requests are run with the
+    /// daemon's environment, not the client's.  Stop it with `--daemon-stop`.
+    #[arg(long, hide_short_help = true, group = "mode")]
+    pub(crate) daemon: bool,
+
+    /// Replay the last successful run in this worktree, also the default without arguments
+    ///
+    /// Options given along with it override the remembered ones.  Runs are remembered in
+    /// `$GIT_DIR/dive-last` unless `dive.remember` is `false`.
+    #[arg(long, group = "mode")]
+    pub(crate) again: bool,
+
+    /// Annotate each path read from stdin at `REV`, defaulting to `HEAD`, as JSON lines
+    ///
+    /// Paths are newline-terminated, or NUL-terminated with `-z`.  Each gets a line like
+    /// `--format json`'s files, or `{"path": PATH, "error": MESSAGE, "code": EXIT_CODE}` when
+    /// it fails, without stopping the rest.  Exits non-zero if any failed.
+    #[arg(
+        long,
+        value_name = "REV",
+        num_args = 0..=1,
+        require_equals = true,
+        group = "mode",
+        conflicts_with_all = ["stat", "compare", "changed_only", "contents"]
+    )]
+    pub(crate) stdin_paths: Option<Option<String>>,
+
+    /// With `--stdin-paths`, paths are NUL-terminated
+    #[arg(short = 'z', requires = "stdin_paths")]
+    pub(crate) null: bool,
+
+    /// Stop the `--daemon` listening for this repository
+    #[arg(long, hide_short_help = true, group = "mode")]
+    pub(crate) daemon_stop: bool,
+
     /// Run as if git was started in `PATH` instead of the current working directory.
     ///
     /// When multiple `-C` options are given, each subsequent
@@ -42,6 +100,512 @@ pub(crate) struct Args {
     #[arg(short = 'C', hide = true, value_name = "PATH")]
     pub(crate) current_dir: Option<Vec<std::path::PathBuf>>,
 
+    /// Use the repository at `PATH` rather than searching for one, like `GIT_DIR`
+    ///
+    /// Unless `--work-tree` is given, the current directory is the top of the worktree, like
+    /// git.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) git_dir: Option<std::path::PathBuf>,
+
+    /// Use `PATH` as the top of the worktree, like `GIT_WORK_TREE`
+    #[arg(long, value_name = "PATH")]
+    pub(crate) work_tree: Option<std::path::PathBuf>,
+
+    /// Read `REV` from the first line of `PATH` that isn't blank or a `#` comment
+    ///
+    /// Like a file that release tooling writes the deployed commit to, without needing command
+    /// substitution.
+    #[arg(long, value_name = "PATH", conflicts_with = "rev")]
+    pub(crate) rev_file: Option<std::path::PathBuf>,
+
+    /// Blame `FILE` as of `DATE`, at the last commit on `REV`'s first-parent history from then
+    ///
+    /// Like `2023-06-01`, which counts that whole day, `2023-06-01 14:30 +0200`, or `3 weeks
+    /// ago`, in UTC without an offset.  Commit dates are compared, since they say when a
+    /// commit landed rather than when it was written.  Clocks can be skewed, leaving dates out
+    /// of order, so history is scanned from `REV` and the first commit from `DATE` or before
+    /// wins, like `git rev-list --first-parent --before`.
+    #[arg(long, value_name = "DATE", conflicts_with_all = ["compare", "ours", "theirs", "base"])]
+    pub(crate) at: Option<crate::date::Approxidate>,
+
+    /// Blame `FILE` at `OLD` and at `NEW` and show the two aligned, each line with its commit
+    ///
+    /// Side by side when the terminal is at least `dive.compare-split-width` columns wide
+    /// (default `160`), otherwise unified with `-` and `+` markers.  Unchanged lines that blame
+    /// to the same commit on both sides are dimmed.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        action = clap::ArgAction::Set,
+        conflicts_with_all = ["rev", "rev_file", "stat", "contents", "changed_only"]
+    )]
+    pub(crate) compare: Option<Vec<String>>,
+
+    /// Only blame the lines of `REV` that uncommitted changes replace, delete, or insert next to
+    ///
+    /// One `PATH:LINE: HASH AUTHOR DATE SUMMARY` entry per line, numbered as of `REV`, for a
+    /// pre-commit hook to show what a change is touching.
+    #[arg(long, conflicts_with_all = ["stat", "contents"])]
+    pub(crate) changed_only: bool,
+
+    /// With `--changed-only`, compare against the index rather than the worktree
+    #[arg(long, requires = "changed_only")]
+    pub(crate) staged: bool,
+
+    /// With `--changed-only`, exit with `1` when any touched line is older than `DURATION`
+    ///
+    /// Like `2y`, `18m`, `1y6m`, or `90d`, as with `--fade-older-than`.
+    #[arg(long, value_name = "DURATION", requires = "changed_only")]
+    pub(crate) fail_older_than: Option<crate::date::Age>,
+
+    /// With `--detail`, the line to break down, 1-based
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "detail",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub(crate) line: Option<u64>,
+
+    /// Break the `--line` down by which commit wrote each part of it
+    ///
+    /// The line is followed back through its earlier versions, and the words each version kept
+    /// from the one before are followed further, up to `dive.detail-depth` versions (default
+    /// `20`, `0` for no limit).  Each part is marked with a key for the commits listed below.
+    #[arg(
+        long,
+        requires = "line",
+        conflicts_with_all = ["stat", "compare", "changed_only", "contents", "blob"]
+    )]
+    pub(crate) detail: bool,
+
+    /// Use the flags stored in `dive.profile.<NAME>.args`
+    ///
+    /// Flags given on the command line take precedence over the profile's, which take precedence
+    /// over `dive.default-args`.
+    #[arg(long, value_name = "NAME")]
+    pub(crate) profile: Vec<String>,
+
+    /// Look up `FILE` in `REV` regardless of case, like the `:(icase)` pathspec magic
+    ///
+    /// This happens on its own when `FILE` exists in the workdir but not with that case in
+    /// `REV`.
+    #[arg(long)]
+    pub(crate) icase_paths: bool,
+
+    /// Annotate these contents, `-` for stdin, rather than `FILE` as of `REV`
+    ///
+    /// Lines that aren't in `REV` are shown as not committed, like an editor's unsaved
+    /// buffer.  This also works when `FILE` isn't in `REV` yet, including before the first
+    /// commit.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) contents: Option<std::path::PathBuf>,
+
+    /// Show the blob `OID` rather than `FILE`'s contents, `FILE` only hinting at the syntax
+    ///
+    /// With `REV`, lines are attributed by blaming `FILE` as of `REV`, which fails unless `FILE`
+    /// is `OID` there.  Without, the contents are shown without attribution.
+    #[arg(long, value_name = "OID", conflicts_with_all = ["contents", "compare", "find_deleted"])]
+    pub(crate) blob: Option<String>,
+
+    /// During a conflict, annotate our side of `FILE` from the index, against `HEAD`
+    ///
+    /// Like `:2:FILE`.  Lines that are only in the index are shown as not committed.
+    #[arg(
+        long,
+        conflicts_with_all = ["rev", "rev_file", "contents", "compare", "changed_only", "theirs", "base"]
+    )]
+    pub(crate) ours: bool,
+
+    /// During a conflict, annotate their side of `FILE` from the index, against the commit
+    /// being merged, cherry-picked, rebased, or reverted
+    ///
+    /// Like `:3:FILE`.
+    #[arg(
+        long,
+        conflicts_with_all = ["rev", "rev_file", "contents", "compare", "changed_only", "base"]
+    )]
+    pub(crate) theirs: bool,
+
+    /// During a conflict, annotate the common ancestor's version of `FILE` from the index
+    ///
+    /// Like `:1:FILE`.
+    #[arg(
+        long,
+        conflicts_with_all = ["rev", "rev_file", "contents", "compare", "changed_only"]
+    )]
+    pub(crate) base: bool,
+
+    /// Annotate `FILE` even when gitattributes mark it generated, vendored, or `-diff`
+    ///
+    /// Otherwise files with `linguist-generated`, `linguist-vendored`, or `-diff` as of `REV`,
+    /// like lock files, are skipped with a notice.
+    #[arg(long)]
+    pub(crate) force_generated: bool,
+
+    /// Annotate the content of a Git LFS pointer rather than skipping it
+    ///
+    /// Each version of the pointer is run through `filter.lfs.smudge`, as set by `git lfs
+    /// install`, and the results are diffed, so lines are attributed to the commit that changed
+    /// the pointer to a version with them.  Without it, or when smudging fails, the pointer is
+    /// skipped with a notice naming its object.
+    #[arg(long)]
+    pub(crate) lfs_smudge: bool,
+
+    /// When `FILE` is a directory, include files in its subdirectories
+    #[arg(short, long)]
+    pub(crate) recursive: bool,
+
+    /// How to write the output
+    ///
+    /// `quickfix` writes `PATH:LINE: HASH AUTHOR DATE SUMMARY` for every line, without color or
+    /// paging, for editor quickfix lists like vim's `:cexpr system('git-dive --format quickfix
+    /// FILE')`.  `PATH` follows `--path-display` and `DATE` is `YYYY-MM-DD`.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t)]
+    pub(crate) format: OutputFormat,
+
+    /// With `--format json`, ask the `--daemon` for the output when one is listening
+    #[arg(long, hide_short_help = true)]
+    pub(crate) client: bool,
+
+    /// Show `FILE` as of `REV` highlighted, without blaming it
+    ///
+    /// Nothing is attributed, so this is quick however long the history.  The gutter is line
+    /// numbers and the grid, which `--style` can narrow to either or neither, like
+    /// `--style=grid`.  Only `--format text` is supported.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "stat", "line", "compare", "changed_only", "contents", "find_deleted",
+            "squeeze_unchanged", "hide_boundary", "hunks_only", "color_by", "highlight_mine",
+            "show_release", "coloring", "fade_older_than", "ignore_rev", "ignore_revs_file",
+            "show_ignored", "resolve_reverts", "detect_resolution", "touched_since",
+        ]
+    )]
+    pub(crate) no_blame: bool,
+
+    /// Instead of the file, count its lines by author
+    ///
+    /// Lines between `dive:ignore-start` and `dive:ignore-end`, or the pairs in
+    /// `dive.ignore-markers`, aren't counted.
+    #[arg(long)]
+    pub(crate) stat: bool,
+
+    /// With `--stat`, also count lines by team, approximated by email domain
+    ///
+    /// An author's team is the first `dive.team-map` entry, `PATTERN=TEAM` where `PATTERN` is a
+    /// glob for mailmapped emails, that matches, then their domain.  Teams are also counted
+    /// when `dive.team-map` is set, with unmatched authors under `(other)`.
+    #[arg(long, requires = "stat")]
+    pub(crate) group_by_domain: bool,
+
+    /// With `--stat`, don't count lines from root commits or a shallow clone's boundary
+    ///
+    /// They are still listed, after the counts.
+    #[arg(long, requires = "stat")]
+    pub(crate) no_boundary: bool,
+
+    /// With `--format quickfix`, only list the first line of each hunk
+    #[arg(long)]
+    pub(crate) hunks_only: bool,
+
+    /// How to spell paths in output and messages
+    ///
+    /// Also controlled by `dive.path-display`.  Defaults to `relative-to-root`, except that
+    /// directory listings are relative to the directory unless a mode is chosen.
+    #[arg(long, value_name = "MODE", value_enum)]
+    pub(crate) path_display: Option<crate::path_display::PathDisplay>,
+
+    /// Shorthand for `--path-display relative-to-cwd`
+    #[arg(long, conflicts_with_all = ["path_display", "absolute_paths"])]
+    pub(crate) relative_paths: bool,
+
+    /// Shorthand for `--path-display absolute`
+    #[arg(long, conflicts_with = "path_display")]
+    pub(crate) absolute_paths: bool,
+
+    /// Parts of the gutter to show
+    ///
+    /// Without this, when the gutter is wider than `dive.max-gutter-ratio` of the terminal
+    /// (default `0.4`), authors are abbreviated to initials, then dates to `YY-MM-DD`, then
+    /// commits to 6 characters until it fits.
+    #[arg(
+        long,
+        value_name = "COMPONENTS",
+        value_delimiter = ',',
+        action = clap::ArgAction::Set
+    )]
+    pub(crate) style: Option<Vec<StyleComponent>>,
+
+    /// Collapse runs of more than `N` lines from the same commit down to `N` lines
+    ///
+    /// The first and last lines of the run are kept around a marker saying how many were
+    /// hidden.  Ignored by `--format json` and `--format quickfix`.
+    #[arg(long, value_name = "N")]
+    pub(crate) squeeze_unchanged: Option<usize>,
+
+    /// Collapse each run of lines from a root commit, or a shallow clone's boundary, to a marker
+    ///
+    /// Short for `dive.boundary-style = hide`; `dim` dims those lines instead.  Ignored by
+    /// `--format json`, which gives each line's `boundary`, and `--format quickfix`.
+    #[arg(long)]
+    pub(crate) hide_boundary: bool,
+
+    /// Show only lines whose commit is from `DATE` or later, collapsing older ones to markers
+    ///
+    /// For reviewing what changed in a file lately with who changed it.  `DATE` is like
+    /// `--at`'s, and is compared with the date shown, the author date unless
+    /// `--committer-date`.  With `--stat`, only those lines are counted.
+    #[arg(
+        long,
+        value_name = "DATE",
+        conflicts_with_all = ["compare", "changed_only", "line"]
+    )]
+    pub(crate) touched_since: Option<crate::date::Approxidate>,
+
+    /// Color the author column, and the `stripe` style component, by who wrote each line
+    ///
+    /// Colors come from `dive.author-palette`, which may be given multiple times, and are
+    /// assigned by mailmapped email so they are the same across runs.
+    #[arg(long, value_name = "WHAT", value_enum)]
+    pub(crate) color_by: Option<ColorBy>,
+
+    /// Accent the gutter of lines you wrote and summarize how many there are
+    ///
+    /// Authors are matched by mailmapped email against `user.email` and `dive.my-emails`,
+    /// which may be given multiple times.  Also controlled by `dive.highlight-mine`; the accent
+    /// is `dive.mine-color`.
+    #[arg(long)]
+    pub(crate) highlight_mine: bool,
+
+    /// Mark trailing whitespace, tabs after spaces in indentation, and overly long lines
+    ///
+    /// Marks are `dive.lint-color` (default a red background) layered over the highlighting,
+    /// and lines are too long past `dive.max-line-length` columns (default `100`, `0` for no
+    /// limit).  A footer counts the lines with each problem, overall and by author.
+    #[arg(long)]
+    pub(crate) lint_whitespace: bool,
+
+    /// Show the earliest release tag containing each line's commit
+    ///
+    /// Tags are those matching `dive.release-tag-glob`, defaulting to `v*`.  Commits no tag
+    /// contains show as `unreleased`, in `dive.unreleased-color`.
+    #[arg(long)]
+    pub(crate) show_release: bool,
+
+    /// With `--show-release`, look releases up again rather than reading them from the cache,
+    /// still caching them
+    ///
+    /// The cache is under `$XDG_CACHE_HOME/git-dive/releases`.  Answers are keyed by what the
+    /// tags point to, so this is for when the cache is suspect.
+    #[arg(long, requires = "show_release")]
+    pub(crate) refresh_releases: bool,
+
+    /// How to color the gutter
+    ///
+    /// Defaults to `dive.coloring`, then git's `blame.coloring`.
+    #[arg(long, value_name = "MODE", value_enum)]
+    pub(crate) coloring: Option<crate::coloring::Coloring>,
+
+    /// Highlight the code as `LANGUAGE`, a name or extension from `--list-languages`
+    ///
+    /// Otherwise, the syntax is picked by the file's `dive-language` gitattribute, then
+    /// `dive.syntax-for-extension = EXT=LANGUAGE`, then its `diff=` gitattribute, then its
+    /// extension, then its first line.
+    #[arg(short = 'l', long, value_name = "LANGUAGE")]
+    pub(crate) language: Option<String>,
+
+    /// Don't highlight the code's syntax, still coloring the gutter
+    ///
+    /// Also controlled by `dive.syntax`.  Files with more lines than `dive.syntax-max-lines`,
+    /// defaulting to 20000, are never highlighted; `0` is no limit.
+    #[arg(long)]
+    pub(crate) no_syntax: bool,
+
+    /// Dim the code and commit details of lines whose commit is older than `DURATION`
+    ///
+    /// Like `2y`, `18m`, `1y6m`, or `90d`, with units `y`, `m`, `w`, `d`, and `h`.  A quieter
+    /// alternative to `--coloring highlight-recent`.  Defaults to `dive.fade-older-than`.
+    #[arg(long, value_name = "DURATION")]
+    pub(crate) fade_older_than: Option<crate::date::Age>,
+
+    /// How to write dates, like git's `--date`
+    ///
+    /// Defaults to `dive.date`, then git's `blame.date`.  Relative dates and the names in `rfc`
+    /// dates are in the language of `dive.locale`, which defaults to the locale from `LC_ALL`,
+    /// `LC_TIME`, or `LANG`.  Languages without translations (only `en`, `de`, `es`, `fr`, `ja`,
+    /// and `zh` have them) are written in English.
+    #[arg(long, value_name = "FORMAT", value_enum)]
+    pub(crate) date: Option<crate::date::DateFormat>,
+
+    /// Show and sort by when commits landed rather than when they were written
+    ///
+    /// Also controlled by `dive.use-committer-date`.
+    #[arg(long)]
+    pub(crate) committer_date: bool,
+
+    /// Show commit details beside blank and whitespace-only lines
+    ///
+    /// With `false`, they still have line numbers and count toward `--stat` and
+    /// `--squeeze-unchanged` as part of their commit.  Defaults to `dive.annotate-blank-lines`,
+    /// then `true`.
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) annotate_blank_lines: Option<bool>,
+
+    /// Mark the last line when the file doesn't end with a newline
+    ///
+    /// The marker is `dive.no-newline-marker`, defaulting to `%`.
+    #[arg(long)]
+    pub(crate) show_no_newline: bool,
+
+    /// Ignore changes made by `REV` when assigning blame, as if the change never happened
+    #[arg(long, value_name = "REV")]
+    pub(crate) ignore_rev: Vec<String>,
+
+    /// Ignore the revisions listed in `FILE`, one per line
+    ///
+    /// Adds to `blame.ignoreRevsFile`, which defaults to a top-level `.git-blame-ignore-revs`
+    /// unless `dive.auto-ignore-revs` is `false`.  An empty path clears all previously listed files.
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_parser = clap::builder::TypedValueParser::map(
+            clap::builder::OsStringValueParser::new(),
+            std::path::PathBuf::from
+        )
+    )]
+    pub(crate) ignore_revs_file: Vec<std::path::PathBuf>,
+
+    /// Mark lines attributed by looking past an ignored revision and summarize what was skipped
+    #[arg(long)]
+    pub(crate) show_ignored: bool,
+
+    /// Show the original commit of lines from a revert or cherry-pick
+    ///
+    /// Commits are recognized by messages like `git revert`'s "This reverts commit SHA" and
+    /// `git cherry-pick -x`'s "(cherry picked from commit SHA)".  The original is noted after
+    /// the hunk's first line, or shown in the gutter instead with `replace`, badged with `↩` for
+    /// reverts and `⇢` for cherry-picks.  `--format json` gains `via` either way.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "alongside"
+    )]
+    pub(crate) resolve_reverts: Option<ResolveReverts>,
+
+    /// Mark lines a merge introduced itself, like when resolving a conflict, with `!`
+    ///
+    /// These are lines blamed on a merge that aren't in any of its parents.  Skipped when more
+    /// than `dive.max-resolution-merges` merges are blamed, defaulting to 100.
+    #[arg(long)]
+    pub(crate) detect_resolution: bool,
+
+    /// When `FILE` doesn't exist at `REV`, show it as of just before it was deleted
+    #[arg(long)]
+    pub(crate) find_deleted: bool,
+
+    /// When `FILE` doesn't exist at `REV`, check whether it's untracked, ignored, or staged to
+    /// say so
+    ///
+    /// Only that file's status is checked.  Defaults to `dive.status-hints`, then `true`.
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) status_hints: Option<bool>,
+
+    /// Stop searching for a deleted file after this many commits
+    #[arg(long, value_name = "N", default_value_t = 10_000)]
+    pub(crate) max_commits: usize,
+
+    /// Only follow the first parent of merge commits when searching history
+    #[arg(long)]
+    pub(crate) first_parent: bool,
+
+    /// Don't read `.git/objects/info/commit-graph` when searching history
+    ///
+    /// Also controlled by `core.commitGraph`.  The commit-graph lets `--find-deleted
+    /// --first-parent` walk history without parsing each commit.
+    #[arg(long)]
+    pub(crate) no_commit_graph: bool,
+
+    /// In a partial clone, fail listing the missing versions of `FILE` rather than fetching them
+    ///
+    /// Also controlled by `GIT_NO_LAZY_FETCH`.  Otherwise they are fetched in one request before
+    /// blaming.
+    #[arg(long)]
+    pub(crate) no_lazy_fetch: bool,
+
+    /// Fail when an object `FILE`'s history needs can't be read, like from a corrupt pack
+    ///
+    /// Otherwise lines are followed back as far as their history can be read, the rest shown as
+    /// `unknown (object missing)`, and the objects are listed after blaming.
+    #[arg(long)]
+    pub(crate) strict_objects: bool,
+
+    /// Don't follow commits replaced with `git replace`, like grafts
+    #[arg(long)]
+    pub(crate) no_replace_objects: bool,
+
+    /// Pager to use, bypassing `dive.pager-long`, `core.pager`, `GIT_PAGER`, and `PAGER`
+    ///
+    /// `cat` or an empty command disables paging.  Output shorter than `dive.pager-min-lines`,
+    /// which defaults to the terminal's height, is never paged; `0` always pages.  Without
+    /// this, `dive.pager-short` is run for that shorter output instead.
+    /// With `less`, its version is checked to pick flags unless `dive.pager-probe` is
+    /// `false`.  Nothing is paged when `GIT_PAGER_IN_USE` is set, like when git already started
+    /// a pager.  With `dive.pager-sections` set to `ov` or `moar`, hunks are marked for that
+    /// pager to fold or jump between, when it's the one run.
+    #[arg(long, value_name = "COMMAND")]
+    pub(crate) pager: Option<String>,
+
+    /// Also write the output to `PATH`, without colors
+    ///
+    /// `PATH` is truncated up front and written as output is produced, so an interrupted run
+    /// keeps what was shown so far.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) tee: Option<std::path::PathBuf>,
+
+    /// With `--tee`, keep colors and other escape sequences
+    #[arg(long, requires = "tee")]
+    pub(crate) tee_raw: bool,
+
+    /// Do not pipe output into a pager
+    #[arg(long, conflicts_with = "pager")]
+    pub(crate) no_pager: bool,
+
+    /// Have `less` search for the first line of each hunk, so `n` and `N` jump between hunks
+    ///
+    /// Needs the `hash` style component, which those lines start with.
+    #[arg(long)]
+    pub(crate) pager_marks: bool,
+
+    /// Do everything but write the output, like to warm caches
+    #[arg(long, conflicts_with = "tee")]
+    pub(crate) no_output: bool,
+
+    /// Don't warn about unknown `dive.*` config keys
+    ///
+    /// Also controlled by `dive.strict`.
+    #[arg(long)]
+    pub(crate) no_config_check: bool,
+
+    /// Render the same on every machine, for snapshot tests
+    #[arg(long, hide = true)]
+    pub(crate) render_test: bool,
+
     #[command(flatten)]
     pub(crate) color: colorchoice_clap::Color,
 
@@ -49,6 +613,91 @@ pub(crate) struct Args {
     pub(crate) verbose: clap_verbosity_flag::Verbosity<clap_verbosity_flag::InfoLevel>,
 }
 
+impl Args {
+    /// Like [`clap::Parser::parse_from`], also noting whether `REV` was given
+    pub(crate) fn parse_argv<I, T>(argv: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        Self::try_parse_argv(argv).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Like [`clap::Parser::try_parse_from`], also noting whether `REV` was given
+    pub(crate) fn try_parse_argv<I, T>(argv: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let mut matches = <Self as clap::CommandFactory>::command().try_get_matches_from(argv)?;
+        let rev_given = matches.value_source("rev") == Some(clap::parser::ValueSource::CommandLine);
+        let mut args = <Self as clap::FromArgMatches>::from_arg_matches_mut(&mut matches)
+            .map_err(|err| err.format(&mut <Self as clap::CommandFactory>::command()))?;
+        args.rev_given = rev_given;
+        Ok(args)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// For people
+    #[default]
+    Text,
+    /// For scripts; every line of a file, or the last change to each entry of a directory
+    Json,
+    /// For editors' quickfix lists; only supported for files
+    Quickfix,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum StyleComponent {
+    /// A thin margin colored by `--color-by`
+    Stripe,
+    /// The commit, relative to `REV` when possible
+    ///
+    /// Spelled per `dive.hash-style` (`short` or `long`), `dive.hash-case` (`lower` or
+    /// `upper`), and `dive.hash-prefix`, as are commits elsewhere in text output.
+    Hash,
+    /// The commit's author
+    Author,
+    /// The commit's author date, or committer date with `--committer-date`
+    Date,
+    /// The earliest release tag containing the commit
+    ///
+    /// Shown without `--style` with `--show-release`.
+    Release,
+    /// How many first-parent commits back from `REV` the commit is
+    ///
+    /// Commits merged in count as their merge.  Past `dive.distance-cap` (default `99`), shown
+    /// as `99+`.
+    Distance,
+    /// Line numbers
+    Numbers,
+    /// The separator between the gutter and the code
+    Grid,
+    /// A rule between lines from different commits
+    Separator,
+    /// After the first line of each hunk from another file, like one a line was copied from,
+    /// that file's path
+    ///
+    /// Shown without `--style` when any line came from another file.
+    File,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum ResolveReverts {
+    /// The reverting or picking commit in the gutter, with the original noted after the code
+    Alongside,
+    /// The original commit in the gutter, with the reverting or picking one noted after the code
+    Replace,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum ColorBy {
+    /// Each author's lines get their own color
+    Author,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
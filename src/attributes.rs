@@ -0,0 +1,580 @@
+//! Look up gitattributes for a path as of a commit, rather than the workdir
+//!
+//! Every lookup goes through a [`Stack`], which layers files the way git does, lowest priority
+//! first: the system file unless `GIT_ATTR_NOSYSTEM` is set, `core.attributesFile`, each
+//! `.gitattributes` from the top of the tree down to the path's directory, then
+//! `$GIT_DIR/info/attributes`.  Patterns are matched with git's wildmatch rules and only the
+//! built-in `binary` macro is expanded.  Settings for how a file is shown are instead read from
+//! the worktree, like git does, so they apply without being committed, falling back to the
+//! blamed tree in a bare repository.
+
+use crate::git2_config::Config;
+use crate::git2_config::RawField;
+
+/// The state of an attribute for a path
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum State {
+    /// `attr`
+    Set,
+    /// `-attr`
+    Unset,
+    /// `attr=value`
+    Value(String),
+    /// `!attr`, or no matching rule
+    Unspecified,
+}
+
+impl State {
+    /// Set, or a value git treats as true
+    pub(crate) fn is_true(&self) -> bool {
+        match self {
+            Self::Set => true,
+            Self::Value(value) => value.eq_ignore_ascii_case("true") || value == "1",
+            Self::Unset | Self::Unspecified => false,
+        }
+    }
+}
+
+/// The attribute files outside of the tree, which tests can point elsewhere
+#[derive(Default, Debug)]
+pub(crate) struct Stack {
+    /// `$(prefix)/etc/gitattributes`
+    pub(crate) system: Option<std::path::PathBuf>,
+    /// `core.attributesFile`, or `$XDG_CONFIG_HOME/git/attributes`
+    pub(crate) global: Option<std::path::PathBuf>,
+    /// `$GIT_DIR/info/attributes`, shared by linked worktrees
+    pub(crate) info: Option<std::path::PathBuf>,
+}
+
+impl Stack {
+    pub(crate) fn new(repo: &git2::Repository, config: &Config) -> Self {
+        let global = config
+            .get(&ATTRIBUTES_FILE)
+            .map(crate::ignore_revs::expand_home)
+            .or_else(default_global_file);
+        let info = crate::boundary::common_dir(repo)
+            .join("info")
+            .join("attributes");
+        Self {
+            system: system_file(crate::git2_config::env_flag("GIT_ATTR_NOSYSTEM")),
+            global,
+            info: Some(info),
+        }
+    }
+
+    /// The rules that apply to `tree_path` in `tree`
+    pub(crate) fn load(
+        &self,
+        repo: &git2::Repository,
+        tree: &git2::Tree<'_>,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Attributes> {
+        self.load_with(tree_path, |attributes, dir| {
+            attributes.add_tree_file(repo, tree, dir)
+        })
+    }
+
+    /// The rules that apply to `tree_path` in `workdir`
+    pub(crate) fn load_dir(
+        &self,
+        workdir: &std::path::Path,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Attributes> {
+        self.load_with(tree_path, |attributes, dir| {
+            let source = format!("{dir}.gitattributes");
+            attributes.add_file(&workdir.join(&source), dir, &source);
+            Ok(())
+        })
+    }
+
+    /// Lowest priority first, `add_dir` adding the `.gitattributes` of each `/`-terminated
+    /// directory from the top to `tree_path`'s
+    fn load_with(
+        &self,
+        tree_path: &std::path::Path,
+        mut add_dir: impl FnMut(&mut Attributes, &str) -> anyhow::Result<()>,
+    ) -> anyhow::Result<Attributes> {
+        let mut attributes = Attributes::default();
+        for path in [&self.system, &self.global].into_iter().flatten() {
+            attributes.add_file(path, "", &path.display().to_string());
+        }
+
+        let mut dir = String::new();
+        add_dir(&mut attributes, &dir)?;
+        let components = tree_path
+            .to_str()
+            .unwrap_or_default()
+            .split('/')
+            .collect::<Vec<_>>();
+        if let Some((_file, dirs)) = components.split_last() {
+            for component in dirs {
+                dir.push_str(component);
+                dir.push('/');
+                add_dir(&mut attributes, &dir)?;
+            }
+        }
+
+        if let Some(info) = &self.info {
+            attributes.add_file(info, "", &info.display().to_string());
+        }
+        Ok(attributes)
+    }
+}
+
+/// Attribute rules, lowest priority first
+#[derive(Default, Debug)]
+pub(crate) struct Attributes {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// The directory of the `.gitattributes` file, `/`-terminated or empty
+    base: String,
+    pattern: String,
+    /// The pattern has a `/` so is matched against the whole path rather than the file name
+    anchored: bool,
+    attrs: Vec<(String, State)>,
+    /// Like `docs/.gitattributes:3`, for pointing at a bad value
+    source: String,
+}
+
+impl Attributes {
+    /// The rules that apply to `tree_path` in `commit`
+    pub(crate) fn load(
+        repo: &git2::Repository,
+        config: &Config,
+        commit: &git2::Commit<'_>,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Self> {
+        Stack::new(repo, config).load(repo, &commit.tree()?, tree_path)
+    }
+
+    /// The rules that apply to `tree_path` in the worktree, or in `tree` for a bare repository
+    pub(crate) fn load_worktree(
+        repo: &git2::Repository,
+        config: &Config,
+        tree: Option<&git2::Tree<'_>>,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Self> {
+        let stack = Stack::new(repo, config);
+        match (repo.workdir(), tree) {
+            (Some(workdir), _) => stack.load_dir(workdir, tree_path),
+            (None, Some(tree)) => stack.load(repo, tree, tree_path),
+            (None, None) => stack.load_with(tree_path, |_, _| Ok(())),
+        }
+    }
+
+    fn add_tree_file(
+        &mut self,
+        repo: &git2::Repository,
+        tree: &git2::Tree<'_>,
+        dir: &str,
+    ) -> anyhow::Result<()> {
+        let path = format!("{dir}.gitattributes");
+        let entry = match tree.get_path(std::path::Path::new(&path)) {
+            Ok(entry) => entry,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let Ok(blob) = entry.to_object(repo)?.into_blob() else {
+            return Ok(());
+        };
+        log::trace!("reading attributes from {path}");
+        self.add(&String::from_utf8_lossy(blob.content()), dir, &path);
+        Ok(())
+    }
+
+    fn add_file(&mut self, path: &std::path::Path, dir: &str, source: &str) {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            log::trace!("reading attributes from {}", path.display());
+            self.add(&content, dir, source);
+        }
+    }
+
+    /// `source` naming where `content` is from, for messages
+    fn add(&mut self, content: &str, base: &str, source: &str) {
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            if pattern.starts_with('[') && pattern.contains("attr]") {
+                // Macro definitions are only allowed at the top-level and are unsupported
+                continue;
+            }
+            let attrs = fields.flat_map(parse_attr).collect::<Vec<_>>();
+            let anchored = pattern.trim_end_matches('/').contains('/');
+            self.rules.push(Rule {
+                base: base.to_owned(),
+                pattern: pattern.trim_start_matches('/').to_owned(),
+                anchored,
+                attrs,
+                source: format!("{source}:{}", i + 1),
+            });
+        }
+    }
+
+    /// The state of `name` for `tree_path`, a `/`-separated path from the top of the repo
+    pub(crate) fn get(&self, tree_path: &std::path::Path, name: &str) -> State {
+        self.find(tree_path, name)
+            .map(|(state, _)| state.clone())
+            .unwrap_or(State::Unspecified)
+    }
+
+    /// Like [`Self::get`], along with the line of the rule that set it
+    pub(crate) fn find(&self, tree_path: &std::path::Path, name: &str) -> Option<(&State, &str)> {
+        let path = tree_path.to_str().unwrap_or_default();
+        for rule in self.rules.iter().rev() {
+            let Some((_, state)) = rule.attrs.iter().rev().find(|(n, _)| n == name) else {
+                continue;
+            };
+            if rule.matches(path) {
+                return Some((state, &rule.source));
+            }
+        }
+        None
+    }
+}
+
+impl Rule {
+    fn matches(&self, path: &str) -> bool {
+        let Some(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        if self.anchored {
+            wildmatch(self.pattern.as_bytes(), relative.as_bytes())
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            wildmatch(self.pattern.as_bytes(), name.as_bytes())
+        }
+    }
+}
+
+/// An attribute and, for macros like `binary`, what it expands to
+fn parse_attr(attr: &str) -> Vec<(String, State)> {
+    let (name, state) = if let Some(name) = attr.strip_prefix('-') {
+        (name, State::Unset)
+    } else if let Some(name) = attr.strip_prefix('!') {
+        (name, State::Unspecified)
+    } else if let Some((name, value)) = attr.split_once('=') {
+        (name, State::Value(value.to_owned()))
+    } else {
+        (attr, State::Set)
+    };
+    let mut attrs = Vec::new();
+    if name == "binary" && state == State::Set {
+        for expanded in ["diff", "merge", "text"] {
+            attrs.push((expanded.to_owned(), State::Unset));
+        }
+    }
+    attrs.push((name.to_owned(), state));
+    attrs
+}
+
+/// `gitattributes` beside the system config, which is `/etc/gitattributes` for most installs
+fn system_file(nosystem: bool) -> Option<std::path::PathBuf> {
+    if nosystem {
+        return None;
+    }
+    let dir = git2::Config::find_system()
+        .ok()
+        .and_then(|config| config.parent().map(ToOwned::to_owned))
+        .unwrap_or_else(|| std::path::PathBuf::from("/etc"));
+    Some(dir.join("gitattributes"))
+}
+
+/// `$XDG_CONFIG_HOME/git/attributes`, git's default for `core.attributesFile`
+fn default_global_file() -> Option<std::path::PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+        })?;
+    Some(config_home.join("git").join("attributes"))
+}
+
+/// Match `text` against a gitattributes pattern
+///
+/// `*` and `?` don't match `/`, `**` between slashes matches any number of directories, and
+/// `[...]` matches a character class.
+pub(crate) fn wildmatch(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = &pattern[2..];
+            if rest.is_empty() {
+                return true;
+            }
+            if let Some(rest) = rest.strip_prefix(b"/") {
+                if wildmatch(rest, text) {
+                    return true;
+                }
+                return text
+                    .iter()
+                    .enumerate()
+                    .any(|(i, c)| *c == b'/' && wildmatch(rest, &text[i + 1..]));
+            }
+            (0..=text.len()).any(|i| wildmatch(rest, &text[i..]))
+        }
+        Some(b'*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if wildmatch(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => {
+            text.first().is_some_and(|c| *c != b'/') && wildmatch(&pattern[1..], &text[1..])
+        }
+        Some(b'[') => {
+            let Some((matches, len)) = match_class(&pattern[1..], text.first().copied()) else {
+                // No closing `]`, so it's a literal
+                return text.first() == Some(&b'[') && wildmatch(&pattern[1..], &text[1..]);
+            };
+            matches && wildmatch(&pattern[1 + len..], &text[1..])
+        }
+        Some(b'\\') if 1 < pattern.len() => {
+            text.first() == Some(&pattern[1]) && wildmatch(&pattern[2..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && wildmatch(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Whether `c` is in the class starting after `[`, and the length of the class through `]`
+fn match_class(class: &[u8], c: Option<u8>) -> Option<(bool, usize)> {
+    let (negated, mut i) = match class.first() {
+        Some(b'!' | b'^') => (true, 1),
+        _ => (false, 0),
+    };
+    let start = i;
+    let mut matched = false;
+    loop {
+        let first = *class.get(i)?;
+        if first == b']' && start < i {
+            break;
+        }
+        if class.get(i + 1) == Some(&b'-') && class.get(i + 2).is_some_and(|e| *e != b']') {
+            let last = class[i + 2];
+            matched |= c.is_some_and(|c| first <= c && c <= last);
+            i += 3;
+        } else {
+            matched |= c == Some(first);
+            i += 1;
+        }
+    }
+    let matched = c.is_some_and(|c| c != b'/') && matched != negated;
+    Some((matched, i + 1))
+}
+
+pub(crate) const ATTRIBUTES_FILE: RawField<std::path::PathBuf> =
+    RawField::<std::path::PathBuf>::new("core.attributesFile");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wildmatch_patterns() {
+        let matches = |p: &str, t: &str| wildmatch(p.as_bytes(), t.as_bytes());
+        assert!(matches("package-lock.json", "package-lock.json"));
+        assert!(matches("*.pb.go", "api.pb.go"));
+        assert!(!matches("*.pb.go", "gen/api.pb.go"));
+        assert!(matches("gen/*.go", "gen/api.go"));
+        assert!(!matches("gen/*.go", "gen/sub/api.go"));
+        assert!(matches("gen/**", "gen/sub/api.go"));
+        assert!(matches("**/vendor/**", "vendor/lib.js"));
+        assert!(matches("**/vendor/**", "web/vendor/lib.js"));
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(!matches("file?.txt", "file/.txt"));
+        assert!(matches("[a-c]*.rs", "b.rs"));
+        assert!(!matches("[!a-c]*.rs", "b.rs"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+    }
+
+    #[test]
+    fn later_rules_win() {
+        let mut attributes = Attributes::default();
+        attributes.add(
+            "\
+# Generated
+*.json linguist-generated
+config.json -linguist-generated
+*.bin binary
+",
+            "",
+            ".gitattributes",
+        );
+        attributes.add("*.json !linguist-generated\n", "web/", "web/.gitattributes");
+        let get = |p: &str, name| attributes.get(std::path::Path::new(p), name);
+        assert_eq!(get("package-lock.json", "linguist-generated"), State::Set);
+        assert_eq!(get("sub/data.json", "linguist-generated"), State::Set);
+        assert_eq!(get("config.json", "linguist-generated"), State::Unset);
+        assert_eq!(
+            get("web/package-lock.json", "linguist-generated"),
+            State::Unspecified
+        );
+        assert_eq!(get("a.bin", "diff"), State::Unset);
+        assert_eq!(get("a.rs", "diff"), State::Unspecified);
+    }
+
+    #[test]
+    fn source_lines() {
+        let mut attributes = Attributes::default();
+        attributes.add("*.md dive-theme=light\n", "", ".gitattributes");
+        attributes.add(
+            "# Screenshots\n\n*.md dive-theme=dark\n",
+            "docs/",
+            "docs/.gitattributes",
+        );
+        let find = |p: &str| {
+            attributes
+                .find(std::path::Path::new(p), "dive-theme")
+                .map(|(state, source)| (state.clone(), source.to_owned()))
+        };
+        assert_eq!(
+            find("README.md"),
+            Some((State::Value("light".into()), ".gitattributes:1".into()))
+        );
+        assert_eq!(
+            find("docs/guide.md"),
+            Some((State::Value("dark".into()), "docs/.gitattributes:3".into()))
+        );
+        assert_eq!(find("main.rs"), None);
+    }
+
+    #[test]
+    fn anchored_patterns() {
+        let mut attributes = Attributes::default();
+        attributes.add(
+            "/top.txt linguist-vendored=true\ngen/ x\n",
+            "src/",
+            "src/.gitattributes",
+        );
+        let get = |p: &str, name| attributes.get(std::path::Path::new(p), name);
+        assert!(get("src/top.txt", "linguist-vendored").is_true());
+        assert!(!get("src/sub/top.txt", "linguist-vendored").is_true());
+        assert_eq!(get("top.txt", "linguist-vendored"), State::Unspecified);
+    }
+
+    /// A tree setting `dive-theme` at the top and in `sub/`, with a file for each other layer
+    fn stack_fixture(root_path: &std::path::Path) -> (git2::Repository, git2::Oid, Stack) {
+        let repo = git2::Repository::init(root_path).unwrap();
+        let tree = {
+            let mut sub = repo.treebuilder(None).unwrap();
+            let content = repo.blob(b"*.md dive-theme=sub\n").unwrap();
+            sub.insert(".gitattributes", content, 0o100644).unwrap();
+            let sub = sub.write().unwrap();
+            let mut top = repo.treebuilder(None).unwrap();
+            let content = repo
+                .blob(b"*.md dive-theme=top dive-language=top dive-tab-width=2\n")
+                .unwrap();
+            top.insert(".gitattributes", content, 0o100644).unwrap();
+            top.insert("sub", sub, 0o040000).unwrap();
+            top.write().unwrap()
+        };
+
+        let layer = |name: &str, content: &str| {
+            let path = root_path.join(name);
+            std::fs::write(&path, content).unwrap();
+            Some(path)
+        };
+        let stack = Stack {
+            system: layer("system", "*.md dive-theme=system x=system y=system\n"),
+            global: layer("global", "*.md dive-theme=global x=global\n"),
+            info: layer("info", "sub/*.md dive-language=info\n"),
+        };
+        (repo, tree, stack)
+    }
+
+    #[test]
+    fn stack_precedence() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let (repo, tree, stack) = stack_fixture(root_path);
+        let tree = repo.find_tree(tree).unwrap();
+
+        let path = std::path::Path::new("sub/guide.md");
+        let attributes = stack.load(&repo, &tree, path).unwrap();
+        let get = |name| attributes.get(path, name);
+        // The deepest `.gitattributes` over the top one, over the files outside the tree
+        assert_eq!(get("dive-theme"), State::Value("sub".into()));
+        // `info/attributes` over every `.gitattributes`
+        assert_eq!(get("dive-language"), State::Value("info".into()));
+        assert_eq!(get("dive-tab-width"), State::Value("2".into()));
+        // `core.attributesFile` over the system file
+        assert_eq!(get("x"), State::Value("global".into()));
+        assert_eq!(get("y"), State::Value("system".into()));
+
+        let top = std::path::Path::new("guide.md");
+        let attributes = stack.load(&repo, &tree, top).unwrap();
+        assert_eq!(
+            attributes.get(top, "dive-theme"),
+            State::Value("top".into())
+        );
+        assert_eq!(
+            attributes.get(top, "dive-language"),
+            State::Value("top".into())
+        );
+
+        let no_system = Stack {
+            system: None,
+            ..stack
+        };
+        let attributes = no_system.load(&repo, &tree, path).unwrap();
+        assert_eq!(attributes.get(path, "y"), State::Unspecified);
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn worktree_matches_tree() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let (repo, tree, stack) = stack_fixture(root_path);
+        let tree = repo.find_tree(tree).unwrap();
+        let workdir = root_path.join("work");
+        std::fs::create_dir_all(workdir.join("sub")).unwrap();
+        for (path, entry) in [
+            (".gitattributes", ".gitattributes"),
+            ("sub/.gitattributes", "sub/.gitattributes"),
+        ] {
+            let blob = tree.get_path(std::path::Path::new(entry)).unwrap().id();
+            let content = repo.find_blob(blob).unwrap().content().to_owned();
+            std::fs::write(workdir.join(path), content).unwrap();
+        }
+
+        let path = std::path::Path::new("sub/guide.md");
+        let from_tree = stack.load(&repo, &tree, path).unwrap();
+        let from_dir = stack.load_dir(&workdir, path).unwrap();
+        for name in ["dive-theme", "dive-language", "dive-tab-width", "x", "y"] {
+            assert_eq!(
+                from_tree.find(path, name),
+                from_dir.find(path, name),
+                "{name}"
+            );
+        }
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn nosystem() {
+        assert_eq!(system_file(true), None);
+        assert!(system_file(false).unwrap().ends_with("gitattributes"));
+    }
+}
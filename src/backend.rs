@@ -0,0 +1,202 @@
+//! What blame and history read from the repository, apart from the library reading it
+//!
+//! [`crate::engine::Engine`] attributes lines and [`crate::replace::Walk`] walks history using
+//! only these operations, so another library, like gitoxide, can stand in for libgit2 by
+//! implementing [`Backend`].
+
+use crate::blame::DiffHunk;
+use crate::replace::Replacements;
+
+/// Reads commits, trees, and blobs for blame and history walks
+///
+/// Commits are as seen through any replacements, like grafts.
+pub(crate) trait Backend {
+    /// Every parent of `commit`, first parent first
+    fn parents(&self, commit: git2::Oid) -> anyhow::Result<Vec<git2::Oid>>;
+
+    /// The parent blame follows
+    fn first_parent(&self, commit: git2::Oid) -> anyhow::Result<Option<git2::Oid>> {
+        Ok(self.parents(commit)?.first().copied())
+    }
+
+    /// Seconds since the epoch, for walking history newest first
+    fn commit_time(&self, commit: git2::Oid) -> anyhow::Result<i64>;
+
+    /// The blob at `path` as of `commit`, `None` if it's missing or not a file
+    fn blob_at(
+        &self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<git2::Oid>>;
+
+    fn read_blob(&self, blob: git2::Oid) -> anyhow::Result<Vec<u8>>;
+
+    /// Where `path` in `commit` was renamed from, as of `parent`
+    fn renamed_from(
+        &self,
+        parent: git2::Oid,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<std::path::PathBuf>>;
+
+    /// The changed lines, ignoring whitespace like blame does
+    ///
+    /// The paths are for attributes, like `-diff`.
+    fn diff_blobs(
+        &self,
+        old: &[u8],
+        old_path: &std::path::Path,
+        new: &[u8],
+        new_path: &std::path::Path,
+    ) -> anyhow::Result<Vec<DiffHunk>>;
+}
+
+/// libgit2, through [`Replacements`] since it doesn't honor replace refs
+pub(crate) struct Git2<'r> {
+    repo: &'r git2::Repository,
+    replacements: &'r Replacements,
+}
+
+impl<'r> Git2<'r> {
+    pub(crate) fn new(repo: &'r git2::Repository, replacements: &'r Replacements) -> Self {
+        Self { repo, replacements }
+    }
+}
+
+impl Backend for Git2<'_> {
+    fn parents(&self, commit: git2::Oid) -> anyhow::Result<Vec<git2::Oid>> {
+        self.replacements.parents(self.repo, commit)
+    }
+
+    fn commit_time(&self, commit: git2::Oid) -> anyhow::Result<i64> {
+        Ok(self.repo.find_commit(commit)?.time().seconds())
+    }
+
+    fn blob_at(
+        &self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<git2::Oid>> {
+        let tree = self.repo.find_commit(commit)?.tree()?;
+        let Ok(entry) = tree.get_path(path) else {
+            return Ok(None);
+        };
+        Ok((entry.kind() == Some(git2::ObjectType::Blob)).then(|| entry.id()))
+    }
+
+    fn read_blob(&self, blob: git2::Oid) -> anyhow::Result<Vec<u8>> {
+        Ok(self.repo.find_blob(blob)?.content().to_owned())
+    }
+
+    fn renamed_from(
+        &self,
+        parent: git2::Oid,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<std::path::PathBuf>> {
+        let parent_tree = self.repo.find_commit(parent)?.tree()?;
+        let tree = self.repo.find_commit(commit)?.tree()?;
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+        diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+        let renamed_from = diff
+            .deltas()
+            .filter(|delta| delta.status() == git2::Delta::Renamed)
+            .find(|delta| delta.new_file().path() == Some(path))
+            .and_then(|delta| delta.old_file().path().map(ToOwned::to_owned));
+        Ok(renamed_from)
+    }
+
+    fn diff_blobs(
+        &self,
+        old: &[u8],
+        old_path: &std::path::Path,
+        new: &[u8],
+        new_path: &std::path::Path,
+    ) -> anyhow::Result<Vec<DiffHunk>> {
+        let mut options = git2::DiffOptions::new();
+        options.context_lines(0).ignore_whitespace(true);
+        let patch = git2::Patch::from_buffers(
+            old,
+            Some(old_path),
+            new,
+            Some(new_path),
+            Some(&mut options),
+        )?;
+        let mut hunks = Vec::with_capacity(patch.num_hunks());
+        for i in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(i)?;
+            hunks.push(DiffHunk {
+                old_start: hunk.old_start() as usize,
+                old_lines: hunk.old_lines() as usize,
+                new_start: hunk.new_start() as usize,
+                new_lines: hunk.new_lines() as usize,
+            });
+        }
+        Ok(hunks)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn git2_follows_renames() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = git2::Repository::init(root_path).unwrap();
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+        let content = "one\ntwo\nthree\nfour\nfive\n";
+        let commit = |path: &str, content: &str, parents: &[git2::Oid]| {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            let mut tree = repo.treebuilder(None).unwrap();
+            tree.insert(path, blob, 0o100644).unwrap();
+            let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+            let parents = parents
+                .iter()
+                .map(|id| repo.find_commit(*id).unwrap())
+                .collect::<Vec<_>>();
+            let parents = parents.iter().collect::<Vec<_>>();
+            repo.commit(None, &sig, &sig, path, &tree, &parents)
+                .unwrap()
+        };
+        let old = commit("old.txt", content, &[]);
+        let new = commit("new.txt", &format!("{content}six\n"), &[old]);
+        let replacements = Replacements::default();
+        let backend = Git2::new(&repo, &replacements);
+
+        assert_eq!(backend.first_parent(new).unwrap(), Some(old));
+        assert_eq!(backend.first_parent(old).unwrap(), None);
+        let (old_path, new_path) = (
+            std::path::Path::new("old.txt"),
+            std::path::Path::new("new.txt"),
+        );
+        assert_eq!(backend.blob_at(old, new_path).unwrap(), None);
+        assert_eq!(
+            backend.renamed_from(old, new, new_path).unwrap().as_deref(),
+            Some(old_path)
+        );
+        let old_blob = backend
+            .read_blob(backend.blob_at(old, old_path).unwrap().unwrap())
+            .unwrap();
+        let new_blob = backend
+            .read_blob(backend.blob_at(new, new_path).unwrap().unwrap())
+            .unwrap();
+        assert_eq!(
+            backend
+                .diff_blobs(&old_blob, old_path, &new_blob, new_path)
+                .unwrap(),
+            [DiffHunk {
+                old_start: 5,
+                old_lines: 0,
+                new_start: 6,
+                new_lines: 1,
+            }]
+        );
+
+        root.close().unwrap();
+    }
+}
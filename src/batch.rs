@@ -0,0 +1,126 @@
+//! `--stdin-paths`, annotating many files in one process
+//!
+//! The repository and config are opened once for the whole batch.  Each path gets a line of
+//! JSON, the file as with `--format json` or `{"path": PATH, "error": MESSAGE, "code":
+//! EXIT_CODE}`, and the batch carries on past failures.
+
+use std::io::Write as _;
+
+use proc_exit::WithCodeResultExt;
+
+use crate::args::Args;
+use crate::args::OutputFormat;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::renderer::JsonRenderer;
+
+pub(crate) fn stdin_paths(config: &mut Config, args: &Args) -> proc_exit::ExitResult {
+    if args.format != OutputFormat::Json {
+        return Err(anyhow::format_err!(
+            "`--stdin-paths` only supports `--format json`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    let mut args = args.clone();
+    if let Some(Some(rev)) = &args.stdin_paths {
+        rev.clone_into(&mut args.rev);
+    }
+
+    let cwd = std::env::current_dir().with_code(proc_exit::Code::FAILURE)?;
+    let repo = crate::repo::open(&cwd, &args)?;
+    config.add_repo(&repo);
+
+    let delimiter = if args.null { b'\0' } else { b'\n' };
+    let mut out = crate::git_pager::unpaged(&args);
+    let (mut total, mut failed) = (0, 0);
+    for path in std::io::BufRead::split(std::io::stdin().lock(), delimiter) {
+        let path = path.with_code(exit_code::IO)?;
+        let path = if args.null {
+            path.as_slice()
+        } else {
+            path.strip_suffix(b"\r").unwrap_or(&path)
+        };
+        if path.is_empty() {
+            continue;
+        }
+        let path = to_path(path);
+        total += 1;
+        if let Err(err) = annotate(&repo, &cwd, &path, config, &args, &mut out) {
+            failed += 1;
+            let message = err.to_string();
+            // Also logs the error
+            let code = proc_exit::report(Err(err));
+            let record = JsonError {
+                path: path.display().to_string(),
+                error: message,
+                code: code.as_raw(),
+            };
+            if let Ok(record) = serde_json::to_string(&record) {
+                let _ = writeln!(out, "{record}");
+            }
+        }
+    }
+    let _ = out.flush();
+
+    if 0 < failed {
+        return Err(proc_exit::Exit::new(proc_exit::Code::FAILURE)
+            .with_message(format!("{failed} of {total} paths failed")));
+    }
+    log::debug!("annotated {total} paths");
+    Ok(())
+}
+
+/// A path that failed, its keys in this order rather than `serde_json::Map`'s
+#[derive(serde::Serialize)]
+struct JsonError {
+    path: String,
+    error: String,
+    code: i32,
+}
+
+fn annotate(
+    repo: &git2::Repository,
+    cwd: &std::path::Path,
+    path: &std::path::Path,
+    config: &mut Config,
+    args: &Args,
+    out: &mut dyn std::io::Write,
+) -> proc_exit::ExitResult {
+    let mut renderer = JsonRenderer::records(out);
+    match crate::blame::annotate(repo, cwd, path, config, args)? {
+        crate::blame::Target::File(blamed) => {
+            crate::renderer::render(
+                &mut renderer,
+                &blamed.shown_path,
+                &blamed.file,
+                &blamed.attributions,
+                &blamed.annotations,
+                &blamed.resolutions,
+            )
+            .with_code(proc_exit::Code::FAILURE)?;
+        }
+        crate::blame::Target::Skipped { path, notice } => {
+            renderer.skip_file(&path, &notice);
+            crate::renderer::Renderer::finish(&mut renderer).with_code(proc_exit::Code::FAILURE)?;
+        }
+        crate::blame::Target::Dir { .. } => {
+            return Err(anyhow::format_err!(
+                "{} is a directory; `--stdin-paths` only annotates files",
+                path.display()
+            ))
+            .with_code(exit_code::USAGE);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn to_path(bytes: &[u8]) -> std::path::PathBuf {
+    use std::os::unix::ffi::OsStrExt as _;
+    std::ffi::OsStr::from_bytes(bytes).into()
+}
+
+#[cfg(not(unix))]
+fn to_path(bytes: &[u8]) -> std::path::PathBuf {
+    String::from_utf8_lossy(bytes).into_owned().into()
+}
@@ -1,10 +1,55 @@
 use anyhow::Context as _;
 use proc_exit::WithCodeResultExt;
 
+use crate::args::ColorBy;
+use crate::args::OutputFormat;
+use crate::args::ResolveReverts;
+use crate::args::StyleComponent;
+use crate::attributes::Attributes;
+use crate::backend::Backend;
+use crate::boundary::BoundaryStyle;
+use crate::coloring::recent_style;
+use crate::coloring::Coloring;
+use crate::date::DateKind;
+use crate::date::Dates;
+use crate::deleted::find_deletion;
+use crate::deleted::has_path;
+use crate::engine::BlameEngine;
+use crate::engine::BlameHunk;
+use crate::engine::Engine;
+use crate::engine::BLAME_ENGINE;
+use crate::error::Error;
+use crate::exit_code;
 use crate::git2_config::Config;
 use crate::git2_config::DefaultField;
+use crate::git2_config::Field as _;
 use crate::git2_config::RawField;
+use crate::git_color::GitColor;
 use crate::git_pager::Pager;
+use crate::git_pager::PAGER_SECTIONS;
+use crate::glyphs::Glyphs;
+use crate::hash::HashFormat;
+use crate::ignore_revs::IgnoreRevs;
+use crate::layout::GutterLayout;
+use crate::layout::MAX_GUTTER_RATIO;
+use crate::layout::SHORT_HASH_LEN;
+use crate::lint::Lint;
+use crate::mine::Mine;
+use crate::palette::AuthorPalette;
+use crate::path_display::PathDisplay;
+use crate::path_display::Paths;
+use crate::pathspec::Pathspec;
+use crate::release::Releases;
+use crate::release::RELEASE_TAG_GLOB;
+use crate::renderer::render;
+use crate::renderer::AnnotatedLine;
+use crate::renderer::JsonRenderer;
+use crate::renderer::QuickfixRenderer;
+use crate::replace::Replacements;
+use crate::resolution::Resolutions;
+use crate::resolution::MAX_RESOLUTION_MERGES;
+use crate::unicode::ControlChars;
+use crate::unicode::SHOW_CONTROL_CHARS;
 
 pub(crate) fn blame(
     file_path: &std::path::Path,
@@ -13,31 +58,1639 @@ pub(crate) fn blame(
 ) -> proc_exit::ExitResult {
     let colored_stdout =
         anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
-    let total_width = terminal_size::terminal_size()
-        .map(|(w, _h)| w.0)
-        .or_else(|| std::env::var_os("COLUMNS").and_then(|s| s.to_str()?.parse::<u16>().ok()))
-        .unwrap_or(80) as usize;
+    let total_width = crate::render_test::terminal_width();
+    if args.no_blame {
+        check_no_blame(args)?;
+    }
+    if args.touched_since.is_some() && !args.stat && args.format != OutputFormat::Text {
+        return Err(anyhow::format_err!(
+            "`--touched-since` only supports `--format text`, or `--stat`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
 
     let cwd = std::env::current_dir().with_code(proc_exit::Code::FAILURE)?;
-    let repo = git2::Repository::discover(&cwd).with_code(proc_exit::Code::FAILURE)?;
-    config.add_repo(&repo);
-    let theme = config.get(&THEME);
+    let repo = crate::repo::open(&cwd, args)?;
+    if let Some([old, new]) = args.compare.as_deref() {
+        return crate::compare::compare(&repo, &cwd, file_path, old, new, config, args);
+    }
+    if args.changed_only {
+        return crate::changed::changed_only(&repo, &cwd, file_path, config, args);
+    }
+    if let Some(line) = args.line {
+        let line = usize::try_from(line).unwrap_or(usize::MAX);
+        return crate::detail::detail(&repo, &cwd, file_path, line, config, args);
+    }
+    let Blamed {
+        file_path,
+        tree_path,
+        shown_path,
+        file,
+        attributions,
+        annotations,
+        ignore,
+        resolutions,
+        diff_driver,
+        path_config,
+    } = match annotate(&repo, &cwd, file_path, config, args)? {
+        Target::File(blamed) => blamed,
+        Target::Dir { .. } if args.no_blame => {
+            return Err(anyhow::format_err!(
+                "`--no-blame` is only supported for files"
+            ))
+            .with_code(exit_code::USAGE);
+        }
+        Target::Dir {
+            commit,
+            tree_path,
+            paths,
+        } => {
+            return crate::summary::summarize(&repo, &commit, &tree_path, &paths, config, args);
+        }
+        Target::Skipped { path, notice } => return skip_file(&path, &notice, config, args),
+    };
+    let file_path = file_path.as_path();
 
-    let rel_path = to_repo_relative(&cwd, file_path, &repo).with_code(proc_exit::Code::FAILURE)?;
+    if args.stat {
+        let excluded = crate::ignore_markers::excluded_lines(&shown_path, &file, config);
+        return crate::stat::stat(
+            &shown_path,
+            &attributions,
+            &excluded,
+            &annotations,
+            config,
+            args,
+        );
+    }
 
-    let rev_obj = repo
-        .revparse_single(&args.rev)
-        .with_code(proc_exit::Code::FAILURE)?;
-    let rev_commit = rev_obj
-        .peel_to_commit()
-        .map_err(|_| {
-            anyhow::format_err!(
-                "Unsupported rev `{}` ({})",
-                args.rev,
-                rev_obj.kind().map(|k| k.str()).unwrap_or("unknown")
+    match args.format {
+        OutputFormat::Quickfix => {
+            // Not paged, so editors can read it
+            let mut renderer =
+                QuickfixRenderer::new(crate::git_pager::unpaged(args), args.hunks_only);
+            render(
+                &mut renderer,
+                &shown_path,
+                &file,
+                &attributions,
+                &annotations,
+                &resolutions,
+            )
+            .with_code(proc_exit::Code::FAILURE)?;
+            return Ok(());
+        }
+        OutputFormat::Json => {
+            let mut pager = Pager::resolve(config, args)?;
+            let mut pager = pager.start();
+            // JSON is always UTF-8
+            let pager = pager.as_writer().with_code(exit_code::IO)?;
+            let mut renderer = JsonRenderer::new(pager);
+            render(
+                &mut renderer,
+                &shown_path,
+                &file,
+                &attributions,
+                &annotations,
+                &resolutions,
             )
+            .with_code(proc_exit::Code::FAILURE)?;
+            return Ok(());
+        }
+        OutputFormat::Text => {}
+    }
+
+    let line_count = file.lines().count();
+    let highlight = colored_stdout && syntax_enabled(config, args, line_count);
+
+    let start = std::time::Instant::now();
+    let syntax_set = if highlight {
+        crate::assets::load_syntaxes()
+    } else {
+        syntect::parsing::SyntaxSet::new()
+    };
+    let theme_set = crate::assets::load_themes();
+    let theme = path_config
+        .theme(&theme_set)
+        .unwrap_or_else(|| config.get(&THEME));
+    let theme = theme_set
+        .get(&theme)
+        .or_else(|| {
+            log::debug!("unknown theme `{}`, using `{}`", theme, THEME_DEFAULT);
+            theme_set.get(THEME_DEFAULT)
         })
+        .expect("default theme is present");
+
+    let syntax = if highlight {
+        let mappings = config.get(&crate::language::SYNTAX_FOR_EXTENSION);
+        let attribute = args
+            .language
+            .is_none()
+            .then(|| path_config.language(&syntax_set))
+            .flatten();
+        let hints = crate::language::Hints {
+            language: args.language.as_deref(),
+            attribute: attribute.as_deref(),
+            mappings: &mappings,
+            diff_driver: diff_driver.as_deref(),
+            path: file_path,
+            first_line: file.lines().next().unwrap_or_default(),
+        };
+        let (syntax, source) =
+            crate::language::resolve(&syntax_set, &hints).with_code(exit_code::USAGE)?;
+        log::debug!(
+            "highlighting as {} per {source} in {:?}",
+            syntax.name,
+            start.elapsed()
+        );
+        Some(syntax)
+    } else {
+        None
+    };
+
+    let default_style = if args.no_blame {
+        NO_BLAME_STYLE
+    } else {
+        DEFAULT_STYLE
+    };
+    let mut style = args.style.clone().unwrap_or_else(|| default_style.to_vec());
+    let other_files = attributions
+        .iter()
+        .any(|a| a.path.as_ref().is_some_and(|path| *path != tree_path));
+    if args.style.is_none() && other_files {
+        log::debug!("showing the `file` style component since lines came from other files");
+        style.push(StyleComponent::File);
+    }
+    if args.style.is_none() && args.show_release {
+        style.push(StyleComponent::Release);
+    }
+    let style = style.as_slice();
+    let show = |component| style.contains(&component);
+    let glyphs = Glyphs::resolve(config);
+    let releases = if show(StyleComponent::Release) {
+        Releases::resolve(
+            &repo,
+            &config.get(&RELEASE_TAG_GLOB),
+            args.refresh_releases,
+            annotations.notes.keys().copied(),
+        )
+    } else {
+        Releases::default()
+    };
+
+    let line_count_width = line_count.to_string().len(); // bytes = chars = columns with digits
+    let show_marker = args.show_ignored || args.detect_resolution;
+    let marker_width = if show_marker { 1 } else { 0 };
+    let date_format = Dates::resolve(config, args);
+    let distance_cap = usize::try_from(config.get(&DISTANCE_CAP)).unwrap_or(0);
+    let hashes = HashFormat::resolve(config);
+    let measure = |layout: &GutterLayout| {
+        let origin_width = annotations
+            .notes
+            .values()
+            .map(|a| {
+                if layout.short_hash {
+                    crate::unicode::display_width(hashes.truncate(&a.hash, SHORT_HASH_LEN))
+                } else {
+                    crate::unicode::display_width(a.origin())
+                }
+            })
+            .max()
+            .unwrap_or(0);
+        let author_width = annotations
+            .notes
+            .values()
+            .map(|a| crate::unicode::display_width(&a.author_for(layout)))
+            .max()
+            .unwrap_or(0);
+        let date_width = annotations
+            .notes
+            .values()
+            .map(|a| crate::unicode::display_width(&a.date_for(layout, &date_format)))
+            .max()
+            .unwrap_or(0);
+        let distance_width = if show(StyleComponent::Distance) {
+            annotations
+                .notes
+                .values()
+                .map(|a| a.distance_for(distance_cap).len())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let mut field_widths = Vec::new();
+        if show(StyleComponent::Stripe) {
+            field_widths.push(crate::unicode::display_width(glyphs.stripe));
+        }
+        if show(StyleComponent::Hash) {
+            field_widths.push(origin_width + marker_width);
+        }
+        if show(StyleComponent::Author) {
+            field_widths.push(author_width);
+        }
+        if show(StyleComponent::Date) {
+            field_widths.push(date_width);
+        }
+        if show(StyleComponent::Release) {
+            field_widths.push(releases.width());
+        }
+        if show(StyleComponent::Distance) {
+            field_widths.push(distance_width);
+        }
+        if show(StyleComponent::Numbers) {
+            field_widths.push(line_count_width);
+        }
+        let fields_width =
+            field_widths.iter().sum::<usize>() + field_widths.len().saturating_sub(1);
+        let mut gutter_width = fields_width;
+        if show(StyleComponent::Grid) {
+            gutter_width += crate::unicode::display_width(&glyphs.grid) + 2;
+        } else if !field_widths.is_empty() {
+            gutter_width += 1;
+        }
+        (
+            gutter_width,
+            origin_width,
+            author_width,
+            date_width,
+            distance_width,
+            fields_width,
+        )
+    };
+    let max_gutter_width = config.get(&MAX_GUTTER_RATIO).max_width(total_width);
+    let layout = if args.style.is_some() {
+        // Respect exactly what was asked for
+        let (gutter_width, ..) = measure(&GutterLayout::default());
+        if max_gutter_width < gutter_width {
+            log::warn!(
+                "gutter takes {gutter_width} of {total_width} columns; remove components from `--style` to make room for the code"
+            );
+        }
+        GutterLayout::default()
+    } else {
+        GutterLayout::fit(max_gutter_width, |layout| measure(layout).0)
+    };
+    let (gutter_width, origin_width, author_width, date_width, distance_width, fields_width) =
+        measure(&layout);
+
+    let code_width = total_width.saturating_sub(gutter_width);
+
+    let highlighter = match syntax {
+        Some(syntax) => Highlighter::enabled(syntax, theme),
+        None if colored_stdout => Highlighter::plain(theme),
+        None => Highlighter::disabled(),
+    }
+    .controls(config.get(&SHOW_CONTROL_CHARS))
+    .tab_width(path_config.tab_width().or_else(|| {
+        usize::try_from(config.get(&TAB_WIDTH))
+            .ok()
+            .filter(|width| *width != 0)
+    }));
+
+    let reset = if colored_stdout {
+        anstyle::Reset.render().to_string()
+    } else {
+        "".to_owned()
+    };
+    let gutter_styles = if colored_stdout {
+        GutterStyles::from_theme(highlighter.theme()).with_overrides(config)
+    } else {
+        GutterStyles::default()
+    };
+    let palette = (args.color_by == Some(ColorBy::Author) && colored_stdout)
+        .then(|| AuthorPalette::from_config(config));
+    let coloring = if colored_stdout {
+        Coloring::resolve(config, args)
+    } else {
+        Coloring::None
+    };
+    let wrap = textwrap::Options::new(code_width)
+        .break_words(false)
+        .wrap_algorithm(textwrap::WrapAlgorithm::FirstFit);
+
+    let marks = args.pager_marks.then(|| hunk_marks(style)).flatten();
+    let mut pager = Pager::resolve(config, args)?
+        .marks(marks.as_deref())
+        .sections(config.get(&PAGER_SECTIONS), hunk_marks(style).as_deref());
+    let section_mark = pager
+        .section_marks()
+        .then_some(crate::git_pager::SECTION_MARK);
+    let mut pager = pager.start();
+    let pager = pager.as_writer().with_code(exit_code::IO)?;
+    let mut transcoder;
+    let pager = if let Some(encoding) = crate::encoding::output_encoding(config) {
+        log::debug!("writing output as {}", encoding.name());
+        transcoder = crate::encoding::Transcoder::new(pager, encoding);
+        &mut transcoder as &mut dyn std::io::Write
+    } else {
+        pager
+    };
+    let no_newline_marker = if args.show_no_newline && !file.is_empty() && !file.ends_with('\n') {
+        let marker = config.get(&NO_NEWLINE_MARKER);
+        let style = if colored_stdout {
+            anstyle::Effects::INVERT.into()
+        } else {
+            anstyle::Style::new()
+        };
+        format!("{reset}{}{marker}{}", style.render(), style.render_reset())
+    } else {
+        String::new()
+    };
+    // Where the code would be, for lines that aren't from the file
+    let blank_gutter = |grid: &str| {
+        let style = gutter_styles.grid;
+        let mut gutter = " ".repeat(fields_width);
+        if show(StyleComponent::Grid) {
+            if fields_width != 0 {
+                gutter.push(' ');
+            }
+            gutter.push_str(&format!(
+                "{}{grid}{} ",
+                style.render(),
+                style.render_reset()
+            ));
+        } else if fields_width != 0 {
+            gutter.push(' ');
+        }
+        gutter
+    };
+    let separator = show(StyleComponent::Separator).then(|| {
+        if show(StyleComponent::Grid) {
+            blank_gutter(glyphs.hunk_sep)
+        } else {
+            let style = gutter_styles.grid;
+            let rule = glyphs.hunk_rule.repeat(fields_width.max(1));
+            format!("{}{rule}{}", style.render(), style.render_reset())
+        }
+    });
+    let note_style = if colored_stdout {
+        anstyle::Effects::DIMMED.into()
+    } else {
+        anstyle::Style::new()
+    };
+    let fade_before = args
+        .fade_older_than
+        .clone()
+        .or_else(|| config.get(&crate::date::FADE_OLDER_THAN))
+        .filter(|_| colored_stdout)
+        .map(|age| age.before(crate::date::now()));
+    let boundary_style = BoundaryStyle::resolve(config, args);
+    let mut squeezed = args
+        .squeeze_unchanged
+        .map(|keep| squeeze_runs(&attributions, keep))
+        .unwrap_or_default();
+    if boundary_style == BoundaryStyle::Hide {
+        hide_boundaries(&attributions, &annotations, &mut squeezed);
+    }
+    if let Some(since) = &args.touched_since {
+        let since = since.seconds(crate::date::now());
+        hide_untouched(&attributions, &annotations, since, &mut squeezed);
+    }
+    let mut renderer = TerminalRenderer {
+        out: pager,
+        syntax_set: &syntax_set,
+        highlighter,
+        wrap,
+        code_width,
+        style,
+        layout,
+        origin_width,
+        author_width,
+        date_width,
+        line_count,
+        line_count_width,
+        show_marker,
+        show_ignored: args.show_ignored,
+        date_format,
+        gutter_styles,
+        palette,
+        coloring,
+        now: crate::date::now(),
+        reset,
+        no_newline_marker,
+        separator,
+        squeeze_gutter: blank_gutter(glyphs.continued),
+        squeezed,
+        touched_since: args.touched_since.clone(),
+        excluded: crate::ignore_markers::excluded_lines(&shown_path, &file, config),
+        tree_path: &tree_path,
+        note_style,
+        move_badge_threshold: usize::try_from(config.get(&MOVE_BADGE_THRESHOLD)).unwrap_or(0),
+        fade_before,
+        dim_boundaries: boundary_style == BoundaryStyle::Dim,
+        releases,
+        distance_width,
+        distance_cap,
+        hashes,
+        glyphs,
+        annotations: &annotations,
+        claimed: (args.show_ignored && !ignore.is_empty()).then(Default::default),
+        authors: Default::default(),
+        mine: Mine::resolve(&repo, config, args),
+        mine_lines: (0, 0),
+        annotate_blank_lines: args
+            .annotate_blank_lines
+            .unwrap_or_else(|| config.get(&ANNOTATE_BLANK_LINES)),
+        deferred_hunk: false,
+        resolve_reverts: args.resolve_reverts,
+        section_mark,
+        lint: args.lint_whitespace.then(|| Lint::resolve(config)),
+        lint_counts: Default::default(),
+    };
+    render(
+        &mut renderer,
+        &shown_path,
+        &file,
+        &attributions,
+        &annotations,
+        &resolutions,
+    )
+    .with_code(proc_exit::Code::FAILURE)?;
+
+    Ok(())
+}
+
+/// What [`annotate`] found at a path
+pub(crate) enum Target<'r> {
+    File(Blamed),
+    /// Summarized rather than blamed
+    Dir {
+        commit: git2::Commit<'r>,
+        tree_path: std::path::PathBuf,
+        paths: Paths,
+    },
+    /// Per gitattributes without `--force-generated`, or a Git LFS pointer
+    Skipped {
+        path: String,
+        /// Why, and how to annotate it anyway
+        notice: String,
+    },
+}
+
+/// A file with the commit each of its lines came from
+pub(crate) struct Blamed {
+    /// Without pathspec magic, for picking a syntax
+    pub(crate) file_path: std::path::PathBuf,
+    /// `/`-separated from the top of the repo
+    pub(crate) tree_path: std::path::PathBuf,
+    /// Per `--path-display`
+    pub(crate) shown_path: String,
+    pub(crate) file: String,
+    pub(crate) attributions: Vec<Attribution>,
+    pub(crate) annotations: Annotations,
+    pub(crate) ignore: IgnoreRevs,
+    /// Indexed by line, whether a merge introduced it
+    pub(crate) resolutions: Vec<bool>,
+    /// The `diff=` gitattribute, a hint for the syntax
+    pub(crate) diff_driver: Option<String>,
+    /// Per the worktree's gitattributes
+    pub(crate) path_config: crate::path_config::PathConfig,
+}
+
+/// Blame `file_path` in `repo` as of `args.rev`, `cwd` being where relative paths start
+pub(crate) fn annotate<'r>(
+    repo: &'r git2::Repository,
+    cwd: &std::path::Path,
+    file_path: &std::path::Path,
+    config: &mut Config,
+    args: &crate::args::Args,
+) -> Result<Target<'r>, proc_exit::Exit> {
+    config.add_repo(repo);
+    let hashes = HashFormat::resolve(config);
+
+    // Like git, `:2:PATH` is from the top of the worktree
+    let (side, file_path, cwd) = match crate::conflict::split_stage(file_path) {
+        Some((side, path)) => (Some(side), path, repo.workdir().unwrap_or(cwd)),
+        None => (crate::conflict::Side::from_args(args), file_path, cwd),
+    };
+    let side_args;
+    let args = if let Some(side) = side {
+        let mut args = args.clone();
+        args.rev = crate::conflict::rev(repo, side)?;
+        side_args = args;
+        &side_args
+    } else {
+        args
+    };
+    let replacements =
+        Replacements::load(repo, config, args).with_code(proc_exit::Code::FAILURE)?;
+    let backend = crate::backend::Git2::new(repo, &replacements);
+    let at_args;
+    let args = if let Some(at) = &args.at {
+        let (rev, commit) = crate::rev::at_date(
+            repo,
+            &replacements,
+            &args.rev,
+            at.seconds(crate::date::now()),
+        )?;
+        log::info!(
+            "as of {at}, {rev} is {} ({}), committed {}",
+            show_id(&commit, &hashes),
+            crate::encoding::subject(&commit),
+            crate::date::format_short(commit.committer().when())
+        );
+        let mut args = args.clone();
+        args.rev = rev;
+        at_args = args;
+        &at_args
+    } else {
+        args
+    };
+
+    let pathspec = Pathspec::parse(file_path);
+    let file_path = pathspec.path.as_path();
+    let mut rel_path = to_repo_relative(cwd, file_path, repo, args.no_blame)?;
+    let mut tree_path = to_tree_path(&rel_path);
+    let paths = Paths::new(
+        PathDisplay::resolve(config, args),
+        cwd,
+        // Only bare with `--no-blame`, where paths are from the top of the tree
+        repo.workdir().unwrap_or_else(|| repo.path()),
+    );
+
+    // Attributed by blaming `FILE` at `REV`, once it's checked to be the same blob
+    let blob = args
+        .blob
+        .as_deref()
+        .map(|oid| read_blob(repo, oid))
+        .transpose()?;
+    let contents = if let Some(side) = side {
+        Some(crate::conflict::read_stage(repo, &tree_path, side)?)
+    } else {
+        args.contents
+            .as_deref()
+            .map(read_contents)
+            .transpose()
+            .with_code(proc_exit::Code::FAILURE)?
+    };
+    let date = DateKind::resolve(config, args);
+    let uncommitted = |file: Vec<u8>| {
+        let line_count = String::from_utf8_lossy(&file).lines().count();
+        let attributions = (0..line_count)
+            .map(|i| Attribution {
+                commit: git2::Oid::zero(),
+                path: None,
+                line: i + 1,
+                ignored_via: Vec::new(),
+                previous: None,
+            })
+            .collect::<Vec<_>>();
+        let annotations = Annotations::new(repo, date, [git2::Oid::zero()]);
+        (attributions, annotations, IgnoreRevs::default(), file)
+    };
+
+    let rev_name = crate::rev::rev_name(args);
+    let mut diff_driver = None;
+    // Where a bare repository's attributes are read from
+    let mut rev_tree = None;
+    let (attributions, annotations, ignore, file) = 'blame: {
+        if let Some((oid, content)) = blob.as_ref().filter(|_| !attributed(args)) {
+            log::debug!("showing blob {oid} without attribution");
+            break 'blame (
+                unattributed(content),
+                Annotations::unattributed(),
+                IgnoreRevs::default(),
+                content.clone(),
+            );
+        }
+        let rev_obj = match crate::rev::revparse_single(repo, &args.rev) {
+            Ok(rev_obj) => rev_obj,
+            Err(err) => {
+                if let Some(rev_file) = &args.rev_file {
+                    return Err(anyhow::format_err!(
+                        "could not resolve `{}` from {}: {err}",
+                        args.rev,
+                        rev_file.display()
+                    ))
+                    .with_code(exit_code::BAD_REV);
+                }
+                if !names_head(&args.rev) || !is_unborn(repo) {
+                    return Err(err.into());
+                }
+                if let Some(contents) = contents {
+                    log::warn!(
+                        "repository has no commits yet; showing every line as not committed"
+                    );
+                    break 'blame uncommitted(contents);
+                }
+                return Err(anyhow::format_err!("repository has no commits yet"))
+                    .with_code(exit_code::USAGE);
+            }
+        };
+        let mut rev = args.rev.clone();
+        let mut rev_commit = crate::rev::peel_to_commit(&rev_obj, &rev_name)?;
+        if let Some(found) = find_tree_case(
+            repo,
+            &rev_commit,
+            &tree_path,
+            &rel_path,
+            args.icase_paths || pathspec.icase,
+            config.get(&crate::pathspec::PRECOMPOSE_UNICODE),
+        )
+        .with_code(exit_code::PATH_NOT_IN_TREE)?
+        {
+            log::info!(
+                "showing {} for {}",
+                paths.show(&found),
+                paths.show(&tree_path)
+            );
+            rel_path = found.clone();
+            tree_path = found;
+        }
+        if blob.is_none() && is_dir(&rev_commit, &tree_path).with_code(proc_exit::Code::FAILURE)? {
+            return Ok(Target::Dir {
+                commit: rev_commit,
+                tree_path,
+                paths,
+            });
+        }
+        if !has_path(&rev_commit, &tree_path).with_code(proc_exit::Code::FAILURE)? {
+            let empty = rev_commit
+                .tree()
+                .with_code(proc_exit::Code::FAILURE)?
+                .is_empty();
+            if !args.find_deleted {
+                if let Some(contents) = contents {
+                    log::warn!(
+                        "{} does not exist at {}; showing every line as not committed",
+                        paths.show(&tree_path),
+                        rev_name
+                    );
+                    break 'blame uncommitted(contents);
+                }
+                if empty {
+                    return Err(anyhow::format_err!("{} has no files", rev_name))
+                        .with_code(exit_code::USAGE);
+                }
+                return Err(Error::PathNotInTree {
+                    path: paths.show(&tree_path),
+                    rev: rev_name,
+                    status: crate::status_hints::probe(repo, config, args, &tree_path),
+                }
+                .into());
+            }
+            let deletion = find_deletion(
+                repo,
+                &replacements,
+                &rev_commit,
+                &args.rev,
+                &tree_path,
+                args,
+                crate::commit_graph::CommitGraph::load(repo, config, args).as_ref(),
+            )
+            .with_code(proc_exit::Code::FAILURE)?
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "{} was not found in the last {} commits of {}",
+                    paths.show(&tree_path),
+                    args.max_commits,
+                    rev_name
+                )
+            })
+            .with_code(exit_code::PATH_NOT_IN_TREE)?;
+            log::warn!(
+                "file was deleted in {} ({}); showing it as of {}",
+                show_id(&deletion.deleted_in, &hashes),
+                crate::encoding::subject(&deletion.deleted_in),
+                show_id(&deletion.last_seen, &hashes),
+            );
+            rev = deletion.rev;
+            rev_commit = deletion.last_seen;
+        }
+        if let Some((oid, _)) = &blob {
+            let actual = rev_commit
+                .tree()
+                .and_then(|tree| tree.get_path(&tree_path))
+                .with_code(proc_exit::Code::FAILURE)?
+                .id();
+            if actual != *oid {
+                return Err(Error::BlobMismatch {
+                    path: paths.show(&tree_path),
+                    rev: rev_name,
+                    blob: *oid,
+                    actual,
+                }
+                .into());
+            }
+        }
+        let attributes = Attributes::load(repo, config, &rev_commit, &tree_path)
+            .with_code(proc_exit::Code::FAILURE)?;
+        rev_tree = rev_commit.tree().ok();
+        if !args.force_generated {
+            if let Some(reason) = skip_reason(&attributes, &tree_path) {
+                let path = paths.show(&tree_path);
+                return Ok(Target::Skipped {
+                    notice: skip_notice(&path, reason),
+                    path,
+                });
+            }
+        }
+        let unblamed = args.no_blame && blob.is_none();
+        if !unblamed {
+            // Before reading `REV`'s version, which may be missing too
+            let no_lazy_fetch_env = std::env::var("GIT_NO_LAZY_FETCH")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false);
+            let lazy_fetch = !args.no_lazy_fetch && !no_lazy_fetch_env;
+            crate::promisor::ensure_history(repo, &rev_commit, &tree_path, lazy_fetch)
+                .with_code(proc_exit::Code::FAILURE)?;
+        }
+        // The content of a Git LFS pointer, per `--lfs-smudge`
+        let mut smudge = None;
+        if contents.is_none() && blob.is_none() {
+            let pointer = crate::lfs::Pointer::at(repo, &rev_commit, &tree_path)
+                .with_code(proc_exit::Code::FAILURE)?;
+            if let Some(pointer) = pointer {
+                let path = paths.show(&tree_path);
+                if !args.lfs_smudge {
+                    return Ok(Target::Skipped {
+                        notice: pointer.notice(&path, None),
+                        path,
+                    });
+                }
+                smudge = match smudge_rev(
+                    repo,
+                    &backend,
+                    config,
+                    &attributes,
+                    &rev_commit,
+                    &tree_path,
+                ) {
+                    Ok(smudged) => Some(smudged),
+                    Err(err) => {
+                        return Ok(Target::Skipped {
+                            notice: pointer.notice(&path, Some(&err)),
+                            path,
+                        });
+                    }
+                };
+                log::info!(
+                    "note: {path} is stored in Git LFS, so its lines are attributed to the commits \
+                     that changed its pointer"
+                );
+            }
+        }
+        diff_driver = match attributes.get(&tree_path, "diff") {
+            crate::attributes::State::Value(driver) => Some(driver),
+            _ => None,
+        };
+        if unblamed {
+            log::debug!("showing {} at {rev} without blame", tree_path.display());
+            let file = if let Some((content, _)) = smudge {
+                content
+            } else {
+                read_file(repo, &rev_commit, &rev, &rel_path, &paths)
+                    .with_code(proc_exit::Code::FAILURE)?
+            };
+            break 'blame (
+                unattributed(&file),
+                Annotations::unattributed(),
+                IgnoreRevs::default(),
+                file,
+            );
+        }
+        // Naming a tag or branch can show a much older version than expected
+        if contents.is_none()
+            && rev == args.rev
+            && !names_head(&args.rev)
+            && differs_from_head(repo, &rev_commit, &tree_path)
+        {
+            log::info!(
+                "note: {} differs from HEAD; showing it as of {}",
+                paths.show(&tree_path),
+                rev_name
+            );
+        }
+        log::debug!(
+            "blaming {} at {} ({})",
+            tree_path.display(),
+            rev,
+            rev_commit.id()
+        );
+        let start = std::time::Instant::now();
+        let mut objects = crate::objects::Objects::new(repo, args.strict_objects)
+            .with_code(proc_exit::Code::FAILURE)?;
+        let (smudged, smudge) = smudge.unzip();
+        let mut engine = match (config.get(&BLAME_ENGINE), smudge) {
+            // Only the internal engine can diff content that isn't in the repo
+            (_, Some(smudge)) => Some(Engine::new(&backend).objects(objects).smudge(smudge)),
+            (BlameEngine::Internal, None) if contents.is_none() => {
+                Some(Engine::new(&backend).objects(objects))
+            }
+            (engine, None) => {
+                if engine == BlameEngine::Internal {
+                    log::debug!("blaming `--contents` with libgit2");
+                }
+                // libgit2 can hang on, or silently skip past, objects it can't read
+                let unreadable = objects
+                    .first_unreadable(&replacements, rev_commit.id(), &tree_path)
+                    .with_code(proc_exit::Code::FAILURE)?;
+                match unreadable {
+                    Some(id) if contents.is_some() => {
+                        return Err(crate::objects::unreadable_error(id, &tree_path))
+                            .with_code(proc_exit::Code::FAILURE);
+                    }
+                    Some(id) => {
+                        log::debug!("{id} can't be read, so blaming with the internal engine");
+                        Some(Engine::new(&backend).objects(objects))
+                    }
+                    None => None,
+                }
+            }
+        };
+        let hunks = if let Some(engine) = engine.as_mut() {
+            engine
+                .blame(rev_commit.id(), &tree_path)
+                .with_code(proc_exit::Code::FAILURE)?
+        } else {
+            let blame = repo
+                .blame_file(&tree_path, Some(&mut blame_options(rev_commit.id())))
+                .with_code(proc_exit::Code::FAILURE)?;
+            if let Some(contents) = contents.as_deref() {
+                let blame = blame
+                    .blame_buffer(contents)
+                    .with_code(proc_exit::Code::FAILURE)?;
+                BlameHunk::from_git2(&blame)
+            } else {
+                BlameHunk::from_git2(&blame)
+            }
+        };
+        log::debug!("blamed {} hunks in {:?}", hunks.len(), start.elapsed());
+        let unreadable = engine
+            .as_mut()
+            .map(|engine| engine.take_unreadable())
+            .unwrap_or_default();
+        let ignore = IgnoreRevs::load(repo, config, args).with_code(proc_exit::Code::FAILURE)?;
+        let start = std::time::Instant::now();
+        let attributions = attribute_lines(repo, &backend, &hunks, &ignore, &replacements, engine)
+            .with_code(proc_exit::Code::FAILURE)?;
+        log::debug!("attributed lines in {:?}", start.elapsed());
+        let placeholder = crate::objects::placeholder();
+        crate::objects::report(
+            &unreadable,
+            attributions
+                .iter()
+                .filter(|a| a.commit == placeholder)
+                .count(),
+        );
+        let mut annotations = Annotations::new(
+            repo,
+            date,
+            attributions
+                .iter()
+                .flat_map(|a| std::iter::once(a.commit).chain(a.ignored_via.iter().copied())),
+        );
+        if args.resolve_reverts.is_some() {
+            annotations.resolve_reverts(repo);
+        }
+        annotations
+            .relative_origin(repo, &rev_commit, &rev, &replacements)
+            .with_code(proc_exit::Code::FAILURE)?;
+        annotations.spell_hashes(&hashes);
+        annotations.mark_boundaries(repo);
+
+        let file = if let Some(contents) = contents.or(smudged) {
+            contents
+        } else {
+            read_file(repo, &rev_commit, &rev, &rel_path, &paths)
+                .with_code(proc_exit::Code::FAILURE)?
+        };
+        (attributions, annotations, ignore, file)
+    };
+
+    let shown_path = paths.show(&tree_path);
+    let file = convert_file(&file, std::path::Path::new(&shown_path))
         .with_code(proc_exit::Code::FAILURE)?;
+    let resolutions = if args.detect_resolution {
+        let lines = file
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                if i == 0 {
+                    l.strip_prefix('\u{feff}').unwrap_or(l)
+                } else {
+                    l
+                }
+            })
+            .collect::<Vec<_>>();
+        let max_merges = usize::try_from(config.get(&MAX_RESOLUTION_MERGES)).unwrap_or(0);
+        Resolutions::new(repo)
+            .detect(&attributions, &lines, max_merges)
+            .with_code(proc_exit::Code::FAILURE)?
+    } else {
+        vec![false; attributions.len()]
+    };
+
+    let path_config = Attributes::load_worktree(repo, config, rev_tree.as_ref(), &tree_path)
+        .map(|attributes| crate::path_config::PathConfig::new(&attributes, &tree_path))
+        .with_code(proc_exit::Code::FAILURE)?;
+
+    Ok(Target::File(Blamed {
+        file_path: file_path.to_owned(),
+        tree_path,
+        shown_path,
+        file,
+        attributions,
+        annotations,
+        ignore,
+        resolutions,
+        diff_driver,
+        path_config,
+    }))
+}
+
+/// A pattern for `less` matching the first line of each hunk, which shows its commit
+///
+/// Other lines have `Glyphs::continued` or blanks there instead.  `less` ignores colors when
+/// searching, so only the text has to match.
+fn hunk_marks(style: &[StyleComponent]) -> Option<String> {
+    if !style.contains(&StyleComponent::Hash) {
+        log::debug!("not marking hunks without the `hash` style component");
+        return None;
+    }
+    // Only the stripe comes before the hash
+    let stripe = if style.contains(&StyleComponent::Stripe) {
+        "[^ ]+ "
+    } else {
+        ""
+    };
+    Some(format!("^{stripe}[0-9A-Za-z]"))
+}
+
+/// With few authors, telling their colors apart is easy enough without a legend
+const LEGEND_MIN_AUTHORS: usize = 3;
+
+/// The gutter beside highlighted code, for people
+struct TerminalRenderer<'a, W> {
+    out: W,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    highlighter: Highlighter<'a>,
+    wrap: textwrap::Options<'a>,
+    /// Columns after the gutter
+    code_width: usize,
+    style: &'a [StyleComponent],
+    layout: GutterLayout,
+    origin_width: usize,
+    author_width: usize,
+    date_width: usize,
+    line_count: usize,
+    line_count_width: usize,
+    show_marker: bool,
+    show_ignored: bool,
+    date_format: Dates,
+    gutter_styles: GutterStyles,
+    palette: Option<AuthorPalette>,
+    coloring: Coloring,
+    now: i64,
+    reset: String,
+    no_newline_marker: String,
+    /// Written before each hunk after the first, with the `separator` style component
+    separator: Option<String>,
+    /// Beside the marker for lines hidden by `--squeeze-unchanged`, `--hide-boundary`, or
+    /// `--touched-since`
+    squeeze_gutter: String,
+    /// Indexed by line, empty without `--squeeze-unchanged`, `--hide-boundary`, or
+    /// `--touched-since`
+    squeezed: Vec<Squeeze>,
+    /// As given to `--touched-since`, for the markers of older lines
+    touched_since: Option<crate::date::Approxidate>,
+    /// Indexed by line, whether it's between ignore markers, so its gutter is dimmed
+    excluded: Vec<bool>,
+    /// The file being blamed, to tell which lines came from others
+    tree_path: &'a std::path::Path,
+    /// For what's noted after the first line of a hunk
+    note_style: anstyle::Style,
+    /// Per `dive.move-badge-threshold`, 0 being never
+    move_badge_threshold: usize,
+    /// Lines from commits before this are dimmed, with `--fade-older-than`
+    fade_before: Option<i64>,
+    /// Per `dive.boundary-style = dim`
+    dim_boundaries: bool,
+    /// Empty without the `release` style component
+    releases: Releases,
+    /// 0 without the `distance` style component
+    distance_width: usize,
+    /// Per `dive.distance-cap`
+    distance_cap: usize,
+    hashes: HashFormat,
+    glyphs: Glyphs,
+    annotations: &'a Annotations,
+    /// Lines claimed through each ignored revision, when summarizing them
+    claimed: Option<std::collections::BTreeMap<git2::Oid, usize>>,
+    /// Names by lowercased email, for the `--color-by` legend
+    authors: std::collections::BTreeMap<String, String>,
+    mine: Option<Mine>,
+    /// Lines by the current user, then all lines, with `--highlight-mine`
+    mine_lines: (usize, usize),
+    /// Per `dive.annotate-blank-lines`, else whitespace-only lines get no commit details
+    annotate_blank_lines: bool,
+    /// A hunk started on a blank line that wasn't annotated
+    deferred_hunk: bool,
+    /// Which side of a revert or cherry-pick goes in the gutter, the other noted after the code
+    resolve_reverts: Option<ResolveReverts>,
+    /// Before the first line of each hunk, per `dive.pager-sections`
+    section_mark: Option<&'static str>,
+    /// With `--lint-whitespace`
+    lint: Option<Lint>,
+    /// Lines with problems, then by lowercased email the author's name and theirs
+    lint_counts: (
+        crate::lint::Counts,
+        std::collections::BTreeMap<String, (String, crate::lint::Counts)>,
+    ),
+}
+
+impl<W: std::io::Write> TerminalRenderer<'_, W> {
+    /// In place of hidden lines, like `… 3 more lines from abc1234 …`
+    fn write_marker(&mut self, text: std::fmt::Arguments<'_>) {
+        let style = self.gutter_styles.grid;
+        let ellipsis = self.glyphs.ellipsis;
+        let _ = writeln!(
+            self.out,
+            "{}{}{ellipsis} {text} {ellipsis}{}{}",
+            self.squeeze_gutter,
+            style.render(),
+            style.render_reset(),
+            self.reset
+        );
+    }
+
+    /// Whether the line at `number` was at least `move_badge_threshold` lines away in its commit
+    fn is_moved(&self, number: usize, attribution: &Attribution) -> bool {
+        self.move_badge_threshold != 0
+            && !attribution.commit.is_zero()
+            && attribution.commit != crate::objects::placeholder()
+            && self.move_badge_threshold <= number.abs_diff(attribution.line)
+    }
+}
+
+impl<W: std::io::Write> crate::renderer::Renderer for TerminalRenderer<'_, W> {
+    fn begin_file(&mut self, _path: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn line(&mut self, line: &AnnotatedLine<'_>, commit: &Annotation) -> anyhow::Result<()> {
+        let style = self.style;
+        let show = |component| style.contains(&component);
+        let gutter_styles = self.gutter_styles;
+        let author_color = |ann: &Annotation| self.palette.as_ref().map(|p| p.color(&ann.email));
+
+        // The commit in the gutter and the other side of a revert or cherry-pick, if noted
+        let mut shown_id = line.attribution.commit;
+        let (commit, other) = match (commit.via, self.resolve_reverts) {
+            (Some(via), Some(mode)) => {
+                let original = self
+                    .annotations
+                    .notes
+                    .get(&via.commit)
+                    .expect("originals of reverts are annotated");
+                if mode == ResolveReverts::Replace {
+                    shown_id = via.commit;
+                    (original, Some((via.kind, commit)))
+                } else {
+                    (commit, Some((via.kind, original)))
+                }
+            }
+            _ => (commit, None),
+        };
+
+        if let Some(claimed) = &mut self.claimed {
+            if let Some(id) = line.attribution.ignored_via.first() {
+                *claimed.entry(*id).or_insert(0) += 1;
+            }
+        }
+        let is_mine = self
+            .mine
+            .as_ref()
+            .is_some_and(|mine| mine.contains(&commit.email));
+        if self.mine.is_some() {
+            self.mine_lines.0 += usize::from(is_mine);
+            self.mine_lines.1 += 1;
+        }
+        let is_missing = line.attribution.commit == crate::objects::placeholder();
+        let accent = |style: anstyle::Style| {
+            if is_mine {
+                gutter_styles.mine
+            } else if is_missing {
+                gutter_styles.missing
+            } else {
+                style
+            }
+        };
+        if self.palette.is_some() {
+            self.authors
+                .entry(commit.email.to_lowercase())
+                .or_insert_with(|| commit.author.clone());
+        }
+
+        let faded = self
+            .fade_before
+            .is_some_and(|cutoff| commit.time.seconds() < cutoff)
+            || (self.dim_boundaries && commit.boundary.is_some());
+        let excluded = self.excluded.get(line.number - 1).copied().unwrap_or(false);
+        let (marks, mark_style) = match &self.lint {
+            Some(lint) => (lint.find(line.text), lint.style),
+            None => (Vec::new(), anstyle::Style::new()),
+        };
+        if self.lint.is_some() {
+            self.lint_counts.0.add(&marks);
+            self.lint_counts
+                .1
+                .entry(commit.email.to_lowercase())
+                .or_insert_with(|| (commit.author.clone(), Default::default()))
+                .1
+                .add(&marks);
+        }
+        // Hidden lines are still highlighted so multi-line constructs stay in sync
+        let file_line = self.highlighter.highlight_marked(
+            line.text,
+            self.syntax_set,
+            faded,
+            &marks,
+            mark_style,
+        )?;
+        let squeeze = self
+            .squeezed
+            .get(line.number - 1)
+            .copied()
+            .unwrap_or(Squeeze::Shown);
+        if squeeze == Squeeze::Hidden {
+            return Ok(());
+        }
+        if line.hunk_start && 1 < line.number {
+            if let Some(separator) = &self.separator {
+                let _ = writeln!(self.out, "{separator}{}", self.reset);
+            }
+        }
+        let lines = |hidden: usize| if hidden == 1 { "line" } else { "lines" };
+        match squeeze {
+            Squeeze::Marker(hidden) => {
+                self.write_marker(format_args!(
+                    "{hidden} more {} from {}",
+                    lines(hidden),
+                    commit.hash
+                ));
+                return Ok(());
+            }
+            Squeeze::Boundary(hidden) => {
+                let boundary = commit.boundary.expect("only boundary lines are hidden");
+                self.write_marker(format_args!(
+                    "{hidden} {} from {}, {}",
+                    lines(hidden),
+                    commit.hash,
+                    boundary.describe()
+                ));
+                return Ok(());
+            }
+            Squeeze::Untouched(hidden) => {
+                let since = self
+                    .touched_since
+                    .clone()
+                    .expect("only with `--touched-since` are untouched lines hidden");
+                self.write_marker(format_args!(
+                    "{hidden} {} from before {since}",
+                    lines(hidden)
+                ));
+                return Ok(());
+            }
+            Squeeze::Shown | Squeeze::Hidden => {}
+        }
+        let blank = !self.annotate_blank_lines && line.text.trim().is_empty();
+        let hunk_start = if blank {
+            // Show the commit on the hunk's first line with content instead
+            self.deferred_hunk |= line.hunk_start;
+            false
+        } else {
+            line.hunk_start || std::mem::take(&mut self.deferred_hunk)
+        };
+        #[allow(clippy::never_loop)]
+        for (i, visual_line) in textwrap::wrap(&file_line, &self.wrap)
+            .into_iter()
+            .enumerate()
+        {
+            let ann = (i == 0 && hunk_start).then_some(commit);
+            let details_style = |style: anstyle::Style| {
+                let style = match self.coloring {
+                    Coloring::None => style,
+                    Coloring::HighlightRecent => recent_style(commit.time, self.now),
+                    Coloring::RepeatedLines if ann.is_none() => {
+                        style.effects(style.get_effects() | anstyle::Effects::DIMMED)
+                    }
+                    Coloring::RepeatedLines => style,
+                };
+                if faded || excluded {
+                    style.effects(style.get_effects() | anstyle::Effects::DIMMED)
+                } else {
+                    style
+                }
+            };
+
+            let mut fields = Vec::new();
+            if show(StyleComponent::Stripe) {
+                let style = author_color(commit).unwrap_or(gutter_styles.grid);
+                let stripe = if blank { " " } else { self.glyphs.stripe };
+                fields.push(format!(
+                    "{}{stripe}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            if show(StyleComponent::Hash) {
+                let origin_width = self.origin_width;
+                let origin = ann
+                    .map(|a| {
+                        if self.layout.short_hash {
+                            self.hashes.truncate(&a.hash, SHORT_HASH_LEN)
+                        } else {
+                            a.origin()
+                        }
+                    })
+                    .unwrap_or(if blank { "" } else { self.glyphs.continued });
+                let resolution = i == 0 && line.resolution && !blank;
+                let marker = if !self.show_marker {
+                    ""
+                } else if blank {
+                    " "
+                } else if resolution {
+                    "!"
+                } else if i == 0 && self.show_ignored && !line.attribution.ignored_via.is_empty() {
+                    "*"
+                } else {
+                    " "
+                };
+                let style = if resolution {
+                    gutter_styles.resolution
+                } else {
+                    accent(details_style(gutter_styles.hash))
+                };
+                fields.push(format!(
+                    "{}{origin:origin_width$}{marker}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            if show(StyleComponent::Author) {
+                let author = ann.map(|a| a.author_for(&self.layout)).unwrap_or_default();
+                let author = crate::unicode::pad(&author, self.author_width);
+                let style = ann
+                    .and_then(author_color)
+                    .unwrap_or_else(|| accent(details_style(gutter_styles.author)));
+                fields.push(format!(
+                    "{}{author}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            if show(StyleComponent::Date) {
+                let date_width = self.date_width;
+                let date = ann
+                    .map(|a| a.date_for(&self.layout, &self.date_format))
+                    .unwrap_or_default();
+                let date = crate::unicode::pad(&date, date_width);
+                let style = accent(details_style(gutter_styles.date));
+                fields.push(format!("{}{date}{}", style.render(), style.render_reset()));
+            }
+            if show(StyleComponent::Release) {
+                let release_width = self.releases.width();
+                let release = ann
+                    .and_then(|_| self.releases.get(shown_id))
+                    .unwrap_or_default();
+                let style = if release == crate::release::UNRELEASED {
+                    gutter_styles.unreleased
+                } else {
+                    gutter_styles.date
+                };
+                let style = accent(details_style(style));
+                let release = crate::unicode::pad(release, release_width);
+                fields.push(format!(
+                    "{}{release}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            if show(StyleComponent::Distance) {
+                let distance_width = self.distance_width;
+                let distance = ann
+                    .map(|a| a.distance_for(self.distance_cap))
+                    .unwrap_or_default();
+                let style = accent(details_style(gutter_styles.date));
+                fields.push(format!(
+                    "{}{distance:>distance_width$}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            if show(StyleComponent::Numbers) {
+                let line_count_width = self.line_count_width;
+                let line_num = if i == 0 {
+                    line.number.to_string()
+                } else {
+                    self.glyphs.continued.to_owned()
+                };
+                let mut style = accent(gutter_styles.line_number);
+                if excluded {
+                    style = style.effects(style.get_effects() | anstyle::Effects::DIMMED);
+                }
+                fields.push(format!(
+                    "{}{line_num:>line_count_width$}{}",
+                    style.render(),
+                    style.render_reset()
+                ));
+            }
+            let mut gutter = fields.join(" ");
+            if show(StyleComponent::Grid) {
+                let style = gutter_styles.grid;
+                if !gutter.is_empty() {
+                    gutter.push(' ');
+                }
+                gutter.push_str(&format!(
+                    "{}{}{} ",
+                    style.render(),
+                    self.glyphs.grid,
+                    style.render_reset()
+                ));
+            } else if !gutter.is_empty() {
+                gutter.push(' ');
+            }
+
+            let eol = if line.number == self.line_count {
+                self.no_newline_marker.as_str()
+            } else {
+                ""
+            };
+            let from_elsewhere = line
+                .attribution
+                .path
+                .as_deref()
+                .filter(|path| *path != self.tree_path);
+            let note = match from_elsewhere {
+                _ if ann.is_none() => None,
+                Some(path) if show(StyleComponent::File) => {
+                    let used = textwrap::core::display_width(&visual_line)
+                        + crate::unicode::display_width(self.glyphs.origin_path);
+                    let path = path.display().to_string();
+                    let path = crate::path_display::truncate_start(
+                        &path,
+                        self.code_width.saturating_sub(used),
+                        self.glyphs.ellipsis,
+                    );
+                    Some(format!("{}{path}", self.glyphs.origin_path))
+                }
+                None if self.is_moved(line.number, line.attribution) => {
+                    Some(self.glyphs.moved.to_owned())
+                }
+                _ => None,
+            };
+            let note = match (ann.and(other), note) {
+                (Some((kind, other)), note) => {
+                    let badge = match kind {
+                        crate::reverts::ViaKind::Revert => self.glyphs.revert,
+                        crate::reverts::ViaKind::CherryPick => self.glyphs.cherry_pick,
+                    };
+                    Some(format!(
+                        "{}{badge}{} {}, {}",
+                        note.unwrap_or_default(),
+                        other.hash,
+                        other.author,
+                        crate::date::format_short(other.time)
+                    ))
+                }
+                (None, note) => note,
+            };
+            let note = note
+                .map(|note| {
+                    let style = self.note_style;
+                    format!(
+                        "{}{}{note}{}",
+                        self.reset,
+                        style.render(),
+                        style.render_reset()
+                    )
+                })
+                .unwrap_or_default();
+            let reset = &self.reset;
+            let mark = self
+                .section_mark
+                .filter(|_| i == 0 && line.hunk_start)
+                .unwrap_or_default();
+            let _ = write!(
+                self.out,
+                "{mark}{gutter}{reset}{visual_line}{eol}{note}\n{reset}"
+            );
+            // HACK: Truncate until we fix our coloring of wrapped lines
+            break;
+        }
+        Ok(())
+    }
+
+    fn end_file(&mut self) -> anyhow::Result<()> {
+        let style = self.gutter_styles.hash;
+        let (style, style_reset) = (style.render(), style.render_reset());
+
+        if let Some(claimed) = self.claimed.take() {
+            let _ = writeln!(self.out);
+            let _ = writeln!(self.out, "{style}Ignored revisions:{style_reset}");
+            if claimed.is_empty() {
+                let _ = writeln!(self.out, "{style}  (none affected this file){style_reset}");
+            }
+            let origin_width = self.origin_width;
+            for (id, count) in claimed {
+                let origin = self
+                    .annotations
+                    .notes
+                    .get(&id)
+                    .expect("all ignored revisions are annotated")
+                    .origin();
+                let lines = if count == 1 { "line" } else { "lines" };
+                let _ = writeln!(
+                    self.out,
+                    "{style}  {origin:origin_width$} {count} {lines}{style_reset}"
+                );
+            }
+        }
+
+        if let Some(palette) = &self.palette {
+            let authors = std::mem::take(&mut self.authors);
+            if LEGEND_MIN_AUTHORS < authors.len() {
+                let mut authors = authors.into_iter().collect::<Vec<_>>();
+                authors.sort_by(|(a_email, a_name), (b_email, b_name)| {
+                    a_name.cmp(b_name).then_with(|| a_email.cmp(b_email))
+                });
+                let _ = writeln!(self.out);
+                let _ = writeln!(self.out, "{style}Authors:{style_reset}");
+                for (email, name) in authors {
+                    let color = palette.color(&email);
+                    let (color, color_reset) = (color.render(), color.render_reset());
+                    let _ = writeln!(
+                        self.out,
+                        "  {color}{}{color_reset} {name}",
+                        self.glyphs.stripe
+                    );
+                }
+            }
+        }
+
+        if let Some(lint) = &self.lint {
+            let (counts, by_author) = std::mem::take(&mut self.lint_counts);
+            let max_line_length = lint.max_line_length;
+            let _ = writeln!(self.out);
+            let _ = writeln!(
+                self.out,
+                "{style}Whitespace problems: {}{style_reset}",
+                counts.describe(max_line_length)
+            );
+            let mut by_author = by_author
+                .into_values()
+                .filter(|(_, counts)| counts.total() != 0)
+                .collect::<Vec<_>>();
+            by_author.sort_by(|(a_name, a), (b_name, b)| {
+                b.total().cmp(&a.total()).then_with(|| a_name.cmp(b_name))
+            });
+            let name_width = by_author
+                .iter()
+                .map(|(name, _)| crate::unicode::display_width(name))
+                .max()
+                .unwrap_or(0);
+            for (name, counts) in by_author {
+                let _ = writeln!(
+                    self.out,
+                    "{style}  {} {}{style_reset}",
+                    crate::unicode::pad(&name, name_width),
+                    counts.describe(max_line_length)
+                );
+            }
+        }
+
+        if self.mine.is_some() {
+            let (mine, total) = std::mem::take(&mut self.mine_lines);
+            let percent = (mine * 100).checked_div(total).unwrap_or(0);
+            let _ = writeln!(self.out);
+            let _ = writeln!(
+                self.out,
+                "{style}You authored {mine}/{total} lines, {percent}%{style_reset}"
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let _ = self.out.flush();
+        Ok(())
+    }
+}
+
+/// How `--squeeze-unchanged`, `--hide-boundary`, and `--touched-since` treat a line
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Squeeze {
+    Shown,
+    /// Replaced by a marker for it and the `usize` lines hidden after it
+    Marker(usize),
+    /// Replaced by a marker for the run of `usize` lines from a boundary commit it starts
+    Boundary(usize),
+    /// Replaced by a marker for the run of `usize` lines from before `--touched-since` it starts
+    Untouched(usize),
+    Hidden,
+}
+
+/// Keep the first and last of `keep` lines of each run from the same commit
+fn squeeze_runs(attributions: &[Attribution], keep: usize) -> Vec<Squeeze> {
+    let mut squeezed = vec![Squeeze::Shown; attributions.len()];
+    let mut start = 0;
+    while start < attributions.len() {
+        let commit = attributions[start].commit;
+        let len = attributions[start..]
+            .iter()
+            .take_while(|a| a.commit == commit)
+            .count();
+        if keep < len {
+            let head = keep.div_ceil(2);
+            let tail = keep / 2;
+            let hidden = start + head..start + len - tail;
+            squeezed[hidden.start] = Squeeze::Marker(hidden.len());
+            for squeeze in &mut squeezed[hidden.start + 1..hidden.end] {
+                *squeeze = Squeeze::Hidden;
+            }
+        }
+        start += len;
+    }
+    squeezed
+}
+
+/// Hide each run of lines from the same boundary commit behind a marker, whether or not
+/// `--squeeze-unchanged` already squeezed it
+fn hide_boundaries(
+    attributions: &[Attribution],
+    annotations: &Annotations,
+    squeezed: &mut Vec<Squeeze>,
+) {
+    hide_runs(
+        attributions,
+        squeezed,
+        |attribution| {
+            annotations
+                .notes
+                .get(&attribution.commit)
+                .is_some_and(|note| note.boundary.is_some())
+                .then_some(attribution.commit)
+        },
+        Squeeze::Boundary,
+    );
+}
+
+/// Hide each run of lines from commits before `since` behind a marker, over any other
+fn hide_untouched(
+    attributions: &[Attribution],
+    annotations: &Annotations,
+    since: i64,
+    squeezed: &mut Vec<Squeeze>,
+) {
+    hide_runs(
+        attributions,
+        squeezed,
+        |attribution| (!touched_since(annotations, attribution, since)).then_some(()),
+        Squeeze::Untouched,
+    );
+}
+
+/// Whether `attribution`'s commit is from `since` or later, by the date shown for it
+pub(crate) fn touched_since(
+    annotations: &Annotations,
+    attribution: &Attribution,
+    since: i64,
+) -> bool {
+    annotations
+        .notes
+        .get(&attribution.commit)
+        .is_some_and(|note| since <= note.time.seconds())
+}
+
+/// Replace each run of lines with the same `run` key by the `marker` for its length, leaving
+/// those without a key as they were
+fn hide_runs<K: PartialEq>(
+    attributions: &[Attribution],
+    squeezed: &mut Vec<Squeeze>,
+    run: impl Fn(&Attribution) -> Option<K>,
+    marker: impl Fn(usize) -> Squeeze,
+) {
+    squeezed.resize(attributions.len(), Squeeze::Shown);
+    let mut start = 0;
+    while start < attributions.len() {
+        let Some(key) = run(&attributions[start]) else {
+            start += 1;
+            continue;
+        };
+        let len = attributions[start..]
+            .iter()
+            .take_while(|a| run(a).as_ref() == Some(&key))
+            .count();
+        squeezed[start] = marker(len);
+        for squeeze in &mut squeezed[start + 1..start + len] {
+            *squeeze = Squeeze::Hidden;
+        }
+        start += len;
+    }
+}
+
+fn blame_options(newest_commit: git2::Oid) -> git2::BlameOptions {
     let mut settings = git2::BlameOptions::new();
     settings
         .track_copies_same_file(true)
@@ -46,170 +1699,554 @@ pub(crate) fn blame(
         .track_copies_any_commit_copies(true)
         .first_parent(true)
         .ignore_whitespace(true)
-        .newest_commit(rev_commit.id());
-    let blame = repo
-        .blame_file(&rel_path, Some(&mut settings))
-        .with_code(proc_exit::Code::FAILURE)?;
-    let mut annotations = Annotations::new(&repo, &blame);
-    annotations
-        .relative_origin(&repo, &args.rev)
-        .with_code(proc_exit::Code::FAILURE)?;
+        .newest_commit(newest_commit);
+    settings
+}
 
-    let file = read_file(&repo, &args.rev, &rel_path).with_code(proc_exit::Code::FAILURE)?;
+/// The commit responsible for a line
+pub(crate) struct Attribution {
+    pub(crate) commit: git2::Oid,
+    /// The file's path as of `commit`
+    pub(crate) path: Option<std::path::PathBuf>,
+    /// The line's number as of `commit`, 1-based
+    pub(crate) line: usize,
+    /// Ignored commits that were looked past to find `commit`, newest first
+    pub(crate) ignored_via: Vec<git2::Oid>,
+    /// Where to look for the line before `commit`, like `git blame --porcelain`'s `previous`
+    pub(crate) previous: Option<Previous>,
+}
 
-    let syntax_set = crate::assets::load_syntaxes();
-    let theme_set = crate::assets::load_themes();
-    let theme = theme_set
-        .get(&theme)
-        .or_else(|| theme_set.get(THEME_DEFAULT))
-        .expect("default theme is present");
+/// The file as of the parent of the commit responsible for a line
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Previous {
+    pub(crate) commit: git2::Oid,
+    /// As of `commit`, following a rename in the child
+    pub(crate) path: std::path::PathBuf,
+}
+
+fn attribute_lines<'r>(
+    repo: &'r git2::Repository,
+    backend: &'r dyn Backend,
+    hunks: &[BlameHunk],
+    ignore: &IgnoreRevs,
+    replacements: &Replacements,
+    engine: Option<Engine<'r>>,
+) -> anyhow::Result<Vec<Attribution>> {
+    let mut reblame = Reblame::new(repo, backend, engine);
+    let mut attributions = Vec::new();
+    for hunk in hunks {
+        for offset in 0..hunk.lines {
+            let mut commit = hunk.commit;
+            let mut ignored_via = Vec::new();
+            let mut path = hunk.path.clone();
+            let mut line = hunk.orig_start_line + offset;
+            if let Some(path) = path.as_mut() {
+                for _ in 0..=MAX_IGNORE_DEPTH {
+                    let ignored = ignore.contains(commit);
+                    // libgit2 stops at the graft point, so continue on through the replacement
+                    if !ignored && !replacements.is_replaced(commit) {
+                        break;
+                    }
+                    let Some(prior) = reblame.parent_line(commit, path, line)? else {
+                        break;
+                    };
+                    if ignored {
+                        ignored_via.push(commit);
+                    }
+                    (commit, *path, line) = prior;
+                }
+            }
+            let previous = match path.as_deref() {
+                Some(path) => reblame.previous(commit, path)?,
+                None => None,
+            };
+            attributions.push(Attribution {
+                commit,
+                path,
+                line,
+                ignored_via,
+                previous,
+            });
+        }
+    }
+    Ok(attributions)
+}
+
+/// Guard against pathological chains of ignored or replaced commits
+const MAX_IGNORE_DEPTH: usize = 100;
+
+/// Blame a commit's parent to look past that commit
+struct Reblame<'r> {
+    /// For libgit2's blame, without an engine
+    repo: &'r git2::Repository,
+    backend: &'r dyn Backend,
+    /// Per `dive.blame-engine`, reusing what blaming the file already walked
+    engine: Option<Engine<'r>>,
+    blames: std::collections::HashMap<(git2::Oid, std::path::PathBuf), Option<Vec<BlameHunk>>>,
+    hunks: std::collections::HashMap<(git2::Oid, std::path::PathBuf), Option<Vec<DiffHunk>>>,
+    previous: std::collections::HashMap<(git2::Oid, std::path::PathBuf), Option<Previous>>,
+}
+
+impl<'r> Reblame<'r> {
+    fn new(
+        repo: &'r git2::Repository,
+        backend: &'r dyn Backend,
+        engine: Option<Engine<'r>>,
+    ) -> Self {
+        Self {
+            repo,
+            backend,
+            engine,
+            blames: Default::default(),
+            hunks: Default::default(),
+            previous: Default::default(),
+        }
+    }
+
+    /// The first parent of `commit` and `path` as of it, if the file was there
+    ///
+    /// This is the first parent blame follows, through any replacement.  A root commit, or one
+    /// that added the file, has no previous.
+    fn previous(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<Previous>> {
+        let key = (commit, path.to_owned());
+        if let Some(previous) = self.previous.get(&key) {
+            return Ok(previous.clone());
+        }
+        let previous = find_previous(self.backend, commit, path)?;
+        self.previous.insert(key, previous.clone());
+        Ok(previous)
+    }
+
+    /// The commit, path, and line responsible for `line` in `commit`'s first parent
+    ///
+    /// Returns `None` if the line has no counterpart in the parent, like when `commit` added it.
+    fn parent_line(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+        line: usize,
+    ) -> anyhow::Result<Option<(git2::Oid, std::path::PathBuf, usize)>> {
+        let Some(parent) = self.backend.first_parent(commit)? else {
+            return Ok(None);
+        };
+
+        let key = (commit, path.to_owned());
+        if self.hunks.contains_key(&key) {
+            log::trace!("diff cache hit for {} {}", commit, path.display());
+        } else {
+            log::trace!("diff cache miss for {} {}", commit, path.display());
+            let hunks = diff_hunks(self.backend, parent, path, commit, path)?;
+            self.hunks.insert(key.clone(), hunks);
+        }
+        let Some(hunks) = self.hunks[&key].as_deref() else {
+            return Ok(None);
+        };
+        let Some(parent_line) = map_to_old_line(hunks, line) else {
+            return Ok(None);
+        };
+
+        let key = (parent, path.to_owned());
+        if self.blames.contains_key(&key) {
+            log::trace!("blame cache hit for {} {}", parent, path.display());
+        } else {
+            log::trace!("blame cache miss for {} {}", parent, path.display());
+            let blame = if let Some(engine) = self.engine.as_mut() {
+                engine.try_blame(parent, path)?
+            } else {
+                self.repo
+                    .blame_file(path, Some(&mut blame_options(parent)))
+                    .ok()
+                    .map(|blame| BlameHunk::from_git2(&blame))
+            };
+            self.blames.insert(key.clone(), blame);
+        }
+        let Some(blame) = self.blames[&key].as_deref() else {
+            return Ok(None);
+        };
+        let Some(hunk) = BlameHunk::find(blame, parent_line) else {
+            return Ok(None);
+        };
+        let Some(hunk_path) = hunk.path.as_ref() else {
+            return Ok(None);
+        };
+        let orig_line = hunk.orig_start_line + (parent_line - hunk.final_start_line);
+        Ok(Some((hunk.commit, hunk_path.to_owned(), orig_line)))
+    }
+}
+
+pub(crate) fn find_previous(
+    backend: &dyn Backend,
+    commit: git2::Oid,
+    path: &std::path::Path,
+) -> anyhow::Result<Option<Previous>> {
+    if commit.is_zero() || commit == crate::objects::placeholder() {
+        return Ok(None);
+    }
+    let Some(parent) = backend.first_parent(commit)? else {
+        return Ok(None);
+    };
+    if backend.blob_at(parent, path)?.is_some() {
+        return Ok(Some(Previous {
+            commit: parent,
+            path: path.to_owned(),
+        }));
+    }
+    let renamed_from = backend.renamed_from(parent, commit, path)?;
+    Ok(renamed_from.map(|path| Previous {
+        commit: parent,
+        path,
+    }))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiffHunk {
+    pub(crate) old_start: usize,
+    pub(crate) old_lines: usize,
+    pub(crate) new_start: usize,
+    pub(crate) new_lines: usize,
+}
+
+/// The changes between `old_path` in one commit and `new_path` in another, if both exist
+pub(crate) fn diff_hunks(
+    backend: &dyn Backend,
+    old: git2::Oid,
+    old_path: &std::path::Path,
+    new: git2::Oid,
+    new_path: &std::path::Path,
+) -> anyhow::Result<Option<Vec<DiffHunk>>> {
+    let (Some(old_blob), Some(new_blob)) = (
+        backend.blob_at(old, old_path)?,
+        backend.blob_at(new, new_path)?,
+    ) else {
+        return Ok(None);
+    };
+    if old_blob == new_blob {
+        return Ok(Some(Vec::new()));
+    }
+    let (old_content, new_content) = (backend.read_blob(old_blob)?, backend.read_blob(new_blob)?);
+    backend
+        .diff_blobs(&old_content, old_path, &new_content, new_path)
+        .map(Some)
+}
+
+/// Find the line in the old version of a file that corresponds to `new_line`
+///
+/// Changed lines are paired up with the lines they replaced, in order, like git's fallback for
+/// `--ignore-rev`.  Added lines have no counterpart.
+fn map_to_old_line(hunks: &[DiffHunk], new_line: usize) -> Option<usize> {
+    let mut old_line = new_line;
+    for hunk in hunks {
+        // A hunk without new lines is positioned after `new_start`
+        let new_start = if hunk.new_lines == 0 {
+            hunk.new_start + 1
+        } else {
+            hunk.new_start
+        };
+        if new_line < new_start {
+            break;
+        } else if new_line < new_start + hunk.new_lines {
+            if hunk.old_lines == 0 {
+                return None;
+            }
+            let offset = (new_line - new_start).min(hunk.old_lines - 1);
+            return Some(hunk.old_start + offset);
+        }
+        old_line = old_line + hunk.old_lines - hunk.new_lines;
+    }
+    Some(old_line)
+}
+
+/// `path` from the top of `repo`'s worktree
+///
+/// Without a worktree, like in a bare repo, `path` is taken as being from the top of the tree
+/// when `allow_bare`.
+fn to_repo_relative(
+    cwd: &std::path::Path,
+    path: &std::path::Path,
+    repo: &git2::Repository,
+    allow_bare: bool,
+) -> Result<std::path::PathBuf, Error> {
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir,
+        None if allow_bare && path.is_relative() => {
+            let tree_path = path_clean::clean(path);
+            if !tree_path.starts_with("..") {
+                return Ok(tree_path);
+            }
+            return Err(Error::PathNotInWorktree {
+                path: path.to_owned(),
+                worktree: None,
+            });
+        }
+        None => {
+            return Err(Error::PathNotInWorktree {
+                path: path.to_owned(),
+                worktree: None,
+            })
+        }
+    };
+    let abs_path =
+        dunce::canonicalize(path).unwrap_or_else(|_err| path_clean::clean(cwd.join(path)));
+    let rel_path = abs_path
+        .strip_prefix(workdir)
+        .map_err(|_| Error::PathNotInWorktree {
+            path: abs_path.clone(),
+            worktree: Some(workdir.to_owned()),
+        })?;
+    Ok(rel_path.to_owned())
+}
+
+/// The in-tree spelling of `tree_path`, when it doesn't exist as given
+///
+/// Paths differing only in Unicode normalization, like NFD names from macOS, always match.
+/// Case is only ignored with `icase` or when the file exists in the workdir, like on
+/// case-insensitive filesystems where the wrong case still finds the file.
+fn find_tree_case(
+    repo: &git2::Repository,
+    commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+    rel_path: &std::path::Path,
+    icase: bool,
+    precompose: bool,
+) -> anyhow::Result<Option<std::path::PathBuf>> {
+    if tree_path.as_os_str().is_empty() {
+        return Ok(None);
+    }
+    // Even an exact match may have another normalization alongside it
+    let ascii = tree_path.to_str().is_some_and(|path| path.is_ascii());
+    if (ascii || !precompose) && has_path(commit, tree_path)? {
+        return Ok(None);
+    }
+    let on_disk = repo
+        .workdir()
+        .map(|workdir| workdir.join(rel_path).exists())
+        .unwrap_or(false);
+    let found = crate::pathspec::find_equivalent(
+        repo,
+        &commit.tree()?,
+        tree_path,
+        icase || on_disk,
+        precompose,
+    )?;
+    Ok(found.filter(|found| found != tree_path))
+}
+
+/// Why gitattributes say annotating `tree_path` isn't worth it
+fn skip_reason(attributes: &Attributes, tree_path: &std::path::Path) -> Option<&'static str> {
+    if attributes.get(tree_path, "linguist-generated").is_true() {
+        Some("marked generated")
+    } else if attributes.get(tree_path, "linguist-vendored").is_true() {
+        Some("marked vendored")
+    } else if attributes.get(tree_path, "diff") == crate::attributes::State::Unset {
+        Some("marked `-diff`")
+    } else {
+        None
+    }
+}
 
-    let syntax = syntax_set
-        .find_syntax_for_file(file_path)
-        .with_code(proc_exit::Code::FAILURE)?
-        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+/// Smudge `tree_path` as of `rev_commit`, for [`Engine::smudge`] to start from
+fn smudge_rev(
+    repo: &git2::Repository,
+    backend: &dyn Backend,
+    config: &Config,
+    attributes: &Attributes,
+    rev_commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+) -> anyhow::Result<(Vec<u8>, crate::lfs::Smudge)> {
+    let mut smudge = crate::lfs::Smudge::new(repo, config, attributes, tree_path)?;
+    let blob = rev_commit.tree()?.get_path(tree_path)?.id();
+    let content = smudge.content(backend, blob, tree_path)?.to_vec();
+    Ok((content, smudge))
+}
 
-    let file = convert_file(&file, file_path).with_code(proc_exit::Code::FAILURE)?;
+/// Why `path` wasn't annotated
+fn skip_notice(path: &str, reason: &str) -> String {
+    format!("{path} is {reason}; pass `--force-generated` to annotate")
+}
 
-    let line_count = file.lines().count();
-    let line_count_width = line_count.to_string().len(); // bytes = chars = columns with digits
-    let sep = "│";
-    let space_count = 3;
-    let origin_width = annotations
-        .notes
-        .values()
-        .map(|a| {
-            // HACK: when we support more than IDs, we'll need to respect UTF-8
-            a.origin().len()
-        })
-        .max()
-        .unwrap_or(0);
-    let gutter_width = origin_width + line_count_width + sep.len() + space_count;
+/// Per `--no-syntax` and `dive.syntax`, unless the file has more than `dive.syntax-max-lines`
+fn syntax_enabled(config: &Config, args: &crate::args::Args, line_count: usize) -> bool {
+    if args.no_syntax || !config.get(&SYNTAX) {
+        log::debug!("not highlighting syntax");
+        return false;
+    }
+    let max_lines = config.get(&SYNTAX_MAX_LINES);
+    if 0 < max_lines && max_lines < line_count as i64 {
+        log::warn!(
+            "not highlighting syntax: {line_count} lines is more than `{}` ({max_lines})",
+            SYNTAX_MAX_LINES.name()
+        );
+        return false;
+    }
+    true
+}
 
-    let code_width = total_width.saturating_sub(gutter_width);
+/// Say that `path` was skipped rather than annotating it
+fn skip_file(
+    path: &str,
+    notice: &str,
+    config: &Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    match args.format {
+        OutputFormat::Json => {
+            let mut pager = Pager::resolve(config, args)?;
+            let mut pager = pager.start();
+            let pager = pager.as_writer().with_code(exit_code::IO)?;
+            let mut renderer = JsonRenderer::new(pager);
+            renderer.skip_file(path, notice);
+            crate::renderer::Renderer::finish(&mut renderer).with_code(proc_exit::Code::FAILURE)?;
+        }
+        OutputFormat::Text | OutputFormat::Quickfix => {
+            log::warn!("{notice}");
+        }
+    }
+    Ok(())
+}
 
-    let mut highlighter = if colored_stdout {
-        Highlighter::enabled(syntax, theme)
-    } else {
-        Highlighter::disabled()
-    };
+/// Whether `HEAD` points to a branch without any commits, like in a new repo
+fn is_unborn(repo: &git2::Repository) -> bool {
+    matches!(repo.head(), Err(err) if err.code() == git2::ErrorCode::UnbornBranch)
+}
 
-    let reset = if colored_stdout {
-        anstyle::Reset.render().to_string()
-    } else {
-        "".to_owned()
+/// Whether `rev` is `HEAD` or relative to it, like `HEAD~2`
+fn names_head(rev: &str) -> bool {
+    rev == "@" || rev.split(['~', '^']).next() == Some("HEAD")
+}
+
+/// Whether `tree_path` is another version, or missing, at `HEAD` than at `commit`
+fn differs_from_head(
+    repo: &git2::Repository,
+    commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+) -> bool {
+    let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) else {
+        return false;
     };
-    let gutter_style = if colored_stdout {
-        gutter_style(highlighter.theme()).render().to_string()
-    } else {
-        "".to_owned()
+    if head.id() == commit.id() {
+        return false;
+    }
+    let blob = |commit: &git2::Commit<'_>| {
+        let entry = commit.tree().ok()?.get_path(tree_path).ok()?;
+        Some(entry.id())
     };
-    let wrap = textwrap::Options::new(code_width)
-        .break_words(false)
-        .wrap_algorithm(textwrap::WrapAlgorithm::FirstFit);
+    blob(&head) != blob(commit)
+}
 
-    let pager = config.get(&crate::git2_config::PAGER);
-    let mut pager = Pager::stdout(&pager);
-    let mut pager = pager.start();
-    let pager = pager.as_writer().with_code(proc_exit::Code::FAILURE)?;
-    let mut prev_hunk_id = git2::Oid::zero();
-    for (line_num, file_line) in file.lines().enumerate() {
-        let line_num = line_num + 1;
+/// Every line attributed to nothing, for showing `content` without blame
+fn unattributed(content: &[u8]) -> Vec<Attribution> {
+    let line_count = String::from_utf8_lossy(content).lines().count();
+    (0..line_count)
+        .map(|i| Attribution {
+            commit: git2::Oid::zero(),
+            path: None,
+            line: i + 1,
+            ignored_via: Vec::new(),
+            previous: None,
+        })
+        .collect()
+}
 
-        let file_line = if line_num == 1 {
-            file_line.strip_prefix('\u{feff}').unwrap_or(file_line)
-        } else {
-            file_line
-        };
+/// `--no-blame` has nothing to show in JSON or quickfix, or in most of the gutter
+fn check_no_blame(args: &crate::args::Args) -> Result<(), proc_exit::Exit> {
+    if args.format != OutputFormat::Text {
+        return Err(anyhow::format_err!(
+            "`--no-blame` only supports `--format text`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    let style = args.style.as_deref().unwrap_or_default();
+    if let Some(component) = style.iter().find(|c| !NO_BLAME_STYLE.contains(c)) {
+        let name = clap::ValueEnum::to_possible_value(component)
+            .expect("no values are skipped")
+            .get_name()
+            .to_owned();
+        return Err(anyhow::format_err!(
+            "`--style {name}` needs blame, which `--no-blame` skips; only `numbers` and `grid` can be shown"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    Ok(())
+}
 
-        let file_line = highlighter
-            .highlight_line(file_line, &syntax_set)
-            .with_code(proc_exit::Code::FAILURE)?;
-        #[allow(clippy::never_loop)]
-        for (i, visual_line) in textwrap::wrap(&file_line, &wrap).into_iter().enumerate() {
-            let origin = if i == 0 {
-                let hunk = blame.get_line(line_num).unwrap_or_else(|| {
-                    panic!("Mismatch in line numbers between dive ({line_num}) and git2")
-                });
-                let hunk_id = hunk.orig_commit_id();
-                if hunk_id != prev_hunk_id {
-                    prev_hunk_id = hunk_id;
-                    let ann = annotations
-                        .notes
-                        .get(&hunk_id)
-                        .expect("all blame hunks are annotated");
-                    ann.origin()
-                } else {
-                    "⋮"
-                }
-            } else {
-                "⋮"
-            };
+/// Whether `--blob` should be attributed, with `REV` given some way
+fn attributed(args: &crate::args::Args) -> bool {
+    args.rev_given || args.rev_file.is_some() || args.at.is_some()
+}
 
-            let line_num = if i == 0 {
-                line_num.to_string()
-            } else {
-                "⋮".to_owned()
-            };
-            let _ = write!(
-                pager,
-                "{gutter_style}{origin:origin_width$} {line_num:>line_count_width$} {sep} {reset}{visual_line}\n{reset}"
-            );
-            // HACK: Truncate until we fix our coloring of wrapped lines
-            break;
-        }
+/// `--blob`'s ID and content
+fn read_blob(repo: &git2::Repository, oid: &str) -> Result<(git2::Oid, Vec<u8>), proc_exit::Exit> {
+    let object = crate::rev::revparse_single(repo, oid)?;
+    let blob = object.as_blob().ok_or_else(|| Error::NotABlob {
+        oid: oid.to_owned(),
+        kind: object.kind().map(|k| k.str()).unwrap_or("unknown"),
+    })?;
+    Ok((blob.id(), blob.content().to_owned()))
+}
+
+/// `--contents`, with `-` being stdin
+fn read_contents(path: &std::path::Path) -> anyhow::Result<Vec<u8>> {
+    if path == std::path::Path::new("-") {
+        let mut contents = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut contents)
+            .context("could not read contents from stdin")?;
+        Ok(contents)
+    } else {
+        std::fs::read(path).with_context(|| format!("could not read {}", path.display()))
     }
+}
 
-    Ok(())
+fn is_dir(commit: &git2::Commit<'_>, tree_path: &std::path::Path) -> anyhow::Result<bool> {
+    if tree_path.as_os_str().is_empty() {
+        return Ok(true);
+    }
+    match commit.tree()?.get_path(tree_path) {
+        Ok(entry) => Ok(entry.kind() == Some(git2::ObjectType::Tree)),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
 }
 
-fn to_repo_relative(
-    cwd: &std::path::Path,
-    path: &std::path::Path,
-    repo: &git2::Repository,
-) -> anyhow::Result<std::path::PathBuf> {
-    let workdir = repo.workdir().ok_or_else(|| {
-        anyhow::format_err!("No workdir found; Bare repositories are not supported")
-    })?;
-    let abs_path =
-        dunce::canonicalize(path).unwrap_or_else(|_err| path_clean::clean(cwd.join(path)));
-    let rel_path = abs_path.strip_prefix(workdir).map_err(|_| {
-        anyhow::format_err!(
-            "File {} is not in the repository's workdir {}",
-            abs_path.display(),
-            workdir.display()
-        )
-    })?;
-    Ok(rel_path.to_owned())
+/// Convert a repo-relative path into the `/`-separated form used within git trees
+fn to_tree_path(rel_path: &std::path::Path) -> std::path::PathBuf {
+    let mut tree_path = std::ffi::OsString::new();
+    for component in rel_path.components() {
+        if !tree_path.is_empty() {
+            tree_path.push("/");
+        }
+        tree_path.push(component.as_os_str());
+    }
+    tree_path.into()
 }
 
 fn read_file(
     repo: &git2::Repository,
+    rev_commit: &git2::Commit<'_>,
     rev: &str,
     rel_path: &std::path::Path,
+    paths: &Paths,
 ) -> anyhow::Result<Vec<u8>> {
-    let rev_obj = repo.revparse_single(rev)?;
-    let rev_tree = rev_obj.peel_to_tree().map_err(|_| {
-        anyhow::format_err!(
-            "Unsupported rev `{}` ({})",
-            rev,
-            rev_obj.kind().map(|k| k.str()).unwrap_or("unknown")
-        )
-    })?;
+    let tree_path = to_tree_path(rel_path);
+    let shown = paths.show(&tree_path);
+    let rev_tree = rev_commit.tree()?;
     let file_entry = rev_tree
-        .get_path(rel_path)
-        .with_context(|| format!("Could not read {} at {}", rel_path.display(), rev))?;
+        .get_path(&tree_path)
+        .with_context(|| format!("Could not read {shown} at {rev}"))?;
     let file_obj = file_entry
         .to_object(repo)
-        .with_context(|| format!("Could not read {} at {}", rel_path.display(), rev))?;
+        .with_context(|| format!("Could not read {shown} at {rev}"))?;
     let file_blob = file_obj
         .as_blob()
-        .with_context(|| format!("Could not read {} at {}", rel_path.display(), rev))?;
+        .with_context(|| format!("Could not read {shown} at {rev}"))?;
     let file = file_blob.content();
     Ok(file.to_owned())
 }
 
-fn convert_file(buffer: &[u8], path: &std::path::Path) -> anyhow::Result<String> {
+pub(crate) fn convert_file(buffer: &[u8], path: &std::path::Path) -> anyhow::Result<String> {
     let content_type = content_inspector::inspect(buffer);
 
     let buffer = match content_type {
@@ -217,7 +2254,7 @@ fn convert_file(buffer: &[u8], path: &std::path::Path) -> anyhow::Result<String>
         // HACK: We don't support UTF-32 yet
         content_inspector::ContentType::UTF_32LE |
         content_inspector::ContentType::UTF_32BE => {
-            anyhow::bail!("Could not ready binary file {}", path.display())
+            return Err(Error::BinaryFile { path: path.to_owned() }.into())
         },
         content_inspector::ContentType::UTF_8 |
         content_inspector::ContentType::UTF_8_BOM => {
@@ -253,69 +2290,183 @@ fn convert_file(buffer: &[u8], path: &std::path::Path) -> anyhow::Result<String>
 }
 
 pub(crate) struct Annotations {
-    notes: std::collections::HashMap<git2::Oid, Annotation>,
+    pub(crate) notes: std::collections::HashMap<git2::Oid, Annotation>,
+    date: DateKind,
+    mailmap: Option<git2::Mailmap>,
 }
 
 impl Annotations {
-    pub(crate) fn new(repo: &git2::Repository, blame: &git2::Blame<'_>) -> Self {
+    pub(crate) fn new(
+        repo: &git2::Repository,
+        date: DateKind,
+        ids: impl IntoIterator<Item = git2::Oid>,
+    ) -> Self {
+        let mailmap = repo.mailmap().ok();
         let mut notes = std::collections::HashMap::new();
-        for hunk in blame.iter() {
-            let id = hunk.orig_commit_id();
-            notes.entry(id).or_insert_with(|| Annotation::new(repo, id));
+        for id in ids {
+            notes
+                .entry(id)
+                .or_insert_with(|| Annotation::new(repo, id, date, mailmap.as_ref()));
+        }
+
+        Annotations {
+            notes,
+            date,
+            mailmap,
+        }
+    }
+
+    /// For `--blob` without `REV`, every line being [`Annotation::unattributed`]
+    pub(crate) fn unattributed() -> Self {
+        let notes = [(git2::Oid::zero(), Annotation::unattributed())]
+            .into_iter()
+            .collect();
+        Annotations {
+            notes,
+            date: DateKind::Author,
+            mailmap: None,
+        }
+    }
+
+    /// With `--resolve-reverts`, note which commits revert or cherry-pick another, annotating
+    /// that one too
+    pub(crate) fn resolve_reverts(&mut self, repo: &git2::Repository) {
+        let ids = self
+            .notes
+            .keys()
+            .copied()
+            .filter(|id| !id.is_zero())
+            .collect::<Vec<_>>();
+        for id in ids {
+            let Ok(commit) = repo.find_commit(id) else {
+                continue;
+            };
+            let Some(via) = crate::reverts::Via::of(repo, &commit) else {
+                continue;
+            };
+            log::debug!("{id} is a {} of {}", via.kind, via.commit);
+            self.notes.entry(via.commit).or_insert_with(|| {
+                Annotation::new(repo, via.commit, self.date, self.mailmap.as_ref())
+            });
+            if let Some(note) = self.notes.get_mut(&id) {
+                note.via = Some(via);
+            }
+        }
+    }
+
+    /// Note which commits are root commits or a shallow clone's boundary
+    pub(crate) fn mark_boundaries(&mut self, repo: &git2::Repository) {
+        let boundaries = crate::boundary::Boundaries::new(repo);
+        for (id, note) in &mut self.notes {
+            if let Ok(commit) = repo.find_commit(*id) {
+                note.boundary = boundaries.of(&commit);
+            }
         }
+    }
 
-        Annotations { notes }
+    /// Spell each commit's ID for people, leaving placeholders like for uncommitted lines as
+    /// they are
+    pub(crate) fn spell_hashes(&mut self, hashes: &HashFormat) {
+        for (id, note) in &mut self.notes {
+            if !id.is_zero() && *id != crate::objects::placeholder() {
+                note.hash = hashes.show(*id, &note.short);
+            }
+        }
     }
 
     pub(crate) fn relative_origin(
         &mut self,
         repo: &git2::Repository,
+        rev_commit: &git2::Commit<'_>,
         head: &str,
+        replacements: &Replacements,
     ) -> anyhow::Result<()> {
         let mut queue = self
             .notes
             .keys()
             .copied()
+            .filter(|id| !id.is_zero() && *id != crate::objects::placeholder())
             .collect::<std::collections::HashSet<_>>();
 
-        let rev_obj = repo.revparse_single(head)?;
-        let rev_commit = rev_obj.peel_to_commit().map_err(|_| {
-            anyhow::format_err!(
-                "Unsupported rev `{}` ({})",
-                head,
-                rev_obj.kind().map(|k| k.str()).unwrap_or("unknown")
-            )
-        })?;
-
         let (head, offset) = split_revset(head);
 
-        let mut revwalk = repo.revwalk()?;
-        revwalk.simplify_first_parent()?;
-        // If just walking first parents, shouldn't really need to sort
-        revwalk.set_sorting(git2::Sort::NONE)?;
-        revwalk.push(rev_commit.id())?;
-        for (i, id) in revwalk.enumerate() {
-            let i = i + offset;
-            let id = id?;
+        // Walk first parents by hand so replaced commits, like grafts, are followed
+        let mut next = Some(rev_commit.id());
+        let mut i = offset;
+        while let Some(id) = next {
             let relative = if i == 0 {
                 head.to_owned()
             } else {
                 format!("{head}~{i}")
             };
-            self.notes
+            let distance = i - offset;
+            let note = self
+                .notes
                 .entry(id)
-                .or_insert_with(|| Annotation::new(repo, id))
-                .relative = Some(relative);
+                .or_insert_with(|| Annotation::new(repo, id, self.date, self.mailmap.as_ref()));
+            note.relative = Some(relative);
+            note.distance = Some(distance);
 
             queue.remove(&id);
             if queue.is_empty() {
                 break;
             }
+            let parents = replacements.parents(repo, id)?;
+            if let [first, merged @ ..] = parents.as_slice() {
+                if !merged.is_empty() {
+                    self.merged_distance(repo, replacements, *first, merged, distance, &mut queue)?;
+                }
+            }
+            next = parents.first().copied();
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// Count commits that a merge `distance` first parents back brought in as that far too
+    ///
+    /// They're never on the first-parent chain further back, so they're done with.
+    fn merged_distance(
+        &mut self,
+        repo: &git2::Repository,
+        replacements: &Replacements,
+        first: git2::Oid,
+        merged: &[git2::Oid],
+        distance: usize,
+        queue: &mut std::collections::HashSet<git2::Oid>,
+    ) -> anyhow::Result<()> {
+        let backend = crate::backend::Git2::new(repo, replacements);
+        let mut walk = crate::replace::Walk::new(&backend);
+        for parent in merged {
+            walk.push(*parent)?;
+        }
+        walk.hide(first)?;
+        for id in walk {
+            let id = id?;
+            if queue.remove(&id) {
+                if let Some(note) = self.notes.get_mut(&id) {
+                    note.distance = Some(distance);
+                }
+                if queue.is_empty() {
+                    break;
+                }
+            }
         }
         Ok(())
     }
 }
 
+/// `commit`'s ID for messages, per `hashes`
+fn show_id(commit: &git2::Commit<'_>, hashes: &HashFormat) -> String {
+    let short = commit
+        .as_object()
+        .short_id()
+        .ok()
+        .and_then(|id| id.as_str().map(ToOwned::to_owned))
+        .unwrap_or_else(|| commit.id().to_string());
+    hashes.show(commit.id(), &short)
+}
+
 fn split_revset(mut head: &str) -> (&str, usize) {
     let mut offset = 0;
     while let Some((start, end)) = head.rsplit_once('~') {
@@ -333,33 +2484,168 @@ fn split_revset(mut head: &str) -> (&str, usize) {
 }
 
 pub(crate) struct Annotation {
-    short: String,
+    /// git's abbreviation, for machine output
+    pub(crate) short: String,
+    /// For people, per [`Annotations::spell_hashes`]
+    pub(crate) hash: String,
     relative: Option<String>,
+    pub(crate) author: String,
+    /// Mailmapped, to identify the author
+    pub(crate) email: String,
+    pub(crate) time: git2::Time,
+    /// Untouched, for machine output
+    pub(crate) summary: String,
+    /// On one line, for people
+    pub(crate) subject: String,
+    /// The commit this reverts or cherry-picks, with `--resolve-reverts`
+    pub(crate) via: Option<crate::reverts::Via>,
+    /// First-parent commits back from `REV` to this one, or to the merge bringing it in
+    pub(crate) distance: Option<usize>,
+    /// Per [`Annotations::mark_boundaries`]
+    pub(crate) boundary: Option<crate::boundary::Boundary>,
 }
 
 impl Annotation {
-    pub(crate) fn new(repo: &git2::Repository, id: git2::Oid) -> Self {
-        let obj = repo.find_object(id, None).expect("blame has valid ids");
-        let short = obj
+    pub(crate) fn new(
+        repo: &git2::Repository,
+        id: git2::Oid,
+        date: DateKind,
+        mailmap: Option<&git2::Mailmap>,
+    ) -> Self {
+        if id.is_zero() {
+            return Self::not_committed();
+        }
+        if id == crate::objects::placeholder() {
+            return Self::missing();
+        }
+        let commit = repo.find_commit(id).expect("blame has valid ids");
+        let short = commit
+            .as_object()
             .short_id()
             .unwrap_or_else(|e| panic!("unknown failure for short_id for {id}: {e}"))
             .as_str()
             .expect("short_id is always valid UTF-8")
             .to_owned();
+        let author = crate::unicode::sanitize(&crate::encoding::author_name(&commit));
+        let time = date.of(&commit);
+        let email = mailmap
+            .and_then(|mailmap| commit.author_with_mailmap(mailmap).ok())
+            .unwrap_or_else(|| commit.author().to_owned())
+            .email_bytes()
+            .to_owned();
+        let email = String::from_utf8_lossy(&email).into_owned();
+        let summary = crate::encoding::summary(&commit);
+        let subject = crate::encoding::subject(&commit);
+        Self {
+            hash: short.clone(),
+            short,
+            relative: None,
+            author,
+            email,
+            time,
+            summary,
+            subject,
+            via: None,
+            distance: None,
+            boundary: None,
+        }
+    }
+
+    /// Lines from `--contents` that aren't in any commit
+    fn not_committed() -> Self {
+        let name = "Not committed";
+        let short = "0".repeat(SHORT_HASH_LEN + 1);
+        Self {
+            hash: short.clone(),
+            short,
+            relative: None,
+            author: name.to_owned(),
+            email: String::new(),
+            time: git2::Time::new(crate::date::now(), 0),
+            summary: name.to_owned(),
+            subject: name.to_owned(),
+            via: None,
+            distance: None,
+            boundary: None,
+        }
+    }
+
+    /// Lines whose history couldn't be read past [`crate::objects::placeholder`]
+    fn missing() -> Self {
+        let name = crate::objects::MISSING;
+        let short = "!".repeat(SHORT_HASH_LEN + 1);
+        Self {
+            hash: short.clone(),
+            short,
+            relative: None,
+            author: name.to_owned(),
+            email: String::new(),
+            time: git2::Time::new(0, 0),
+            summary: name.to_owned(),
+            subject: name.to_owned(),
+            via: None,
+            distance: None,
+            boundary: None,
+        }
+    }
+
+    /// Lines from `--blob` without `REV`, with nothing to attribute them to
+    fn unattributed() -> Self {
+        let name = "Attribution unavailable";
+        let short = "?".repeat(SHORT_HASH_LEN + 1);
         Self {
+            hash: short.clone(),
             short,
             relative: None,
+            author: name.to_owned(),
+            email: String::new(),
+            time: git2::Time::new(0, 0),
+            summary: name.to_owned(),
+            subject: name.to_owned(),
+            via: None,
+            distance: None,
+            boundary: None,
         }
     }
 
     pub(crate) fn origin(&self) -> &str {
-        self.relative.as_deref().unwrap_or(self.short.as_str())
+        self.relative.as_deref().unwrap_or(self.hash.as_str())
+    }
+
+    /// For the `distance` style component, `cap+` past `cap`
+    fn distance_for(&self, cap: usize) -> String {
+        match self.distance {
+            Some(distance) if cap < distance => format!("{cap}+"),
+            Some(distance) => distance.to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn date_for(&self, layout: &GutterLayout, format: &Dates) -> String {
+        if layout.short_date {
+            crate::date::format_compact(self.time)
+        } else {
+            format.format(self.time)
+        }
+    }
+
+    fn author_for(&self, layout: &GutterLayout) -> std::borrow::Cow<'_, str> {
+        if layout.author_initials {
+            crate::layout::initials(&self.author).into()
+        } else {
+            self.author.as_str().into()
+        }
     }
 }
 
 pub(crate) struct Highlighter<'a> {
     highlighter: Option<syntect::easy::HighlightLines<'a>>,
     theme: &'a syntect::highlighting::Theme,
+    /// Whether to write escape codes, like for fading, without a syntax to highlight
+    styled: bool,
+    controls: ControlChars,
+    /// Columns between tab stops, or leaving tabs to the terminal
+    tab_width: Option<usize>,
 }
 
 impl<'a> Highlighter<'a> {
@@ -368,7 +2654,24 @@ impl<'a> Highlighter<'a> {
         theme: &'a syntect::highlighting::Theme,
     ) -> Self {
         let highlighter = Some(syntect::easy::HighlightLines::new(syntax, theme));
-        Self { highlighter, theme }
+        Self {
+            highlighter,
+            theme,
+            styled: true,
+            controls: ControlChars::default(),
+            tab_width: None,
+        }
+    }
+
+    /// Colored like `theme` would, without highlighting the code
+    pub(crate) fn plain(theme: &'a syntect::highlighting::Theme) -> Self {
+        Self {
+            highlighter: None,
+            theme,
+            styled: true,
+            controls: ControlChars::default(),
+            tab_width: None,
+        }
     }
 
     pub(crate) fn disabled() -> Self {
@@ -413,6 +2716,28 @@ impl<'a> Highlighter<'a> {
         Self {
             highlighter,
             theme: &THEME,
+            styled: false,
+            controls: ControlChars::default(),
+            tab_width: None,
+        }
+    }
+
+    /// How to write control characters in the code
+    pub(crate) fn controls(mut self, controls: ControlChars) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// Expand tabs in the code to every `tab_width`th column
+    pub(crate) fn tab_width(mut self, tab_width: Option<usize>) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    fn expand_tabs<'s>(&self, s: &'s str, column: &mut usize) -> std::borrow::Cow<'s, str> {
+        match self.tab_width {
+            Some(tab_width) => crate::unicode::expand_tabs(s, tab_width, column),
+            None => s.into(),
         }
     }
 
@@ -420,12 +2745,37 @@ impl<'a> Highlighter<'a> {
         self.theme
     }
 
+    /// `line` styled per the syntax and, when `faded`, dimmed
+    ///
+    /// Each region is styled and reset on its own, so dimming is part of every region's style
+    /// rather than set once for the line.
     pub(crate) fn highlight_line(
         &mut self,
         line: &str,
         syntax_set: &syntect::parsing::SyntaxSet,
+        faded: bool,
+    ) -> anyhow::Result<String> {
+        self.highlight_marked(line, syntax_set, faded, &[], anstyle::Style::new())
+    }
+
+    /// Like [`Self::highlight_line`], with `mark_style` layered over `marks`
+    pub(crate) fn highlight_marked(
+        &mut self,
+        line: &str,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        faded: bool,
+        marks: &[crate::lint::Mark],
+        mark_style: anstyle::Style,
     ) -> anyhow::Result<String> {
-        if let Some(highlighter) = &mut self.highlighter {
+        if !self.styled {
+            let line = self.expand_tabs(line, &mut 0);
+            return Ok(
+                crate::unicode::show_controls(&line, self.controls, |c| c.to_owned()).into_owned(),
+            );
+        }
+        // Without a syntax, the line is one region so fading and control characters are styled
+        // the same way
+        let ranges = if let Some(highlighter) = &mut self.highlighter {
             // skip syntax highlighting on long lines
             let too_long = line.len() > 1024 * 16;
             let for_highlighting: &str = if too_long { "\n" } else { line };
@@ -433,23 +2783,41 @@ impl<'a> Highlighter<'a> {
             if too_long {
                 ranges[0].1 = line;
             }
+            ranges
+                .into_iter()
+                .map(|(style, region)| (body_style(style), region))
+                .collect()
+        } else {
+            vec![(anstyle::Style::new(), line)]
+        };
+        let ranges = crate::lint::overlay(ranges, marks, mark_style);
 
-            let mut escaped = String::new();
-            for (style, region) in ranges {
-                use std::fmt::Write;
-                let style = body_style(style);
-                let _ = write!(
-                    &mut escaped,
-                    "{}{}{}",
-                    style.render(),
-                    region,
-                    anstyle::Reset.render()
-                );
+        let mut escaped = String::new();
+        let mut column = 0;
+        for (mut style, region) in ranges {
+            use std::fmt::Write;
+            if faded {
+                style = style.effects(style.get_effects() | anstyle::Effects::DIMMED);
             }
-            Ok(escaped)
-        } else {
-            Ok(line.to_owned())
+            let dimmed = style.effects(style.get_effects() | anstyle::Effects::DIMMED);
+            let region = self.expand_tabs(region, &mut column);
+            let region = crate::unicode::show_controls(&region, self.controls, |c| {
+                format!(
+                    "{}{c}{}{}",
+                    dimmed.render(),
+                    anstyle::Reset.render(),
+                    style.render()
+                )
+            });
+            let _ = write!(
+                &mut escaped,
+                "{}{}{}",
+                style.render(),
+                region,
+                anstyle::Reset.render()
+            );
         }
+        Ok(escaped)
     }
 }
 
@@ -479,19 +2847,351 @@ fn body_style(style: syntect::highlighting::Style) -> anstyle::Style {
     output
 }
 
-fn gutter_style(theme: &syntect::highlighting::Theme) -> anstyle::Style {
-    const DEFAULT_GUTTER_COLOR: u8 = 238;
+/// Styles for each part of the gutter
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct GutterStyles {
+    pub(crate) hash: anstyle::Style,
+    pub(crate) author: anstyle::Style,
+    pub(crate) date: anstyle::Style,
+    pub(crate) line_number: anstyle::Style,
+    pub(crate) grid: anstyle::Style,
+    /// Lines a merge introduced, with `--detect-resolution`
+    pub(crate) resolution: anstyle::Style,
+    /// The current user's lines, with `--highlight-mine`
+    pub(crate) mine: anstyle::Style,
+    /// Commits no release contains, with `--show-release`
+    pub(crate) unreleased: anstyle::Style,
+    /// Lines whose history couldn't be read
+    pub(crate) missing: anstyle::Style,
+}
+
+impl GutterStyles {
+    /// Derive styles that blend in with the theme
+    ///
+    /// Structural parts of the gutter use the theme's gutter color while commit metadata uses its
+    /// comment color, falling back to a grey suited to the theme's background.
+    pub(crate) fn from_theme(theme: &syntect::highlighting::Theme) -> Self {
+        let gutter_fg = theme
+            .settings
+            .gutter_foreground
+            .map(crate::assets::to_anstyle_color)
+            .unwrap_or_else(|| Some(default_gutter_color(theme).into()));
+        let gutter = anstyle::Style::new().fg_color(gutter_fg);
+
+        let comment_fg = comment_color(theme).map(crate::assets::to_anstyle_color);
+        let comment = comment_fg
+            .map(|fg| anstyle::Style::new().fg_color(fg))
+            .unwrap_or(gutter);
+
+        Self {
+            hash: gutter,
+            author: comment,
+            date: comment,
+            line_number: gutter,
+            grid: gutter,
+            resolution: anstyle::Style::new()
+                .fg_color(Some(anstyle::AnsiColor::Yellow.into()))
+                .bold(),
+            mine: anstyle::Style::new()
+                .fg_color(Some(anstyle::AnsiColor::Cyan.into()))
+                .bold(),
+            unreleased: anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Green.into())),
+            missing: anstyle::Style::new()
+                .fg_color(Some(anstyle::AnsiColor::Red.into()))
+                .bold(),
+        }
+    }
+
+    /// The configured theme's styles when `colored`, for output outside the gutter
+    pub(crate) fn for_stdout(config: &Config, colored: bool) -> Self {
+        if !colored {
+            return Self::default();
+        }
+        let theme_set = crate::assets::load_themes();
+        let theme = config.get(&THEME);
+        let theme = theme_set
+            .get(&theme)
+            .or_else(|| theme_set.get(THEME_DEFAULT))
+            .expect("default theme is present");
+        Self::from_theme(theme).with_overrides(config)
+    }
+
+    pub(crate) fn with_overrides(mut self, config: &Config) -> Self {
+        let overrides = [
+            (&mut self.hash, &HASH_COLOR),
+            (&mut self.author, &AUTHOR_COLOR),
+            (&mut self.date, &DATE_COLOR),
+            (&mut self.line_number, &LINE_NUMBER_COLOR),
+            (&mut self.grid, &GRID_COLOR),
+            (&mut self.resolution, &RESOLUTION_COLOR),
+            (&mut self.mine, &MINE_COLOR),
+            (&mut self.unreleased, &UNRELEASED_COLOR),
+        ];
+        for (style, field) in overrides {
+            if let Some(color) = config.get(field) {
+                *style = color.style();
+            }
+        }
+        self
+    }
+}
+
+fn default_gutter_color(theme: &syntect::highlighting::Theme) -> anstyle::Ansi256Color {
+    const DEFAULT_DARK_GUTTER_COLOR: u8 = 238;
+    const DEFAULT_LIGHT_GUTTER_COLOR: u8 = 246;
 
-    // If the theme provides a gutter foreground color, use it.
-    let fg_color = theme
-        .settings
-        .gutter_foreground
-        .map(crate::assets::to_anstyle_color)
-        .unwrap_or_else(|| Some(anstyle::Ansi256Color(DEFAULT_GUTTER_COLOR).into()));
+    let background = theme.settings.gutter.or(theme.settings.background);
+    let is_light = background
+        .filter(|c| 1 < c.a)
+        .map(|c| {
+            // Perceived lightness, per ITU-R BT.601
+            let luma = 299 * u32::from(c.r) + 587 * u32::from(c.g) + 114 * u32::from(c.b);
+            128 * 1000 < luma
+        })
+        .unwrap_or(false);
+    if is_light {
+        anstyle::Ansi256Color(DEFAULT_LIGHT_GUTTER_COLOR)
+    } else {
+        anstyle::Ansi256Color(DEFAULT_DARK_GUTTER_COLOR)
+    }
+}
 
-    anstyle::Style::new().fg_color(fg_color)
+/// The theme's color for comments, if it has one
+fn comment_color(theme: &syntect::highlighting::Theme) -> Option<syntect::highlighting::Color> {
+    let comment = syntect::parsing::Scope::new("comment").expect("valid scope");
+    let highlighter = syntect::highlighting::Highlighter::new(theme);
+    let style = highlighter.style_for_stack(&[comment]);
+    let default = highlighter.get_default();
+    (style.foreground != default.foreground).then_some(style.foreground)
 }
 
-const THEME_DEFAULT: &str = "Monokai Extended";
+pub(crate) const HASH_COLOR: RawField<GitColor> = RawField::<GitColor>::new("dive.hash-color");
+pub(crate) const AUTHOR_COLOR: RawField<GitColor> = RawField::<GitColor>::new("dive.author-color");
+pub(crate) const DATE_COLOR: RawField<GitColor> = RawField::<GitColor>::new("dive.date-color");
+pub(crate) const LINE_NUMBER_COLOR: RawField<GitColor> =
+    RawField::<GitColor>::new("dive.line-number-color");
+pub(crate) const GRID_COLOR: RawField<GitColor> = RawField::<GitColor>::new("dive.grid-color");
+pub(crate) const RESOLUTION_COLOR: RawField<GitColor> =
+    RawField::<GitColor>::new("dive.resolution-color");
+pub(crate) const MINE_COLOR: RawField<GitColor> = RawField::<GitColor>::new("dive.mine-color");
+pub(crate) const UNRELEASED_COLOR: RawField<GitColor> =
+    RawField::<GitColor>::new("dive.unreleased-color");
+
+const DEFAULT_STYLE: &[StyleComponent] = &[
+    StyleComponent::Hash,
+    StyleComponent::Numbers,
+    StyleComponent::Grid,
+];
+
+/// With `--no-blame`, the only components that don't need a commit
+const NO_BLAME_STYLE: &[StyleComponent] = &[StyleComponent::Numbers, StyleComponent::Grid];
+
+pub(crate) const MOVE_BADGE_THRESHOLD: DefaultField<i64> =
+    RawField::<i64>::new("dive.move-badge-threshold").default_value(|| 50);
+pub(crate) const DISTANCE_CAP: DefaultField<i64> =
+    RawField::<i64>::new("dive.distance-cap").default_value(|| 99);
+pub(crate) const ANNOTATE_BLANK_LINES: DefaultField<bool> =
+    RawField::<bool>::new("dive.annotate-blank-lines").default_value(|| true);
+
+/// Like zsh's `PROMPT_EOL_MARK`
+pub(crate) const NO_NEWLINE_MARKER: DefaultField<String> =
+    RawField::<String>::new("dive.no-newline-marker").default_value(|| "%".to_owned());
+
+pub(crate) const SYNTAX: DefaultField<bool> =
+    RawField::<bool>::new("dive.syntax").default_value(|| true);
+pub(crate) const SYNTAX_MAX_LINES: DefaultField<i64> =
+    RawField::<i64>::new("dive.syntax-max-lines").default_value(|| 20000);
+
+pub(crate) const THEME_DEFAULT: &str = "Monokai Extended";
 pub(crate) const THEME: DefaultField<String> =
     RawField::<String>::new("dive.theme").default_value(|| THEME_DEFAULT.to_owned());
+/// `0` leaves tabs to the terminal
+pub(crate) const TAB_WIDTH: DefaultField<i64> =
+    RawField::<i64>::new("dive.tab-width").default_value(|| 0);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hunk(old_start: usize, old_lines: usize, new_start: usize, new_lines: usize) -> DiffHunk {
+        DiffHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+        }
+    }
+
+    #[test]
+    fn squeeze_long_runs() {
+        use Squeeze::*;
+
+        let a = git2::Oid::from_bytes(&[1; 20]).unwrap();
+        let b = git2::Oid::from_bytes(&[2; 20]).unwrap();
+        let attributions = [a, a, a, a, a, a, b, b, a]
+            .into_iter()
+            .enumerate()
+            .map(|(i, commit)| Attribution {
+                commit,
+                path: None,
+                line: i + 1,
+                ignored_via: Vec::new(),
+                previous: None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            squeeze_runs(&attributions, 3),
+            [
+                Shown,
+                Shown,
+                Marker(3),
+                Hidden,
+                Hidden,
+                Shown,
+                Shown,
+                Shown,
+                Shown
+            ]
+        );
+        assert_eq!(
+            squeeze_runs(&attributions, 0),
+            [
+                Marker(6),
+                Hidden,
+                Hidden,
+                Hidden,
+                Hidden,
+                Hidden,
+                Marker(2),
+                Hidden,
+                Marker(1)
+            ]
+        );
+        assert_eq!(squeeze_runs(&attributions, 6), [Shown; 9]);
+    }
+
+    #[test]
+    fn map_unchanged_lines() {
+        // 1 line replaced by 2 at line 3, 2 lines removed after new line 6
+        let hunks = [hunk(3, 1, 3, 2), hunk(6, 2, 6, 0)];
+        let actual = (1..=10)
+            .map(|l| map_to_old_line(&hunks, l))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            [
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(3),
+                Some(4),
+                Some(5),
+                Some(8),
+                Some(9),
+                Some(10),
+                Some(11)
+            ]
+        );
+    }
+
+    #[test]
+    fn map_added_lines() {
+        // 2 lines added after old line 1
+        let hunks = [hunk(1, 0, 2, 2)];
+        let actual = (1..=4)
+            .map(|l| map_to_old_line(&hunks, l))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, [Some(1), None, None, Some(2)]);
+    }
+
+    fn dump_styles(theme: &str) -> String {
+        let theme_set = crate::assets::load_themes();
+        let theme = theme_set.get(theme).unwrap();
+        let styles = GutterStyles::from_theme(theme);
+        let color = |s: anstyle::Style| GitColor::from(s).to_string();
+        format!(
+            "hash: {}\nauthor: {}\ndate: {}\nline-number: {}\ngrid: {}\n",
+            color(styles.hash),
+            color(styles.author),
+            color(styles.date),
+            color(styles.line_number),
+            color(styles.grid),
+        )
+    }
+
+    #[test]
+    fn gutter_styles_dark_theme() {
+        assert_eq!(
+            dump_styles("OneHalfDark"),
+            "\
+hash: #919baa
+author: #5c6370
+date: #5c6370
+line-number: #919baa
+grid: #919baa
+"
+        );
+    }
+
+    #[test]
+    fn gutter_styles_light_theme() {
+        assert_eq!(
+            dump_styles("GitHub"),
+            "\
+hash: 246
+author: #969896
+date: #969896
+line-number: 246
+grid: 246
+"
+        );
+    }
+
+    #[test]
+    fn gutter_styles_default_theme() {
+        assert_eq!(
+            dump_styles(THEME_DEFAULT),
+            "\
+hash: 238
+author: #75715e
+date: #75715e
+line-number: 238
+grid: 238
+"
+        );
+    }
+
+    #[test]
+    fn tree_path_native() {
+        let rel_path = std::path::Path::new("src").join("blame.rs");
+        assert_eq!(
+            to_tree_path(&rel_path),
+            std::path::Path::new("src/blame.rs")
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn tree_path_backslash() {
+        let rel_path = std::path::Path::new(r"src\assets\mod.rs");
+        assert_eq!(
+            to_tree_path(rel_path),
+            std::path::Path::new("src/assets/mod.rs")
+        );
+    }
+
+    #[test]
+    fn hunk_marks_follow_the_gutter() {
+        use StyleComponent::*;
+
+        assert_eq!(
+            hunk_marks(&[Hash, Numbers, Grid]).as_deref(),
+            Some("^[0-9A-Za-z]")
+        );
+        assert_eq!(
+            hunk_marks(&[Stripe, Hash, Author]).as_deref(),
+            Some("^[^ ]+ [0-9A-Za-z]")
+        );
+        assert_eq!(hunk_marks(&[Author, Numbers]), None);
+    }
+}
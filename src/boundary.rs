@@ -0,0 +1,160 @@
+//! Commits history stops at, the root commits and the grafted ends of a shallow clone
+//!
+//! These often hold an import of everything from before, so `dive.boundary-style` can dim or
+//! hide their lines and `--stat --no-boundary` can leave them out of the counts.
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Boundary {
+    /// A commit without parents
+    Root,
+    /// A commit whose parents were cut off by a shallow clone
+    Shallow,
+}
+
+impl Boundary {
+    /// How the marker for hidden lines describes the commit
+    pub(crate) fn describe(self) -> &'static str {
+        match self {
+            Self::Root => "a root commit",
+            Self::Shallow => "the shallow boundary",
+        }
+    }
+}
+
+impl std::fmt::Display for Boundary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Root => "root",
+            Self::Shallow => "shallow",
+        }
+        .fmt(f)
+    }
+}
+
+/// Finds which commits are boundaries
+pub(crate) struct Boundaries {
+    /// From `$GIT_DIR/shallow`, whose parents are missing
+    shallow: std::collections::HashSet<git2::Oid>,
+}
+
+impl Boundaries {
+    pub(crate) fn new(repo: &git2::Repository) -> Self {
+        let shallow = if repo.is_shallow() {
+            let path = common_dir(repo).join("shallow");
+            match std::fs::read_to_string(&path) {
+                Ok(ids) => parse_shallow(&ids),
+                Err(err) => {
+                    log::debug!("could not read {}: {err}", path.display());
+                    Default::default()
+                }
+            }
+        } else {
+            Default::default()
+        };
+        Self { shallow }
+    }
+
+    pub(crate) fn of(&self, commit: &git2::Commit<'_>) -> Option<Boundary> {
+        if self.shallow.contains(&commit.id()) {
+            Some(Boundary::Shallow)
+        } else if commit.parent_count() == 0 {
+            Some(Boundary::Root)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where a worktree's repository keeps what its worktrees share, like `shallow`
+pub(crate) fn common_dir(repo: &git2::Repository) -> std::path::PathBuf {
+    let git_dir = repo.path();
+    if repo.is_worktree() {
+        if let Ok(common) = std::fs::read_to_string(git_dir.join("commondir")) {
+            return git_dir.join(common.trim());
+        }
+    }
+    git_dir.to_owned()
+}
+
+fn parse_shallow(ids: &str) -> std::collections::HashSet<git2::Oid> {
+    ids.lines()
+        .filter_map(|line| git2::Oid::from_str(line.trim()).ok())
+        .collect()
+}
+
+/// How lines from boundary commits are shown
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum BoundaryStyle {
+    #[default]
+    Normal,
+    /// Dimmed like `--fade-older-than`
+    Dim,
+    /// Each run replaced by a marker, like `--squeeze-unchanged`
+    Hide,
+}
+
+impl BoundaryStyle {
+    /// `--hide-boundary`, falling back to `dive.boundary-style`
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Self {
+        if args.hide_boundary {
+            Self::Hide
+        } else {
+            config.get(&BOUNDARY_STYLE)
+        }
+    }
+}
+
+impl Parseable for BoundaryStyle {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "dim" => Ok(Self::Dim),
+            "hide" => Ok(Self::Hide),
+            _ => anyhow::bail!(
+                "unsupported boundary style `{s}`, expected `normal`, `dim`, or `hide`"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for BoundaryStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => "normal",
+            Self::Dim => "dim",
+            Self::Hide => "hide",
+        }
+        .fmt(f)
+    }
+}
+
+pub(crate) const BOUNDARY_STYLE: DefaultField<BoundaryStyle> =
+    RawField::<BoundaryStyle>::new("dive.boundary-style").default();
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shallow_file() {
+        let ids = parse_shallow(
+            "0123456789abcdef0123456789abcdef01234567\n\nnot an id\n89abcdef0123456789abcdef0123456789abcdef\n",
+        );
+        assert_eq!(ids.len(), 2);
+        assert!(
+            ids.contains(&git2::Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap())
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(BoundaryStyle::parse("dim").unwrap(), BoundaryStyle::Dim);
+        assert_eq!(BoundaryStyle::parse("hide").unwrap(), BoundaryStyle::Hide);
+        assert!(BoundaryStyle::parse("hidden").is_err());
+    }
+}
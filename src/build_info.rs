@@ -0,0 +1,103 @@
+//! How this binary was built, for `--build-info` and `--diagnostic`
+//!
+//! Most of it is embedded by `build.rs`.
+
+/// `unknown` stands in for what couldn't be found when building, like the commit outside of a
+/// git checkout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BuildInfo {
+    pub(crate) version: &'static str,
+    /// The commit of git-dive's repo that was built
+    pub(crate) commit: &'static str,
+    /// `YYYY-MM-DD`, per `SOURCE_DATE_EPOCH` when set
+    pub(crate) date: &'static str,
+    pub(crate) rustc: &'static str,
+    pub(crate) target: &'static str,
+    /// Cargo features, like `vendored-libgit2`
+    pub(crate) features: Vec<&'static str>,
+    /// `major.minor.patch`
+    pub(crate) libgit2: String,
+    pub(crate) libgit2_vendored: bool,
+    /// Digests of the embedded syntax and theme sets, which aren't versioned themselves
+    pub(crate) syntaxes: &'static str,
+    pub(crate) themes: &'static str,
+}
+
+impl BuildInfo {
+    pub(crate) fn get() -> Self {
+        let libgit2 = git2::Version::get();
+        let (major, minor, patch) = libgit2.libgit2_version();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: env!("GIT_DIVE_BUILD_COMMIT"),
+            date: env!("GIT_DIVE_BUILD_DATE"),
+            rustc: env!("GIT_DIVE_BUILD_RUSTC"),
+            target: env!("GIT_DIVE_BUILD_TARGET"),
+            features: env!("GIT_DIVE_BUILD_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty() && *feature != "default")
+                .collect(),
+            libgit2: format!("{major}.{minor}.{patch}"),
+            libgit2_vendored: libgit2.vendored(),
+            syntaxes: env!("GIT_DIVE_BUILD_SYNTAXES"),
+            themes: env!("GIT_DIVE_BUILD_THEMES"),
+        }
+    }
+
+    /// Names and values, in the order they are reported
+    pub(crate) fn fields(&self) -> Vec<(&'static str, String)> {
+        let features = if self.features.is_empty() {
+            "none".to_owned()
+        } else {
+            self.features.join(", ")
+        };
+        let libgit2 = if self.libgit2_vendored {
+            format!("{} (vendored)", self.libgit2)
+        } else {
+            format!("{} (system)", self.libgit2)
+        };
+        vec![
+            ("version", self.version.to_owned()),
+            ("commit-hash", self.commit.to_owned()),
+            ("build-date", self.date.to_owned()),
+            ("rustc", self.rustc.to_owned()),
+            ("target", self.target.to_owned()),
+            ("features", features),
+            ("libgit2", libgit2),
+            ("syntaxes", self.syntaxes.to_owned()),
+            ("themes", self.themes.to_owned()),
+        ]
+    }
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "git-dive {}", self.version)?;
+        for (name, value) in self.fields().into_iter().skip(1) {
+            writeln!(f, "{name}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fields_are_filled_in() {
+        let info = BuildInfo::get();
+        for (name, value) in info.fields() {
+            assert!(!value.is_empty(), "`{name}` is empty");
+        }
+        assert_eq!(info.libgit2.split('.').count(), 3, "{}", info.libgit2);
+        assert_eq!(info.date.len(), "YYYY-MM-DD".len(), "{}", info.date);
+        assert!(info.rustc.starts_with("rustc "), "{}", info.rustc);
+        // Release builds are made from a checkout, so nothing should be left unknown
+        if std::env::var_os("CI").is_some() {
+            for (name, value) in info.fields() {
+                assert_ne!(value, "unknown", "`{name}` is unknown");
+            }
+        }
+    }
+}
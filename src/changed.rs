@@ -0,0 +1,186 @@
+//! `--changed-only`: blame just the lines that local modifications touch, for pre-commit hooks
+
+use proc_exit::WithCodeResultExt;
+
+use crate::args::OutputFormat;
+use crate::blame::Blamed;
+use crate::blame::Target;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::renderer::AnnotatedLine;
+use crate::renderer::QuickfixRenderer;
+use crate::renderer::Renderer;
+
+/// Blame the lines of `file_path` as of `args.rev` that the worktree, or the index with
+/// `--staged`, replaces, deletes, or inserts next to
+///
+/// Fails with `1` when `--fail-older-than` is given and any of those lines is older.
+pub(crate) fn changed_only(
+    repo: &git2::Repository,
+    cwd: &std::path::Path,
+    file_path: &std::path::Path,
+    config: &mut Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    if args.format == OutputFormat::Json {
+        return Err(anyhow::format_err!(
+            "`--changed-only` only supports `--format text` or `--format quickfix`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    let mut blame_args = args.clone();
+    blame_args.changed_only = false;
+    let Target::File(Blamed {
+        tree_path,
+        shown_path,
+        file,
+        attributions,
+        annotations,
+        resolutions,
+        ..
+    }) = crate::blame::annotate(repo, cwd, file_path, config, &blame_args)?
+    else {
+        return Err(anyhow::format_err!(
+            "`--changed-only` is only supported for files"
+        ))
+        .with_code(exit_code::USAGE);
+    };
+    let modified = if args.staged {
+        staged(repo, &tree_path)
+    } else {
+        worktree(repo, &tree_path)
+    }
+    .with_code(proc_exit::Code::FAILURE)?;
+    let touched = touched_lines(&file, &modified).with_code(proc_exit::Code::FAILURE)?;
+
+    let fail_before = args
+        .fail_older_than
+        .as_ref()
+        .map(|age| age.before(crate::date::now()));
+    let mut stale = 0;
+    // Not paged, so hooks and editors can read it
+    let mut renderer = QuickfixRenderer::new(crate::git_pager::unpaged(args), false);
+    renderer
+        .begin_file(&shown_path)
+        .with_code(proc_exit::Code::FAILURE)?;
+    let mut prev_commit = None;
+    for (i, text) in file.lines().enumerate() {
+        let number = i + 1;
+        if !touched.contains(&number) {
+            continue;
+        }
+        let text = if number == 1 {
+            text.strip_prefix('\u{feff}').unwrap_or(text)
+        } else {
+            text
+        };
+        let attribution = &attributions[i];
+        let commit = annotations
+            .notes
+            .get(&attribution.commit)
+            .expect("all blame hunks are annotated");
+        if fail_before
+            .map(|before| commit.time.seconds() < before)
+            .unwrap_or(false)
+        {
+            stale += 1;
+        }
+        let line = AnnotatedLine {
+            number,
+            text,
+            attribution,
+            hunk_start: prev_commit != Some(attribution.commit),
+            resolution: resolutions.get(i).copied().unwrap_or(false),
+        };
+        prev_commit = Some(attribution.commit);
+        renderer
+            .line(&line, commit)
+            .with_code(proc_exit::Code::FAILURE)?;
+    }
+    renderer.end_file().with_code(proc_exit::Code::FAILURE)?;
+    renderer.finish().with_code(proc_exit::Code::FAILURE)?;
+
+    match args.fail_older_than.as_ref() {
+        Some(age) if 0 < stale => {
+            let lines = if stale == 1 { "line" } else { "lines" };
+            Err(proc_exit::Code::FAILURE
+                .with_message(format!("{stale} touched {lines} older than {age}")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `tree_path` as checked out, empty when it was deleted
+fn worktree(repo: &git2::Repository, tree_path: &std::path::Path) -> anyhow::Result<Vec<u8>> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::format_err!("`--changed-only` needs a worktree; try `--staged`"))?;
+    let path = workdir.join(tree_path);
+    match std::fs::read(&path) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(anyhow::format_err!(
+            "could not read {}: {err}",
+            path.display()
+        )),
+    }
+}
+
+/// `tree_path` as staged, empty when its removal is
+fn staged(repo: &git2::Repository, tree_path: &std::path::Path) -> anyhow::Result<Vec<u8>> {
+    let index = repo.index()?;
+    let Some(entry) = index.get_path(tree_path, 0) else {
+        return Ok(Vec::new());
+    };
+    let blob = repo.find_blob(entry.id)?;
+    Ok(blob.content().to_owned())
+}
+
+/// 1-based lines of `old` that `new` replaces or deletes, or that are on either side of lines
+/// `new` inserts
+fn touched_lines(old: &str, new: &[u8]) -> anyhow::Result<std::collections::BTreeSet<usize>> {
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(0);
+    let patch = git2::Patch::from_buffers(old.as_bytes(), None, new, None, Some(&mut opts))?;
+    let mut touched = std::collections::BTreeSet::new();
+    for h in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(h)?;
+        let start = hunk.old_start() as usize;
+        let count = hunk.old_lines() as usize;
+        if count == 0 {
+            // Without lines, the start is the line the insertion comes after
+            touched.extend([start, start + 1]);
+        } else {
+            touched.extend(start..start + count);
+        }
+    }
+    // Insertions at the top or the bottom only have a neighbor on one side
+    touched.remove(&0);
+    let line_count = old.lines().count();
+    touched.retain(|&line| line <= line_count);
+    Ok(touched)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn touched() {
+        let old = "one\ntwo\nthree\nfour\n";
+        let touched = |new: &[u8]| {
+            touched_lines(old, new)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(touched(b"one\ntwo\nthree\nfour\n"), Vec::<usize>::new());
+        assert_eq!(touched(b"one\nTWO\nthree\nfour\n"), [2]);
+        assert_eq!(touched(b"one\nthree\nfour\n"), [2]);
+        assert_eq!(touched(b"one\ntwo\nnew\nthree\nfour\n"), [2, 3]);
+        assert_eq!(touched(b"new\none\ntwo\nthree\nfour\n"), [1]);
+        assert_eq!(touched(b"one\ntwo\nthree\nfour\nnew\n"), [4]);
+        assert_eq!(touched(b""), [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// How to color the gutter, named like git's `blame.coloring`
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum Coloring {
+    /// Only the theme's colors
+    #[default]
+    None,
+    /// Color commits by age, red for the last month fading to blue after a year
+    #[value(alias = "highlightRecent")]
+    HighlightRecent,
+    /// Dim the gutter of lines from the same commit as the line above
+    #[value(alias = "repeatedLines")]
+    RepeatedLines,
+}
+
+impl Coloring {
+    /// `--coloring`, falling back to `dive.coloring`, then to git's own `blame.coloring`
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Self {
+        args.coloring
+            .or_else(|| config.get(&COLORING))
+            .unwrap_or_else(|| config.get(&BLAME_COLORING))
+    }
+}
+
+impl Parseable for Coloring {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        <Self as clap::ValueEnum>::from_str(s, true)
+            .map_err(|_| anyhow::format_err!("unsupported coloring `{s}`"))
+    }
+}
+
+impl std::fmt::Display for Coloring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // As git spells them
+        match self {
+            Self::None => "none",
+            Self::HighlightRecent => "highlightRecent",
+            Self::RepeatedLines => "repeatedLines",
+        }
+        .fmt(f)
+    }
+}
+
+pub(crate) const COLORING: RawField<Coloring> = RawField::<Coloring>::new("dive.coloring");
+
+pub(crate) const BLAME_COLORING: DefaultField<Coloring> =
+    RawField::<Coloring>::new("blame.coloring").default();
+
+/// The color for a commit from `time` with `highlightRecent`
+///
+/// Matches git's default `color.blame.highlightRecent` of
+/// `blue,12 month ago,white,1 month ago,red`.
+pub(crate) fn recent_style(time: git2::Time, now: i64) -> anstyle::Style {
+    let age = now - time.seconds();
+    let color = if MONTHS_12 < age {
+        anstyle::AnsiColor::Blue
+    } else if MONTHS_1 < age {
+        anstyle::AnsiColor::White
+    } else {
+        anstyle::AnsiColor::Red
+    };
+    anstyle::Style::new().fg_color(Some(color.into()))
+}
+
+/// git's approximate month, for `N month ago`
+const MONTH: i64 = 30 * 24 * 60 * 60;
+const MONTHS_1: i64 = MONTH;
+const MONTHS_12: i64 = 12 * MONTH;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_git_names() {
+        assert_eq!(
+            Coloring::parse("highlightRecent").unwrap(),
+            Coloring::HighlightRecent
+        );
+        assert_eq!(
+            Coloring::parse("repeated-lines").unwrap(),
+            Coloring::RepeatedLines
+        );
+        assert!(Coloring::parse("rainbow").is_err());
+    }
+
+    #[test]
+    fn recent_fades() {
+        let now = 100 * MONTHS_12;
+        let style = |ago: i64| recent_style(git2::Time::new(now - ago, 0), now);
+        let fg = |c: anstyle::AnsiColor| anstyle::Style::new().fg_color(Some(c.into()));
+        assert_eq!(style(0), fg(anstyle::AnsiColor::Red));
+        assert_eq!(style(2 * MONTH), fg(anstyle::AnsiColor::White));
+        assert_eq!(style(13 * MONTH), fg(anstyle::AnsiColor::Blue));
+    }
+}
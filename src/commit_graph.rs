@@ -0,0 +1,298 @@
+//! Read git's commit-graph, to walk history without parsing each commit
+//!
+//! See git's `Documentation/gitformat-commit-graph.txt`.  Only a single
+//! `objects/info/commit-graph` file with SHA-1 ids is supported, not split graph chains.
+
+use anyhow::Context as _;
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+pub(crate) struct CommitGraph {
+    data: Vec<u8>,
+    count: usize,
+    fanout: usize,
+    ids: usize,
+    commits: usize,
+    extra_edges: Option<usize>,
+}
+
+/// What the commit-graph knows about a commit
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct GraphCommit {
+    pub(crate) tree: git2::Oid,
+    pub(crate) parents: Vec<git2::Oid>,
+}
+
+impl CommitGraph {
+    /// The repo's commit-graph, unless disabled by `--no-commit-graph` or `core.commitGraph`
+    pub(crate) fn load(
+        repo: &git2::Repository,
+        config: &Config,
+        args: &crate::args::Args,
+    ) -> Option<Self> {
+        if args.no_commit_graph || !config.get(&CORE_COMMIT_GRAPH) {
+            log::debug!("not using commit-graph");
+            return None;
+        }
+        let path = graph_path(repo);
+        if !path.exists() {
+            log::debug!("no commit-graph at {}", path.display());
+            return None;
+        }
+        match Self::open(&path) {
+            Ok(graph) => {
+                log::debug!(
+                    "using commit-graph with {} commits from {}",
+                    graph.len(),
+                    path.display()
+                );
+                Some(graph)
+            }
+            Err(err) => {
+                log::debug!("ignoring commit-graph at {}: {:#}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn open(path: &std::path::Path) -> anyhow::Result<Self> {
+        let data =
+            std::fs::read(path).with_context(|| format!("could not read {}", path.display()))?;
+        Self::parse(data)
+    }
+
+    fn parse(data: Vec<u8>) -> anyhow::Result<Self> {
+        const HEADER_LEN: usize = 8;
+        const CHUNK_ENTRY_LEN: usize = 12;
+
+        if data.len() < HEADER_LEN || &data[0..4] != SIGNATURE {
+            anyhow::bail!("not a commit-graph");
+        }
+        let version = data[4];
+        if version != 1 {
+            anyhow::bail!("unsupported version {version}");
+        }
+        let hash_version = data[5];
+        if hash_version != 1 {
+            anyhow::bail!("unsupported hash version {hash_version}");
+        }
+        let chunk_count = usize::from(data[6]);
+        let base_graphs = data[7];
+        if base_graphs != 0 {
+            anyhow::bail!("split commit-graphs are unsupported");
+        }
+
+        let mut chunks = std::collections::HashMap::new();
+        for i in 0..chunk_count {
+            let entry = HEADER_LEN + i * CHUNK_ENTRY_LEN;
+            let id: [u8; 4] = read_array(&data, entry)?;
+            let offset = usize::try_from(read_u64(&data, entry + 4)?)?;
+            if data.len() < offset {
+                anyhow::bail!("chunk is past the end of the file");
+            }
+            chunks.insert(id, offset);
+        }
+        let chunk = |id: &[u8; 4]| {
+            chunks.get(id).copied().ok_or_else(|| {
+                anyhow::format_err!("missing `{}` chunk", String::from_utf8_lossy(id))
+            })
+        };
+
+        let fanout = chunk(b"OIDF")?;
+        let ids = chunk(b"OIDL")?;
+        let commits = chunk(b"CDAT")?;
+        let extra_edges = chunk(b"EDGE").ok();
+        let count = read_u32(&data, fanout + 255 * 4)? as usize;
+        if data.len() < ids + count * HASH_LEN || data.len() < commits + count * COMMIT_DATA_LEN {
+            anyhow::bail!("truncated commit-graph");
+        }
+
+        Ok(Self {
+            data,
+            count,
+            fanout,
+            ids,
+            commits,
+            extra_edges,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.count
+    }
+
+    pub(crate) fn get(&self, id: git2::Oid) -> Option<GraphCommit> {
+        let position = self.position(id)?;
+        self.commit(position).ok()
+    }
+
+    fn position(&self, id: git2::Oid) -> Option<usize> {
+        let id = id.as_bytes();
+        let first = usize::from(id[0]);
+        let start = if first == 0 {
+            0
+        } else {
+            read_u32(&self.data, self.fanout + (first - 1) * 4).ok()? as usize
+        };
+        let end = read_u32(&self.data, self.fanout + first * 4).ok()? as usize;
+        let (mut low, mut high) = (start, end.min(self.count));
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.id_bytes(mid).cmp(id) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn id_bytes(&self, position: usize) -> &[u8] {
+        let start = self.ids + position * HASH_LEN;
+        &self.data[start..start + HASH_LEN]
+    }
+
+    fn id(&self, position: usize) -> anyhow::Result<git2::Oid> {
+        if self.count <= position {
+            anyhow::bail!("commit {position} is out of range");
+        }
+        Ok(git2::Oid::from_bytes(self.id_bytes(position))?)
+    }
+
+    fn commit(&self, position: usize) -> anyhow::Result<GraphCommit> {
+        let start = self.commits + position * COMMIT_DATA_LEN;
+        let tree = git2::Oid::from_bytes(&self.data[start..start + HASH_LEN])?;
+        let first_parent = read_u32(&self.data, start + HASH_LEN)?;
+        let second_parent = read_u32(&self.data, start + HASH_LEN + 4)?;
+
+        let mut parents = Vec::new();
+        if first_parent != NO_PARENT {
+            parents.push(self.id(first_parent as usize)?);
+        }
+        if second_parent & EXTRA_EDGES != 0 {
+            // Octopus merges list their second and later parents separately
+            let edges = self
+                .extra_edges
+                .context("octopus merge without an `EDGE` chunk")?;
+            let mut index = (second_parent & !EXTRA_EDGES) as usize;
+            loop {
+                let edge = read_u32(&self.data, edges + index * 4)?;
+                parents.push(self.id((edge & !LAST_EDGE) as usize)?);
+                if edge & LAST_EDGE != 0 {
+                    break;
+                }
+                index += 1;
+            }
+        } else if second_parent != NO_PARENT {
+            parents.push(self.id(second_parent as usize)?);
+        }
+
+        Ok(GraphCommit { tree, parents })
+    }
+}
+
+pub(crate) fn graph_path(repo: &git2::Repository) -> std::path::PathBuf {
+    repo.path()
+        .join("objects")
+        .join("info")
+        .join("commit-graph")
+}
+
+pub(crate) const CORE_COMMIT_GRAPH: DefaultField<bool> =
+    RawField::<bool>::new("core.commitGraph").default_value(|| true);
+
+const SIGNATURE: &[u8] = b"CGPH";
+const HASH_LEN: usize = 20;
+/// Tree, two parents, then generation and commit time
+const COMMIT_DATA_LEN: usize = HASH_LEN + 16;
+const NO_PARENT: u32 = 0x7000_0000;
+const EXTRA_EDGES: u32 = 0x8000_0000;
+const LAST_EDGE: u32 = 0x8000_0000;
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> anyhow::Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .context("truncated commit-graph")
+}
+
+fn read_u32(data: &[u8], offset: usize) -> anyhow::Result<u32> {
+    read_array(data, offset).map(u32::from_be_bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> anyhow::Result<u64> {
+    read_array(data, offset).map(u64::from_be_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(CommitGraph::parse(b"PACK\x00\x00\x00\x02".to_vec()).is_err());
+        assert!(CommitGraph::parse(Vec::new()).is_err());
+        assert!(CommitGraph::parse(b"CGPH\x01\x02\x00\x00".to_vec()).is_err());
+    }
+
+    #[test]
+    fn reads_git_graph() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = git2::Repository::init(root_path).unwrap();
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let base = repo.commit(None, &sig, &sig, "base", &tree, &[]).unwrap();
+        let base = repo.find_commit(base).unwrap();
+        let side = [
+            repo.commit(None, &sig, &sig, "a", &tree, &[&base]).unwrap(),
+            repo.commit(None, &sig, &sig, "b", &tree, &[&base]).unwrap(),
+            repo.commit(None, &sig, &sig, "c", &tree, &[&base]).unwrap(),
+        ]
+        .map(|id| repo.find_commit(id).unwrap());
+        let octopus = repo
+            .commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "octopus",
+                &tree,
+                &side.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+
+        let Ok(status) = std::process::Command::new("git")
+            .args(["commit-graph", "write", "--reachable"])
+            .current_dir(root_path)
+            .status()
+        else {
+            // `git` isn't available
+            root.close().unwrap();
+            return;
+        };
+        assert!(status.success());
+
+        let graph = CommitGraph::open(&graph_path(&repo)).unwrap();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(
+            graph.get(base.id()),
+            Some(GraphCommit {
+                tree: tree.id(),
+                parents: vec![],
+            })
+        );
+        assert_eq!(
+            graph.get(octopus),
+            Some(GraphCommit {
+                tree: tree.id(),
+                parents: side.iter().map(|c| c.id()).collect(),
+            })
+        );
+        assert_eq!(graph.get(tree.id()), None);
+
+        root.close().unwrap();
+    }
+}
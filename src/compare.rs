@@ -0,0 +1,434 @@
+use proc_exit::WithCodeResultExt;
+
+use crate::args::OutputFormat;
+use crate::blame::Annotations;
+use crate::blame::Attribution;
+use crate::blame::Blamed;
+use crate::blame::GutterStyles;
+use crate::blame::Target;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+use crate::glyphs::Glyphs;
+use crate::unicode::ControlChars;
+
+/// Blame `file_path` at `old` and `new` and show the two aligned, each line with its own commit
+pub(crate) fn compare(
+    repo: &git2::Repository,
+    cwd: &std::path::Path,
+    file_path: &std::path::Path,
+    old: &str,
+    new: &str,
+    config: &mut Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    if args.format != OutputFormat::Text {
+        return Err(anyhow::format_err!(
+            "`--compare` only supports `--format text`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    let colored_stdout =
+        anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
+    let total_width = crate::render_test::terminal_width();
+
+    let old = Side::blame(repo, cwd, file_path, old, config, args)?;
+    let new = Side::blame(repo, cwd, file_path, new, config, args)?;
+    let rows = align(&old.lines, &new.lines).with_code(proc_exit::Code::FAILURE)?;
+
+    let split_width = usize::try_from(config.get(&COMPARE_SPLIT_WIDTH)).unwrap_or(0);
+    let view = View {
+        old: &old,
+        new: &new,
+        styles: GutterStyles::for_stdout(config, colored_stdout),
+        colored: colored_stdout,
+        controls: config.get(&crate::unicode::SHOW_CONTROL_CHARS),
+        hash_width: old.hash_width().max(new.hash_width()),
+        number_width: old
+            .lines
+            .len()
+            .max(new.lines.len())
+            .max(1)
+            .to_string()
+            .len(),
+        glyphs: Glyphs::resolve(config),
+    };
+    let output = if split_width <= total_width {
+        view.side_by_side(&rows, total_width)
+    } else {
+        view.unified(&rows, total_width)
+    };
+    crate::summary::page(&output, config, args)
+}
+
+/// One revision's blame of the file
+struct Side {
+    rev: String,
+    lines: Vec<String>,
+    attributions: Vec<Attribution>,
+    annotations: Annotations,
+}
+
+impl Side {
+    fn blame(
+        repo: &git2::Repository,
+        cwd: &std::path::Path,
+        file_path: &std::path::Path,
+        rev: &str,
+        config: &mut Config,
+        args: &crate::args::Args,
+    ) -> Result<Self, proc_exit::Exit> {
+        let mut args = args.clone();
+        rev.clone_into(&mut args.rev);
+        args.compare = None;
+        let Target::File(Blamed {
+            file,
+            attributions,
+            annotations,
+            ..
+        }) = crate::blame::annotate(repo, cwd, file_path, config, &args)?
+        else {
+            return Err(anyhow::format_err!(
+                "`--compare` is only supported for files"
+            ))
+            .with_code(exit_code::USAGE);
+        };
+        let lines = file
+            .lines()
+            .enumerate()
+            .map(|(i, text)| {
+                let text = if i == 0 {
+                    text.strip_prefix('\u{feff}').unwrap_or(text)
+                } else {
+                    text
+                };
+                text.to_owned()
+            })
+            .collect();
+        Ok(Self {
+            rev: rev.to_owned(),
+            lines,
+            attributions,
+            annotations,
+        })
+    }
+
+    fn hash(&self, i: usize) -> &str {
+        let commit = self.attributions[i].commit;
+        self.annotations
+            .notes
+            .get(&commit)
+            .expect("all blame hunks are annotated")
+            .hash
+            .as_str()
+    }
+
+    fn hash_width(&self) -> usize {
+        (0..self.lines.len())
+            .map(|i| crate::unicode::display_width(self.hash(i)))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A line of the output, by 0-based line index on each side
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Row {
+    /// The same content on both sides
+    Same(usize, usize),
+    /// Part of a change, paired with the other side's line at the same offset into the hunk
+    Changed(Option<usize>, Option<usize>),
+}
+
+/// Pair up `old` and `new` lines per their diff
+fn align(old: &[String], new: &[String]) -> anyhow::Result<Vec<Row>> {
+    let join = |lines: &[String]| {
+        let mut joined = lines.join("\n");
+        if !lines.is_empty() {
+            joined.push('\n');
+        }
+        joined
+    };
+    let old_buffer = join(old);
+    let new_buffer = join(new);
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(0);
+    let patch = git2::Patch::from_buffers(
+        old_buffer.as_bytes(),
+        None,
+        new_buffer.as_bytes(),
+        None,
+        Some(&mut opts),
+    )?;
+
+    let mut rows = Vec::new();
+    let (mut o, mut n) = (0, 0);
+    for h in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(h)?;
+        let old_lines = hunk.old_lines() as usize;
+        let new_lines = hunk.new_lines() as usize;
+        // Without lines, the start is the line the hunk comes after
+        let old_start = hunk.old_start() as usize - usize::from(old_lines != 0);
+        let new_start = hunk.new_start() as usize - usize::from(new_lines != 0);
+        while o < old_start && n < new_start {
+            rows.push(Row::Same(o, n));
+            o += 1;
+            n += 1;
+        }
+        for i in 0..old_lines.max(new_lines) {
+            rows.push(Row::Changed(
+                (i < old_lines).then_some(o + i),
+                (i < new_lines).then_some(n + i),
+            ));
+        }
+        o += old_lines;
+        n += new_lines;
+    }
+    while o < old.len() && n < new.len() {
+        rows.push(Row::Same(o, n));
+        o += 1;
+        n += 1;
+    }
+    Ok(rows)
+}
+
+struct View<'s> {
+    old: &'s Side,
+    new: &'s Side,
+    styles: GutterStyles,
+    colored: bool,
+    controls: ControlChars,
+    hash_width: usize,
+    number_width: usize,
+    glyphs: Glyphs,
+}
+
+impl View<'_> {
+    /// The old blame on the left and the new on the right
+    fn side_by_side(&self, rows: &[Row], total_width: usize) -> String {
+        let half = total_width.saturating_sub(crate::unicode::display_width(self.glyphs.split)) / 2;
+        let removed = self.marker_style(anstyle::AnsiColor::Red);
+        let added = self.marker_style(anstyle::AnsiColor::Green);
+        let separator = format!(
+            "{}{}{}",
+            self.styles.grid.render(),
+            self.glyphs.split,
+            self.styles.grid.render_reset()
+        );
+
+        let mut output = String::new();
+        output.push_str(&self.header(&self.old.rev, half, true));
+        output.push_str(&separator);
+        output.push_str(&self.header(&self.new.rev, half, false));
+        output.push('\n');
+        for row in rows {
+            let (old, new, dimmed) = match *row {
+                Row::Same(o, n) => (Some(o), Some(n), self.same_commit(o, n)),
+                Row::Changed(o, n) => (o, n, false),
+            };
+            let (old_style, new_style) = match row {
+                Row::Same(..) => (anstyle::Style::new(), anstyle::Style::new()),
+                Row::Changed(..) => (removed, added),
+            };
+            output.push_str(&self.cell(self.old, old, half, old_style, dimmed, true));
+            output.push_str(&separator);
+            output.push_str(&self.cell(self.new, new, half, new_style, dimmed, false));
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Old lines marked with `-` above the new lines marked with `+` that replaced them
+    fn unified(&self, rows: &[Row], total_width: usize) -> String {
+        let removed = self.marker_style(anstyle::AnsiColor::Red);
+        let added = self.marker_style(anstyle::AnsiColor::Green);
+        let width = total_width.saturating_sub(2);
+
+        let mut output = String::new();
+        for (marker, rev) in [("-", &self.old.rev), ("+", &self.new.rev)] {
+            let header = format!("{marker}{marker}{marker} {rev}");
+            output.push_str(&self.header(&header, total_width, false));
+            output.push('\n');
+        }
+        let mut i = 0;
+        while i < rows.len() {
+            match rows[i] {
+                Row::Same(o, n) => {
+                    let dimmed = self.same_commit(o, n);
+                    let style = anstyle::Style::new();
+                    output.push_str("  ");
+                    output.push_str(&self.cell(self.new, Some(n), width, style, dimmed, false));
+                    output.push('\n');
+                    i += 1;
+                }
+                Row::Changed(..) => {
+                    let end = rows[i..]
+                        .iter()
+                        .position(|row| matches!(row, Row::Same(..)))
+                        .map(|len| i + len)
+                        .unwrap_or(rows.len());
+                    let changed = &rows[i..end];
+                    let olds = changed.iter().filter_map(|row| match row {
+                        Row::Changed(o, _) => *o,
+                        Row::Same(..) => None,
+                    });
+                    let news = changed.iter().filter_map(|row| match row {
+                        Row::Changed(_, n) => *n,
+                        Row::Same(..) => None,
+                    });
+                    for (side, line, marker, style) in olds
+                        .map(|o| (self.old, o, '-', removed))
+                        .chain(news.map(|n| (self.new, n, '+', added)))
+                    {
+                        output.push_str(&format!(
+                            "{}{marker}{} ",
+                            style.render(),
+                            style.render_reset()
+                        ));
+                        output.push_str(&self.cell(side, Some(line), width, style, false, false));
+                        output.push('\n');
+                    }
+                    i = end;
+                }
+            }
+        }
+        output
+    }
+
+    fn header(&self, text: &str, width: usize, padded: bool) -> String {
+        let text = fit(&crate::unicode::sanitize(text), width);
+        let padding = if padded {
+            width - crate::unicode::display_width(&text)
+        } else {
+            0
+        };
+        let style = if self.colored {
+            anstyle::Style::new().bold()
+        } else {
+            anstyle::Style::new()
+        };
+        format!(
+            "{}{text}{}{:padding$}",
+            style.render(),
+            style.render_reset(),
+            ""
+        )
+    }
+
+    /// A side's gutter and text for `line`, cut to `width` columns and, when `padded`, filling
+    /// them
+    fn cell(
+        &self,
+        side: &Side,
+        line: Option<usize>,
+        width: usize,
+        text_style: anstyle::Style,
+        dimmed: bool,
+        padded: bool,
+    ) -> String {
+        let gutter_width = self.hash_width + 1 + self.number_width + 3;
+        let Some(line) = line else {
+            return if padded {
+                " ".repeat(width)
+            } else {
+                String::new()
+            };
+        };
+        let dim = |style: anstyle::Style| {
+            if dimmed && self.colored {
+                style.dimmed()
+            } else {
+                style
+            }
+        };
+        let (hash, number, grid, text_style) = (
+            dim(self.styles.hash),
+            dim(self.styles.line_number),
+            dim(self.styles.grid),
+            dim(text_style),
+        );
+        // Tabs expanded to every 4th column, so both sides line up
+        let text = crate::unicode::expand_tabs(&side.lines[line], 4, &mut 0);
+        let text = crate::unicode::show_controls(&text, self.controls, |c| c.to_owned());
+        let text = fit(&text, width.saturating_sub(gutter_width));
+        let padding = if padded {
+            width.saturating_sub(gutter_width + crate::unicode::display_width(&text))
+        } else {
+            0
+        };
+        format!(
+            "{}{:<ow$}{} {}{:>nw$}{} {}{}{} {}{text}{}{:padding$}",
+            hash.render(),
+            side.hash(line),
+            hash.render_reset(),
+            number.render(),
+            line + 1,
+            number.render_reset(),
+            grid.render(),
+            self.glyphs.grid,
+            grid.render_reset(),
+            text_style.render(),
+            text_style.render_reset(),
+            "",
+            ow = self.hash_width,
+            nw = self.number_width,
+        )
+    }
+
+    fn same_commit(&self, old: usize, new: usize) -> bool {
+        self.old.attributions[old].commit == self.new.attributions[new].commit
+    }
+
+    fn marker_style(&self, color: anstyle::AnsiColor) -> anstyle::Style {
+        if self.colored {
+            anstyle::Style::new().fg_color(Some(color.into()))
+        } else {
+            anstyle::Style::new()
+        }
+    }
+}
+
+/// `s` cut to at most `width` columns
+fn fit(s: &str, width: usize) -> String {
+    let mut fitted = String::with_capacity(s.len());
+    let mut used = 0;
+    for c in s.chars() {
+        let c_width = crate::unicode::display_width(c.encode_utf8(&mut [0; 4]));
+        if width < used + c_width {
+            break;
+        }
+        used += c_width;
+        fitted.push(c);
+    }
+    fitted
+}
+
+pub(crate) const COMPARE_SPLIT_WIDTH: DefaultField<i64> =
+    RawField::<i64>::new("dive.compare-split-width").default_value(|| 160);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn align_pairs_changes() {
+        let lines = |s: &str| s.split_whitespace().map(str::to_owned).collect::<Vec<_>>();
+        let rows = align(&lines("a b c d e"), &lines("a B c e f")).unwrap();
+        assert_eq!(
+            rows,
+            [
+                Row::Same(0, 0),
+                Row::Changed(Some(1), Some(1)),
+                Row::Same(2, 2),
+                Row::Changed(Some(3), None),
+                Row::Same(4, 3),
+                Row::Changed(None, Some(4)),
+            ]
+        );
+        assert_eq!(
+            align(&lines(""), &lines("a")).unwrap(),
+            [Row::Changed(None, Some(0))]
+        );
+    }
+}
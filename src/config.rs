@@ -1,30 +1,219 @@
 use proc_exit::WithCodeResultExt;
 
 use crate::git2_config::Config;
+use crate::git2_config::ConfigSource as _;
+use crate::git2_config::DefaultField;
+use crate::git2_config::ExplainField;
+use crate::git2_config::RawField;
 use crate::git2_config::ReflectField;
 
 pub(crate) fn dump_config(
     output_path: &std::path::Path,
     config: &mut Config,
+    args: &crate::args::Args,
 ) -> proc_exit::ExitResult {
     let cwd = std::env::current_dir().with_code(proc_exit::Code::FAILURE)?;
-    let repo = git2::Repository::discover(cwd).with_code(proc_exit::Code::FAILURE)?;
+    let repo = crate::repo::open(&cwd, args)?;
 
     config.add_repo(&repo);
-    let output = config.dump([
+    let mut output = config.dump([
         &crate::git2_config::COLOR_UI as &dyn ReflectField,
         &crate::git2_config::PAGER as &dyn ReflectField,
+        &crate::date::BLAME_DATE as &dyn ReflectField,
+        &crate::coloring::BLAME_COLORING as &dyn ReflectField,
         &crate::blame::THEME as &dyn ReflectField,
+        &STRICT as &dyn ReflectField,
     ]);
+    output.push_str(&dump_profiles(config));
 
     if output_path == std::path::Path::new("-") {
         use std::io::Write;
         anstream::stdout()
             .write_all(output.as_bytes())
-            .with_code(proc_exit::Code::FAILURE)?;
+            .with_code(crate::exit_code::IO)?;
     } else {
-        std::fs::write(output_path, &output).with_code(proc_exit::Code::FAILURE)?;
+        std::fs::write(output_path, &output).with_code(crate::exit_code::IO)?;
     }
 
     Ok(())
 }
+
+/// Show how `key` is resolved, or list the known keys it's close to
+pub(crate) fn explain(key: &str, config: &Config) -> proc_exit::ExitResult {
+    use std::io::Write;
+
+    let fields = explainable_fields();
+    let Some(field) = fields.iter().find(|f| f.name().eq_ignore_ascii_case(key)) else {
+        let known = fields.iter().map(|f| f.name()).collect::<Vec<_>>();
+        let near = near_matches(key, &known);
+        let err = if near.is_empty() {
+            anyhow::format_err!("unknown config `{key}`")
+        } else {
+            let near = near
+                .iter()
+                .map(|k| format!("`{k}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow::format_err!("unknown config `{key}`; did you mean {near}?")
+        };
+        return Err(err).with_code(crate::exit_code::USAGE);
+    };
+
+    anstream::stdout()
+        .write_all(config.explain(*field).as_bytes())
+        .with_code(crate::exit_code::IO)?;
+    Ok(())
+}
+
+fn dump_profiles(config: &Config) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    for (name, args, source) in crate::profile::defined(config) {
+        let _ = writeln!(&mut output, "[dive \"profile.{name}\"]");
+        let _ = writeln!(&mut output, "\targs = {args}  # {source}");
+    }
+    output
+}
+
+/// Warn about `dive.*` keys that don't match any field, like typos
+pub(crate) fn check_config(config: &Config) {
+    let known = dive_fields()
+        .into_iter()
+        .map(|f| f.name())
+        .collect::<Vec<_>>();
+    let entries = match config.entries("dive") {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::debug!("could not list config: {}", err);
+            return;
+        }
+    };
+    for (key, source) in entries {
+        if known.iter().any(|k| k.eq_ignore_ascii_case(&key))
+            || crate::profile::is_profile_key(&key)
+        {
+            continue;
+        }
+        if let Some(suggestion) = closest(&key, &known) {
+            log::warn!("unknown config `{key}` in {source}; did you mean `{suggestion}`?");
+        } else {
+            log::warn!("unknown config `{key}` in {source}");
+        }
+    }
+}
+
+/// The dumped git fields and every `dive.*` field
+fn explainable_fields() -> Vec<&'static dyn ExplainField> {
+    let mut fields: Vec<&'static dyn ExplainField> = vec![
+        &crate::git2_config::COLOR_UI,
+        &crate::git2_config::PAGER,
+        &crate::date::BLAME_DATE,
+        &crate::coloring::BLAME_COLORING,
+    ];
+    fields.extend(dive_fields());
+    fields
+}
+
+/// Every `dive.*` field that is read
+fn dive_fields() -> Vec<&'static dyn ExplainField> {
+    vec![
+        &crate::blame::THEME,
+        &crate::blame::TAB_WIDTH,
+        &crate::blame::SYNTAX,
+        &crate::blame::SYNTAX_MAX_LINES,
+        &crate::blame::HASH_COLOR,
+        &crate::blame::AUTHOR_COLOR,
+        &crate::blame::DATE_COLOR,
+        &crate::blame::LINE_NUMBER_COLOR,
+        &crate::blame::GRID_COLOR,
+        &crate::glyphs::GRID_CHAR,
+        &crate::glyphs::ASCII,
+        &crate::blame::RESOLUTION_COLOR,
+        &crate::blame::MINE_COLOR,
+        &crate::blame::UNRELEASED_COLOR,
+        &crate::release::RELEASE_TAG_GLOB,
+        &crate::profile::DEFAULT_ARGS,
+        &crate::blame::MOVE_BADGE_THRESHOLD,
+        &crate::blame::DISTANCE_CAP,
+        &crate::boundary::BOUNDARY_STYLE,
+        &crate::lint::LINT_COLOR,
+        &crate::lint::MAX_LINE_LENGTH,
+        &crate::hash::HASH_STYLE,
+        &crate::hash::HASH_CASE,
+        &crate::hash::HASH_PREFIX,
+        &crate::detail::DETAIL_DEPTH,
+        &crate::compare::COMPARE_SPLIT_WIDTH,
+        &crate::again::REMEMBER,
+        &crate::mine::HIGHLIGHT_MINE,
+        &crate::mine::MY_EMAILS,
+        &crate::resolution::MAX_RESOLUTION_MERGES,
+        &crate::blame::NO_NEWLINE_MARKER,
+        &crate::blame::ANNOTATE_BLANK_LINES,
+        &crate::layout::MAX_GUTTER_RATIO,
+        &crate::palette::AUTHOR_PALETTE,
+        &crate::git_pager::PAGER_MIN_LINES,
+        &crate::git_pager::PAGER_PROBE,
+        &crate::git_pager::PAGER_SECTIONS,
+        &crate::git_pager::PAGER_LONG,
+        &crate::git_pager::PAGER_SHORT,
+        &crate::ignore_revs::GLOBAL_IGNORE_REVS_FILE,
+        &crate::ignore_revs::AUTO_IGNORE_REVS,
+        &crate::ignore_revs::MERGE_IGNORE_REVS,
+        &crate::date::USE_COMMITTER_DATE,
+        &crate::date::DATE_FORMAT,
+        &crate::locale::LOCALE,
+        &crate::date::FADE_OLDER_THAN,
+        &crate::coloring::COLORING,
+        &crate::path_display::PATH_DISPLAY,
+        &crate::stat::TEAM_MAP,
+        &crate::ignore_markers::IGNORE_MARKERS,
+        &crate::language::SYNTAX_FOR_EXTENSION,
+        &crate::engine::BLAME_ENGINE,
+        &crate::status_hints::STATUS_HINTS,
+        &crate::unicode::SHOW_CONTROL_CHARS,
+        &STRICT,
+    ]
+}
+
+fn closest<'k>(key: &str, known: &[&'k str]) -> Option<&'k str> {
+    near_matches(key, known).first().copied()
+}
+
+/// Keys within a few edits of `key`, closest first
+fn near_matches<'k>(key: &str, known: &[&'k str]) -> Vec<&'k str> {
+    let key = key.to_ascii_lowercase();
+    let mut near = known
+        .iter()
+        .map(|k| {
+            (
+                strsim::damerau_levenshtein(&key, &k.to_ascii_lowercase()),
+                *k,
+            )
+        })
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect::<Vec<_>>();
+    near.sort();
+    near.into_iter().map(|(_, k)| k).collect()
+}
+
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+pub(crate) const STRICT: DefaultField<bool> =
+    RawField::<bool>::new("dive.strict").default_value(|| true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn suggest_closest() {
+        let known = dive_fields()
+            .into_iter()
+            .map(|f| f.name())
+            .collect::<Vec<_>>();
+        assert_eq!(closest("dive.thme", &known), Some("dive.theme"));
+        assert_eq!(closest("dive.Hash-Colour", &known), Some("dive.hash-color"));
+        assert_eq!(closest("dive.unrelated", &known), None);
+    }
+}
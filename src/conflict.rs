@@ -0,0 +1,182 @@
+//! Blaming one side of a conflicted file, from its stage in the index
+
+use proc_exit::WithCodeResultExt;
+
+use crate::exit_code;
+
+/// A version of a conflicted file, by its stage in the index
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Side {
+    /// Stage 1, the common ancestor
+    Base,
+    /// Stage 2, the branch being merged into
+    Ours,
+    /// Stage 3, the branch being merged
+    Theirs,
+}
+
+impl Side {
+    /// Per `--ours`, `--theirs`, and `--base`
+    pub(crate) fn from_args(args: &crate::args::Args) -> Option<Self> {
+        if args.base {
+            Some(Self::Base)
+        } else if args.ours {
+            Some(Self::Ours)
+        } else if args.theirs {
+            Some(Self::Theirs)
+        } else {
+            None
+        }
+    }
+
+    fn stage(self) -> i32 {
+        match self {
+            Self::Base => 1,
+            Self::Ours => 2,
+            Self::Theirs => 3,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::Ours => "ours",
+            Self::Theirs => "theirs",
+        }
+    }
+}
+
+/// `:1:PATH`, `:2:PATH`, or `:3:PATH`, like `git show` takes
+pub(crate) fn split_stage(path: &std::path::Path) -> Option<(Side, &std::path::Path)> {
+    let s = path.to_str()?;
+    let (stage, rest) = s.strip_prefix(':')?.split_once(':')?;
+    let side = match stage {
+        "1" => Side::Base,
+        "2" => Side::Ours,
+        "3" => Side::Theirs,
+        _ => return None,
+    };
+    Some((side, std::path::Path::new(rest)))
+}
+
+/// What was merged into `HEAD` to conflict, and how to find the base
+struct Operation {
+    /// In the git dir, like `MERGE_HEAD`
+    head: &'static str,
+    theirs: &'static str,
+    base: Base,
+}
+
+enum Base {
+    MergeBase,
+    Rev(&'static str),
+}
+
+/// The first of these that is in progress is the one that conflicted
+const OPERATIONS: [Operation; 4] = [
+    Operation {
+        head: "MERGE_HEAD",
+        theirs: "MERGE_HEAD",
+        base: Base::MergeBase,
+    },
+    Operation {
+        head: "CHERRY_PICK_HEAD",
+        theirs: "CHERRY_PICK_HEAD",
+        base: Base::Rev("CHERRY_PICK_HEAD^"),
+    },
+    Operation {
+        head: "REBASE_HEAD",
+        theirs: "REBASE_HEAD",
+        base: Base::Rev("REBASE_HEAD^"),
+    },
+    // Reverting applies the reverse of `REVERT_HEAD`, so it is the base for its parent
+    Operation {
+        head: "REVERT_HEAD",
+        theirs: "REVERT_HEAD^",
+        base: Base::Rev("REVERT_HEAD"),
+    },
+];
+
+/// The revision whose history the `side` of a conflict comes from
+pub(crate) fn rev(repo: &git2::Repository, side: Side) -> Result<String, proc_exit::Exit> {
+    if side == Side::Ours {
+        return Ok("HEAD".to_owned());
+    }
+    let Some(operation) = OPERATIONS
+        .iter()
+        .find(|op| repo.revparse_single(op.head).is_ok())
+    else {
+        return Err(anyhow::format_err!(
+            "no merge, cherry-pick, rebase, or revert is in progress to take `--{}` from",
+            side.name()
+        ))
+        .with_code(exit_code::BAD_REV);
+    };
+    log::debug!("taking `--{}` from `{}`", side.name(), operation.head);
+    match (side, &operation.base) {
+        (Side::Base, Base::MergeBase) => {
+            let head = repo
+                .revparse_single("HEAD")
+                .and_then(|head| head.peel_to_commit())
+                .with_code(exit_code::BAD_REV)?;
+            let theirs = repo
+                .revparse_single(operation.theirs)
+                .and_then(|theirs| theirs.peel_to_commit())
+                .with_code(exit_code::BAD_REV)?;
+            let base = repo
+                .merge_base(head.id(), theirs.id())
+                .with_code(exit_code::BAD_REV)?;
+            Ok(base.to_string())
+        }
+        (Side::Base, Base::Rev(rev)) => Ok((*rev).to_owned()),
+        _ => Ok(operation.theirs.to_owned()),
+    }
+}
+
+/// `tree_path`'s content at `side`'s stage of the index
+pub(crate) fn read_stage(
+    repo: &git2::Repository,
+    tree_path: &std::path::Path,
+    side: Side,
+) -> Result<Vec<u8>, proc_exit::Exit> {
+    let index = repo.index().with_code(proc_exit::Code::FAILURE)?;
+    let Some(entry) = index.get_path(tree_path, side.stage()) else {
+        let conflicted = (1..=3).any(|stage| index.get_path(tree_path, stage).is_some());
+        let err = if conflicted {
+            anyhow::format_err!(
+                "{} has no `{}` version in the conflict",
+                tree_path.display(),
+                side.name()
+            )
+        } else {
+            anyhow::format_err!("{} isn't conflicted", tree_path.display())
+        };
+        return Err(err).with_code(exit_code::PATH_NOT_IN_TREE);
+    };
+    let blob = repo
+        .find_blob(entry.id)
+        .with_code(proc_exit::Code::FAILURE)?;
+    Ok(blob.content().to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stage_paths() {
+        let split = |path: &str| {
+            split_stage(std::path::Path::new(path))
+                .map(|(side, path)| (side, path.to_str().unwrap().to_owned()))
+        };
+        assert_eq!(
+            split(":2:src/lib.rs"),
+            Some((Side::Ours, "src/lib.rs".to_owned()))
+        );
+        assert_eq!(split(":3:a:b"), Some((Side::Theirs, "a:b".to_owned())));
+        assert_eq!(split(":1:x"), Some((Side::Base, "x".to_owned())));
+        assert_eq!(split(":0:src/lib.rs"), None);
+        assert_eq!(split(":(icase)src/lib.rs"), None);
+        assert_eq!(split("src/lib.rs"), None);
+    }
+}
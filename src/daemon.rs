@@ -0,0 +1,365 @@
+//! Answer `--format json` requests over a unix socket, keeping repositories open between them
+//!
+//! Each request is a line of JSON:
+//!
+//! ```json
+//! {"cwd": "/path/to/repo", "args": ["--ignore-rev", "abc123"], "rev": "HEAD", "path": "src/lib.rs"}
+//! ```
+//!
+//! `args` are what would follow `git-dive` on the command line, with `rev` and `path`, when
+//! present, appended.  Each response is a line with the `--format json` output or
+//! `{"error": MESSAGE, "code": EXIT_CODE}`.  `{"stop": true}` stops the daemon.
+//!
+//! Requests are answered one at a time with the daemon's environment and the current directory
+//! set to `cwd`.
+
+use proc_exit::WithCodeResultExt;
+
+use crate::args::Args;
+use crate::args::OutputFormat;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::renderer::JsonFile;
+use crate::renderer::JsonRenderer;
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct Request {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<std::path::PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rev: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stop: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Files(Vec<JsonFile>),
+    Error { error: String, code: i32 },
+    Stopped { stopped: bool },
+}
+
+/// Where the daemon listens
+///
+/// `$XDG_RUNTIME_DIR/git-dive/daemon.sock`, else `git-dive/daemon.sock` in the git dir of
+/// the repo for `args`.
+fn socket_path(args: &Args) -> anyhow::Result<std::path::PathBuf> {
+    let dir = if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .filter(|p| p.is_absolute())
+    {
+        runtime.join("git-dive")
+    } else {
+        let cwd = std::env::current_dir()?;
+        let repo = crate::repo::open(&cwd, args).map_err(|err| {
+            anyhow::format_err!("no `XDG_RUNTIME_DIR` or repository for the socket: {err}")
+        })?;
+        repo.path().join("git-dive")
+    };
+    Ok(dir.join("daemon.sock"))
+}
+
+#[cfg(unix)]
+pub(crate) fn serve(args: &Args) -> proc_exit::ExitResult {
+    use std::io::BufRead as _;
+    use std::os::unix::fs::DirBuilderExt as _;
+
+    let path = socket_path(args).with_code(exit_code::NOT_A_REPO)?;
+    let dir = path.parent().expect("socket is in a dir");
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .with_code(exit_code::IO)?;
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            return Err(anyhow::format_err!(
+                "a daemon is already listening on {}",
+                path.display()
+            ))
+            .with_code(proc_exit::Code::FAILURE);
+        }
+        log::debug!("removing stale socket {}", path.display());
+        std::fs::remove_file(&path).with_code(exit_code::IO)?;
+    }
+    let listener = std::os::unix::net::UnixListener::bind(&path).with_code(exit_code::IO)?;
+    let _socket = RemoveOnDrop(path.clone());
+    log::info!("listening on {}", path.display());
+
+    let mut daemon = Daemon::default();
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::warn!("could not accept a connection: {err}");
+                continue;
+            }
+        };
+        let mut out = std::io::BufWriter::new(&stream);
+        for line in std::io::BufReader::new(&stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let stop = daemon.respond(&line, &mut out);
+            if std::io::Write::flush(&mut out).is_err() {
+                break;
+            }
+            if stop {
+                log::info!("stopping");
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn serve(_args: &Args) -> proc_exit::ExitResult {
+    Err(anyhow::format_err!("`--daemon` needs unix sockets")).with_code(exit_code::USAGE)
+}
+
+/// What stays loaded between requests
+#[derive(Default)]
+struct Daemon {
+    /// By the directory and flags they were opened for
+    repos: std::collections::HashMap<RepoKey, git2::Repository>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct RepoKey {
+    cwd: std::path::PathBuf,
+    git_dir: Option<std::path::PathBuf>,
+    work_tree: Option<std::path::PathBuf>,
+}
+
+impl Daemon {
+    /// Answer `line`, returning whether to stop
+    fn respond(&mut self, line: &str, out: &mut dyn std::io::Write) -> bool {
+        let request = match serde_json::from_str::<Request>(line) {
+            Ok(request) => request,
+            Err(err) => {
+                write_error(out, &format!("invalid request: {err}"), exit_code::USAGE);
+                return false;
+            }
+        };
+        if request.stop {
+            write_response(out, &Response::Stopped { stopped: true });
+            return true;
+        }
+        if let Err(err) = self.annotate(request, out) {
+            let message = err.to_string();
+            // Also logs the error
+            let code = proc_exit::report(Err(err));
+            write_error(out, &message, code);
+        }
+        false
+    }
+
+    fn annotate(
+        &mut self,
+        request: Request,
+        out: &mut dyn std::io::Write,
+    ) -> proc_exit::ExitResult {
+        if let Some(cwd) = &request.cwd {
+            std::env::set_current_dir(cwd).with_code(proc_exit::Code::FAILURE)?;
+        }
+        let cwd = std::env::current_dir().with_code(proc_exit::Code::FAILURE)?;
+        let argv = std::iter::once("git-dive".to_owned())
+            .chain(request.args)
+            .chain(request.rev)
+            .chain(request.path);
+        let mut args = Args::try_parse_argv(argv).with_code(exit_code::USAGE)?;
+        if let Some(rev_file) = args.rev_file.as_deref() {
+            args.rev = crate::rev::read_rev_file(rev_file)?;
+        }
+        let Some(file_path) = args.file.as_deref() else {
+            return Err(anyhow::format_err!("the daemon only annotates files"))
+                .with_code(exit_code::USAGE);
+        };
+        if args.format == OutputFormat::Quickfix
+            || args.stat
+            || args.compare.is_some()
+            || args.changed_only
+            || args.detail
+        {
+            return Err(anyhow::format_err!(
+                "the daemon only answers with `--format json`"
+            ))
+            .with_code(exit_code::USAGE);
+        }
+        if args.contents.as_deref() == Some(std::path::Path::new("-")) {
+            return Err(anyhow::format_err!(
+                "`--contents -` can't be read by the daemon"
+            ))
+            .with_code(exit_code::USAGE);
+        }
+
+        let key = RepoKey {
+            cwd: cwd.clone(),
+            git_dir: args.git_dir.clone(),
+            work_tree: args.work_tree.clone(),
+        };
+        let repo = match self.repos.entry(key) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let repo = crate::repo::open(&cwd, &args)?;
+                log::debug!("opened {}", repo.path().display());
+                entry.insert(repo)
+            }
+        };
+        let mut config = Config::system();
+        let mut renderer = JsonRenderer::compact(out);
+        match crate::blame::annotate(repo, &cwd, file_path, &mut config, &args)? {
+            crate::blame::Target::File(blamed) => {
+                crate::renderer::render(
+                    &mut renderer,
+                    &blamed.shown_path,
+                    &blamed.file,
+                    &blamed.attributions,
+                    &blamed.annotations,
+                    &blamed.resolutions,
+                )
+                .with_code(proc_exit::Code::FAILURE)?;
+            }
+            crate::blame::Target::Skipped { path, notice } => {
+                renderer.skip_file(&path, &notice);
+                crate::renderer::Renderer::finish(&mut renderer)
+                    .with_code(proc_exit::Code::FAILURE)?;
+            }
+            crate::blame::Target::Dir { .. } => {
+                return Err(anyhow::format_err!("the daemon only annotates files"))
+                    .with_code(exit_code::USAGE);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_response(out: &mut dyn std::io::Write, response: &Response) {
+    let mut line = serde_json::to_string(response).expect("responses are serializable");
+    line.push('\n');
+    let _ = out.write_all(line.as_bytes());
+}
+
+fn write_error(out: &mut dyn std::io::Write, message: &str, code: proc_exit::Code) {
+    write_response(
+        out,
+        &Response::Error {
+            error: message.to_owned(),
+            code: code.as_raw(),
+        },
+    );
+}
+
+/// Ask a listening daemon for the output of `argv`, `None` when it should be run here
+///
+/// Only `--format json` is answered by the daemon, and not `--contents -`.
+#[cfg(unix)]
+pub(crate) fn client(argv: &[std::ffi::OsString], args: &Args) -> Option<proc_exit::ExitResult> {
+    if args.format != OutputFormat::Json {
+        log::debug!("not using the daemon without `--format json`");
+        return None;
+    }
+    if args.contents.as_deref() == Some(std::path::Path::new("-")) {
+        log::debug!("not using the daemon with `--contents -`");
+        return None;
+    }
+    let args_utf8 = argv
+        .iter()
+        .skip(1)
+        .map(|arg| arg.to_str().map(ToOwned::to_owned))
+        .collect::<Option<Vec<_>>>()?;
+    let request = Request {
+        cwd: std::env::current_dir().ok(),
+        args: args_utf8,
+        ..Default::default()
+    };
+    let response = match ask(args, &request) {
+        Ok(response) => response,
+        Err(err) => {
+            log::debug!("not using the daemon: {err}");
+            return None;
+        }
+    };
+    log::debug!("answered by the daemon");
+    let result = match response {
+        Response::Files(files) => write_files(files, args),
+        Response::Error { error, code } => {
+            Err(proc_exit::Exit::new(proc_exit::Code::new(code)).with_message(error))
+        }
+        Response::Stopped { .. } => {
+            Err(anyhow::format_err!("the daemon stopped")).with_code(proc_exit::Code::FAILURE)
+        }
+    };
+    Some(result)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn client(_argv: &[std::ffi::OsString], _args: &Args) -> Option<proc_exit::ExitResult> {
+    None
+}
+
+#[cfg(unix)]
+fn write_files(files: Vec<JsonFile>, args: &Args) -> proc_exit::ExitResult {
+    let config = Config::system();
+    let mut pager = crate::git_pager::Pager::resolve(&config, args)?;
+    let mut pager = pager.start();
+    let pager = pager.as_writer().with_code(exit_code::IO)?;
+    let mut renderer = JsonRenderer::from_files(pager, files);
+    crate::renderer::Renderer::finish(&mut renderer).with_code(proc_exit::Code::FAILURE)
+}
+
+/// Stop the daemon listening for `args`
+#[cfg(unix)]
+pub(crate) fn stop(args: &Args) -> proc_exit::ExitResult {
+    let request = Request {
+        stop: true,
+        ..Default::default()
+    };
+    ask(args, &request).with_code(proc_exit::Code::FAILURE)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn stop(_args: &Args) -> proc_exit::ExitResult {
+    Err(anyhow::format_err!("`--daemon-stop` needs unix sockets")).with_code(exit_code::USAGE)
+}
+
+#[cfg(unix)]
+fn ask(args: &Args, request: &Request) -> anyhow::Result<Response> {
+    use std::io::BufRead as _;
+    use std::io::Write as _;
+
+    let path = socket_path(args)?;
+    let stream = std::os::unix::net::UnixStream::connect(&path).map_err(|err| {
+        anyhow::format_err!("no daemon is listening on {}: {err}", path.display())
+    })?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    (&stream).write_all(line.as_bytes())?;
+    let mut response = String::new();
+    std::io::BufReader::new(&stream).read_line(&mut response)?;
+    if response.is_empty() {
+        anyhow::bail!("the daemon closed the connection");
+    }
+    Ok(serde_json::from_str(&response)?)
+}
+
+/// Remove the socket when the daemon exits
+struct RemoveOnDrop(std::path::PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.0) {
+            log::debug!("could not remove {}: {err}", self.0.display());
+        }
+    }
+}
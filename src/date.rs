@@ -0,0 +1,640 @@
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+use crate::locale::Locale;
+use crate::locale::Unit;
+
+/// Which of a commit's dates to show
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum DateKind {
+    /// When the change was written
+    #[default]
+    Author,
+    /// When the change landed, which differs after a rebase
+    Committer,
+}
+
+impl DateKind {
+    /// `--committer-date`, falling back to `dive.use-committer-date`
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Self {
+        if args.committer_date || config.get(&USE_COMMITTER_DATE) {
+            Self::Committer
+        } else {
+            Self::Author
+        }
+    }
+
+    pub(crate) fn of(self, commit: &git2::Commit<'_>) -> git2::Time {
+        match self {
+            Self::Author => commit.author().when(),
+            Self::Committer => commit.committer().when(),
+        }
+    }
+}
+
+pub(crate) const USE_COMMITTER_DATE: DefaultField<bool> =
+    RawField::<bool>::new("dive.use-committer-date").default();
+
+/// How to write dates, named like git's `--date`
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum DateFormat {
+    /// `2024-02-29`
+    #[default]
+    Short,
+    /// `2024-02-29 12:00:00 +0000`
+    #[value(alias = "iso8601")]
+    Iso,
+    /// `2024-02-29T12:00:00+00:00`
+    #[value(alias = "iso8601-strict")]
+    IsoStrict,
+    /// `Thu, 29 Feb 2024 12:00:00 +0000`
+    #[value(alias = "rfc2822")]
+    Rfc,
+    /// `1709208000 +0000`
+    Raw,
+    /// `1709208000`
+    Unix,
+    /// `2 years, 3 months ago`
+    Relative,
+}
+
+impl DateFormat {
+    /// `--date`, falling back to `dive.date`, then to git's own `blame.date`
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Self {
+        args.date
+            .or_else(|| config.get(&DATE_FORMAT))
+            .unwrap_or_else(|| config.get(&BLAME_DATE))
+    }
+
+    pub(crate) fn format(self, time: git2::Time, locale: &Locale) -> String {
+        match self {
+            Self::Short => format_short(time),
+            Self::Iso => {
+                let (date, clock) = civil_time(time);
+                format!("{date} {clock} {}", offset(time, ""))
+            }
+            Self::IsoStrict => {
+                let (date, clock) = civil_time(time);
+                format!("{date}T{clock}{}", offset(time, ":"))
+            }
+            Self::Rfc => {
+                let (year, month, day) = civil_date(time);
+                let (_, clock) = civil_time(time);
+                // The epoch was a Thursday
+                let weekday = locale.weekday((local_days(time) + 3).rem_euclid(7) as usize);
+                let month = locale.month(month);
+                format!(
+                    "{weekday}, {day} {month} {year} {clock} {}",
+                    offset(time, "")
+                )
+            }
+            Self::Raw => format!("{} {}", time.seconds(), offset(time, "")),
+            Self::Unix => time.seconds().to_string(),
+            Self::Relative => format_relative(time, now(), locale),
+        }
+    }
+}
+
+impl Parseable for DateFormat {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        <Self as clap::ValueEnum>::from_str(s, true)
+            .map_err(|_| anyhow::format_err!("unsupported date format `{s}`"))
+    }
+}
+
+impl std::fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = clap::ValueEnum::to_possible_value(self).expect("no values are skipped");
+        value.get_name().fmt(f)
+    }
+}
+
+/// A [`DateFormat`] written in a [`Locale`]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Dates {
+    format: DateFormat,
+    locale: &'static Locale,
+}
+
+impl Dates {
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Self {
+        Self {
+            format: DateFormat::resolve(config, args),
+            locale: Locale::resolve(config),
+        }
+    }
+
+    pub(crate) fn format(&self, time: git2::Time) -> String {
+        self.format.format(time, self.locale)
+    }
+}
+
+pub(crate) const DATE_FORMAT: RawField<DateFormat> = RawField::<DateFormat>::new("dive.date");
+
+pub(crate) const BLAME_DATE: DefaultField<DateFormat> =
+    RawField::<DateFormat>::new("blame.date").default();
+
+/// How old a commit is, like `2y`, `18m`, `1y6m`, or `90d`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Age {
+    seconds: i64,
+    spelled: String,
+}
+
+impl Age {
+    /// The time `self` before `now`
+    pub(crate) fn before(&self, now: i64) -> i64 {
+        now.saturating_sub(self.seconds)
+    }
+}
+
+impl std::str::FromStr for Age {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            anyhow::format_err!(
+                "invalid duration `{s}`; expected counts with units like `90d`, where the units are `y`, `m`, `w`, `d`, and `h`"
+            )
+        };
+        let mut seconds = 0_i64;
+        let mut rest = s.trim();
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let count = rest[..digits].parse::<i64>().map_err(|_| invalid())?;
+            let unit = rest[digits..].chars().next().ok_or_else(invalid)?;
+            let unit_seconds = match unit {
+                'y' => 365 * SECONDS_PER_DAY,
+                'm' => 30 * SECONDS_PER_DAY,
+                'w' => 7 * SECONDS_PER_DAY,
+                'd' => SECONDS_PER_DAY,
+                'h' => 60 * 60,
+                _ => return Err(invalid()),
+            };
+            seconds = count
+                .checked_mul(unit_seconds)
+                .and_then(|count| seconds.checked_add(count))
+                .ok_or_else(invalid)?;
+            rest = &rest[digits + unit.len_utf8()..];
+        }
+        Ok(Self {
+            seconds,
+            spelled: s.trim().to_owned(),
+        })
+    }
+}
+
+impl Parseable for Age {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Age {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.spelled.fmt(f)
+    }
+}
+
+pub(crate) const FADE_OLDER_THAN: RawField<Age> = RawField::<Age>::new("dive.fade-older-than");
+
+/// A point in time, like `git log --since` takes, though only its common forms
+///
+/// - `2023-06-01`, through the end of that day
+/// - `2023-06-01 14:30`, `2023-06-01T14:30:00`, with an optional `+0200` or `Z`
+/// - `3 weeks ago`, `3.weeks.ago`, or a duration like `90d`
+/// - `yesterday`, `now`, or `@SECONDS` since the epoch
+///
+/// Without an offset, times are in UTC.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Approxidate {
+    when: When,
+    spelled: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum When {
+    At(i64),
+    Ago(i64),
+}
+
+impl Approxidate {
+    /// Seconds since the epoch, relative ones counting back from `now`
+    pub(crate) fn seconds(&self, now: i64) -> i64 {
+        match self.when {
+            When::At(seconds) => seconds,
+            When::Ago(seconds) => now.saturating_sub(seconds),
+        }
+    }
+}
+
+impl std::str::FromStr for Approxidate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let spelled = s.trim();
+        let lower = spelled.to_ascii_lowercase();
+        let when = match lower.as_str() {
+            "now" => When::Ago(0),
+            "yesterday" => When::Ago(SECONDS_PER_DAY),
+            _ => {
+                if let Some(epoch) = lower.strip_prefix('@') {
+                    When::At(epoch.parse().map_err(|_| invalid_date(s))?)
+                } else if let Some(ago) = parse_ago(&lower) {
+                    When::Ago(ago)
+                } else if let Ok(age) = lower.parse::<Age>() {
+                    When::Ago(age.seconds)
+                } else {
+                    When::At(parse_absolute(&lower).ok_or_else(|| invalid_date(s))?)
+                }
+            }
+        };
+        Ok(Self {
+            when,
+            spelled: spelled.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for Approxidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.spelled.fmt(f)
+    }
+}
+
+fn invalid_date(s: &str) -> anyhow::Error {
+    anyhow::format_err!(
+        "invalid date `{s}`; expected one like `2023-06-01`, `2023-06-01 14:30`, or `3 weeks ago`"
+    )
+}
+
+/// `3 weeks ago` or `3.weeks.ago`, in seconds
+fn parse_ago(s: &str) -> Option<i64> {
+    let mut words = s.split([' ', '.']).filter(|w| !w.is_empty());
+    let count = words.next()?.parse::<i64>().ok()?;
+    let unit = words.next()?;
+    if words.next()? != "ago" || words.next().is_some() {
+        return None;
+    }
+    let unit_seconds = match unit.strip_suffix('s').unwrap_or(unit) {
+        "second" => 1,
+        "minute" => 60,
+        "hour" => 60 * 60,
+        "day" => SECONDS_PER_DAY,
+        "week" => 7 * SECONDS_PER_DAY,
+        "month" => 30 * SECONDS_PER_DAY,
+        "year" => 365 * SECONDS_PER_DAY,
+        _ => return None,
+    };
+    count.checked_mul(unit_seconds)
+}
+
+/// `YYYY-MM-DD[( |T)HH:MM[:SS]][ ][(+|-)HHMM|Z]`, in seconds
+fn parse_absolute(s: &str) -> Option<i64> {
+    let number = |s: &str| s.parse::<i64>().ok();
+    let (date, time) = match s.split_once(['t', ' ']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (s, None),
+    };
+    let mut fields = date.splitn(3, '-');
+    let year = number(fields.next()?)?;
+    let month = number(fields.next()?).filter(|m| (1..=12).contains(m))?;
+    let day = number(fields.next()?).filter(|d| (1..=31).contains(d))?;
+    let days = days_from_civil(year, month as u32, day as u32);
+
+    let Some(time) = time else {
+        // Through the end of the day, so its commits count
+        return Some(days * SECONDS_PER_DAY + SECONDS_PER_DAY - 1);
+    };
+    let (clock, offset) = if let Some(clock) = time.strip_suffix('z') {
+        (clock.trim(), 0)
+    } else if let Some(i) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(i);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let offset = offset[1..].replace(':', "");
+        if offset.len() != 4 {
+            return None;
+        }
+        let minutes = number(&offset[..2])? * 60 + number(&offset[2..])?;
+        (clock.trim(), sign * minutes * 60)
+    } else {
+        (time, 0)
+    };
+    let mut fields = clock.split(':');
+    let hours = number(fields.next()?).filter(|h| (0..24).contains(h))?;
+    let minutes = number(fields.next()?).filter(|m| (0..60).contains(m))?;
+    let seconds = match fields.next() {
+        Some(seconds) => number(seconds).filter(|s| (0..61).contains(s))?,
+        None => 0,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(days * SECONDS_PER_DAY + hours * 60 * 60 + minutes * 60 + seconds - offset)
+}
+
+/// Format as `YYYY-MM-DD` in the time's own timezone
+pub(crate) fn format_short(time: git2::Time) -> String {
+    let (year, month, day) = civil_date(time);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Format as `YY-MM-DD` in the time's own timezone, for narrow terminals
+pub(crate) fn format_compact(time: git2::Time) -> String {
+    let (year, month, day) = civil_date(time);
+    format!("{:02}-{month:02}-{day:02}", year.rem_euclid(100))
+}
+
+/// Seconds since the unix epoch
+pub(crate) fn now() -> i64 {
+    if crate::render_test::enabled() {
+        return crate::render_test::NOW;
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Like git's `--date=relative`, rounding the same way
+fn format_relative(time: git2::Time, now: i64, locale: &Locale) -> String {
+    let ago = |count: i64, unit: Unit| locale.ago(&locale.count(count, unit));
+    let seconds = now - time.seconds();
+    if seconds < 0 {
+        return locale.future().to_owned();
+    }
+    if seconds < 90 {
+        return ago(seconds, Unit::Second);
+    }
+    let minutes = (seconds + 30) / 60;
+    if minutes < 90 {
+        return ago(minutes, Unit::Minute);
+    }
+    let hours = (minutes + 30) / 60;
+    if hours < 36 {
+        return ago(hours, Unit::Hour);
+    }
+    let days = (hours + 12) / 24;
+    if days < 14 {
+        return ago(days, Unit::Day);
+    }
+    if days < 70 {
+        return ago((days + 3) / 7, Unit::Week);
+    }
+    if days < 365 {
+        return ago((days + 15) / 30, Unit::Month);
+    }
+    if days < 1825 {
+        let total_months = (days * 12 * 2 + 365) / (365 * 2);
+        let (years, months) = (total_months / 12, total_months % 12);
+        if months != 0 {
+            let age = locale.join(
+                &locale.count(years, Unit::Year),
+                &locale.count(months, Unit::Month),
+            );
+            return locale.ago(&age);
+        }
+        return ago(years, Unit::Year);
+    }
+    ago((days + 183) / 365, Unit::Year)
+}
+
+/// `+HHMM`, with `separator` between hours and minutes
+fn offset(time: git2::Time, separator: &str) -> String {
+    let minutes = time.offset_minutes();
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("{sign}{:02}{separator}{:02}", minutes / 60, minutes % 60)
+}
+
+/// `YYYY-MM-DD` and `HH:MM:SS`, in the time's own timezone
+fn civil_time(time: git2::Time) -> (String, String) {
+    let local = local_seconds(time).rem_euclid(SECONDS_PER_DAY);
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        local / 3600,
+        local / 60 % 60,
+        local % 60
+    );
+    (format_short(time), clock)
+}
+
+/// The calendar date for `time`, in its own timezone
+fn civil_date(time: git2::Time) -> (i64, u32, u32) {
+    civil_from_days(local_days(time))
+}
+
+fn local_seconds(time: git2::Time) -> i64 {
+    time.seconds() + i64::from(time.offset_minutes()) * 60
+}
+
+/// Days since the unix epoch, in the time's own timezone
+fn local_days(time: git2::Time) -> i64 {
+    local_seconds(time).div_euclid(SECONDS_PER_DAY)
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Convert days since the unix epoch to a proleptic Gregorian date
+///
+/// See Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert a proleptic Gregorian date to days since the unix epoch
+///
+/// See Howard Hinnant's `days_from_civil`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let mp = if 2 < month { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ages() {
+        let seconds = |s: &str| s.parse::<Age>().map(|age| age.seconds).ok();
+        assert_eq!(seconds("90d"), Some(90 * SECONDS_PER_DAY));
+        assert_eq!(seconds("2y"), Some(2 * 365 * SECONDS_PER_DAY));
+        assert_eq!(seconds("1y6m"), Some((365 + 6 * 30) * SECONDS_PER_DAY));
+        assert_eq!(seconds("12h"), Some(12 * 60 * 60));
+        assert_eq!(seconds("2"), None);
+        assert_eq!(seconds("d"), None);
+        assert_eq!(seconds("3x"), None);
+        assert_eq!(seconds(""), None);
+        assert_eq!(seconds("9999999999999999y"), None);
+    }
+
+    #[test]
+    fn epoch() {
+        assert_eq!(format_short(git2::Time::new(0, 0)), "1970-01-01");
+    }
+
+    #[test]
+    fn leap_day() {
+        // 2024-02-29T12:00:00Z
+        assert_eq!(
+            format_short(git2::Time::new(1_709_208_000, 0)),
+            "2024-02-29"
+        );
+    }
+
+    #[test]
+    fn respects_offset() {
+        // 2023-03-16T23:30:00Z
+        let seconds = 1_679_009_400;
+        assert_eq!(format_short(git2::Time::new(seconds, 0)), "2023-03-16");
+        assert_eq!(format_short(git2::Time::new(seconds, 60)), "2023-03-17");
+        assert_eq!(
+            format_short(git2::Time::new(seconds, -24 * 60)),
+            "2023-03-15"
+        );
+    }
+
+    #[test]
+    fn compact() {
+        // 2024-02-29T12:00:00Z
+        assert_eq!(
+            format_compact(git2::Time::new(1_709_208_000, 0)),
+            "24-02-29"
+        );
+        assert_eq!(format_compact(git2::Time::new(0, 0)), "70-01-01");
+    }
+
+    #[test]
+    fn formats() {
+        // 2024-02-29T12:00:00Z, a Thursday, seen from UTC-05:30
+        let time = git2::Time::new(1_709_208_000, -330);
+        let format = |f: DateFormat| f.format(time, crate::locale::ENGLISH);
+        assert_eq!(format(DateFormat::Short), "2024-02-29");
+        assert_eq!(format(DateFormat::Iso), "2024-02-29 06:30:00 -0530");
+        assert_eq!(format(DateFormat::IsoStrict), "2024-02-29T06:30:00-05:30");
+        assert_eq!(format(DateFormat::Rfc), "Thu, 29 Feb 2024 06:30:00 -0530");
+        assert_eq!(format(DateFormat::Raw), "1709208000 -0530");
+        assert_eq!(format(DateFormat::Unix), "1709208000");
+    }
+
+    #[test]
+    fn relative() {
+        let now = 1_709_208_000;
+        let at =
+            |ago: i64| format_relative(git2::Time::new(now - ago, 0), now, crate::locale::ENGLISH);
+        assert_eq!(at(-10), "in the future");
+        assert_eq!(at(1), "1 second ago");
+        assert_eq!(at(45 * 60), "45 minutes ago");
+        assert_eq!(at(3 * SECONDS_PER_DAY), "3 days ago");
+        assert_eq!(at(30 * SECONDS_PER_DAY), "4 weeks ago");
+        assert_eq!(at(400 * SECONDS_PER_DAY), "1 year, 1 month ago");
+        assert_eq!(at(3650 * SECONDS_PER_DAY), "10 years ago");
+    }
+
+    #[test]
+    fn localized() {
+        let now = 1_709_208_000;
+        let de = Locale::find("de").unwrap();
+        let at = |ago: i64| format_relative(git2::Time::new(now - ago, 0), now, de);
+        assert_eq!(at(1), "vor 1 Sekunde");
+        assert_eq!(at(3 * SECONDS_PER_DAY), "vor 3 Tagen");
+        assert_eq!(at(800 * SECONDS_PER_DAY), "vor 2 Jahren, 2 Monaten");
+
+        // 2024-02-29T12:00:00Z, a Thursday
+        let time = git2::Time::new(1_709_208_000, 0);
+        let ja = Locale::find("ja").unwrap();
+        assert_eq!(
+            DateFormat::Rfc.format(time, ja),
+            "木, 29 2月 2024 12:00:00 +0000"
+        );
+        // Only relative and `rfc` dates have words
+        assert_eq!(
+            DateFormat::Iso.format(time, ja),
+            "2024-02-29 12:00:00 +0000"
+        );
+    }
+
+    #[test]
+    fn parse_git_names() {
+        assert_eq!(DateFormat::parse("iso8601").unwrap(), DateFormat::Iso);
+        assert_eq!(
+            DateFormat::parse("ISO-strict").unwrap(),
+            DateFormat::IsoStrict
+        );
+        assert_eq!(DateFormat::parse("rfc2822").unwrap(), DateFormat::Rfc);
+        assert!(DateFormat::parse("format:%Y").is_err());
+    }
+
+    #[test]
+    fn before_epoch() {
+        assert_eq!(format_short(git2::Time::new(-1, 0)), "1969-12-31");
+    }
+
+    #[test]
+    fn approxidates() {
+        let now = crate::render_test::NOW;
+        let seconds = |s: &str| s.parse::<Approxidate>().map(|date| date.seconds(now)).ok();
+        // 2023-06-01T00:00:00Z
+        let day = 1_685_577_600;
+        assert_eq!(seconds("2023-06-01"), Some(day + SECONDS_PER_DAY - 1));
+        assert_eq!(seconds("2023-06-01 14:30"), Some(day + 14 * 3600 + 30 * 60));
+        assert_eq!(
+            seconds("2023-06-01T14:30:15Z"),
+            Some(day + 14 * 3600 + 30 * 60 + 15)
+        );
+        assert_eq!(
+            seconds("2023-06-01 14:30 +0200"),
+            Some(day + 12 * 3600 + 30 * 60)
+        );
+        assert_eq!(
+            seconds("2023-06-01T14:30-07:00"),
+            Some(day + 21 * 3600 + 30 * 60)
+        );
+        assert_eq!(seconds("3 weeks ago"), Some(now - 21 * SECONDS_PER_DAY));
+        assert_eq!(seconds("1.day.ago"), Some(now - SECONDS_PER_DAY));
+        assert_eq!(seconds("90d"), Some(now - 90 * SECONDS_PER_DAY));
+        assert_eq!(seconds("yesterday"), Some(now - SECONDS_PER_DAY));
+        assert_eq!(seconds("Now"), Some(now));
+        assert_eq!(seconds("@1685577600"), Some(day));
+
+        for invalid in [
+            "",
+            "2023-13-01",
+            "2023-06",
+            "3 fortnights ago",
+            "25:00",
+            "soon",
+        ] {
+            assert_eq!(seconds(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn civil_round_trip() {
+        for days in [-719_468, -1, 0, 19_509, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::commit_graph::CommitGraph;
+use crate::replace::Replacements;
+
+/// Where a file was last seen before it was deleted
+pub(crate) struct Deletion<'r> {
+    pub(crate) deleted_in: git2::Commit<'r>,
+    pub(crate) last_seen: git2::Commit<'r>,
+    /// `last_seen`, spelled relative to the starting rev when possible
+    pub(crate) rev: String,
+}
+
+/// Search the history of `start` for the most recent commit that deleted `path`
+///
+/// Gives up after looking at `--max-commits` commits, following only first parents with
+/// `--first-parent`.  Parents are seen through `replacements`, so grafted history is searched
+/// too.
+pub(crate) fn find_deletion<'r>(
+    repo: &'r git2::Repository,
+    replacements: &Replacements,
+    start: &git2::Commit<'r>,
+    start_rev: &str,
+    path: &std::path::Path,
+    args: &crate::args::Args,
+    graph: Option<&CommitGraph>,
+) -> anyhow::Result<Option<Deletion<'r>>> {
+    let max_commits = args.max_commits;
+    if args.first_parent {
+        // Like git, the commit-graph isn't used when replace refs change the parents
+        let graph = graph.filter(|_| replacements.is_empty());
+        return find_first_parent_deletion(
+            repo,
+            replacements,
+            graph,
+            start,
+            start_rev,
+            path,
+            max_commits,
+        );
+    }
+
+    let backend = crate::backend::Git2::new(repo, replacements);
+    let mut walk = crate::replace::Walk::new(&backend);
+    walk.push(start.id())?;
+    for id in walk.take(max_commits) {
+        let commit = repo.find_commit(id?)?;
+        if has_path(&commit, path)? {
+            continue;
+        }
+        for parent in replacements.parents(repo, commit.id())? {
+            let parent = repo.find_commit(parent)?;
+            if has_path(&parent, path)? {
+                let rev = parent
+                    .as_object()
+                    .short_id()?
+                    .as_str()
+                    .expect("short_id is always valid UTF-8")
+                    .to_owned();
+                return Ok(Some(Deletion {
+                    deleted_in: commit,
+                    last_seen: parent,
+                    rev,
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Like [`find_deletion`] along first parents, reading trees and parents from the commit-graph
+/// when there is one
+///
+/// Commits written after the commit-graph are parsed as usual.
+fn find_first_parent_deletion<'r>(
+    repo: &'r git2::Repository,
+    replacements: &Replacements,
+    graph: Option<&CommitGraph>,
+    start: &git2::Commit<'r>,
+    start_rev: &str,
+    path: &std::path::Path,
+    max_commits: usize,
+) -> anyhow::Result<Option<Deletion<'r>>> {
+    let first_parent = |id: git2::Oid| -> anyhow::Result<(git2::Oid, Option<git2::Oid>)> {
+        if let Some(commit) = graph.and_then(|graph| graph.get(id)) {
+            Ok((commit.tree, commit.parents.first().copied()))
+        } else {
+            let tree = repo.find_commit(id)?.tree_id();
+            Ok((tree, replacements.first_parent(repo, id)?))
+        }
+    };
+
+    let mut next = Some(start.id());
+    // libgit2 doesn't see replacements when parsing `REV~N`
+    let mut grafted = false;
+    for i in 0..max_commits {
+        let Some(id) = next else {
+            break;
+        };
+        grafted |= replacements.is_replaced(id);
+        let (tree, parent) = first_parent(id)?;
+        next = parent;
+        if tree_has_path(repo, tree, path)? {
+            continue;
+        }
+        let Some(parent) = parent else {
+            break;
+        };
+        let (parent_tree, _) = first_parent(parent)?;
+        if tree_has_path(repo, parent_tree, path)? {
+            let last_seen = repo.find_commit(parent)?;
+            let rev = if grafted {
+                last_seen
+                    .as_object()
+                    .short_id()?
+                    .as_str()
+                    .expect("short_id is always valid UTF-8")
+                    .to_owned()
+            } else {
+                format!("{}~{}", start_rev, i + 1)
+            };
+            return Ok(Some(Deletion {
+                deleted_in: repo.find_commit(id)?,
+                last_seen,
+                rev,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+pub(crate) fn has_path(commit: &git2::Commit<'_>, path: &std::path::Path) -> anyhow::Result<bool> {
+    match commit.tree()?.get_path(path) {
+        Ok(_) => Ok(true),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn tree_has_path(
+    repo: &git2::Repository,
+    tree: git2::Oid,
+    path: &std::path::Path,
+) -> anyhow::Result<bool> {
+    match repo.find_tree(tree)?.get_path(path) {
+        Ok(_) => Ok(true),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
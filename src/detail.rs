@@ -0,0 +1,544 @@
+//! `--line N --detail`, which commits wrote which parts of a line
+//!
+//! The line is followed back along first parents like the internal blame engine does.  At each
+//! commit that changed it, its previous version is the most alike of the lines the commit
+//! replaced, and the words both versions share, by longest common subsequence, keep being
+//! followed back.  The rest are that commit's.  After `dive.detail-depth` versions, the oldest
+//! one reached takes whatever is left.
+
+use proc_exit::WithCodeResultExt;
+
+use crate::args::OutputFormat;
+use crate::blame::Blamed;
+use crate::blame::GutterStyles;
+use crate::blame::Target;
+use crate::engine::Before;
+use crate::engine::Engine;
+use crate::exit_code;
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+pub(crate) fn detail(
+    repo: &git2::Repository,
+    cwd: &std::path::Path,
+    file_path: &std::path::Path,
+    number: usize,
+    config: &mut Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    if args.format != OutputFormat::Text {
+        return Err(anyhow::format_err!(
+            "`--detail` only supports `--format text`"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+    let Target::File(Blamed {
+        tree_path,
+        shown_path,
+        file,
+        attributions,
+        ..
+    }) = crate::blame::annotate(repo, cwd, file_path, config, args)?
+    else {
+        return Err(anyhow::format_err!(
+            "`--detail` is only supported for files"
+        ))
+        .with_code(exit_code::USAGE);
+    };
+    let Some(text) = file.lines().nth(number - 1) else {
+        return Err(anyhow::format_err!(
+            "`--line {number}` is past the end of {shown_path}, which has {} lines",
+            file.lines().count()
+        ))
+        .with_code(exit_code::USAGE);
+    };
+    let text = if number == 1 {
+        text.strip_prefix('\u{feff}').unwrap_or(text)
+    } else {
+        text
+    };
+    let attribution = &attributions[number - 1];
+    if attribution.commit.is_zero() {
+        return Err(anyhow::format_err!(
+            "line {number} of {shown_path} isn't committed, so it has no history"
+        ))
+        .with_code(exit_code::USAGE);
+    }
+
+    let replacements = crate::replace::Replacements::load(repo, config, args)
+        .with_code(proc_exit::Code::FAILURE)?;
+    let backend = crate::backend::Git2::new(repo, &replacements);
+    let mut engine = Engine::new(&backend);
+    let depth = usize::try_from(config.get(&DETAIL_DEPTH)).unwrap_or(0);
+    let path = attribution.path.clone().unwrap_or(tree_path);
+    let start = std::time::Instant::now();
+    let walk = walk(
+        repo,
+        &mut engine,
+        attribution.commit,
+        &path,
+        attribution.line,
+        text,
+        depth,
+    )
+    .with_code(proc_exit::Code::FAILURE)?;
+    log::debug!(
+        "followed line {number} through {} versions in {:?}",
+        walk.commits.len(),
+        start.elapsed()
+    );
+
+    let mut annotations = crate::blame::Annotations::new(
+        repo,
+        crate::date::DateKind::resolve(config, args),
+        walk.commits.iter().copied(),
+    );
+    annotations.spell_hashes(&crate::hash::HashFormat::resolve(config));
+    let colored_stdout =
+        anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
+    let output = render(
+        &format!("{shown_path}:{number}"),
+        &tokenize(text),
+        &walk,
+        &annotations,
+        &Styles {
+            gutter: GutterStyles::for_stdout(config, colored_stdout),
+            palette: colored_stdout.then(|| crate::palette::AuthorPalette::from_config(config)),
+            date_format: crate::date::Dates::resolve(config, args),
+            ellipsis: crate::glyphs::Glyphs::resolve(config).ellipsis,
+        },
+        crate::render_test::terminal_width(),
+    );
+    crate::summary::page(&output, config, args)
+}
+
+/// What [`walk`] found
+struct Walk {
+    /// Newest first, each having changed the line
+    commits: Vec<git2::Oid>,
+    /// Per token of the line, the index of the commit that wrote it
+    owners: Vec<usize>,
+    /// Stopped at `dive.detail-depth`, so the oldest commit may have only kept its tokens
+    capped: bool,
+}
+
+/// Follow `text`, line `line` of `path` as of `commit`, back through its versions
+fn walk(
+    repo: &git2::Repository,
+    engine: &mut Engine<'_>,
+    commit: git2::Oid,
+    path: &std::path::Path,
+    line: usize,
+    text: &str,
+    depth: usize,
+) -> anyhow::Result<Walk> {
+    let mut trace = Trace::new(tokenize(text).len());
+    let mut commits = Vec::new();
+    let mut capped = false;
+    let mut current = text.to_owned();
+    let mut change = engine.last_change(commit, path, line)?;
+    loop {
+        let k = commits.len();
+        commits.push(change.commit);
+        log::trace!(
+            "line {} of {} as of {}",
+            change.line,
+            change.path.display(),
+            change.commit
+        );
+        let version = line_of(repo, change.commit, &change.path, change.line)?;
+        // Like for whitespace changes, which the walk looks past
+        if version != current {
+            if !trace.step(k, &tokenize(&current), &tokenize(&version)) {
+                break;
+            }
+            current = version;
+        }
+        let Some(before) = &change.before else {
+            break;
+        };
+        if depth != 0 && depth <= commits.len() {
+            capped = true;
+            break;
+        }
+        let Some((before_line, before_text)) = predecessor(repo, before, change.line, &current)?
+        else {
+            break;
+        };
+        if !trace.step(k, &tokenize(&current), &tokenize(&before_text)) {
+            break;
+        }
+        current = before_text;
+        let next = engine.last_change(before.commit, &before.path, before_line)?;
+        change = next;
+    }
+    Ok(Walk {
+        owners: trace.finish(commits.len() - 1),
+        commits,
+        capped,
+    })
+}
+
+/// Line `line` of `path` as of `commit`, empty if it's past the end
+fn line_of(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    path: &std::path::Path,
+    line: usize,
+) -> anyhow::Result<String> {
+    let content = read(repo, commit, path)?;
+    let text = content.lines().nth(line - 1).unwrap_or_default();
+    let text = if line == 1 {
+        text.strip_prefix('\u{feff}').unwrap_or(text)
+    } else {
+        text
+    };
+    Ok(text.to_owned())
+}
+
+fn read(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    path: &std::path::Path,
+) -> anyhow::Result<String> {
+    let blob = repo
+        .find_commit(commit)?
+        .tree()?
+        .get_path(path)?
+        .to_object(repo)?
+        .peel_to_blob()?;
+    crate::blame::convert_file(blob.content(), path)
+}
+
+/// Of the lines `before.hunk` replaced, the one most like `text`, with its 1-based number
+///
+/// `text` is line `line` of the changed version.  Ties go to the line at the same place in the
+/// hunk.  `None` when none share a word, the line being new rather than edited.
+fn predecessor(
+    repo: &git2::Repository,
+    before: &Before,
+    line: usize,
+    text: &str,
+) -> anyhow::Result<Option<(usize, String)>> {
+    let hunk = before.hunk;
+    if hunk.old_lines == 0 {
+        return Ok(None);
+    }
+    let content = read(repo, before.commit, &before.path)?;
+    let tokens = tokenize(text);
+    let offset = line - hunk.new_start;
+    let best = content
+        .lines()
+        .enumerate()
+        .skip(hunk.old_start - 1)
+        .take(hunk.old_lines)
+        .map(|(i, old)| {
+            let shared = lcs(&tokens, &tokenize(old))
+                .into_iter()
+                .filter(|(i, _)| !is_space(tokens[*i]))
+                .count();
+            let distance = (i + 1 - hunk.old_start).abs_diff(offset);
+            (shared, std::cmp::Reverse(distance), i, old)
+        })
+        .max_by_key(|(shared, distance, ..)| (*shared, *distance));
+    Ok(best
+        .filter(|(shared, ..)| 0 < *shared)
+        .map(|(_, _, i, old)| (i + 1, old.to_owned())))
+}
+
+/// Which version wrote each token of a line, followed back a version at a time
+#[derive(Debug)]
+struct Trace {
+    owners: Vec<Option<usize>>,
+    /// Per token of the version being followed, which of the line's tokens it is
+    current: Vec<Option<usize>>,
+}
+
+impl Trace {
+    fn new(len: usize) -> Self {
+        Self {
+            owners: vec![None; len],
+            current: (0..len).map(Some).collect(),
+        }
+    }
+
+    /// Give the tokens of version `k` that aren't in the one `before` it to `k`
+    ///
+    /// `false` when none are left to follow.
+    fn step(&mut self, k: usize, version: &[&str], before: &[&str]) -> bool {
+        debug_assert_eq!(version.len(), self.current.len());
+        let mut next = vec![None; before.len()];
+        let mut kept = vec![false; version.len()];
+        for (i, j) in lcs(version, before) {
+            next[j] = self.current[i];
+            kept[i] = true;
+        }
+        for (token, kept) in self.current.iter().zip(kept) {
+            if let (Some(token), false) = (token, kept) {
+                self.owners[*token] = Some(k);
+            }
+        }
+        self.current = next;
+        self.current.iter().any(Option::is_some)
+    }
+
+    /// Give what's left to version `k`
+    fn finish(self, k: usize) -> Vec<usize> {
+        self.owners
+            .into_iter()
+            .map(|owner| owner.unwrap_or(k))
+            .collect()
+    }
+}
+
+/// Words, runs of whitespace, and each other character on its own
+fn tokenize(line: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            1
+        } else if c.is_whitespace() {
+            2
+        } else {
+            0
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let kind = class(c);
+        if kind != 0 {
+            while let Some((i, next)) = chars.next_if(|(_, next)| class(*next) == kind) {
+                end = i + next.len_utf8();
+            }
+        }
+        tokens.push(&line[start..end]);
+    }
+    tokens
+}
+
+fn is_space(token: &str) -> bool {
+    token.chars().all(char::is_whitespace)
+}
+
+/// Pairs of indices into `a` and `b` of a longest common subsequence, in order
+///
+/// Lines too long to compare cheaply share nothing.
+fn lcs(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    if MAX_LCS_CELLS < a.len().saturating_mul(b.len()) {
+        log::debug!("not comparing lines of {} and {} tokens", a.len(), b.len());
+        return Vec::new();
+    }
+    // `lengths[i][j]` is the length of the LCS of `a[i..]` and `b[j..]`
+    let width = b.len() + 1;
+    let mut lengths = vec![0_u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+struct Styles {
+    gutter: GutterStyles,
+    /// `None` without color
+    palette: Option<crate::palette::AuthorPalette>,
+    date_format: crate::date::Dates,
+    /// Where a subject was cut short
+    ellipsis: &'static str,
+}
+
+/// The line with a key under each part, then which commit each key is
+fn render(
+    title: &str,
+    tokens: &[&str],
+    walk: &Walk,
+    annotations: &crate::blame::Annotations,
+    styles: &Styles,
+    width: usize,
+) -> String {
+    use std::fmt::Write as _;
+
+    // Only commits that wrote something get a key, newest first
+    let mut shown = walk.owners.clone();
+    shown.sort_unstable();
+    shown.dedup();
+    let key = |owner: usize| {
+        let i = shown.binary_search(&owner).expect("every owner is shown");
+        KEYS.chars().nth(i).unwrap_or('+')
+    };
+    let style = |owner: usize| {
+        styles
+            .palette
+            .as_ref()
+            .map(|palette| palette.nth(shown.binary_search(&owner).unwrap_or_default()))
+            .unwrap_or_default()
+    };
+
+    let mut output = format!("{title}\n");
+    let mut keys = String::new();
+    for (token, owner) in tokens.iter().zip(&walk.owners) {
+        let style = style(*owner);
+        let _ = write!(output, "{}{token}{}", style.render(), style.render_reset());
+        if is_space(token) {
+            // The same whitespace, so tabs line up
+            keys.push_str(token);
+        } else {
+            let width = crate::unicode::display_width(token);
+            let _ = write!(
+                keys,
+                "{}{}{}",
+                style.render(),
+                key(*owner).to_string().repeat(width),
+                style.render_reset()
+            );
+        }
+    }
+    let _ = writeln!(output);
+    let _ = writeln!(output, "{}", keys.trim_end());
+    let _ = writeln!(output);
+
+    let notes = shown
+        .iter()
+        .map(|owner| {
+            annotations
+                .notes
+                .get(&walk.commits[*owner])
+                .expect("every commit walked is annotated")
+        })
+        .collect::<Vec<_>>();
+    let hash_width = notes
+        .iter()
+        .map(|note| crate::unicode::display_width(&note.hash))
+        .max()
+        .unwrap_or(0);
+    let author_width = notes
+        .iter()
+        .map(|note| crate::unicode::display_width(&note.author))
+        .max()
+        .unwrap_or(0);
+    let gutter = &styles.gutter;
+    for (owner, note) in shown.iter().zip(notes) {
+        let style = style(*owner);
+        let oldest = walk.capped && *owner + 1 == walk.commits.len();
+        let date = styles.date_format.format(note.time);
+        let earlier = if oldest { " (or earlier)" } else { "" };
+        let used = 1
+            + 1
+            + hash_width
+            + 1
+            + author_width
+            + 1
+            + crate::unicode::display_width(&date)
+            + 1
+            + earlier.len();
+        let subject = crate::unicode::truncate_end(
+            &note.subject,
+            width.saturating_sub(used),
+            styles.ellipsis,
+        );
+        let _ = writeln!(
+            output,
+            "{}{}{} {}{}{} {}{}{} {}{}{} {}{}",
+            style.render(),
+            key(*owner),
+            style.render_reset(),
+            gutter.hash.render(),
+            crate::unicode::pad(&note.hash, hash_width),
+            gutter.hash.render_reset(),
+            gutter.author.render(),
+            crate::unicode::pad(&note.author, author_width),
+            gutter.author.render_reset(),
+            gutter.date.render(),
+            date,
+            gutter.date.render_reset(),
+            subject,
+            earlier,
+        );
+    }
+    output
+}
+
+/// Up to 35 commits get their own key, the rest share `+`
+const KEYS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+pub(crate) const DETAIL_DEPTH: DefaultField<i64> =
+    RawField::<i64>::new("dive.detail-depth").default_value(|| 20);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize("fn open(path: &Path) {"),
+            ["fn", " ", "open", "(", "path", ":", " ", "&", "Path", ")", " ", "{"]
+        );
+        assert_eq!(tokenize("\t  x_1 ü"), ["\t  ", "x_1", " ", "ü"]);
+        assert_eq!(tokenize(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn common_subsequence() {
+        let a = tokenize("f(a, b)");
+        let b = tokenize("f(a)");
+        assert_eq!(lcs(&a, &b), [(0, 0), (1, 1), (2, 2), (6, 3)]);
+        assert_eq!(lcs(&a, &[]), []);
+    }
+
+    #[test]
+    fn trace_versions() {
+        // Newest first: `mode` was added, then `open` was renamed from `load`
+        let versions = [
+            "fn open(path: &Path, mode: Mode) {",
+            "fn open(path: &Path) {",
+            "fn load(path: &Path) {",
+        ];
+        let tokens = versions.map(tokenize);
+        let mut trace = Trace::new(tokens[0].len());
+        assert!(trace.step(0, &tokens[0], &tokens[1]));
+        assert!(trace.step(1, &tokens[1], &tokens[2]));
+        let owners = trace.finish(2);
+        let by_owner = |k| {
+            tokens[0]
+                .iter()
+                .zip(&owners)
+                .filter(|(_, owner)| **owner == k)
+                .map(|(token, _)| *token)
+                .collect::<String>()
+        };
+        assert_eq!(by_owner(0), ", mode: Mode");
+        assert_eq!(by_owner(1), "open");
+        assert_eq!(by_owner(2), "fn (path: &Path) {");
+    }
+
+    #[test]
+    fn trace_stops_when_nothing_is_left() {
+        let new = tokenize("a b");
+        let mut trace = Trace::new(new.len());
+        assert!(!trace.step(0, &new, &tokenize("c")));
+        assert_eq!(trace.finish(1), [0, 0, 0]);
+    }
+}
@@ -0,0 +1,158 @@
+use bugreport::collector::CollectionError;
+use bugreport::collector::Collector;
+use bugreport::report::ReportEntry;
+use bugreport::CrateInfo;
+
+use crate::git2_config::Config;
+
+pub(crate) fn diagnostic(config: &Config, args: &crate::args::Args) -> proc_exit::ExitResult {
+    use bugreport::{
+        bugreport,
+        collector::{
+            CommandLine, CompileTimeInformation, EnvironmentVariables, OperatingSystem,
+            SoftwareVersion,
+        },
+        format::Markdown,
+    };
+
+    let mut report = bugreport!()
+        .info(SoftwareVersion::default())
+        .info(crate::build_info::BuildInfo::get())
+        .info(OperatingSystem::default())
+        .info(CommandLine::default())
+        .info(EnvironmentVariables::list(&[
+            "SHELL",
+            "PAGER",
+            "LESS",
+            "LESSCHARSET",
+            "LANG",
+            "LC_ALL",
+            "GIT_PAGER",
+            // Skipping `GIT_CONFIG_COUNT`, `GIT_CONFIG_KEY_*` and `GIT_CONFIG_VALUE_*`
+            "GIT_CONFIG_PARAMETERS",
+            "COLORTERM",
+            "TERM",
+            "NO_COLOR",
+            "CLICOLOR",
+            "CLICOLOR_FORCE",
+        ]))
+        .info(Pager::new(config, args))
+        .info(CommitGraph::new(config, args))
+        .info(CompileTimeInformation::default());
+
+    // Written rather than printed, so a closed pipe isn't a panic
+    let _ = std::io::Write::write_all(
+        &mut crate::git_pager::unpaged(args),
+        report.format::<Markdown>().as_bytes(),
+    );
+
+    Ok(())
+}
+
+impl Collector for crate::build_info::BuildInfo {
+    fn description(&self) -> &str {
+        "Build"
+    }
+
+    fn collect(&mut self, _: &CrateInfo<'_>) -> Result<ReportEntry, CollectionError> {
+        Ok(ReportEntry::List(
+            self.fields()
+                .into_iter()
+                .skip(1)
+                .map(|(name, value)| ReportEntry::Text(format!("{name}: {value}")))
+                .collect(),
+        ))
+    }
+}
+
+/// The pager command git-dive would run
+struct Pager {
+    command: String,
+    source: String,
+    less: Option<String>,
+    short: Option<String>,
+    /// Already running under a pager
+    in_use: bool,
+}
+
+impl Pager {
+    fn new(config: &Config, args: &crate::args::Args) -> Self {
+        let command = crate::git_pager::resolve_pager(config, args);
+        let source = crate::git_pager::pager_source(config, args).to_owned();
+        let less = crate::git_pager::describe_less(&command, config);
+        let short = crate::git_pager::resolve_short_pager(config, args);
+        Self {
+            command,
+            source,
+            less,
+            short,
+            in_use: crate::git_pager::pager_in_use(),
+        }
+    }
+}
+
+impl Collector for Pager {
+    fn description(&self) -> &str {
+        "Pager"
+    }
+
+    fn collect(&mut self, _: &CrateInfo<'_>) -> Result<ReportEntry, CollectionError> {
+        let mut text = format!("`{}` (from {})", self.command, self.source);
+        if let Some(less) = &self.less {
+            text.push_str(&format!(", less version {less}"));
+        }
+        if let Some(short) = &self.short {
+            text.push_str(&format!(", `{short}` for short output"));
+        }
+        if self.in_use {
+            text.push_str(", unused since `GIT_PAGER_IN_USE` is set");
+        }
+        Ok(ReportEntry::Text(text))
+    }
+}
+
+/// Whether history searches can use the repo's commit-graph
+struct CommitGraph {
+    status: String,
+}
+
+impl CommitGraph {
+    fn new(config: &Config, args: &crate::args::Args) -> Self {
+        let status = if args.no_commit_graph {
+            "disabled by `--no-commit-graph`".to_owned()
+        } else if !config.get(&crate::commit_graph::CORE_COMMIT_GRAPH) {
+            "disabled by `core.commitGraph`".to_owned()
+        } else {
+            match std::env::current_dir()
+                .map_err(anyhow::Error::from)
+                .and_then(|cwd| crate::repo::open(&cwd, args).map_err(anyhow::Error::from))
+            {
+                Ok(repo) => {
+                    let path = crate::commit_graph::graph_path(&repo);
+                    if !path.exists() {
+                        format!("not found at `{}`", path.display())
+                    } else {
+                        match crate::commit_graph::CommitGraph::open(&path) {
+                            Ok(graph) => {
+                                format!("used, {} commits at `{}`", graph.len(), path.display())
+                            }
+                            Err(err) => format!("ignored, {err:#}"),
+                        }
+                    }
+                }
+                Err(_) => "not in a repository".to_owned(),
+            }
+        };
+        Self { status }
+    }
+}
+
+impl Collector for CommitGraph {
+    fn description(&self) -> &str {
+        "Commit graph"
+    }
+
+    fn collect(&mut self, _: &CrateInfo<'_>) -> Result<ReportEntry, CollectionError> {
+        Ok(ReportEntry::Text(self.status.clone()))
+    }
+}
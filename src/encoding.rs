@@ -0,0 +1,194 @@
+use crate::git2_config::Config;
+use crate::git2_config::RawField;
+
+/// The commit's author name, decoded according to its `encoding` header
+pub(crate) fn author_name(commit: &git2::Commit<'_>) -> String {
+    decode(commit, commit.author().name_bytes())
+}
+
+/// The commit's subject line, decoded according to its `encoding` header
+///
+/// Like git's `%s`, this is untouched beyond joining the first paragraph's lines, for machine
+/// output.  [`subject`] is for people.
+pub(crate) fn summary(commit: &git2::Commit<'_>) -> String {
+    decode(commit, commit.summary_bytes().unwrap_or_default())
+}
+
+/// The commit's subject tidied to show on one line, per [`display_subject`]
+pub(crate) fn subject(commit: &git2::Commit<'_>) -> String {
+    display_subject(&decode(commit, commit.message_bytes()))
+}
+
+/// The first line of `message`, with whitespace runs like tabs collapsed to a space and other
+/// control characters removed
+///
+/// Only the first line is taken, even without a blank line after it, since messages wrapped
+/// without one would otherwise have their body run into the subject.
+pub(crate) fn display_subject(message: &str) -> String {
+    let line = message
+        .split(['\n', '\r'])
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+    line.split_whitespace()
+        .map(crate::unicode::sanitize)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decode commit metadata, falling back to lossy UTF-8 for unknown encodings
+fn decode(commit: &git2::Commit<'_>, bytes: &[u8]) -> String {
+    let Some(label) = commit.message_encoding() else {
+        return String::from_utf8_lossy(bytes).into_owned();
+    };
+    if let Some(encoding) = encoding_rs::Encoding::for_label(label.as_bytes()) {
+        return encoding.decode_without_bom_handling(bytes).0.into_owned();
+    }
+    if !WARNED_UNKNOWN.swap(true, std::sync::atomic::Ordering::Relaxed) {
+        log::warn!(
+            "commit {} has unknown encoding `{}`; assuming UTF-8",
+            commit.id(),
+            label
+        );
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+static WARNED_UNKNOWN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// The encoding to write output in, if not UTF-8
+///
+/// Like git, this is `i18n.logOutputEncoding`, defaulting to `i18n.commitEncoding`.
+pub(crate) fn output_encoding(config: &Config) -> Option<&'static encoding_rs::Encoding> {
+    let label = config
+        .get(&LOG_OUTPUT_ENCODING)
+        .or_else(|| config.get(&COMMIT_ENCODING))?;
+    let Some(encoding) = encoding_rs::Encoding::for_label(label.as_bytes()) else {
+        log::warn!("unknown output encoding `{}`; using UTF-8", label);
+        return None;
+    };
+    // encoding_rs can't encode to UTF-16, so this maps it to UTF-8
+    let encoding = encoding.output_encoding();
+    (encoding != encoding_rs::UTF_8).then_some(encoding)
+}
+
+/// Transcode UTF-8 written to it into another encoding
+///
+/// Characters the encoding can't represent are replaced with `?`.
+pub(crate) struct Transcoder<'w> {
+    inner: &'w mut dyn std::io::Write,
+    encoder: encoding_rs::Encoder,
+    buffer: Vec<u8>,
+}
+
+impl<'w> Transcoder<'w> {
+    pub(crate) fn new(
+        inner: &'w mut dyn std::io::Write,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> Self {
+        Self {
+            inner,
+            encoder: encoding.new_encoder(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl std::io::Write for Transcoder<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // `write!` hands us whole `str`s, so anything else is passed through untouched
+        let Ok(mut text) = std::str::from_utf8(buf) else {
+            self.inner.write_all(buf)?;
+            return Ok(buf.len());
+        };
+
+        self.buffer.clear();
+        self.buffer.reserve(
+            self.encoder
+                .max_buffer_length_from_utf8_without_replacement(text.len())
+                .unwrap_or(text.len())
+                + 1,
+        );
+        loop {
+            let (result, read) = self.encoder.encode_from_utf8_to_vec_without_replacement(
+                text,
+                &mut self.buffer,
+                false,
+            );
+            text = &text[read..];
+            match result {
+                encoding_rs::EncoderResult::InputEmpty => break,
+                encoding_rs::EncoderResult::OutputFull => {
+                    self.buffer.reserve(text.len() + 1);
+                }
+                encoding_rs::EncoderResult::Unmappable(_) => {
+                    self.buffer.push(b'?');
+                }
+            }
+        }
+        self.inner.write_all(&self.buffer)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(crate) const LOG_OUTPUT_ENCODING: RawField<String> =
+    RawField::<String>::new("i18n.logOutputEncoding");
+
+pub(crate) const COMMIT_ENCODING: RawField<String> = RawField::<String>::new("i18n.commitEncoding");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pathological_subjects() {
+        let cases = [
+            // Body wrapped without a blank line
+            (
+                "Fix the parser so that it handles\nnested brackets, which used to panic\n",
+                "Fix the parser so that it handles",
+            ),
+            ("Fix\tparser\t\tcrash\n\nDetails", "Fix parser crash"),
+            ("\n\n  Leading blank lines  \n", "Leading blank lines"),
+            (
+                "Windows line endings\r\n\r\nBody\r\n",
+                "Windows line endings",
+            ),
+            ("Old Mac line endings\rBody", "Old Mac line endings"),
+            (
+                "Retitle \u{1b}]0;owned\u{7}terminal",
+                "Retitle ]0;ownedterminal",
+            ),
+            ("Escape\u{1b}[31m codes", "Escape[31m codes"),
+            ("Alone \u{7} bell", "Alone bell"),
+            ("evil\u{202E}gnp.exe", "evilgnp.exe"),
+            ("Non\u{a0}breaking\u{2003}spaces", "Non breaking spaces"),
+            (
+                "Form\u{c}feed and\u{b}vertical tab",
+                "Form feed and vertical tab",
+            ),
+            ("squash! fixup!   Spaced   out", "squash! fixup! Spaced out"),
+            ("李小龍\t修正", "李小龍 修正"),
+            ("", ""),
+            ("\n \t\n", ""),
+        ];
+        for (message, subject) in cases {
+            assert_eq!(display_subject(message), subject, "for {message:?}");
+        }
+    }
+
+    #[test]
+    fn transcode_latin1() {
+        use std::io::Write as _;
+
+        let mut actual = Vec::new();
+        let mut transcoder = Transcoder::new(&mut actual, encoding_rs::WINDOWS_1252);
+        let word = "café";
+        write!(transcoder, "Zoë → {word}").unwrap();
+        assert_eq!(actual, b"Zo\xeb ? caf\xe9");
+    }
+}
@@ -0,0 +1,637 @@
+//! git-dive's own blame, in place of libgit2's per `dive.blame-engine`
+//!
+//! Each line is followed back along first parents, through renames and replacements, by diffing
+//! each version of the file against its parent's until a commit changed it.  The diff for each
+//! commit and path is kept, so blaming a parent to look past a commit, like for
+//! `blame.ignoreRevsFile`, only diffs the commits not already walked.
+
+use crate::backend::Backend;
+use crate::blame::DiffHunk;
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+use crate::lfs::Smudge;
+use crate::objects::Objects;
+use crate::objects::PathCheck;
+
+/// Which blame implementation attributes lines
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum BlameEngine {
+    #[default]
+    Libgit2,
+    Internal,
+}
+
+impl Parseable for BlameEngine {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "libgit2" => Ok(Self::Libgit2),
+            "internal" => Ok(Self::Internal),
+            _ => anyhow::bail!("unsupported blame engine `{s}`, expected `libgit2` or `internal`"),
+        }
+    }
+}
+
+impl std::fmt::Display for BlameEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Libgit2 => "libgit2",
+            Self::Internal => "internal",
+        }
+        .fmt(f)
+    }
+}
+
+/// Consecutive lines from the same place in one commit
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct BlameHunk {
+    pub(crate) commit: git2::Oid,
+    /// As of `commit`
+    pub(crate) path: Option<std::path::PathBuf>,
+    /// 1-based, as of `commit`
+    pub(crate) orig_start_line: usize,
+    /// 1-based, in the blamed version
+    pub(crate) final_start_line: usize,
+    pub(crate) lines: usize,
+}
+
+impl BlameHunk {
+    pub(crate) fn from_git2(blame: &git2::Blame<'_>) -> Vec<Self> {
+        blame
+            .iter()
+            .map(|hunk| Self {
+                commit: hunk.orig_commit_id(),
+                path: hunk.path().map(ToOwned::to_owned),
+                orig_start_line: hunk.orig_start_line(),
+                final_start_line: hunk.final_start_line(),
+                lines: hunk.lines_in_hunk(),
+            })
+            .collect()
+    }
+
+    /// The hunk with `final_line`, 1-based
+    pub(crate) fn find(hunks: &[Self], final_line: usize) -> Option<&Self> {
+        let i = hunks.partition_point(|hunk| hunk.final_start_line + hunk.lines <= final_line);
+        hunks
+            .get(i)
+            .filter(|hunk| hunk.final_start_line <= final_line)
+    }
+}
+
+/// The commit that last changed a line, from [`Engine::last_change`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Change {
+    pub(crate) commit: git2::Oid,
+    /// As of `commit`
+    pub(crate) path: std::path::PathBuf,
+    /// 1-based, as of `commit`
+    pub(crate) line: usize,
+    /// `None` where `commit` added the file
+    pub(crate) before: Option<Before>,
+}
+
+/// The version of a file that a [`Change`] was made to
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Before {
+    pub(crate) commit: git2::Oid,
+    /// As of `commit`, following a rename
+    pub(crate) path: std::path::PathBuf,
+    /// Replacing the changed line
+    pub(crate) hunk: DiffHunk,
+}
+
+/// How a version of a file came from its first parent's
+struct Step {
+    parent: git2::Oid,
+    /// As of `parent`, following a rename
+    parent_path: std::path::PathBuf,
+    /// Empty when the file is the same in both
+    hunks: Vec<DiffHunk>,
+}
+
+pub(crate) struct Engine<'r> {
+    backend: &'r dyn Backend,
+    /// `None` where the commit added the file
+    steps: std::collections::HashMap<(git2::Oid, std::path::PathBuf), Option<std::rc::Rc<Step>>>,
+    /// When set, lines are only followed back as far as their objects can be read
+    objects: Option<Objects<'r>>,
+    /// Objects that lines were attributed to [`crate::objects::placeholder`] in front of
+    unreadable: std::collections::BTreeSet<git2::Oid>,
+    /// When set, versions are smudged before they're diffed
+    smudge: Option<Smudge>,
+}
+
+impl<'r> Engine<'r> {
+    pub(crate) fn new(backend: &'r dyn Backend) -> Self {
+        Self {
+            backend,
+            steps: Default::default(),
+            objects: None,
+            unreadable: Default::default(),
+            smudge: None,
+        }
+    }
+
+    /// Check each object before reading it, so unreadable ones stop lines rather than the blame
+    pub(crate) fn objects(mut self, objects: Objects<'r>) -> Self {
+        self.objects = Some(objects);
+        self
+    }
+
+    /// Diff smudged versions of Git LFS pointers rather than the pointers
+    pub(crate) fn smudge(mut self, smudge: Smudge) -> Self {
+        self.smudge = Some(smudge);
+        self
+    }
+
+    /// The objects found unreadable so far, forgetting them
+    pub(crate) fn take_unreadable(&mut self) -> std::collections::BTreeSet<git2::Oid> {
+        std::mem::take(&mut self.unreadable)
+    }
+
+    /// Attribute each line of `path` as of `commit`, ignoring whitespace changes
+    pub(crate) fn blame(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Vec<BlameHunk>> {
+        if let Some(objects) = self.objects.as_mut() {
+            if let PathCheck::Unreadable(id) = objects.check_path(commit, path)? {
+                return Err(crate::objects::unreadable_error(id, path));
+            }
+        }
+        let blob = self
+            .backend
+            .blob_at(commit, path)?
+            .ok_or_else(|| anyhow::format_err!("{} isn't a file in {commit}", path.display()))?;
+        let line_count = if let Some(smudge) = self.smudge.as_mut() {
+            count_lines(&smudge.content(self.backend, blob, path)?)
+        } else {
+            count_lines(&self.backend.read_blob(blob)?)
+        };
+
+        // The line's number in the blamed version, and as of `current`
+        let mut pending = (1..=line_count).map(|l| (l, l)).collect::<Vec<_>>();
+        // The line's number in the blamed version, and where it came from
+        let mut attributed = Vec::with_capacity(line_count);
+        let mut current = commit;
+        let mut current_path = path.to_owned();
+        while !pending.is_empty() {
+            if self.blocked(current, &current_path)? {
+                let placeholder = crate::objects::placeholder();
+                attributed.extend(pending.drain(..).map(|(final_line, line)| {
+                    (final_line, placeholder, current_path.clone(), line)
+                }));
+                break;
+            }
+            let Some(step) = self.step(current, &current_path)? else {
+                attributed.extend(
+                    pending.drain(..).map(|(final_line, line)| {
+                        (final_line, current, current_path.clone(), line)
+                    }),
+                );
+                break;
+            };
+            let mut next = Vec::with_capacity(pending.len());
+            for (final_line, line) in pending {
+                match trace(&step.hunks, line) {
+                    Ok(parent_line) => next.push((final_line, parent_line)),
+                    Err(_) => attributed.push((final_line, current, current_path.clone(), line)),
+                }
+            }
+            pending = next;
+            current = step.parent;
+            current_path.clone_from(&step.parent_path);
+        }
+        if let Some(smudge) = self.smudge.as_mut() {
+            smudge.report(path);
+        }
+
+        attributed.sort_by_key(|(final_line, ..)| *final_line);
+        let mut hunks: Vec<BlameHunk> = Vec::new();
+        for (final_line, commit, path, line) in attributed {
+            if let Some(last) = hunks.last_mut() {
+                if last.commit == commit
+                    && last.path.as_deref() == Some(path.as_path())
+                    && last.orig_start_line + last.lines == line
+                {
+                    last.lines += 1;
+                    continue;
+                }
+            }
+            hunks.push(BlameHunk {
+                commit,
+                path: Some(path),
+                orig_start_line: line,
+                final_start_line: final_line,
+                lines: 1,
+            });
+        }
+        Ok(hunks)
+    }
+
+    /// Whether the parent `path` would be diffed against can't be read, so lines can't be
+    /// followed further than `commit`
+    fn blocked(&mut self, commit: git2::Oid, path: &std::path::Path) -> anyhow::Result<bool> {
+        let Some(objects) = self.objects.as_mut() else {
+            return Ok(false);
+        };
+        let Some(parent) = self.backend.first_parent(commit)? else {
+            return Ok(false);
+        };
+        let PathCheck::Unreadable(id) = objects.check_path(parent, path)? else {
+            return Ok(false);
+        };
+        if objects.strict() {
+            return Err(crate::objects::unreadable_error(id, path));
+        }
+        self.unreadable.insert(id);
+        Ok(true)
+    }
+
+    fn step(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<std::rc::Rc<Step>>> {
+        let key = (commit, path.to_owned());
+        if let Some(step) = self.steps.get(&key) {
+            log::trace!("diff cache hit for {} {}", commit, path.display());
+            return Ok(step.clone());
+        }
+        log::trace!("diff cache miss for {} {}", commit, path.display());
+        let step = self.compute_step(commit, path)?.map(std::rc::Rc::new);
+        self.steps.insert(key, step.clone());
+        Ok(step)
+    }
+
+    fn compute_step(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<Step>> {
+        let Some(previous) = crate::blame::find_previous(self.backend, commit, path)? else {
+            return Ok(None);
+        };
+        let (Some(old_blob), Some(new_blob)) = (
+            self.backend.blob_at(previous.commit, &previous.path)?,
+            self.backend.blob_at(commit, path)?,
+        ) else {
+            return Ok(None);
+        };
+        let hunks = if old_blob == new_blob {
+            Vec::new()
+        } else if let Some(smudge) = self.smudge.as_mut() {
+            // Where the older version can't be smudged, its lines are blamed on this one
+            let (Some(old), Some(new)) = (
+                smudge.try_content(self.backend, old_blob, &previous.path),
+                smudge.try_content(self.backend, new_blob, path),
+            ) else {
+                return Ok(None);
+            };
+            self.backend.diff_blobs(&old, &previous.path, &new, path)?
+        } else {
+            let (old, new) = (
+                self.backend.read_blob(old_blob)?,
+                self.backend.read_blob(new_blob)?,
+            );
+            self.backend.diff_blobs(&old, &previous.path, &new, path)?
+        };
+        Ok(Some(Step {
+            parent: previous.commit,
+            parent_path: previous.path,
+            hunks,
+        }))
+    }
+
+    /// Follow `line` of `path` as of `commit` back to the commit that last changed it
+    pub(crate) fn last_change(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+        line: usize,
+    ) -> anyhow::Result<Change> {
+        let mut current = commit;
+        let mut current_path = path.to_owned();
+        let mut line = line;
+        loop {
+            let Some(step) = self.step(current, &current_path)? else {
+                return Ok(Change {
+                    commit: current,
+                    path: current_path,
+                    line,
+                    before: None,
+                });
+            };
+            match trace(&step.hunks, line) {
+                Ok(parent_line) => {
+                    line = parent_line;
+                    current = step.parent;
+                    current_path.clone_from(&step.parent_path);
+                }
+                Err(hunk) => {
+                    return Ok(Change {
+                        commit: current,
+                        path: current_path,
+                        line,
+                        before: Some(Before {
+                            commit: step.parent,
+                            path: step.parent_path.clone(),
+                            hunk: *hunk,
+                        }),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Like [`Engine::blame`], `None` if `path` isn't in `commit`
+    pub(crate) fn try_blame(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<Vec<BlameHunk>>> {
+        if self.backend.blob_at(commit, path)?.is_none() {
+            return Ok(None);
+        }
+        self.blame(commit, path).map(Some)
+    }
+}
+
+/// The line in the old version that `new_line` is unchanged from, or the hunk changing it
+fn trace(hunks: &[DiffHunk], new_line: usize) -> Result<usize, &DiffHunk> {
+    let mut old_line = new_line;
+    for hunk in hunks {
+        // A hunk without new lines is positioned after `new_start`
+        let new_start = if hunk.new_lines == 0 {
+            hunk.new_start + 1
+        } else {
+            hunk.new_start
+        };
+        if new_line < new_start {
+            break;
+        } else if new_line < new_start + hunk.new_lines {
+            return Err(hunk);
+        }
+        old_line = old_line + hunk.old_lines - hunk.new_lines;
+    }
+    Ok(old_line)
+}
+
+/// Like git, a last line without a newline still counts
+fn count_lines(content: &[u8]) -> usize {
+    let newlines = content.iter().filter(|b| **b == b'\n').count();
+    if content.last().is_some_and(|b| *b != b'\n') {
+        newlines + 1
+    } else {
+        newlines
+    }
+}
+
+pub(crate) const BLAME_ENGINE: DefaultField<BlameEngine> =
+    RawField::<BlameEngine>::new("dive.blame-engine").default();
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::replace::Replacements;
+
+    struct Fixture {
+        repo: git2::Repository,
+        sig: git2::Signature<'static>,
+    }
+
+    impl Fixture {
+        fn new(root_path: &std::path::Path) -> Self {
+            let repo = git2::Repository::init(root_path).unwrap();
+            let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0))
+                .unwrap();
+            Self { repo, sig }
+        }
+
+        /// Commit `files`, replacing the tree, onto `parents`
+        fn commit(
+            &self,
+            files: &[(&str, &str)],
+            parents: &[git2::Oid],
+            message: &str,
+        ) -> git2::Oid {
+            let tree = {
+                let mut tree = self.repo.treebuilder(None).unwrap();
+                for (path, content) in files {
+                    let blob = self.repo.blob(content.as_bytes()).unwrap();
+                    tree.insert(path, blob, 0o100644).unwrap();
+                }
+                tree.write().unwrap()
+            };
+            let tree = self.repo.find_tree(tree).unwrap();
+            let parents = parents
+                .iter()
+                .map(|id| self.repo.find_commit(*id).unwrap())
+                .collect::<Vec<_>>();
+            let parents = parents.iter().collect::<Vec<_>>();
+            self.repo
+                .commit(None, &self.sig, &self.sig, message, &tree, &parents)
+                .unwrap()
+        }
+
+        /// Per line: the commit, path, and line number as of it from `git blame`
+        fn git_blame(&self, commit: git2::Oid, path: &str) -> Vec<(git2::Oid, String, usize)> {
+            let output = std::process::Command::new("git")
+                .args(["blame", "--line-porcelain", "-w", "--first-parent"])
+                .arg(commit.to_string())
+                .args(["--", path])
+                .current_dir(self.repo.workdir().unwrap())
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let mut lines = Vec::new();
+            let mut header = None;
+            for line in stdout.lines() {
+                if line.starts_with('\t') {
+                    lines.push(header.take().unwrap());
+                } else if let Some(filename) = line.strip_prefix("filename ") {
+                    if let Some((_, path, _)) = header.as_mut() {
+                        *path = filename.to_owned();
+                    }
+                } else if header.is_none() {
+                    let mut fields = line.split(' ');
+                    let commit = git2::Oid::from_str(fields.next().unwrap()).unwrap();
+                    let orig_line = fields.next().unwrap().parse().unwrap();
+                    header = Some((commit, String::new(), orig_line));
+                }
+            }
+            lines
+        }
+
+        fn engine_blame(&self, commit: git2::Oid, path: &str) -> Vec<(git2::Oid, String, usize)> {
+            let replacements = Replacements::default();
+            let backend = crate::backend::Git2::new(&self.repo, &replacements);
+            let mut engine = Engine::new(&backend);
+            let hunks = engine.blame(commit, path.as_ref()).unwrap();
+            hunks
+                .iter()
+                .flat_map(|hunk| {
+                    (0..hunk.lines).map(|offset| {
+                        (
+                            hunk.commit,
+                            hunk.path.as_ref().unwrap().to_str().unwrap().to_owned(),
+                            hunk.orig_start_line + offset,
+                        )
+                    })
+                })
+                .collect()
+        }
+
+        fn assert_matches_git(&self, commit: git2::Oid, path: &str) {
+            let expected = self.git_blame(commit, path);
+            assert!(!expected.is_empty());
+            assert_eq!(self.engine_blame(commit, path), expected);
+        }
+    }
+
+    #[test]
+    fn linear_edits() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let fixture = Fixture::new(root.path().unwrap());
+
+        let a = fixture.commit(&[("file.txt", "one\ntwo\nthree\nfour\n")], &[], "A");
+        let b = fixture.commit(&[("file.txt", "one\n2\nthree\nfour\nfive\n")], &[a], "B");
+        let c = fixture.commit(
+            &[("file.txt", "zero\none\n2\n    three\nfive\nsix")],
+            &[b],
+            "C",
+        );
+        let d = fixture.commit(
+            &[
+                ("file.txt", "zero\none\n2\n    three\nfive\nsix"),
+                ("other.txt", "x\n"),
+            ],
+            &[c],
+            "D",
+        );
+        fixture.assert_matches_git(b, "file.txt");
+        fixture.assert_matches_git(d, "file.txt");
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn merges_follow_first_parent() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let fixture = Fixture::new(root.path().unwrap());
+
+        let base = fixture.commit(&[("file.txt", "one\ntwo\nthree\nfour\n")], &[], "Base");
+        let ours = fixture.commit(&[("file.txt", "ONE\ntwo\nthree\nfour\n")], &[base], "Ours");
+        let theirs = fixture.commit(
+            &[("file.txt", "one\ntwo\nthree\nFOUR\n")],
+            &[base],
+            "Theirs",
+        );
+        let merge = fixture.commit(
+            &[("file.txt", "ONE\ntwo\nthree\nFOUR\n")],
+            &[ours, theirs],
+            "Merge",
+        );
+        let after = fixture.commit(&[("file.txt", "ONE\ntwo\n3\nFOUR\n")], &[merge], "After");
+        fixture.assert_matches_git(merge, "file.txt");
+        fixture.assert_matches_git(after, "file.txt");
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn renames() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let fixture = Fixture::new(root.path().unwrap());
+
+        let content = "fn one() {}\nfn two() {}\nfn three() {}\nfn four() {}\n";
+        let a = fixture.commit(&[("old.rs", content)], &[], "A");
+        let b = fixture.commit(
+            &[(
+                "old.rs",
+                "fn one() {}\nfn 2() {}\nfn three() {}\nfn four() {}\n",
+            )],
+            &[a],
+            "B",
+        );
+        let moved = fixture.commit(
+            &[(
+                "new.rs",
+                "fn one() {}\nfn 2() {}\nfn three() {}\nfn four() {}\n",
+            )],
+            &[b],
+            "Move",
+        );
+        let c = fixture.commit(
+            &[(
+                "new.rs",
+                "fn one() {}\nfn 2() {}\nfn three() {}\nfn four() {}\nfn five() {}\n",
+            )],
+            &[moved],
+            "C",
+        );
+        fixture.assert_matches_git(c, "new.rs");
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn parents_reuse_diffs() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let fixture = Fixture::new(root.path().unwrap());
+
+        let a = fixture.commit(&[("file.txt", "one\ntwo\n")], &[], "A");
+        let b = fixture.commit(&[("file.txt", "one\n2\n")], &[a], "B");
+        let c = fixture.commit(&[("file.txt", "1\n2\n")], &[b], "C");
+
+        let replacements = Replacements::default();
+        let backend = crate::backend::Git2::new(&fixture.repo, &replacements);
+        let mut engine = Engine::new(&backend);
+        engine.blame(c, "file.txt".as_ref()).unwrap();
+        let walked = engine.steps.len();
+        // Only `A`, where `one` came from, is new
+        let parent = engine.blame(b, "file.txt".as_ref()).unwrap();
+        assert_eq!(engine.steps.len(), walked + 1);
+        assert_eq!(
+            parent.iter().map(|hunk| hunk.commit).collect::<Vec<_>>(),
+            [a, b]
+        );
+        assert_eq!(engine.try_blame(c, "missing.txt".as_ref()).unwrap(), None);
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn find_hunks() {
+        let hunk = |final_start_line, lines| BlameHunk {
+            commit: git2::Oid::zero(),
+            path: None,
+            orig_start_line: 1,
+            final_start_line,
+            lines,
+        };
+        let hunks = [hunk(1, 2), hunk(3, 1), hunk(4, 3)];
+        let found = (1..=7)
+            .map(|l| BlameHunk::find(&hunks, l).map(|h| h.final_start_line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [Some(1), Some(1), Some(3), Some(4), Some(4), Some(4), None]
+        );
+    }
+
+    #[test]
+    fn count_lines_like_git() {
+        assert_eq!(count_lines(b""), 0);
+        assert_eq!(count_lines(b"one\n"), 1);
+        assert_eq!(count_lines(b"one\ntwo"), 2);
+    }
+}
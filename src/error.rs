@@ -0,0 +1,363 @@
+//! Failures callers may want to tell apart, rather than only report
+//!
+//! Each kind has an exit code from [`crate::exit_code`], so converting to [`proc_exit::Exit`]
+//! at the edges loses nothing.  Other failures stay `anyhow` errors with a code picked where
+//! they are reported.
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub(crate) enum Error {
+    /// No repository at `--git-dir` or `GIT_DIR`, or found from the working directory
+    RepoNotFound {
+        git_dir: Option<std::path::PathBuf>,
+        source: git2::Error,
+    },
+    /// `rev` doesn't name an object
+    RevNotFound { source: git2::Error },
+    /// A short hash matching more than one object, none or several of them commits
+    AmbiguousRev {
+        prefix: String,
+        /// Like git's hints: `abc1234 commit 2023-03-16 - Subject`
+        candidates: Vec<String>,
+        /// How many more there are than `candidates`
+        more: usize,
+    },
+    /// `rev` names something other than a commit, like a tree
+    NotACommit { rev: String, kind: &'static str },
+    /// `--blob` naming something other than a blob
+    NotABlob { oid: String, kind: &'static str },
+    /// `--blob` with a `REV` whose version of `path` is another blob
+    BlobMismatch {
+        path: String,
+        rev: String,
+        blob: git2::Oid,
+        actual: git2::Oid,
+    },
+    /// `--at` a date before the first commit of `rev`
+    NoCommitBefore { rev: String, at: String },
+    /// A `--rev-file` without a rev in it
+    EmptyRevFile { path: std::path::PathBuf },
+    /// A path outside of the worktree, or any path in a bare repo
+    PathNotInWorktree {
+        path: std::path::PathBuf,
+        worktree: Option<std::path::PathBuf>,
+    },
+    /// A path that isn't in the tree of `rev`
+    PathNotInTree {
+        path: String,
+        rev: String,
+        /// Why, when the worktree says
+        status: Option<crate::status_hints::PathStatus>,
+    },
+    /// A file that isn't text
+    BinaryFile { path: std::path::PathBuf },
+    /// Reading an input, like a `--rev-file`
+    Io {
+        /// Like `could not read a rev from PATH`
+        context: String,
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    pub(crate) fn code(&self) -> proc_exit::Code {
+        match self {
+            Self::RepoNotFound { .. } => crate::exit_code::NOT_A_REPO,
+            Self::RevNotFound { .. }
+            | Self::AmbiguousRev { .. }
+            | Self::NotACommit { .. }
+            | Self::NotABlob { .. }
+            | Self::BlobMismatch { .. }
+            | Self::NoCommitBefore { .. }
+            | Self::EmptyRevFile { .. } => crate::exit_code::BAD_REV,
+            Self::PathNotInWorktree { .. } | Self::PathNotInTree { .. } => {
+                crate::exit_code::PATH_NOT_IN_TREE
+            }
+            Self::BinaryFile { .. } => proc_exit::Code::FAILURE,
+            Self::Io { .. } => crate::exit_code::IO,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RepoNotFound {
+                git_dir: Some(git_dir),
+                ..
+            } => write!(f, "not a git repository: {}", git_dir.display()),
+            Self::RepoNotFound {
+                git_dir: None,
+                source,
+            } => source.fmt(f),
+            Self::RevNotFound { source, .. } => source.fmt(f),
+            Self::AmbiguousRev {
+                prefix,
+                candidates,
+                more,
+            } => {
+                write!(
+                    f,
+                    "short object ID {prefix} is ambiguous\nThe candidates are:"
+                )?;
+                for candidate in candidates {
+                    write!(f, "\n  {candidate}")?;
+                }
+                if 0 < *more {
+                    write!(f, "\n  ...and {more} more")?;
+                }
+                Ok(())
+            }
+            Self::NotACommit { rev, kind } => write!(f, "Unsupported rev `{rev}` ({kind})"),
+            Self::NotABlob { oid, kind } => write!(f, "`{oid}` is a {kind}, not a blob"),
+            Self::BlobMismatch {
+                path,
+                rev,
+                blob,
+                actual,
+            } => write!(
+                f,
+                "{path} at {rev} is blob {actual}, not {blob}; drop `REV` to show the blob without attribution"
+            ),
+            Self::NoCommitBefore { rev, at } => {
+                write!(f, "{rev} has no commits from {at} or before")
+            }
+            Self::EmptyRevFile { path } => write!(f, "{} has no rev", path.display()),
+            Self::PathNotInWorktree {
+                path,
+                worktree: Some(worktree),
+            } => write!(
+                f,
+                "File {} is not in the repository's workdir {}",
+                path.display(),
+                worktree.display()
+            ),
+            Self::PathNotInWorktree { worktree: None, .. } => {
+                write!(f, "No workdir found; Bare repositories are not supported")
+            }
+            Self::PathNotInTree {
+                path,
+                rev,
+                status: None,
+            } => write!(
+                f,
+                "{path} does not exist at {rev}; pass `--find-deleted` to search history"
+            ),
+            Self::PathNotInTree {
+                path,
+                status: Some(status),
+                ..
+            } => {
+                let status = match status {
+                    crate::status_hints::PathStatus::Untracked => "is untracked",
+                    crate::status_hints::PathStatus::Ignored => "is ignored",
+                    crate::status_hints::PathStatus::Staged => "is staged but not committed",
+                };
+                write!(
+                    f,
+                    "{path} {status}; commit it or pass `--contents {path}` to annotate local content"
+                )
+            }
+            Self::BinaryFile { path } => {
+                write!(f, "Could not ready binary file {}", path.display())
+            }
+            Self::Io { context, .. } => context.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::RepoNotFound { source, .. } | Self::RevNotFound { source, .. } => Some(source),
+            Self::Io { source, .. } => Some(source),
+            Self::AmbiguousRev { .. }
+            | Self::NotACommit { .. }
+            | Self::NotABlob { .. }
+            | Self::BlobMismatch { .. }
+            | Self::NoCommitBefore { .. }
+            | Self::EmptyRevFile { .. }
+            | Self::PathNotInWorktree { .. }
+            | Self::PathNotInTree { .. }
+            | Self::BinaryFile { .. } => None,
+        }
+    }
+}
+
+impl From<Error> for proc_exit::Exit {
+    fn from(err: Error) -> Self {
+        err.code().with_message(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A repo with a commit and `tree` naming its tree
+    fn fixture(root_path: &std::path::Path) -> git2::Repository {
+        let repo = git2::Repository::init(root_path).unwrap();
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+        let blob = repo.blob(b"one\ntwo\n").unwrap();
+        let tree = {
+            let mut tree = repo.treebuilder(None).unwrap();
+            tree.insert("basic.txt", blob, 0o100644).unwrap();
+            tree.write().unwrap()
+        };
+        repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "A",
+            &repo.find_tree(tree).unwrap(),
+            &[],
+        )
+        .unwrap();
+        repo.reference("refs/tags/tree", tree, false, "").unwrap();
+        repo
+    }
+
+    fn args(argv: &[&str]) -> crate::args::Args {
+        clap::Parser::parse_from(std::iter::once("git-dive").chain(argv.iter().copied()))
+    }
+
+    #[test]
+    fn repo_not_found() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+
+        let Err(err) = crate::repo::open(root_path, &args(&["--git-dir=missing", "file"])) else {
+            panic!("opened a missing repo");
+        };
+        let Error::RepoNotFound {
+            git_dir: Some(git_dir),
+            ..
+        } = &err
+        else {
+            panic!("{err:?}");
+        };
+        assert!(git_dir.ends_with("missing"), "{}", git_dir.display());
+        assert!(
+            err.to_string().starts_with("not a git repository: "),
+            "{err}"
+        );
+        assert_eq!(err.code(), crate::exit_code::NOT_A_REPO);
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn rev_not_found() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = fixture(root_path);
+
+        let err = crate::rev::revparse_single(&repo, "missing").unwrap_err();
+        let Error::RevNotFound { source } = &err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(source.code(), git2::ErrorCode::NotFound);
+        assert_eq!(err.code(), crate::exit_code::BAD_REV);
+
+        let tree = repo.revparse_single("tree").unwrap();
+        let err = crate::rev::peel_to_commit(&tree, "tree").unwrap_err();
+        assert!(
+            matches!(&err, Error::NotACommit { kind: "tree", .. }),
+            "{err:?}"
+        );
+        assert_eq!(err.to_string(), "Unsupported rev `tree` (tree)");
+        assert_eq!(err.code(), crate::exit_code::BAD_REV);
+
+        let err = Error::AmbiguousRev {
+            prefix: "abcd".to_owned(),
+            candidates: vec!["abcd123 blob".to_owned(), "abcd456 tree".to_owned()],
+            more: 2,
+        };
+        assert_eq!(
+            err.to_string(),
+            "\
+short object ID abcd is ambiguous
+The candidates are:
+  abcd123 blob
+  abcd456 tree
+  ...and 2 more"
+        );
+        assert_eq!(err.code(), crate::exit_code::BAD_REV);
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn path_not_in_tree() {
+        let err = Error::PathNotInTree {
+            path: "missing.txt".to_owned(),
+            rev: "HEAD".to_owned(),
+            status: None,
+        };
+        assert_eq!(
+            err.to_string(),
+            "missing.txt does not exist at HEAD; pass `--find-deleted` to search history"
+        );
+        assert_eq!(err.code(), crate::exit_code::PATH_NOT_IN_TREE);
+
+        let err = Error::PathNotInTree {
+            path: "new.txt".to_owned(),
+            rev: "HEAD".to_owned(),
+            status: Some(crate::status_hints::PathStatus::Untracked),
+        };
+        assert_eq!(
+            err.to_string(),
+            "new.txt is untracked; commit it or pass `--contents new.txt` to annotate local content"
+        );
+        assert_eq!(err.code(), crate::exit_code::PATH_NOT_IN_TREE);
+
+        let err = Error::PathNotInWorktree {
+            path: "/elsewhere/file".into(),
+            worktree: Some("/repo".into()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "File /elsewhere/file is not in the repository's workdir /repo"
+        );
+        assert_eq!(err.code(), crate::exit_code::PATH_NOT_IN_TREE);
+    }
+
+    #[test]
+    fn binary_file() {
+        let err = crate::blame::convert_file(b"\0\x01\x02\x03", "binary.bin".as_ref())
+            .unwrap_err()
+            .downcast::<Error>()
+            .unwrap();
+        assert!(
+            matches!(&err, Error::BinaryFile { path } if path.as_os_str() == "binary.bin"),
+            "{err:?}"
+        );
+        assert_eq!(err.code(), proc_exit::Code::FAILURE);
+    }
+
+    #[test]
+    fn io() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+
+        let err = crate::rev::read_rev_file(&root_path.join("missing")).unwrap_err();
+        let Error::Io { source, .. } = &err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        assert!(
+            err.to_string().starts_with("could not read a rev from "),
+            "{err}"
+        );
+        assert_eq!(err.code(), crate::exit_code::IO);
+
+        let rev_file = root_path.join("rev");
+        std::fs::write(&rev_file, "# no rev yet\n").unwrap();
+        let err = crate::rev::read_rev_file(&rev_file).unwrap_err();
+        assert!(matches!(&err, Error::EmptyRevFile { .. }), "{err:?}");
+        assert_eq!(err.code(), crate::exit_code::BAD_REV);
+
+        root.close().unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+//! Exit codes, so scripts can tell failures apart
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success |
+//! | 1 | Any other failure |
+//! | 2 | Invalid arguments |
+//! | 3 | Not in a git repository |
+//! | 4 | The revision could not be resolved to a commit |
+//! | 5 | The path is not in the tree at that revision |
+//! | 6 | Failed to write output, including to the pager |
+
+use proc_exit::Code;
+
+pub(crate) const SUCCESS: Code = Code::SUCCESS;
+/// Matches clap's exit code for invalid arguments
+pub(crate) const USAGE: Code = Code::new(2);
+pub(crate) const NOT_A_REPO: Code = Code::new(3);
+pub(crate) const BAD_REV: Code = Code::new(4);
+pub(crate) const PATH_NOT_IN_TREE: Code = Code::new(5);
+pub(crate) const IO: Code = Code::new(6);
+
+/// What an exit code means, for `-v`
+pub(crate) fn describe(code: Code) -> &'static str {
+    match code {
+        SUCCESS => "success",
+        USAGE => "invalid arguments",
+        NOT_A_REPO => "not in a git repository",
+        BAD_REV => "revision could not be resolved",
+        PATH_NOT_IN_TREE => "path is not in the tree",
+        IO => "failed to write output",
+        _ => "failure",
+    }
+}
@@ -7,6 +7,8 @@ pub(crate) struct Config {
     xdg: Option<GitConfig>,
     global: Option<GitConfig>,
     local: Option<GitConfig>,
+    /// Included by `local` for the current branch, the last winning
+    local_includes: Vec<GitConfig>,
     env: InMemoryConfig,
     cli: InMemoryConfig,
     git_pager: InMemoryConfig,
@@ -19,6 +21,7 @@ impl Config {
         let xdg = GitConfig::open_xdg();
         let global = GitConfig::open_global();
         let local = None;
+        let local_includes = Vec::new();
         let env = InMemoryConfig::git_env();
         let cli = InMemoryConfig::git_cli();
         let git_pager = InMemoryConfig::git_pager();
@@ -28,18 +31,56 @@ impl Config {
             xdg,
             global,
             local,
+            local_includes,
             env,
             cli,
             git_pager,
         }
     }
 
+    /// Only read config from the repo and the environment, ignoring the user's and system's
+    pub(crate) fn isolate(&mut self) {
+        self.pager = InMemoryConfig::default();
+        self.system = None;
+        self.xdg = None;
+        self.global = None;
+        self.git_pager = InMemoryConfig::default();
+    }
+
     pub(crate) fn add_repo(&mut self, repo: &git2::Repository) {
         let local = GitConfig::open_local(repo);
+        self.local_includes = local
+            .as_ref()
+            .map(|local| local.onbranch_includes(repo))
+            .unwrap_or_default();
         self.local = local;
+
+        // Logging isn't initialized yet when the other layers are loaded
+        for (layer, source) in [
+            ("system", &self.system),
+            ("xdg", &self.xdg),
+            ("global", &self.global),
+            ("local", &self.local),
+        ] {
+            log::debug!(
+                target: LOG_TARGET,
+                "{} config: {}",
+                layer,
+                source.as_ref().map(|s| s.name()).unwrap_or("none")
+            );
+        }
+        for include in &self.local_includes {
+            log::debug!(target: LOG_TARGET, "included for the branch: {}", include.name());
+        }
     }
 
     pub(crate) fn get<F: Field>(&self, field: &F) -> F::Output {
+        log::trace!(
+            target: LOG_TARGET,
+            "`{}` from {}",
+            field.name(),
+            field.get_source(self).unwrap_or("nowhere")
+        );
         field.get_from(self)
     }
 
@@ -49,23 +90,68 @@ impl Config {
     ) -> String {
         use std::fmt::Write;
 
-        let mut output = String::new();
-
-        let mut prior_section = "";
+        // Each section is written once, in the order it is first seen
+        let mut sections: Vec<(&str, String)> = Vec::new();
         for field in fields {
             let (section, name) = field
                 .name()
                 .split_once('.')
                 .unwrap_or_else(|| panic!("field `{}` is missing a section", field.name()));
-            if section != prior_section {
-                let _ = writeln!(&mut output, "[{section}]");
-                prior_section = section;
-            }
+            let index = if let Some(index) = sections.iter().position(|(s, _)| *s == section) {
+                index
+            } else {
+                sections.push((section, String::new()));
+                sections.len() - 1
+            };
             let value = field.dump(self);
             let source = field.get_source(self);
-            let _ = writeln!(&mut output, "\t{name} = {value}  # {source}");
+            let _ = writeln!(&mut sections[index].1, "\t{name} = {value}  # {source}");
         }
 
+        let mut output = String::new();
+        for (section, body) in sections {
+            let _ = writeln!(&mut output, "[{section}]");
+            output.push_str(&body);
+        }
+        output
+    }
+
+    /// How `field` is resolved: its type, default, and the value from each source
+    pub(crate) fn explain(&self, field: &dyn ExplainField) -> String {
+        use std::fmt::Write;
+
+        let mut output = String::new();
+        let _ = writeln!(&mut output, "{}", field.name());
+        let _ = writeln!(&mut output, "\ttype: {}", field.type_name());
+        let _ = writeln!(&mut output, "\tdefault: {}", field.default_description());
+
+        let mut used = false;
+        let mut any = false;
+        for source in self.sources() {
+            let values = field.explain_source(source);
+            if values.is_empty() {
+                continue;
+            }
+            any = true;
+            let wins = !used && (field.is_multi() || values.iter().any(|(_, e)| e.is_none()));
+            for (value, error) in values {
+                let note = match error {
+                    Some(error) => format!("ignored, {error}"),
+                    None if wins => "used".to_owned(),
+                    None => "overridden".to_owned(),
+                };
+                let _ = writeln!(&mut output, "\t{} = {value}  # {note}", source.name());
+            }
+            used |= wins;
+        }
+        if !any {
+            let _ = writeln!(&mut output, "\tnot set, so the default is used");
+        } else if !used {
+            let _ = writeln!(
+                &mut output,
+                "\tno value could be read, so the default is used"
+            );
+        }
         output
     }
 
@@ -74,13 +160,21 @@ impl Config {
             Some(&self.git_pager).map(|c| c as &dyn ConfigSource),
             Some(&self.cli).map(|c| c as &dyn ConfigSource),
             Some(&self.env).map(|c| c as &dyn ConfigSource),
+        ]
+        .into_iter()
+        .chain(
+            self.local_includes
+                .iter()
+                .rev()
+                .map(|c| Some(c as &dyn ConfigSource)),
+        )
+        .chain([
             self.local.as_ref().map(|c| c as &dyn ConfigSource),
             self.global.as_ref().map(|c| c as &dyn ConfigSource),
             self.xdg.as_ref().map(|c| c as &dyn ConfigSource),
             self.system.as_ref().map(|c| c as &dyn ConfigSource),
             Some(&self.pager).map(|c| c as &dyn ConfigSource),
-        ]
-        .into_iter()
+        ])
         .flatten()
     }
 }
@@ -94,6 +188,11 @@ pub(crate) trait ConfigSource {
     fn get_i64(&self, name: &str) -> anyhow::Result<i64>;
     fn get_string(&self, name: &str) -> anyhow::Result<String>;
     fn get_path(&self, name: &str) -> anyhow::Result<std::path::PathBuf>;
+    /// Every value of a multi-valued key, oldest first
+    fn get_multi_string(&self, name: &str) -> anyhow::Result<Vec<String>>;
+
+    /// Every key in `section`, with the name of the source it came from
+    fn entries(&self, section: &str) -> anyhow::Result<Vec<(String, &str)>>;
 }
 
 impl ConfigSource for Config {
@@ -174,52 +273,65 @@ impl ConfigSource for Config {
             .expect("always a source")
             .get_path(name)
     }
-}
-
-impl ConfigSource for git2::Config {
-    fn name(&self) -> &str {
-        "gitconfig"
+    /// Unlike git, the values come from the highest priority source with any, rather than all
+    fn get_multi_string(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        for config in self.sources() {
+            if let Ok(v) = config.get_multi_string(name) {
+                return Ok(v);
+            }
+        }
+        // Fallback to the first error
+        self.sources()
+            .next()
+            .expect("always a source")
+            .get_multi_string(name)
     }
 
-    fn get_source(&self, name: &str) -> anyhow::Result<&str> {
-        self.get_entry(name)
-            .map(|_| self.name())
-            .map_err(|e| e.into())
-    }
-    fn get_bool(&self, name: &str) -> anyhow::Result<bool> {
-        self.get_bool(name).map_err(|e| e.into())
-    }
-    fn get_i32(&self, name: &str) -> anyhow::Result<i32> {
-        self.get_i32(name).map_err(|e| e.into())
-    }
-    fn get_i64(&self, name: &str) -> anyhow::Result<i64> {
-        self.get_i64(name).map_err(|e| e.into())
-    }
-    fn get_string(&self, name: &str) -> anyhow::Result<String> {
-        self.get_string(name).map_err(|e| e.into())
-    }
-    fn get_path(&self, name: &str) -> anyhow::Result<std::path::PathBuf> {
-        self.get_path(name).map_err(|e| e.into())
+    fn entries(&self, section: &str) -> anyhow::Result<Vec<(String, &str)>> {
+        let mut entries = Vec::new();
+        for config in self.sources() {
+            entries.extend(config.entries(section)?);
+        }
+        Ok(entries)
     }
 }
 
+/// A config file, read once so values can't change mid-run if the file is edited
+///
+/// Values are copied out of libgit2, so the config can be shared across threads.
 pub(crate) struct GitConfig {
     name: String,
-    config: git2::Config,
+    /// Normalized keys and their values in the order they were read, `None` for a key without `=`
+    values: Vec<(String, Option<String>)>,
 }
 
 impl GitConfig {
+    /// Respects `GIT_CONFIG_NOSYSTEM` and `GIT_CONFIG_SYSTEM`
     pub(crate) fn open_system() -> Option<Self> {
+        if env_flag("GIT_CONFIG_NOSYSTEM") {
+            return None;
+        }
+        if let Some(path) = std::env::var_os("GIT_CONFIG_SYSTEM") {
+            return Self::open_explicit(std::path::Path::new(&path));
+        }
         let path = git2::Config::find_system().ok()?;
         Self::open_path(&path)
     }
 
+    /// Skipped when `GIT_CONFIG_GLOBAL` is set, like git
     pub(crate) fn open_xdg() -> Option<Self> {
+        if std::env::var_os("GIT_CONFIG_GLOBAL").is_some() {
+            return None;
+        }
         let path = git2::Config::find_xdg().ok()?;
         Self::open_path(&path)
     }
 
+    /// Respects `GIT_CONFIG_GLOBAL`
     pub(crate) fn open_global() -> Option<Self> {
+        if let Some(path) = std::env::var_os("GIT_CONFIG_GLOBAL") {
+            return Self::open_explicit(std::path::Path::new(&path));
+        }
         let path = git2::Config::find_global().ok()?;
         Self::open_path(&path)
     }
@@ -227,18 +339,141 @@ impl GitConfig {
     pub(crate) fn open_local(repo: &git2::Repository) -> Option<Self> {
         let path = repo.path().join("config");
         let config = git2::Config::open(&path).ok()?;
-        let name = "$GIT_DIR/config".to_owned();
-        Some(Self { name, config })
+        Self::read("$GIT_DIR/config", config)
+    }
+
+    /// The files `[includeIf "onbranch:PATTERN"]` sections include for the current branch
+    ///
+    /// libgit2 only checks conditions when a file is opened through a repo, and matches
+    /// `onbranch:` differently from git, with `*` crossing `/`, so these are read here instead.
+    /// As with git, the branch is the one `HEAD` names, even before its first commit, and a
+    /// detached `HEAD` matches nothing.  A pattern ending in `/` matches every branch under it.
+    /// Included values win over all of `self`'s, rather than only those before the section.
+    fn onbranch_includes(&self, repo: &git2::Repository) -> Vec<Self> {
+        let Some(branch) = current_branch(repo) else {
+            log::debug!(target: LOG_TARGET, "`HEAD` is detached, so no `onbranch:` applies");
+            return Vec::new();
+        };
+        let mut includes = Vec::new();
+        let paths = self
+            .values
+            .iter()
+            .filter_map(|(name, path)| Some((name.as_str(), path.as_deref()?)));
+        for (name, path) in paths {
+            let Some(pattern) = name
+                .strip_prefix("includeif.onbranch:")
+                .and_then(|name| name.strip_suffix(".path"))
+            else {
+                continue;
+            };
+            if !onbranch_matches(pattern, &branch) {
+                continue;
+            }
+            let path = include_path(path, repo.path());
+            if !path.is_file() {
+                log::debug!(target: LOG_TARGET, "skipping missing include {}", path.display());
+                continue;
+            }
+            includes.extend(Self::open_path(&path));
+        }
+        includes
     }
 
     fn open_path(path: &std::path::Path) -> Option<Self> {
         let config = git2::Config::open(path).ok()?;
-        let name = path.display().to_string();
-        Some(Self { name, config })
+        Self::read(path.display().to_string(), config)
     }
 
-    fn inner(&self) -> &impl ConfigSource {
-        &self.config
+    /// Open a file the user named, treating a missing file as empty
+    fn open_explicit(path: &std::path::Path) -> Option<Self> {
+        if path.as_os_str().is_empty() || !path.exists() {
+            return Some(Self {
+                name: path.display().to_string(),
+                values: Default::default(),
+            });
+        }
+        Self::open_path(path)
+    }
+
+    /// Copy every value out of a snapshot of `config`, including those from `include.path`
+    fn read(name: impl Into<String>, mut config: git2::Config) -> Option<Self> {
+        let snapshot = config.snapshot().ok()?;
+        let mut values = Vec::new();
+        let mut entries = snapshot.entries(None).ok()?;
+        while let Some(entry) = entries.next() {
+            let entry = entry.ok()?;
+            let Some(name) = entry.name() else {
+                continue;
+            };
+            let value = entry
+                .has_value()
+                .then(|| String::from_utf8_lossy(entry.value_bytes()).into_owned());
+            values.push((normalize_key(name), value));
+        }
+        Some(Self {
+            name: name.into(),
+            values,
+        })
+    }
+
+    /// The last value of `name`, `None` for a key without `=`
+    fn get_value(&self, name: &str) -> anyhow::Result<Option<&str>> {
+        let name = normalize_key(name);
+        let (_, value) = self
+            .values
+            .iter()
+            .rfind(|(key, _)| *key == name)
+            .context("field is missing")?;
+        Ok(value.as_deref())
+    }
+
+    fn get_str(&self, name: &str) -> anyhow::Result<&str> {
+        self.get_value(name)?
+            .ok_or_else(|| anyhow::format_err!("`{name}` has no value"))
+    }
+}
+
+/// The branch `HEAD` names, without `refs/heads/`, `None` when it's detached
+fn current_branch(repo: &git2::Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    target.strip_prefix("refs/heads/").map(ToOwned::to_owned)
+}
+
+/// Whether `branch` matches an `onbranch:` pattern, where a trailing `/` means `/**`
+fn onbranch_matches(pattern: &str, branch: &str) -> bool {
+    let pattern = if pattern.ends_with('/') {
+        format!("{pattern}**")
+    } else {
+        pattern.to_owned()
+    };
+    crate::attributes::wildmatch(pattern.as_bytes(), branch.as_bytes())
+}
+
+/// An include's path, relative to the including file in `git_dir`, or `~/` for home
+fn include_path(path: &str, git_dir: &std::path::Path) -> std::path::PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return std::path::Path::new(&home).join(rest);
+        }
+    }
+    git_dir.join(path)
+}
+
+/// Whether a boolean environment variable is set to true, using git's spelling of booleans
+pub(crate) fn env_flag(name: &str) -> bool {
+    let Some(value) = std::env::var_os(name) else {
+        return false;
+    };
+    parse_bool(&value.to_string_lossy()).unwrap_or(false)
+}
+
+/// Parse a boolean the way git spells them
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => Some(true),
+        "false" | "no" | "off" | "" => Some(false),
+        value => value.parse::<i64>().ok().map(|v| v != 0),
     }
 }
 
@@ -248,22 +483,54 @@ impl ConfigSource for GitConfig {
     }
 
     fn get_source(&self, name: &str) -> anyhow::Result<&str> {
-        self.inner().get_source(name)
+        self.get_value(name).map(|_| self.name())
     }
     fn get_bool(&self, name: &str) -> anyhow::Result<bool> {
-        self.inner().get_bool(name)
+        match self.get_value(name)? {
+            Some(value) => Ok(git2::Config::parse_bool(value)?),
+            None => Ok(true),
+        }
     }
     fn get_i32(&self, name: &str) -> anyhow::Result<i32> {
-        self.inner().get_i32(name)
+        Ok(git2::Config::parse_i32(self.get_str(name)?)?)
     }
     fn get_i64(&self, name: &str) -> anyhow::Result<i64> {
-        self.inner().get_i64(name)
+        Ok(git2::Config::parse_i64(self.get_str(name)?)?)
     }
     fn get_string(&self, name: &str) -> anyhow::Result<String> {
-        self.inner().get_string(name)
+        self.get_str(name).map(ToOwned::to_owned)
     }
+    /// Like libgit2, `~/` is the home dir
     fn get_path(&self, name: &str) -> anyhow::Result<std::path::PathBuf> {
-        self.inner().get_path(name)
+        let value = self.get_str(name)?;
+        if let Some(rest) = value.strip_prefix("~/") {
+            let home = std::env::var_os("HOME").context("`HOME` isn't set")?;
+            return Ok(std::path::Path::new(&home).join(rest));
+        }
+        Ok(value.into())
+    }
+    fn get_multi_string(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let name = normalize_key(name);
+        let values = self
+            .values
+            .iter()
+            .filter(|(key, _)| *key == name)
+            .filter_map(|(_, value)| value.clone())
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            anyhow::bail!("field is missing");
+        }
+        Ok(values)
+    }
+
+    fn entries(&self, section: &str) -> anyhow::Result<Vec<(String, &str)>> {
+        let prefix = format!("{}.", section.to_ascii_lowercase());
+        Ok(self
+            .values
+            .iter()
+            .filter(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, _)| (k.clone(), self.name()))
+            .collect())
     }
 }
 
@@ -287,10 +554,20 @@ impl InMemoryConfig {
     }
 
     pub(crate) fn git_cli() -> Self {
+        Self::from_cli("git-cli", git_config_env::ConfigParameters::new().iter())
+    }
+
+    /// Like `git -c`, where a key without a value, as opposed to an empty one, is true
+    ///
+    /// Keys that weren't given stay missing, so a bool from a lower layer still applies.
+    fn from_cli<'v>(
+        name: impl Into<String>,
+        params: impl IntoIterator<Item = (impl Into<String>, Option<std::borrow::Cow<'v, str>>)>,
+    ) -> Self {
         Self::from_env(
-            "git-cli",
-            git_config_env::ConfigParameters::new()
-                .iter()
+            name,
+            params
+                .into_iter()
                 .map(|(k, v)| (k, v.unwrap_or(std::borrow::Cow::Borrowed("true")))),
         )
     }
@@ -315,7 +592,7 @@ impl InMemoryConfig {
         let mut values = std::collections::BTreeMap::new();
         for (key, value) in env {
             values
-                .entry(key.into())
+                .entry(normalize_key(&key.into()))
                 .or_insert_with(Vec::new)
                 .push(value.into());
         }
@@ -325,7 +602,7 @@ impl InMemoryConfig {
     fn get_str(&self, name: &str) -> anyhow::Result<&str> {
         let value = self
             .values
-            .get(name)
+            .get(&normalize_key(name))
             .context("field is missing")?
             .last()
             .expect("always at least one element");
@@ -333,6 +610,21 @@ impl InMemoryConfig {
     }
 }
 
+/// Section and variable names are case-insensitive while the subsection is not
+fn normalize_key(key: &str) -> String {
+    let Some((section, rest)) = key.split_once('.') else {
+        return key.to_ascii_lowercase();
+    };
+    let section = section.to_ascii_lowercase();
+    if let Some((subsection, name)) = rest.rsplit_once('.') {
+        let name = name.to_ascii_lowercase();
+        format!("{section}.{subsection}.{name}")
+    } else {
+        let name = rest.to_ascii_lowercase();
+        format!("{section}.{name}")
+    }
+}
+
 impl Default for InMemoryConfig {
     fn default() -> Self {
         Self {
@@ -351,8 +643,8 @@ impl ConfigSource for InMemoryConfig {
         self.get_str(name).map(|_| self.name())
     }
     fn get_bool(&self, name: &str) -> anyhow::Result<bool> {
-        let v = self.get_str(name).unwrap_or("true");
-        v.parse::<bool>().map_err(|e| e.into())
+        let v = self.get_str(name)?;
+        parse_bool(v).ok_or_else(|| anyhow::format_err!("invalid boolean `{}`", v))
     }
     fn get_i32(&self, name: &str) -> anyhow::Result<i32> {
         self.get_str(name)
@@ -368,48 +660,64 @@ impl ConfigSource for InMemoryConfig {
     fn get_path(&self, name: &str) -> anyhow::Result<std::path::PathBuf> {
         self.get_string(name).map(|v| v.into())
     }
+    fn get_multi_string(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        self.values
+            .get(&normalize_key(name))
+            .cloned()
+            .context("field is missing")
+    }
+
+    fn entries(&self, section: &str) -> anyhow::Result<Vec<(String, &str)>> {
+        let prefix = format!("{}.", section.to_ascii_lowercase());
+        Ok(self
+            .values
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .map(|k| (k.clone(), self.name()))
+            .collect())
+    }
 }
 
 pub(crate) trait FieldReader<T> {
     fn get_field(&self, name: &str) -> anyhow::Result<T>;
 }
 
-impl<C: ConfigSource> FieldReader<bool> for C {
+impl<C: ConfigSource + ?Sized> FieldReader<bool> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<bool> {
         self.get_bool(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
     }
 }
 
-impl<C: ConfigSource> FieldReader<i32> for C {
+impl<C: ConfigSource + ?Sized> FieldReader<i32> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<i32> {
         self.get_i32(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
     }
 }
 
-impl<C: ConfigSource> FieldReader<i64> for C {
+impl<C: ConfigSource + ?Sized> FieldReader<i64> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<i64> {
         self.get_i64(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
     }
 }
 
-impl<C: ConfigSource> FieldReader<String> for C {
+impl<C: ConfigSource + ?Sized> FieldReader<String> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<String> {
         self.get_string(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
     }
 }
 
-impl<C: ConfigSource> FieldReader<std::path::PathBuf> for C {
+impl<C: ConfigSource + ?Sized> FieldReader<std::path::PathBuf> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<std::path::PathBuf> {
         self.get_path(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
     }
 }
 
-impl<P: Parseable, C: ConfigSource> FieldReader<P> for C {
+impl<P: Parseable, C: ConfigSource + ?Sized> FieldReader<P> for C {
     fn get_field(&self, name: &str) -> anyhow::Result<P> {
         self.get_string(name)
             .with_context(|| anyhow::format_err!("failed to read `{}`", name))
@@ -421,37 +729,8 @@ pub(crate) trait Parseable: Sized {
     fn parse(s: &str) -> anyhow::Result<Self>;
 }
 
-pub(crate) struct ParseWrapper<T>(pub(crate) T);
-
-impl<T: std::fmt::Display> std::fmt::Display for ParseWrapper<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.fmt(f)
-    }
-}
-
-impl<T> std::str::FromStr for ParseWrapper<T>
-where
-    T: std::str::FromStr,
-    T::Err: Into<anyhow::Error>,
-{
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        T::from_str(s).map(ParseWrapper).map_err(|e| e.into())
-    }
-}
-
-impl<T> Parseable for ParseWrapper<T>
-where
-    T: Parseable,
-    T: std::str::FromStr,
-    T: std::fmt::Display,
-    T::Err: Into<anyhow::Error>,
-{
-    fn parse(s: &str) -> anyhow::Result<Self> {
-        <Self as std::str::FromStr>::from_str(s)
-    }
-}
+/// Config resolution is logged under one target, regardless of module
+const LOG_TARGET: &str = "git_dive::config";
 
 pub(crate) trait Field {
     type Output;
@@ -505,7 +784,15 @@ where
     }
 
     fn get_from(&self, config: &Config) -> Self::Output {
-        config.get_field(self.name).ok()
+        match config.get_field(self.name) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                if config.get_source(self.name).is_ok() {
+                    log::debug!(target: LOG_TARGET, "ignoring `{}`: {:#}", self.name, err);
+                }
+                None
+            }
+        }
     }
 
     fn get_source<'c>(&self, config: &'c Config) -> Option<&'c str> {
@@ -541,6 +828,51 @@ where
     }
 }
 
+/// A multi-valued field, skipping values that fail to parse
+pub(crate) struct MultiField<R> {
+    name: &'static str,
+    _type: std::marker::PhantomData<R>,
+}
+
+impl<R> MultiField<R> {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _type: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<P: Parseable> Field for MultiField<P> {
+    type Output = Vec<P>;
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get_from(&self, config: &Config) -> Self::Output {
+        let Ok(values) = config.get_multi_string(self.name) else {
+            return Vec::new();
+        };
+        values
+            .iter()
+            .filter_map(|value| match P::parse(value) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    // Unlike a single bad value, the rest of the list still applies, so this is
+                    // easy to miss
+                    log::warn!("ignoring `{} = {}`: {:#}", self.name, value, err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn get_source<'c>(&self, config: &'c Config) -> Option<&'c str> {
+        config.get_source(self.name).ok()
+    }
+}
+
 pub(crate) trait ReflectField {
     fn name(&self) -> &'static str;
 
@@ -565,9 +897,118 @@ where
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// A field as `--explain` shows it, read from each source rather than only the one that wins
+pub(crate) trait ExplainField {
+    fn name(&self) -> &'static str;
+
+    /// What values are parsed as
+    fn type_name(&self) -> String;
+    /// What is used when no source sets the field
+    fn default_description(&self) -> String;
+    /// Every value could be used rather than only the highest priority one
+    fn is_multi(&self) -> bool {
+        false
+    }
+    /// The values `source` sets the field to, each with why it's ignored if it doesn't parse
+    fn explain_source(&self, source: &dyn ConfigSource) -> Vec<(String, Option<String>)>;
+}
+
+impl<R> ExplainField for RawField<R>
+where
+    for<'s> dyn ConfigSource + 's: FieldReader<R>,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn type_name(&self) -> String {
+        short_type_name(std::any::type_name::<R>())
+    }
+    fn default_description(&self) -> String {
+        "none".to_owned()
+    }
+    fn explain_source(&self, source: &dyn ConfigSource) -> Vec<(String, Option<String>)> {
+        if source.get_source(self.name).is_err() {
+            return Vec::new();
+        }
+        // Keys without a value, like `[dive] strict`, are only readable as bools
+        let raw = source.get_string(self.name).unwrap_or_default();
+        let error = FieldReader::<R>::get_field(source, self.name)
+            .err()
+            .map(|err| format!("{err:#}"));
+        vec![(raw, error)]
+    }
+}
+
+impl<R> ExplainField for DefaultField<R>
+where
+    for<'s> dyn ConfigSource + 's: FieldReader<R>,
+    R: std::fmt::Display,
+{
+    fn name(&self) -> &'static str {
+        self.field.name
+    }
+
+    fn type_name(&self) -> String {
+        self.field.type_name()
+    }
+    fn default_description(&self) -> String {
+        format!("`{}`", (self.default)())
+    }
+    fn explain_source(&self, source: &dyn ConfigSource) -> Vec<(String, Option<String>)> {
+        self.field.explain_source(source)
+    }
+}
+
+impl<P: Parseable> ExplainField for MultiField<P> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn type_name(&self) -> String {
+        format!("list of {}", short_type_name(std::any::type_name::<P>()))
+    }
+    fn default_description(&self) -> String {
+        "none".to_owned()
+    }
+    fn is_multi(&self) -> bool {
+        true
+    }
+    fn explain_source(&self, source: &dyn ConfigSource) -> Vec<(String, Option<String>)> {
+        let Ok(values) = source.get_multi_string(self.name) else {
+            return Vec::new();
+        };
+        values
+            .into_iter()
+            .map(|value| {
+                let error = P::parse(&value).err().map(|err| format!("{err:#}"));
+                (value, error)
+            })
+            .collect()
+    }
+}
+
+/// `type_name` without module paths, like `Vec<String>` for `alloc::vec::Vec<alloc::string::String>`
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut path = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            short.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(path.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
 pub(crate) enum ColorWhen {
     Always,
+    #[default]
     Auto,
     Never,
 }
@@ -582,12 +1023,6 @@ impl ColorWhen {
     }
 }
 
-impl Default for ColorWhen {
-    fn default() -> Self {
-        Self::Auto
-    }
-}
-
 impl std::fmt::Display for ColorWhen {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.as_str().fmt(f)
@@ -618,3 +1053,158 @@ pub(crate) const COLOR_UI: DefaultField<ColorWhen> =
 
 pub(crate) const PAGER: DefaultField<String> =
     RawField::<String>::new("core.pager").default_value(|| "less".to_owned());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn in_memory_is_case_insensitive() {
+        let config = InMemoryConfig::from_env(
+            "test",
+            [
+                ("blame.ignorerevsfile", "a"),
+                ("Dive.Profile.Team.Args", "b"),
+            ],
+        );
+        assert_eq!(config.get_string("blame.ignoreRevsFile").unwrap(), "a");
+        assert_eq!(config.get_string("dive.Profile.Team.args").unwrap(), "b");
+        assert!(config.get_string("dive.profile.team.args").is_err());
+    }
+
+    #[test]
+    fn config_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Config>();
+    }
+
+    #[test]
+    fn file_values() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let path = root.path().unwrap().join("config");
+        std::fs::write(
+            &path,
+            "[dive]\n\tbare\n\toff = no\n\tsize = 2k\n\tpath = ~/ignore\n\
+             [dive \"Team\"]\n\targ = one\n\targ = two\n",
+        )
+        .unwrap();
+        let config = GitConfig::open_path(&path).unwrap();
+
+        assert!(config.get_bool("dive.bare").unwrap());
+        assert!(config.get_string("dive.bare").is_err());
+        assert!(!config.get_bool("dive.OFF").unwrap());
+        assert_eq!(config.get_i64("dive.size").unwrap(), 2048);
+        let home = std::env::var_os("HOME").unwrap();
+        assert_eq!(
+            config.get_path("dive.path").unwrap(),
+            std::path::Path::new(&home).join("ignore")
+        );
+        assert_eq!(config.get_string("dive.Team.arg").unwrap(), "two");
+        assert_eq!(
+            config.get_multi_string("dive.Team.arg").unwrap(),
+            ["one", "two"]
+        );
+        assert!(config.get_string("dive.team.arg").is_err());
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn local_is_read_once() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("dive.theme", "before")
+            .unwrap();
+
+        let mut config = Config::system();
+        config.add_repo(&repo);
+        repo.config()
+            .unwrap()
+            .set_str("dive.theme", "after")
+            .unwrap();
+        assert_eq!(config.get_string("dive.theme").unwrap(), "before");
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn onbranch_patterns() {
+        assert!(onbranch_matches("release/*", "release/1.x"));
+        assert!(!onbranch_matches("release/*", "release/1.x/hotfix"));
+        assert!(onbranch_matches("release/**", "release/1.x/hotfix"));
+        assert!(onbranch_matches("release/", "release/1.x/hotfix"));
+        assert!(!onbranch_matches("release/", "releases/1.x"));
+        assert!(onbranch_matches("main", "main"));
+        assert!(!onbranch_matches("main", "maintenance"));
+    }
+
+    #[test]
+    fn in_memory_bool() {
+        let config = InMemoryConfig::from_env("test", [("dive.a", "yes"), ("dive.b", "0")]);
+        assert!(config.get_bool("dive.a").unwrap());
+        assert!(!config.get_bool("dive.b").unwrap());
+        assert!(config.get_bool("dive.missing").is_err());
+    }
+
+    #[test]
+    fn cli_bool() {
+        let config = InMemoryConfig::from_cli(
+            "test",
+            [
+                ("dive.bare", None),
+                ("dive.empty", Some("".into())),
+                ("dive.off", Some("off".into())),
+            ],
+        );
+        assert!(config.get_bool("dive.bare").unwrap());
+        assert!(!config.get_bool("dive.empty").unwrap());
+        assert!(!config.get_bool("dive.off").unwrap());
+        assert!(config.get_bool("dive.missing").is_err());
+    }
+
+    #[test]
+    fn repo_bool_wins_over_unset_layers() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        repo.config().unwrap().set_bool("dive.a", false).unwrap();
+
+        let mut config = Config {
+            pager: InMemoryConfig::default(),
+            system: None,
+            xdg: None,
+            global: None,
+            local: None,
+            local_includes: Vec::new(),
+            env: InMemoryConfig::from_env("env", [("dive.b", "true")]),
+            cli: InMemoryConfig::from_cli("cli", [("dive.c", None)]),
+            git_pager: InMemoryConfig::default(),
+        };
+        config.add_repo(&repo);
+        assert!(!config.get_bool("dive.a").unwrap());
+        assert_eq!(
+            config.get_source("dive.a").unwrap(),
+            config.local.as_ref().unwrap().name()
+        );
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn in_memory_multi() {
+        let config = InMemoryConfig::from_env("test", [("dive.a", "1"), ("Dive.A", "2")]);
+        assert_eq!(config.get_multi_string("dive.a").unwrap(), ["1", "2"]);
+        assert!(config.get_multi_string("dive.missing").is_err());
+    }
+
+    #[test]
+    fn short_type_names() {
+        assert_eq!(short_type_name("bool"), "bool");
+        assert_eq!(short_type_name("alloc::string::String"), "String");
+        assert_eq!(
+            short_type_name("alloc::vec::Vec<std::path::PathBuf>"),
+            "Vec<PathBuf>"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::git2_config::Parseable;
+
+/// A color in git's `color.*` syntax, like `bold red` or `#ff0000 ul`
+///
+/// The first color is the foreground, the second is the background, and any other word is an
+/// attribute, optionally prefixed with `no` or `no-` to turn it off.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct GitColor(anstyle::Style);
+
+impl GitColor {
+    pub(crate) fn style(self) -> anstyle::Style {
+        self.0
+    }
+}
+
+impl From<anstyle::Style> for GitColor {
+    fn from(style: anstyle::Style) -> Self {
+        Self(style)
+    }
+}
+
+impl std::fmt::Display for GitColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut words = Vec::new();
+        for (effect, name) in EFFECTS {
+            if self.0.get_effects().contains(*effect) {
+                words.push((*name).to_owned());
+            }
+        }
+        match (self.0.get_fg_color(), self.0.get_bg_color()) {
+            (fg, Some(bg)) => {
+                words.push(fg.map(color_name).unwrap_or_else(|| "normal".to_owned()));
+                words.push(color_name(bg));
+            }
+            (Some(fg), None) => words.push(color_name(fg)),
+            (None, None) => {}
+        }
+        if words.is_empty() {
+            "normal".fmt(f)
+        } else {
+            words.join(" ").fmt(f)
+        }
+    }
+}
+
+impl std::str::FromStr for GitColor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = anstyle::Style::new();
+        let mut colors = 0;
+        for word in s.split_ascii_whitespace() {
+            let lower = word.to_ascii_lowercase();
+            if let Some(color) = parse_color(&lower)? {
+                match colors {
+                    0 => style = style.fg_color(color),
+                    1 => style = style.bg_color(color),
+                    _ => anyhow::bail!("too many colors in `{}`", s),
+                }
+                colors += 1;
+            } else if let Some(effect) = parse_effect(&lower) {
+                style = style.effects(style.get_effects() | effect);
+            } else if let Some(effect) = lower
+                .strip_prefix("no-")
+                .or_else(|| lower.strip_prefix("no"))
+                .and_then(parse_effect)
+            {
+                style = style.effects(style.get_effects().remove(effect));
+            } else {
+                anyhow::bail!("unsupported color `{}` in `{}`", word, s);
+            }
+        }
+        Ok(Self(style))
+    }
+}
+
+impl Parseable for GitColor {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        <Self as std::str::FromStr>::from_str(s)
+    }
+}
+
+const COLORS: &[(anstyle::AnsiColor, &str)] = &[
+    (anstyle::AnsiColor::Black, "black"),
+    (anstyle::AnsiColor::Red, "red"),
+    (anstyle::AnsiColor::Green, "green"),
+    (anstyle::AnsiColor::Yellow, "yellow"),
+    (anstyle::AnsiColor::Blue, "blue"),
+    (anstyle::AnsiColor::Magenta, "magenta"),
+    (anstyle::AnsiColor::Cyan, "cyan"),
+    (anstyle::AnsiColor::White, "white"),
+    (anstyle::AnsiColor::BrightBlack, "brightblack"),
+    (anstyle::AnsiColor::BrightRed, "brightred"),
+    (anstyle::AnsiColor::BrightGreen, "brightgreen"),
+    (anstyle::AnsiColor::BrightYellow, "brightyellow"),
+    (anstyle::AnsiColor::BrightBlue, "brightblue"),
+    (anstyle::AnsiColor::BrightMagenta, "brightmagenta"),
+    (anstyle::AnsiColor::BrightCyan, "brightcyan"),
+    (anstyle::AnsiColor::BrightWhite, "brightwhite"),
+];
+
+const EFFECTS: &[(anstyle::Effects, &str)] = &[
+    (anstyle::Effects::BOLD, "bold"),
+    (anstyle::Effects::DIMMED, "dim"),
+    (anstyle::Effects::ITALIC, "italic"),
+    (anstyle::Effects::UNDERLINE, "ul"),
+    (anstyle::Effects::BLINK, "blink"),
+    (anstyle::Effects::INVERT, "reverse"),
+    (anstyle::Effects::STRIKETHROUGH, "strike"),
+];
+
+/// Returns `Ok(None)` if `word` isn't a color and `Ok(Some(None))` for the terminal's color
+fn parse_color(word: &str) -> anyhow::Result<Option<Option<anstyle::Color>>> {
+    if word == "normal" || word == "default" {
+        // Placeholders allowing a background to be specified alone
+        return Ok(Some(None));
+    }
+    if let Some((color, _)) = COLORS.iter().find(|(_, name)| *name == word) {
+        return Ok(Some(Some((*color).into())));
+    }
+    if let Some(hex) = word.strip_prefix('#') {
+        let rgb = match hex.len() {
+            6 => u32::from_str_radix(hex, 16).ok().map(|v| {
+                (
+                    ((v >> 16) & 0xff) as u8,
+                    ((v >> 8) & 0xff) as u8,
+                    (v & 0xff) as u8,
+                )
+            }),
+            3 => u32::from_str_radix(hex, 16).ok().map(|v| {
+                let expand = |c: u32| (c * 0x11) as u8;
+                (
+                    expand((v >> 8) & 0xf),
+                    expand((v >> 4) & 0xf),
+                    expand(v & 0xf),
+                )
+            }),
+            _ => None,
+        };
+        let (r, g, b) = rgb.ok_or_else(|| anyhow::format_err!("invalid color `{}`", word))?;
+        return Ok(Some(Some(anstyle::RgbColor(r, g, b).into())));
+    }
+    if word.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+        let index = word
+            .parse::<i16>()
+            .map_err(|_| anyhow::format_err!("invalid color `{}`", word))?;
+        return match index {
+            -1 => Ok(Some(None)),
+            0..=255 => Ok(Some(Some(anstyle::Ansi256Color(index as u8).into()))),
+            _ => Err(anyhow::format_err!("color `{}` is out of range", word)),
+        };
+    }
+    Ok(None)
+}
+
+fn parse_effect(word: &str) -> Option<anstyle::Effects> {
+    match word {
+        "underline" => Some(anstyle::Effects::UNDERLINE),
+        _ => EFFECTS
+            .iter()
+            .find(|(_, name)| *name == word)
+            .map(|(effect, _)| *effect),
+    }
+}
+
+fn color_name(color: anstyle::Color) -> String {
+    match color {
+        anstyle::Color::Ansi(ansi) => COLORS
+            .iter()
+            .find(|(c, _)| *c == ansi)
+            .map(|(_, name)| (*name).to_owned())
+            .expect("all ansi colors are named"),
+        anstyle::Color::Ansi256(anstyle::Ansi256Color(index)) => index.to_string(),
+        anstyle::Color::Rgb(anstyle::RgbColor(r, g, b)) => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_fg_bg_and_attributes() {
+        let actual = "bold red #00ff00 ul".parse::<GitColor>().unwrap();
+        let expected = anstyle::AnsiColor::Red
+            .on(anstyle::RgbColor(0, 255, 0))
+            .effects(anstyle::Effects::BOLD | anstyle::Effects::UNDERLINE);
+        assert_eq!(actual.style(), expected);
+        assert_eq!(actual.to_string(), "bold ul red #00ff00");
+    }
+
+    #[test]
+    fn parse_normal_placeholder() {
+        let actual = "normal 238 nobold".parse::<GitColor>().unwrap();
+        let expected = anstyle::Style::new().bg_color(Some(anstyle::Ansi256Color(238).into()));
+        assert_eq!(actual.style(), expected);
+        assert_eq!(actual.to_string(), "normal 238");
+    }
+
+    #[test]
+    fn parse_short_hex() {
+        let actual = "#f0a".parse::<GitColor>().unwrap();
+        let expected = anstyle::Style::new().fg_color(Some(anstyle::RgbColor(255, 0, 170).into()));
+        assert_eq!(actual.style(), expected);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("bold purple".parse::<GitColor>().is_err());
+        assert!("red green blue".parse::<GitColor>().is_err());
+        assert!("256".parse::<GitColor>().is_err());
+    }
+}
@@ -1,46 +1,280 @@
+use proc_exit::WithCodeResultExt;
+
+use crate::git2_config::Config;
+use crate::git2_config::Field as _;
+
+/// Set for the pager when the user hasn't
 const DEFAULT_ENV: &[(&str, &str)] = &[("LESS", "FRX"), ("LV", "-c")];
-const REQUIRED_ENV: &[(&str, &str)] = &[("LESSCHARSET", "UTF-8")];
+/// Set for the pager regardless, everything else being inherited as is, like `LESSSECURE`
+const REQUIRED_ENV: &[(&str, &str)] = &[("LESSCHARSET", "UTF-8"), (PAGER_IN_USE, "true")];
+/// Set by git for its pager, so we're already being paged when it's true
+const PAGER_IN_USE: &str = "GIT_PAGER_IN_USE";
+
+/// Set while a pager we started is running
+static PAGER_RUNNING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Whether a pager we started is running, drawing on the terminal stdout was
+pub(crate) fn pager_running() -> bool {
+    PAGER_RUNNING.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Whether we're running under a pager git, or git-dive, started
+pub(crate) fn pager_in_use() -> bool {
+    std::env::var(PAGER_IN_USE)
+        .ok()
+        .and_then(|v| crate::git2_config::parse_bool(&v))
+        .unwrap_or(false)
+}
+
+/// The pager command to run, with `--pager` and `--no-pager` taking precedence over config
+///
+/// `dive.pager-long` takes precedence over `core.pager`, `GIT_PAGER`, and `PAGER`.
+pub(crate) fn resolve_pager(config: &Config, args: &crate::args::Args) -> String {
+    let pager = if args.no_pager {
+        "cat".to_owned()
+    } else if let Some(pager) = args.pager.as_deref() {
+        pager.to_owned()
+    } else if let Some(pager) = config.get(&PAGER_LONG) {
+        pager
+    } else {
+        config.get(&crate::git2_config::PAGER)
+    };
+    log::debug!("pager `{}` (from {})", pager, pager_source(config, args));
+    pager
+}
+
+pub(crate) fn pager_source<'c>(config: &'c Config, args: &crate::args::Args) -> &'c str {
+    if args.no_pager {
+        "--no-pager"
+    } else if args.pager.is_some() {
+        "--pager"
+    } else if let Some(source) = PAGER_LONG.get_source(config) {
+        source
+    } else {
+        crate::git2_config::PAGER
+            .get_source(config)
+            .expect("default fields always have a source")
+    }
+}
+
+/// The command for output that fits on the screen, rather than writing it directly
+///
+/// Only `dive.pager-short`, as `--pager` and `--no-pager` apply to all output.
+pub(crate) fn resolve_short_pager(config: &Config, args: &crate::args::Args) -> Option<String> {
+    if args.no_pager || args.pager.is_some() {
+        return None;
+    }
+    let pager = config.get(&PAGER_SHORT)?;
+    log::debug!(
+        "pager `{}` for short output (from {})",
+        pager,
+        PAGER_SHORT
+            .get_source(config)
+            .expect("set fields have a source")
+    );
+    Some(pager)
+}
 
 pub(crate) struct Pager {
     cmd: Option<std::process::Command>,
+    /// For output shorter than `min_lines`, instead of writing it directly
+    short_cmd: Option<std::process::Command>,
+    min_lines: usize,
+    /// Never hand output that fits on the screen to the pager
+    buffer_short: bool,
+    tee: Option<crate::tee::Tee>,
+    /// Write nothing, with `--no-output`
+    discard: bool,
+    /// Whether hunks get `SECTION_MARK`, per `dive.pager-sections`
+    section_marks: bool,
 }
 
 impl Pager {
     pub(crate) fn stdout(args: &str) -> Self {
-        let cmd = anstream::stdout()
-            .is_terminal()
+        let in_use = pager_in_use();
+        if in_use {
+            log::debug!("not paging since `{PAGER_IN_USE}` is set");
+        }
+        let cmd = (anstream::stdout().is_terminal() && !in_use)
             .then(|| parse(args))
             .flatten();
-        Self { cmd }
+        Self {
+            cmd,
+            short_cmd: None,
+            min_lines: 0,
+            buffer_short: false,
+            tee: None,
+            discard: false,
+            section_marks: false,
+        }
     }
 
-    pub(crate) fn start(&mut self) -> ActivePager {
-        let stdout = anstream::stdout().lock();
-        if let Some(cmd) = &mut self.cmd {
-            // should use pager instead of stderr
-            if let Ok(p) = cmd.spawn() {
-                let stderr = anstream::stderr()
-                    .is_terminal()
-                    .then(|| anstream::stderr().lock());
-                ActivePager {
-                    primary: stdout,
-                    _secondary: stderr,
-                    pager: Some(p),
+    /// The pager for stdout per `config` and `args`, choosing a command by how long output is
+    ///
+    /// Output is also copied to `--tee`, failing if it can't be created.
+    pub(crate) fn resolve(
+        config: &Config,
+        args: &crate::args::Args,
+    ) -> Result<Self, proc_exit::Exit> {
+        let pager = resolve_pager(config, args);
+        let short_pager = resolve_short_pager(config, args);
+        let tee = crate::tee::Tee::from_args(args).with_code(crate::exit_code::IO)?;
+        Ok(Self::stdout(&pager)
+            .min_lines(min_lines(config))
+            .probe_less(config)
+            .short_pager(short_pager.as_deref())
+            .discard(args.no_output)
+            .tee(tee))
+    }
+
+    /// Write nothing, not even to a pager
+    pub(crate) fn discard(mut self, discard: bool) -> Self {
+        if discard {
+            self.cmd = None;
+            self.short_cmd = None;
+        }
+        self.discard = discard;
+        self
+    }
+
+    /// Run `args` for output shorter than `min_lines`, rather than writing it directly
+    pub(crate) fn short_pager(mut self, args: Option<&str>) -> Self {
+        if self.cmd.is_some() {
+            self.short_cmd = args.and_then(parse);
+        }
+        self
+    }
+
+    /// Only spawn the pager once the output reaches `min_lines` lines, `0` always paging
+    pub(crate) fn min_lines(mut self, min_lines: usize) -> Self {
+        self.min_lines = min_lines;
+        self
+    }
+
+    /// Adjust `less`'s flags for its version, unless disabled by `dive.pager-probe`
+    ///
+    /// Before less 530, `-F` without `-X` clears the screen on exit, so output that fits on one
+    /// screen vanishes.  Newer versions don't need `-X`, so it's dropped from the default `LESS`.
+    /// With an older version and a `LESS` that has `F` but not `X`, output that fits on the
+    /// screen is written directly instead.
+    pub(crate) fn probe_less(mut self, config: &Config) -> Self {
+        let Some(cmd) = &mut self.cmd else {
+            return self;
+        };
+        let program = std::path::PathBuf::from(cmd.get_program());
+        if !is_less(&program) {
+            return self;
+        }
+        if !config.get(&PAGER_PROBE) {
+            log::debug!("not probing less's version, per `dive.pager-probe`");
+            return self;
+        }
+        let Some(version) = less_version(&program) else {
+            return self;
+        };
+        match std::env::var("LESS") {
+            Err(_) => {
+                if FIXED_QUIT_IF_ONE_SCREEN <= version {
+                    cmd.env("LESS", "FR");
                 }
-            } else {
-                ActivePager {
-                    primary: stdout,
-                    _secondary: None,
-                    pager: None,
+            }
+            Ok(flags) => {
+                if version < FIXED_QUIT_IF_ONE_SCREEN && quits_without_no_init(&flags) {
+                    log::debug!(
+                        "less {version} would clear short output with `LESS={flags}`, not paging it"
+                    );
+                    self.buffer_short = true;
                 }
             }
-        } else {
-            ActivePager {
-                primary: stdout,
-                _secondary: None,
-                pager: None,
+        }
+        self
+    }
+
+    /// Have `less` start by searching for `pattern`, so `n` and `N` jump between its matches
+    pub(crate) fn marks(mut self, pattern: Option<&str>) -> Self {
+        let (Some(cmd), Some(pattern)) = (&mut self.cmd, pattern) else {
+            return self;
+        };
+        if !is_less(std::path::Path::new(cmd.get_program())) {
+            log::debug!("not marking hunks for a pager other than less");
+            return self;
+        }
+        cmd.arg(format!("+/{pattern}"));
+        self
+    }
+
+    /// Mark hunks for the pager `sections` names, when that's the pager being run
+    ///
+    /// `ov` is told to start a section at each line matching `pattern`, while `moar` gets
+    /// `SECTION_MARK` written before them, per `section_marks`.
+    pub(crate) fn sections(mut self, sections: PagerSections, pattern: Option<&str>) -> Self {
+        let (Some(cmd), Some(name)) = (&mut self.cmd, sections.program()) else {
+            return self;
+        };
+        let program = std::path::Path::new(cmd.get_program());
+        if program.file_stem() != Some(std::ffi::OsStr::new(name)) {
+            log::debug!(
+                "not marking sections for {}, per `dive.pager-sections = {sections}`",
+                program.display()
+            );
+            return self;
+        }
+        match sections {
+            PagerSections::None => {}
+            PagerSections::Ov => {
+                if let Some(pattern) = pattern {
+                    cmd.arg("--section-delimiter").arg(pattern);
+                }
+            }
+            PagerSections::Moar => self.section_marks = true,
+        }
+        self
+    }
+
+    /// Whether to write `SECTION_MARK` before the first line of each hunk
+    pub(crate) fn section_marks(&self) -> bool {
+        self.section_marks
+    }
+
+    /// Also copy everything written to `tee`
+    pub(crate) fn tee(mut self, tee: Option<crate::tee::Tee>) -> Self {
+        self.tee = tee;
+        self
+    }
+
+    pub(crate) fn start(&mut self) -> ActivePager {
+        let stdout = anstream::stdout().lock();
+        let mut active = ActivePager {
+            primary: stdout,
+            _secondary: None,
+            pager: None,
+            pending: None,
+            tee: self.tee.take(),
+            discard: self.discard.then(std::io::sink),
+        };
+        if let Some(cmd) = self.cmd.take() {
+            if self.buffer_short {
+                self.min_lines = self.min_lines.max(screen_lines());
+            }
+            if self.min_lines == 0 {
+                active.spawn(cmd);
+            } else {
+                let short_cmd = self.short_cmd.take();
+                // Short output may not reach the pager the marks are for
+                let strip_marks = self.section_marks
+                    && short_cmd.as_ref().map(|short| short.get_program())
+                        != Some(cmd.get_program());
+                active.pending = Some(PendingPager {
+                    cmd,
+                    short_cmd,
+                    strip_marks,
+                    buffer: Vec::new(),
+                    lines: 0,
+                    min_lines: self.min_lines,
+                });
             }
         }
+        active
     }
 }
 
@@ -48,11 +282,35 @@ pub(crate) struct ActivePager {
     primary: anstream::AutoStream<std::io::StdoutLock<'static>>,
     _secondary: Option<anstream::AutoStream<std::io::StderrLock<'static>>>,
     pager: Option<std::process::Child>,
+    pending: Option<PendingPager>,
+    tee: Option<crate::tee::Tee>,
+    /// Instead of `primary`, with `--no-output`
+    discard: Option<std::io::Sink>,
+}
+
+/// Output held back until we know it won't fit on the screen
+struct PendingPager {
+    cmd: std::process::Command,
+    short_cmd: Option<std::process::Command>,
+    /// Remove `SECTION_MARK` when the output turns out to be short
+    strip_marks: bool,
+    buffer: Vec<u8>,
+    lines: usize,
+    min_lines: usize,
 }
 
 impl ActivePager {
     pub(crate) fn as_writer(&mut self) -> std::io::Result<&mut dyn std::io::Write> {
-        if let Some(pager) = &mut self.pager {
+        if self.pending.is_none() {
+            self.writer()?;
+        }
+        Ok(self)
+    }
+
+    fn writer(&mut self) -> std::io::Result<&mut dyn std::io::Write> {
+        if let Some(discard) = &mut self.discard {
+            Ok(discard)
+        } else if let Some(pager) = &mut self.pager {
             pager
                 .stdin
                 .as_mut()
@@ -70,14 +328,332 @@ impl ActivePager {
             Ok(&mut self.primary)
         }
     }
+
+    fn spawn(&mut self, mut cmd: std::process::Command) {
+        // should use pager instead of stderr
+        match cmd.spawn() {
+            Ok(p) => {
+                self._secondary = anstream::stderr()
+                    .is_terminal()
+                    .then(|| anstream::stderr().lock());
+                self.pager = Some(p);
+                PAGER_RUNNING.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            Err(err) => {
+                log::debug!("failed to start pager: {}", err);
+            }
+        }
+    }
+}
+
+impl std::io::Write for ActivePager {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.write_output(buf)?;
+        if let Some(tee) = &mut self.tee {
+            // Only what the pager accepted, so retried bytes aren't duplicated
+            tee.write_all(&buf[..written])?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_output()?;
+        if let Some(tee) = &mut self.tee {
+            tee.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl ActivePager {
+    fn write_output(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Some(pending) = &mut self.pending else {
+            return self.writer()?.write(buf);
+        };
+        pending.buffer.extend_from_slice(buf);
+        pending.lines += buf.iter().filter(|b| **b == b'\n').count();
+        if pending.lines < pending.min_lines {
+            return Ok(buf.len());
+        }
+
+        let pending = self.pending.take().expect("checked above");
+        log::trace!("output reached {} lines, paging", pending.lines);
+        self.spawn(pending.cmd);
+        self.writer()?.write_all(&pending.buffer)?;
+        Ok(buf.len())
+    }
+
+    fn flush_output(&mut self) -> std::io::Result<()> {
+        if self.pending.is_some() {
+            // Still deciding whether to page
+            return Ok(());
+        }
+        self.writer()?.flush()
+    }
 }
 
 impl Drop for ActivePager {
     fn drop(&mut self) {
+        if let Some(mut pending) = self.pending.take() {
+            if pending.strip_marks {
+                pending.buffer = strip_section_marks(&pending.buffer);
+            }
+            if let Some(short_cmd) = pending.short_cmd {
+                log::trace!(
+                    "output fit in {} lines, using the short pager",
+                    pending.lines
+                );
+                self.spawn(short_cmd);
+            } else {
+                log::trace!("output fit in {} lines, not paging", pending.lines);
+            }
+            if let Ok(writer) = self.writer() {
+                let _ = writer.write_all(&pending.buffer);
+            }
+        }
+        if let Some(tee) = &mut self.tee {
+            if let Err(err) = std::io::Write::flush(tee) {
+                log::warn!("could not finish writing tee file: {}", err);
+            }
+        }
         if let Some(pager) = &mut self.pager {
             let _ = pager.wait();
+            PAGER_RUNNING.store(false, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+/// Stdout without a pager, like for editors, or nothing with `--no-output`
+pub(crate) fn unpaged(args: &crate::args::Args) -> Box<dyn std::io::Write> {
+    if args.no_output {
+        Box::new(std::io::sink())
+    } else {
+        Box::new(std::io::stdout().lock())
+    }
+}
+
+/// How long output must be before paging, defaulting to the terminal's height
+pub(crate) fn min_lines(config: &Config) -> usize {
+    if let Some(lines) = config.get(&PAGER_MIN_LINES) {
+        return usize::try_from(lines).unwrap_or(0);
+    }
+    screen_lines()
+}
+
+fn screen_lines() -> usize {
+    terminal_size::terminal_size()
+        .map(|(_w, h)| usize::from(h.0))
+        .unwrap_or(0)
+}
+
+pub(crate) const PAGER_MIN_LINES: crate::git2_config::RawField<i64> =
+    crate::git2_config::RawField::<i64>::new("dive.pager-min-lines");
+
+/// Overrides `core.pager` for output that doesn't fit on the screen
+pub(crate) const PAGER_LONG: crate::git2_config::RawField<String> =
+    crate::git2_config::RawField::<String>::new("dive.pager-long");
+
+/// Used for output that fits on the screen, which otherwise isn't paged
+pub(crate) const PAGER_SHORT: crate::git2_config::RawField<String> =
+    crate::git2_config::RawField::<String>::new("dive.pager-short");
+
+/// Which pager to mark hunks for, so it can fold or jump between them
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum PagerSections {
+    #[default]
+    None,
+    Ov,
+    Moar,
+}
+
+impl PagerSections {
+    /// The pager's executable, without any extension
+    fn program(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Ov => Some("ov"),
+            Self::Moar => Some("moar"),
+        }
+    }
+}
+
+impl crate::git2_config::Parseable for PagerSections {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "ov" => Ok(Self::Ov),
+            "moar" => Ok(Self::Moar),
+            _ => anyhow::bail!("unsupported pager `{s}`, expected `none`, `ov`, or `moar`"),
+        }
+    }
+}
+
+impl std::fmt::Display for PagerSections {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.program().unwrap_or("none").fmt(f)
+    }
+}
+
+pub(crate) const PAGER_SECTIONS: crate::git2_config::DefaultField<PagerSections> =
+    crate::git2_config::RawField::<PagerSections>::new("dive.pager-sections").default();
+
+/// A prompt mark (OSC 133), zero-width, which pagers like `moar` jump between
+pub(crate) const SECTION_MARK: &str = "\x1b]133;A\x1b\\";
+
+fn strip_section_marks(buffer: &[u8]) -> Vec<u8> {
+    let mark = SECTION_MARK.as_bytes();
+    let mut stripped = Vec::with_capacity(buffer.len());
+    let mut rest = buffer;
+    while let Some(i) = rest.windows(mark.len()).position(|window| window == mark) {
+        stripped.extend_from_slice(&rest[..i]);
+        rest = &rest[i + mark.len()..];
+    }
+    stripped.extend_from_slice(rest);
+    stripped
+}
+
+pub(crate) const PAGER_PROBE: crate::git2_config::DefaultField<bool> =
+    crate::git2_config::RawField::<bool>::new("dive.pager-probe").default_value(|| true);
+
+/// The first version where `-F` works without `-X`
+const FIXED_QUIT_IF_ONE_SCREEN: u32 = 530;
+
+/// The version of the `less` that `command` runs, for `--diagnostic`
+pub(crate) fn describe_less(command: &str, config: &Config) -> Option<String> {
+    let program = shlex::Shlex::new(command).next()?;
+    let program = resolve_program(&program, std::env::var_os("PATH").as_deref(), |p| {
+        p.is_file()
+    });
+    if !is_less(&program) {
+        return None;
+    }
+    let description = if !config.get(&PAGER_PROBE) {
+        "not probed, per `dive.pager-probe`".to_owned()
+    } else if let Some(version) = less_version(&program) {
+        if version < FIXED_QUIT_IF_ONE_SCREEN {
+            format!("{version}, which needs `-X` with `-F`")
+        } else {
+            version.to_string()
         }
+    } else {
+        "unknown".to_owned()
+    };
+    Some(description)
+}
+
+fn is_less(program: &std::path::Path) -> bool {
+    program.file_stem() == Some(std::ffi::OsStr::new("less"))
+}
+
+/// Whether `LESS` asks to quit on short output without disabling the screen init
+fn quits_without_no_init(flags: &str) -> bool {
+    let short_flags = flags
+        .split_whitespace()
+        .filter(|f| !f.starts_with("--"))
+        .collect::<String>();
+    let long_flags = flags
+        .split_whitespace()
+        .filter(|f| f.starts_with("--"))
+        .collect::<Vec<_>>();
+    let quits = short_flags.contains('F') || long_flags.contains(&"--quit-if-one-screen");
+    let no_init = short_flags.contains('X') || long_flags.contains(&"--no-init");
+    quits && !no_init
+}
+
+/// `less`'s version, cached by its path and modification time
+fn less_version(program: &std::path::Path) -> Option<u32> {
+    let path = if program.components().count() == 1 {
+        find_in_path(
+            program.to_str()?,
+            std::env::var_os("PATH").as_deref()?,
+            |p| p.is_file(),
+        )?
+    } else {
+        program.to_owned()
+    };
+    let modified = std::fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let cache = cache_dir().map(|dir| dir.join("less-version"));
+    if let Some(version) = cache
+        .as_deref()
+        .and_then(|cache| std::fs::read_to_string(cache).ok())
+        .and_then(|cached| read_cached_version(&cached, &path, modified))
+    {
+        log::trace!("less {version} at {} (cached)", path.display());
+        return Some(version);
     }
+
+    let output = std::process::Command::new(&path)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output();
+    let version = match output {
+        Ok(output) => parse_less_version(&String::from_utf8_lossy(&output.stdout)),
+        Err(err) => {
+            log::debug!("could not run `{} --version`: {}", path.display(), err);
+            None
+        }
+    };
+    let Some(version) = version else {
+        log::debug!("unknown version of less at {}", path.display());
+        return None;
+    };
+    log::debug!("less {version} at {}", path.display());
+    if let Some(cache) = cache {
+        let entry = format!("{version}\t{modified}\t{}\n", path.display());
+        let written = std::fs::create_dir_all(cache.parent().expect("cache file is in a dir"))
+            .and_then(|()| std::fs::write(&cache, entry));
+        if let Err(err) = written {
+            log::debug!(
+                "could not cache less's version in {}: {}",
+                cache.display(),
+                err
+            );
+        }
+    }
+    Some(version)
+}
+
+/// `VERSION\tMTIME\tPATH`, if it is for this `less`
+fn read_cached_version(cached: &str, path: &std::path::Path, modified: u64) -> Option<u32> {
+    let mut fields = cached.trim_end_matches('\n').splitn(3, '\t');
+    let version = fields.next()?.parse().ok()?;
+    let cached_modified = fields.next()?.parse::<u64>().ok()?;
+    let cached_path = fields.next()?;
+    (cached_modified == modified && cached_path == path.display().to_string()).then_some(version)
+}
+
+/// The number from `less --version`'s first line, like `less 590 (GNU regular expressions)`
+fn parse_less_version(output: &str) -> Option<u32> {
+    let version = output.lines().next()?.trim().strip_prefix("less ")?;
+    let digits = version
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(version.len());
+    version[..digits].parse().ok()
+}
+
+/// Where to keep what is slow to look up, like `$XDG_CACHE_HOME/git-dive`
+pub(crate) fn cache_dir() -> Option<std::path::PathBuf> {
+    let env_dir = |name| {
+        std::env::var_os(name)
+            .map(std::path::PathBuf::from)
+            .filter(|p| p.is_absolute())
+    };
+    let base = if let Some(dir) = env_dir("XDG_CACHE_HOME") {
+        dir
+    } else if cfg!(windows) {
+        env_dir("LOCALAPPDATA")?
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME")?.join("Library").join("Caches")
+    } else {
+        env_dir("HOME")?.join(".cache")
+    };
+    Some(base.join("git-dive"))
 }
 
 fn parse(args: &str) -> Option<std::process::Command> {
@@ -86,7 +662,9 @@ fn parse(args: &str) -> Option<std::process::Command> {
     if cmd == "cat" {
         return None;
     }
-    let mut cmd = std::process::Command::new(cmd);
+    let program = resolve_program(&cmd, std::env::var_os("PATH").as_deref(), |p| p.is_file());
+    log::trace!("running pager {}", program.display());
+    let mut cmd = std::process::Command::new(program);
     cmd.stdin(std::process::Stdio::piped());
     cmd.args(args);
     cmd.envs(REQUIRED_ENV.iter().copied());
@@ -98,3 +676,240 @@ fn parse(args: &str) -> Option<std::process::Command> {
     );
     Some(cmd)
 }
+
+/// Find `program`, falling back to the tools bundled with git
+///
+/// Git for Windows ships `less` and friends in its `usr/bin` directory which is only on `PATH`
+/// within git's own shell.  If `program` can't be found on `PATH`, look for it next to `git`.
+fn resolve_program(
+    program: &str,
+    path: Option<&std::ffi::OsStr>,
+    is_file: impl Fn(&std::path::Path) -> bool,
+) -> std::path::PathBuf {
+    let program_path = std::path::Path::new(program);
+    if program_path.components().count() != 1 {
+        return program_path.to_owned();
+    }
+    let Some(path) = path else {
+        return program_path.to_owned();
+    };
+
+    if find_in_path(program, path, &is_file).is_some() {
+        return program_path.to_owned();
+    }
+
+    if let Some(git) = find_in_path("git", path, &is_file) {
+        for tool_dir in git_tool_dirs(&git) {
+            let candidate = tool_dir.join(executable_name(program));
+            if is_file(&candidate) {
+                log::debug!("using {} for pager `{}`", candidate.display(), program);
+                return candidate;
+            }
+        }
+    }
+
+    program_path.to_owned()
+}
+
+fn find_in_path(
+    program: &str,
+    path: &std::ffi::OsStr,
+    is_file: impl Fn(&std::path::Path) -> bool,
+) -> Option<std::path::PathBuf> {
+    let program = executable_name(program);
+    std::env::split_paths(path)
+        .map(|dir| dir.join(&program))
+        .find(|candidate| is_file(candidate))
+}
+
+/// Directories where git keeps its helper tools, relative to the `git` executable
+///
+/// Git for Windows installs `git` into either `cmd/` or `mingw64/bin/` with the tools in `usr/bin/`
+/// at the root of the install.
+fn git_tool_dirs(git: &std::path::Path) -> Vec<std::path::PathBuf> {
+    git.ancestors()
+        .skip(2)
+        .take(2)
+        .map(|root| root.join("usr").join("bin"))
+        .collect()
+}
+
+fn executable_name(program: &str) -> String {
+    let suffix = std::env::consts::EXE_SUFFIX;
+    if suffix.is_empty() || program.ends_with(suffix) {
+        program.to_owned()
+    } else {
+        format!("{program}{suffix}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn exists<'p>(paths: &'p [std::path::PathBuf]) -> impl Fn(&std::path::Path) -> bool + 'p {
+        move |p| paths.iter().any(|e| e == p)
+    }
+
+    #[test]
+    fn resolve_program_on_path() {
+        let bin = std::path::Path::new("bin");
+        let path = std::env::join_paths([bin]).unwrap();
+        let files = [bin.join(executable_name("less"))];
+        let actual = resolve_program("less", Some(&path), exists(&files));
+        assert_eq!(actual, std::path::Path::new("less"));
+    }
+
+    #[test]
+    fn resolve_program_explicit_path() {
+        let program = std::path::Path::new("tools").join("less");
+        let actual = resolve_program(program.to_str().unwrap(), None, exists(&[]));
+        assert_eq!(actual, program);
+    }
+
+    #[test]
+    fn resolve_program_from_git_cmd() {
+        let root = std::path::Path::new("Git");
+        let path = std::env::join_paths([root.join("cmd")]).unwrap();
+        let less = root.join("usr").join("bin").join(executable_name("less"));
+        let files = [root.join("cmd").join(executable_name("git")), less.clone()];
+        let actual = resolve_program("less", Some(&path), exists(&files));
+        assert_eq!(actual, less);
+    }
+
+    #[test]
+    fn resolve_program_from_git_mingw() {
+        let root = std::path::Path::new("Git");
+        let git_bin = root.join("mingw64").join("bin");
+        let path = std::env::join_paths([&git_bin]).unwrap();
+        let less = root.join("usr").join("bin").join(executable_name("less"));
+        let files = [git_bin.join(executable_name("git")), less.clone()];
+        let actual = resolve_program("less", Some(&path), exists(&files));
+        assert_eq!(actual, less);
+    }
+
+    #[test]
+    fn less_versions() {
+        assert_eq!(
+            parse_less_version("less 590 (GNU regular expressions)\nCopyright (C) 1984-2021\n"),
+            Some(590)
+        );
+        assert_eq!(
+            parse_less_version("less 643x (PCRE2 regular expressions)"),
+            Some(643)
+        );
+        assert_eq!(parse_less_version("less 487"), Some(487));
+        assert_eq!(parse_less_version("most 5.1.0"), None);
+        assert_eq!(parse_less_version(""), None);
+    }
+
+    #[test]
+    fn cached_less_version() {
+        let path = std::path::Path::new("/usr/bin/less");
+        let cached = "590\t1700000000\t/usr/bin/less\n";
+        assert_eq!(read_cached_version(cached, path, 1_700_000_000), Some(590));
+        assert_eq!(read_cached_version(cached, path, 1_700_000_001), None);
+        assert_eq!(
+            read_cached_version(cached, std::path::Path::new("/bin/less"), 1_700_000_000),
+            None
+        );
+        assert_eq!(read_cached_version("garbage", path, 1_700_000_000), None);
+    }
+
+    #[test]
+    fn less_flags() {
+        assert!(quits_without_no_init("FR"));
+        assert!(quits_without_no_init("-FR"));
+        assert!(quits_without_no_init("--quit-if-one-screen -R"));
+        assert!(!quits_without_no_init("FRX"));
+        assert!(!quits_without_no_init("-F --no-init"));
+        assert!(!quits_without_no_init("R"));
+        assert!(!quits_without_no_init(""));
+    }
+
+    #[test]
+    fn marks_for_less() {
+        let args = |pager: &str| {
+            let pager = Pager {
+                cmd: parse(pager),
+                ..Pager::stdout("cat")
+            }
+            .marks(Some("^[0-9A-Za-z]"));
+            let cmd = pager.cmd.unwrap();
+            cmd.get_args()
+                .map(|arg| arg.to_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(args("less -S"), ["-S", "+/^[0-9A-Za-z]"]);
+        assert_eq!(args("/usr/bin/less"), ["+/^[0-9A-Za-z]"]);
+        assert_eq!(args("most -s"), ["-s"]);
+    }
+
+    #[test]
+    fn sections_for_their_pager() {
+        let pager = |pager: &str, sections| {
+            Pager {
+                cmd: parse(pager),
+                ..Pager::stdout("cat")
+            }
+            .sections(sections, Some("^[0-9A-Za-z]"))
+        };
+        let args = |pager: Pager| {
+            pager
+                .cmd
+                .unwrap()
+                .get_args()
+                .map(|arg| arg.to_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let ov = pager("ov -F", PagerSections::Ov);
+        assert!(!ov.section_marks());
+        assert_eq!(args(ov), ["-F", "--section-delimiter", "^[0-9A-Za-z]"]);
+        let moar = pager("/usr/bin/moar", PagerSections::Moar);
+        assert!(moar.section_marks());
+        assert!(args(moar).is_empty());
+
+        // Not for other pagers
+        let less = pager("less", PagerSections::Ov);
+        assert!(!less.section_marks());
+        assert!(args(less).is_empty());
+        assert!(!pager("ov", PagerSections::Moar).section_marks());
+        assert!(!pager("moar", PagerSections::None).section_marks());
+    }
+
+    #[test]
+    fn strips_section_marks() {
+        let marked = format!("{SECTION_MARK}a 1\n  2\n{SECTION_MARK}b 3\n");
+        assert_eq!(strip_section_marks(marked.as_bytes()), b"a 1\n  2\nb 3\n");
+        assert_eq!(strip_section_marks(b"plain\n"), b"plain\n");
+    }
+
+    #[test]
+    fn pager_env() {
+        let cmd = parse("less -S").unwrap();
+        let envs = cmd
+            .get_envs()
+            .map(|(k, v)| (k.to_str().unwrap(), v.and_then(|v| v.to_str())))
+            .collect::<Vec<_>>();
+        for (key, value) in &envs {
+            assert!(
+                REQUIRED_ENV
+                    .iter()
+                    .chain(DEFAULT_ENV)
+                    .any(|(k, v)| k == key && Some(*v) == *value),
+                "unexpected {key}={value:?}"
+            );
+        }
+        assert!(envs.contains(&("GIT_PAGER_IN_USE", Some("true"))));
+    }
+
+    #[test]
+    fn resolve_program_missing() {
+        let bin = std::path::Path::new("bin");
+        let path = std::env::join_paths([bin]).unwrap();
+        let files = [bin.join(executable_name("git"))];
+        let actual = resolve_program("less", Some(&path), exists(&files));
+        assert_eq!(actual, std::path::Path::new("less"));
+    }
+}
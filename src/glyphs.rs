@@ -0,0 +1,136 @@
+//! The characters the gutter is drawn with, falling back to ASCII where UTF-8 won't show
+
+use crate::git2_config::Config;
+use crate::git2_config::RawField;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Glyphs {
+    /// Between the gutter and the code, one column wide
+    pub(crate) grid: String,
+    /// In the grid column between hunks, with the `separator` style component
+    pub(crate) hunk_sep: &'static str,
+    /// Between hunks, with the `separator` style component but not `grid`
+    pub(crate) hunk_rule: &'static str,
+    /// A column carrying on from the line above, like in the grid beside lines hidden by
+    /// `--squeeze-unchanged`
+    pub(crate) continued: &'static str,
+    /// A thin margin colored by `--color-by`
+    pub(crate) stripe: &'static str,
+    /// Where text was cut short
+    pub(crate) ellipsis: &'static str,
+    /// Before the path of a hunk from another file
+    pub(crate) origin_path: &'static str,
+    /// After the first line of a hunk that was elsewhere in the file
+    pub(crate) moved: &'static str,
+    /// Before the other side of a revert, with `--resolve-reverts`
+    pub(crate) revert: &'static str,
+    /// Before the other side of a cherry-pick, with `--resolve-reverts`
+    pub(crate) cherry_pick: &'static str,
+    /// Between the two sides of `--compare`
+    pub(crate) split: &'static str,
+}
+
+impl Glyphs {
+    pub(crate) fn utf8() -> Self {
+        Self {
+            grid: "│".to_owned(),
+            hunk_sep: "┆",
+            hunk_rule: "┄",
+            continued: "⋮",
+            stripe: "▎",
+            ellipsis: "…",
+            origin_path: "  ← ",
+            moved: "  ↷ moved",
+            revert: "  ↩ ",
+            cherry_pick: "  ⇢ ",
+            split: " ┃ ",
+        }
+    }
+
+    pub(crate) fn ascii() -> Self {
+        Self {
+            grid: "|".to_owned(),
+            hunk_sep: ":",
+            hunk_rule: "-",
+            continued: ":",
+            stripe: "|",
+            ellipsis: "...",
+            origin_path: "  <- ",
+            moved: "  ~> moved",
+            revert: "  <~ ",
+            cherry_pick: "  => ",
+            split: " | ",
+        }
+    }
+
+    /// ASCII per `dive.ascii`, else when the output encoding or the locale isn't UTF-8, with
+    /// `dive.grid-char` for the grid
+    pub(crate) fn resolve(config: &Config) -> Self {
+        let ascii = config.get(&ASCII).unwrap_or_else(|| {
+            crate::encoding::output_encoding(config).is_some()
+                || (!crate::render_test::enabled() && !utf8_locale())
+        });
+        let mut glyphs = if ascii { Self::ascii() } else { Self::utf8() };
+        if let Some(grid) = config.get(&GRID_CHAR) {
+            if crate::unicode::display_width(&grid) == 1 {
+                glyphs.grid = grid;
+            } else {
+                log::warn!("ignoring `dive.grid-char = {grid}`, which isn't one column wide");
+            }
+        }
+        log::debug!("drawing with {}", if ascii { "ASCII" } else { "UTF-8" });
+        glyphs
+    }
+}
+
+/// Whether the locale, like `LANG`, is UTF-8, assuming so when it isn't set
+fn utf8_locale() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .find(|value| !value.is_empty());
+    let Some(locale) = locale else {
+        return true;
+    };
+    is_utf8_locale(&locale.to_string_lossy())
+}
+
+/// Like `en_US.UTF-8` or `C.utf8`, rather than `C` or `en_US.ISO-8859-1`
+fn is_utf8_locale(locale: &str) -> bool {
+    let codeset = locale
+        .split_once('.')
+        .map(|(_, codeset)| codeset)
+        .unwrap_or_default();
+    let codeset = codeset.split_once('@').map(|(c, _)| c).unwrap_or(codeset);
+    codeset.eq_ignore_ascii_case("utf-8") || codeset.eq_ignore_ascii_case("utf8")
+}
+
+pub(crate) const ASCII: RawField<bool> = RawField::<bool>::new("dive.ascii");
+pub(crate) const GRID_CHAR: RawField<String> = RawField::<String>::new("dive.grid-char");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn utf8_locales() {
+        assert!(is_utf8_locale("en_US.UTF-8"));
+        assert!(is_utf8_locale("C.utf8"));
+        assert!(is_utf8_locale("de_DE.UTF-8@euro"));
+        assert!(!is_utf8_locale("C"));
+        assert!(!is_utf8_locale("POSIX"));
+        assert!(!is_utf8_locale("en_US.ISO-8859-1"));
+    }
+
+    #[test]
+    fn same_widths() {
+        let (utf8, ascii) = (Glyphs::utf8(), Glyphs::ascii());
+        let width = crate::unicode::display_width;
+        // Columns that are blank on other lines stay aligned
+        assert_eq!(width(&utf8.grid), width(&ascii.grid));
+        assert_eq!(width(utf8.hunk_sep), width(ascii.hunk_sep));
+        assert_eq!(width(utf8.continued), width(ascii.continued));
+        assert_eq!(width(utf8.stripe), width(ascii.stripe));
+        assert_eq!(width(utf8.split), width(ascii.split));
+    }
+}
@@ -0,0 +1,161 @@
+//! Spelling commit IDs for people, per `dive.hash-style`, `dive.hash-case`, and `dive.hash-prefix`
+//!
+//! Only human output goes through [`HashFormat`].  `--format json` and `--format quickfix` keep
+//! git's spelling so IDs can be handed back to git, as do hints naming revisions to pass.
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// How much of a commit ID to show
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum HashStyle {
+    /// Abbreviated like git, per `core.abbrev`
+    #[default]
+    Short,
+    Long,
+}
+
+impl Parseable for HashStyle {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "short" => Ok(Self::Short),
+            "long" => Ok(Self::Long),
+            _ => anyhow::bail!("unsupported hash style `{s}`, expected `short` or `long`"),
+        }
+    }
+}
+
+impl std::fmt::Display for HashStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Short => "short",
+            Self::Long => "long",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) enum HashCase {
+    #[default]
+    Lower,
+    Upper,
+}
+
+impl Parseable for HashCase {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            _ => anyhow::bail!("unsupported hash case `{s}`, expected `lower` or `upper`"),
+        }
+    }
+}
+
+impl std::fmt::Display for HashCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct HashFormat {
+    style: HashStyle,
+    case: HashCase,
+    prefix: String,
+}
+
+impl HashFormat {
+    pub(crate) fn resolve(config: &Config) -> Self {
+        Self {
+            style: config.get(&HASH_STYLE),
+            case: config.get(&HASH_CASE),
+            prefix: crate::unicode::sanitize(&config.get(&HASH_PREFIX)),
+        }
+    }
+
+    /// `id` as shown to people, `short` being git's abbreviation of it
+    pub(crate) fn show(&self, id: git2::Oid, short: &str) -> String {
+        let hex = match self.style {
+            HashStyle::Short => short.to_owned(),
+            HashStyle::Long => id.to_string(),
+        };
+        let hex = match self.case {
+            HashCase::Lower => hex,
+            HashCase::Upper => hex.to_ascii_uppercase(),
+        };
+        format!("{}{hex}", self.prefix)
+    }
+
+    /// `hash`, as spelled by [`HashFormat::show`], cut to `len` digits for narrow gutters
+    pub(crate) fn truncate<'h>(&self, hash: &'h str, len: usize) -> &'h str {
+        let digits = hash.strip_prefix(self.prefix.as_str()).unwrap_or(hash);
+        let cut = hash.len() - digits.len() + len.min(digits.len());
+        &hash[..cut]
+    }
+}
+
+pub(crate) const HASH_STYLE: DefaultField<HashStyle> =
+    RawField::<HashStyle>::new("dive.hash-style").default();
+pub(crate) const HASH_CASE: DefaultField<HashCase> =
+    RawField::<HashCase>::new("dive.hash-case").default();
+pub(crate) const HASH_PREFIX: DefaultField<String> =
+    RawField::<String>::new("dive.hash-prefix").default();
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ID: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn format(style: HashStyle, case: HashCase, prefix: &str) -> HashFormat {
+        HashFormat {
+            style,
+            case,
+            prefix: prefix.to_owned(),
+        }
+    }
+
+    #[test]
+    fn spells_ids() {
+        let id = git2::Oid::from_str(ID).unwrap();
+        let show = |format: HashFormat| format.show(id, &ID[..7]);
+        assert_eq!(show(HashFormat::default()), "0123456");
+        assert_eq!(
+            show(format(HashStyle::Long, HashCase::Lower, "")),
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+        assert_eq!(
+            show(format(HashStyle::Short, HashCase::Upper, "#")),
+            "#0123456"
+        );
+        assert_eq!(
+            show(format(HashStyle::Long, HashCase::Upper, "")),
+            "0123456789ABCDEF0123456789ABCDEF01234567"
+        );
+    }
+
+    #[test]
+    fn truncate_keeps_prefix() {
+        let format = format(HashStyle::Long, HashCase::Lower, "#");
+        assert_eq!(format.truncate("#0123456789", 6), "#012345");
+        assert_eq!(format.truncate("#0123", 6), "#0123");
+        // Placeholders aren't prefixed
+        assert_eq!(format.truncate("00000000", 6), "000000");
+        assert_eq!(HashFormat::default().truncate("0123456", 6), "012345");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(HashStyle::parse("long").unwrap(), HashStyle::Long);
+        assert!(HashStyle::parse("full").is_err());
+        assert_eq!(HashCase::parse("upper").unwrap(), HashCase::Upper);
+        assert!(HashCase::parse("UPPER").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Regions of a file between markers like `// dive:ignore-start` and `// dive:ignore-end`
+//!
+//! These are left out of `--stat` and shown with a dimmed gutter, for license headers and
+//! generated tables inside hand-written files.  Markers are found by substring, whatever the
+//! comment syntax, and count as part of the region.
+
+use crate::git2_config::Config;
+use crate::git2_config::MultiField;
+use crate::git2_config::Parseable;
+
+/// A `dive.ignore-markers` entry, `START END`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct MarkerPair {
+    start: String,
+    end: String,
+}
+
+impl Default for MarkerPair {
+    fn default() -> Self {
+        Self {
+            start: "dive:ignore-start".to_owned(),
+            end: "dive:ignore-end".to_owned(),
+        }
+    }
+}
+
+impl Parseable for MarkerPair {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let mut words = s.split_whitespace();
+        let (Some(start), Some(end), None) = (words.next(), words.next(), words.next()) else {
+            anyhow::bail!("expected `START END`");
+        };
+        Ok(Self {
+            start: start.to_owned(),
+            end: end.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for MarkerPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.start, self.end)
+    }
+}
+
+/// Indexed by line, whether it is in an ignored region of `file`, per `dive.ignore-markers`
+///
+/// Unbalanced markers are warned about, as `path`, and a region that is never ended isn't
+/// ignored.
+pub(crate) fn excluded_lines(path: &str, file: &str, config: &Config) -> Vec<bool> {
+    let mut pairs = config.get(&IGNORE_MARKERS);
+    if pairs.is_empty() {
+        pairs.push(MarkerPair::default());
+    }
+    let (excluded, unbalanced) = find_regions(file, &pairs);
+    for unbalanced in unbalanced {
+        log::warn!("{path}:{unbalanced}");
+    }
+    let count = excluded.iter().filter(|e| **e).count();
+    if count != 0 {
+        log::debug!("ignoring {count} lines between markers");
+    }
+    excluded
+}
+
+/// A marker without its other half
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Unbalanced {
+    /// 1-based
+    Start {
+        line: usize,
+        end: String,
+    },
+    End {
+        line: usize,
+        start: String,
+    },
+}
+
+impl std::fmt::Display for Unbalanced {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Start { line, end } => {
+                write!(
+                    f,
+                    "{line}: never ended by `{end}`; not ignoring the lines after it"
+                )
+            }
+            Self::End { line, start } => write!(f, "{line}: not started by `{start}`"),
+        }
+    }
+}
+
+fn find_regions(file: &str, pairs: &[MarkerPair]) -> (Vec<bool>, Vec<Unbalanced>) {
+    let lines = file.lines().collect::<Vec<_>>();
+    let mut excluded = vec![false; lines.len()];
+    let mut unbalanced = Vec::new();
+    // The line and pair of the region being ignored
+    let mut open: Option<(usize, &MarkerPair)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((start, pair)) = open {
+            if line.contains(&pair.end) {
+                excluded[start..=i].fill(true);
+                open = None;
+            }
+        } else if let Some((at, pair)) = pairs
+            .iter()
+            .find_map(|pair| line.find(&pair.start).map(|at| (at, pair)))
+        {
+            if line[at + pair.start.len()..].contains(&pair.end) {
+                // A one-line region
+                excluded[i] = true;
+            } else {
+                open = Some((i, pair));
+            }
+        } else if let Some(pair) = pairs.iter().find(|pair| line.contains(&pair.end)) {
+            unbalanced.push(Unbalanced::End {
+                line: i + 1,
+                start: pair.start.clone(),
+            });
+        }
+    }
+    if let Some((start, pair)) = open {
+        unbalanced.push(Unbalanced::Start {
+            line: start + 1,
+            end: pair.end.clone(),
+        });
+    }
+    (excluded, unbalanced)
+}
+
+pub(crate) const IGNORE_MARKERS: MultiField<MarkerPair> =
+    MultiField::<MarkerPair>::new("dive.ignore-markers");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn regions(file: &str) -> (Vec<usize>, Vec<Unbalanced>) {
+        let (excluded, unbalanced) = find_regions(file, &[MarkerPair::default()]);
+        let lines = excluded
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| **e)
+            .map(|(i, _)| i + 1)
+            .collect();
+        (lines, unbalanced)
+    }
+
+    #[test]
+    fn markers_are_in_the_region() {
+        let file = "\
+fn main() {}
+// dive:ignore-start
+table
+# dive:ignore-end
+fn other() {}
+";
+        assert_eq!(regions(file), (vec![2, 3, 4], vec![]));
+        assert_eq!(
+            regions("code /* dive:ignore-start */ x /* dive:ignore-end */\nmore\n"),
+            (vec![1], vec![])
+        );
+    }
+
+    #[test]
+    fn unbalanced_markers_are_reported() {
+        let file = "\
+// dive:ignore-end
+one
+// dive:ignore-start
+two
+";
+        assert_eq!(
+            regions(file),
+            (
+                vec![],
+                vec![
+                    Unbalanced::End {
+                        line: 1,
+                        start: "dive:ignore-start".to_owned()
+                    },
+                    Unbalanced::Start {
+                        line: 3,
+                        end: "dive:ignore-end".to_owned()
+                    },
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn configured_pairs() {
+        let pairs = [
+            MarkerPair::parse("BEGIN-GENERATED END-GENERATED").unwrap(),
+            MarkerPair::default(),
+        ];
+        let (excluded, unbalanced) = find_regions("BEGIN-GENERATED\nx\nEND-GENERATED\ny\n", &pairs);
+        assert_eq!(excluded, [true, true, true, false]);
+        assert_eq!(unbalanced, []);
+        assert!(MarkerPair::parse("only-start").is_err());
+        assert!(MarkerPair::parse("a b c").is_err());
+    }
+}
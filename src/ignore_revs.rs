@@ -0,0 +1,211 @@
+use anyhow::Context as _;
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// Revisions to look past when assigning blame
+#[derive(Default, Debug)]
+pub(crate) struct IgnoreRevs {
+    revs: std::collections::BTreeSet<git2::Oid>,
+}
+
+impl IgnoreRevs {
+    /// Load `blame.ignoreRevsFile`, `--ignore-revs-file`, and `--ignore-rev`
+    ///
+    /// Without `blame.ignoreRevsFile`, or with `dive.merge-ignore-revs`, a top-level
+    /// `.git-blame-ignore-revs` is loaded as if configured, unless `dive.auto-ignore-revs` is
+    /// `false`.
+    pub(crate) fn load(
+        repo: &git2::Repository,
+        config: &Config,
+        args: &crate::args::Args,
+    ) -> anyhow::Result<Self> {
+        let mut files = Vec::new();
+        if let Some(path) = config.get(&GLOBAL_IGNORE_REVS_FILE) {
+            // Shared across repos, so most revisions won't exist in this one
+            files.push((expand_home(path), Unresolved::Skip));
+        }
+        let configured = config.get(&IGNORE_REVS_FILE).map(|path| {
+            // Like git, relative paths are relative to the top-level of the worktree
+            repo.workdir()
+                .map(|workdir| workdir.join(&path))
+                .unwrap_or(path)
+        });
+        if config.get(&AUTO_IGNORE_REVS) && (configured.is_none() || config.get(&MERGE_IGNORE_REVS))
+        {
+            let conventional = repo
+                .workdir()
+                .map(|workdir| workdir.join(CONVENTIONAL_FILE))
+                .filter(|path| path.is_file() && configured.as_ref() != Some(path));
+            if let Some(path) = conventional {
+                log::debug!("ignoring revisions from {}", path.display());
+                files.push((path, Unresolved::Warn));
+            }
+        }
+        if let Some(path) = configured {
+            files.push((path, Unresolved::Warn));
+        }
+        for path in &args.ignore_revs_file {
+            if path.as_os_str().is_empty() {
+                files.clear();
+            } else {
+                files.push((path.clone(), Unresolved::Warn));
+            }
+        }
+
+        let mut ignore = Self::default();
+        for (path, unresolved) in &files {
+            ignore.add_file(repo, path, *unresolved)?;
+        }
+        for rev in &args.ignore_rev {
+            ignore.add_rev(repo, rev)?;
+        }
+        Ok(ignore)
+    }
+
+    pub(crate) fn add_rev(&mut self, repo: &git2::Repository, rev: &str) -> anyhow::Result<()> {
+        let id = resolve_commit(repo, rev)
+            .with_context(|| format!("Could not resolve ignored revision `{rev}`"))?;
+        self.revs.insert(id);
+        Ok(())
+    }
+
+    /// Add revisions from a file with one revision per line, `#` starting a comment
+    pub(crate) fn add_file(
+        &mut self,
+        repo: &git2::Repository,
+        path: &std::path::Path,
+        unresolved: Unresolved,
+    ) -> anyhow::Result<()> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read ignored revisions from {}", path.display()))?;
+        for (line_num, rev) in parse_revs(&content) {
+            match resolve_commit(repo, rev) {
+                Ok(id) => {
+                    self.revs.insert(id);
+                }
+                Err(err) if unresolved == Unresolved::Skip => {
+                    log::trace!(
+                        "{}:{}: skipping `{}`: {}",
+                        path.display(),
+                        line_num,
+                        rev,
+                        err
+                    );
+                }
+                Err(err) => {
+                    log::warn!(
+                        "{}:{}: skipping `{}`: {}",
+                        path.display(),
+                        line_num,
+                        rev,
+                        err
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn contains(&self, id: git2::Oid) -> bool {
+        self.revs.contains(&id)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.revs.is_empty()
+    }
+}
+
+/// What to do with a revision that isn't in the repo
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Unresolved {
+    Warn,
+    Skip,
+}
+
+/// The file GitHub and many repos use for `blame.ignoreRevsFile`
+const CONVENTIONAL_FILE: &str = ".git-blame-ignore-revs";
+
+/// Expand a leading `~` to the home directory, like `core.excludesFile`
+pub(crate) fn expand_home(path: std::path::PathBuf) -> std::path::PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path;
+    };
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match home {
+        Some(home) => std::path::Path::new(&home).join(rest),
+        None => path,
+    }
+}
+
+/// The revisions in an ignore-revs file, with their line numbers
+fn parse_revs(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.lines().enumerate().filter_map(|(i, line)| {
+        let line = line.split_once('#').map(|(l, _)| l).unwrap_or(line);
+        let line = line.trim();
+        (!line.is_empty()).then_some((i + 1, line))
+    })
+}
+
+fn resolve_commit(repo: &git2::Repository, rev: &str) -> anyhow::Result<git2::Oid> {
+    let obj = crate::rev::revparse_single(repo, rev)?;
+    let commit = crate::rev::peel_to_commit(&obj, rev)?;
+    Ok(commit.id())
+}
+
+pub(crate) const IGNORE_REVS_FILE: RawField<std::path::PathBuf> =
+    RawField::<std::path::PathBuf>::new("blame.ignoreRevsFile");
+
+pub(crate) const AUTO_IGNORE_REVS: DefaultField<bool> =
+    RawField::<bool>::new("dive.auto-ignore-revs").default_value(|| true);
+
+/// Also load [`CONVENTIONAL_FILE`] when `blame.ignoreRevsFile` is set
+pub(crate) const MERGE_IGNORE_REVS: DefaultField<bool> =
+    RawField::<bool>::new("dive.merge-ignore-revs").default();
+
+pub(crate) const GLOBAL_IGNORE_REVS_FILE: RawField<std::path::PathBuf> =
+    RawField::<std::path::PathBuf>::new("dive.global-ignore-revs-file");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expand_home_only_leading_tilde() {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+        if let Some(home) = home {
+            assert_eq!(
+                expand_home("~/revs".into()),
+                std::path::Path::new(&home).join("revs")
+            );
+        }
+        assert_eq!(
+            expand_home("a/~/revs".into()),
+            std::path::Path::new("a/~/revs")
+        );
+        assert_eq!(
+            expand_home("~user/revs".into()),
+            std::path::Path::new("~user/revs")
+        );
+    }
+
+    #[test]
+    fn parse_skips_comments_and_blanks() {
+        let content = "\
+# Formatting
+1111111111111111111111111111111111111111
+
+2222222222222222222222222222222222222222  # rustfmt
+   # indented comment
+";
+        let actual = parse_revs(content).collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            [
+                (2, "1111111111111111111111111111111111111111"),
+                (4, "2222222222222222222222222222222222222222")
+            ]
+        );
+    }
+}
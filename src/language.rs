@@ -0,0 +1,301 @@
+//! Picking the syntax to highlight a file as
+//!
+//! Each source of hints is tried in turn, the first naming a known syntax winning:
+//! 1. `--language`
+//! 2. The `dive-language` gitattribute
+//! 3. `dive.syntax-for-extension`, by the file's name or extension
+//! 4. The `diff=` driver from gitattributes, like `diff=python`
+//! 5. The file's name or extension
+//! 6. Its first line, like a `#!` or a modeline
+//!
+//! falling back to plain text.
+
+use syntect::parsing::SyntaxReference;
+use syntect::parsing::SyntaxSet;
+
+use crate::git2_config::Field as _;
+use crate::git2_config::MultiField;
+use crate::git2_config::Parseable;
+
+/// What is known about a file for picking its syntax
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Hints<'a> {
+    /// Per `--language`
+    pub(crate) language: Option<&'a str>,
+    /// Per the `dive-language` attribute, already checked to be known
+    pub(crate) attribute: Option<&'a str>,
+    /// Per `dive.syntax-for-extension`, lowest priority first
+    pub(crate) mappings: &'a [ExtensionSyntax],
+    /// The `diff=` attribute's value
+    pub(crate) diff_driver: Option<&'a str>,
+    pub(crate) path: &'a std::path::Path,
+    pub(crate) first_line: &'a str,
+}
+
+/// Which hint a syntax was picked by
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Source {
+    Language,
+    Attribute,
+    Config,
+    DiffDriver,
+    Extension,
+    FirstLine,
+    PlainText,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match self {
+            Self::Language => "`--language`",
+            Self::Attribute => "the `dive-language` gitattribute",
+            Self::Config => "`dive.syntax-for-extension`",
+            Self::DiffDriver => "the `diff` gitattribute",
+            Self::Extension => "the file name",
+            Self::FirstLine => "the first line",
+            Self::PlainText => "default",
+        };
+        source.fmt(f)
+    }
+}
+
+/// The syntax to highlight `hints.path` as, and which hint picked it
+///
+/// An unknown `--language` is an error, while other hints naming an unknown syntax are skipped.
+pub(crate) fn resolve<'s>(
+    syntax_set: &'s SyntaxSet,
+    hints: &Hints<'_>,
+) -> anyhow::Result<(&'s SyntaxReference, Source)> {
+    if let Some(language) = hints.language {
+        let syntax = syntax_set.find_syntax_by_token(language).ok_or_else(|| {
+            anyhow::format_err!("unknown language `{language}`; see `--list-languages`")
+        })?;
+        return Ok((syntax, Source::Language));
+    }
+    if let Some(syntax) = hints
+        .attribute
+        .and_then(|language| syntax_set.find_syntax_by_token(language))
+    {
+        return Ok((syntax, Source::Attribute));
+    }
+
+    let file_name = hints
+        .path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let extension = hints
+        .path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    // Later entries, like from the repo's config rather than the global one, win
+    if let Some(mapping) = hints.mappings.iter().rev().find(|mapping| {
+        mapping.extension == file_name || (!extension.is_empty() && mapping.extension == extension)
+    }) {
+        if let Some(syntax) = syntax_set.find_syntax_by_token(&mapping.syntax) {
+            return Ok((syntax, Source::Config));
+        }
+        log::warn!(
+            "ignoring `{} = {mapping}`, which isn't a known language",
+            SYNTAX_FOR_EXTENSION.name()
+        );
+    }
+
+    if let Some(driver) = hints.diff_driver {
+        // git's built-in drivers mostly share names with a syntax or its extension
+        let token = match driver {
+            "golang" => "go",
+            "csharp" => "cs",
+            "objc" => "m",
+            driver => driver,
+        };
+        if let Some(syntax) = syntax_set.find_syntax_by_token(token) {
+            return Ok((syntax, Source::DiffDriver));
+        }
+        log::debug!("`diff={driver}` isn't a known language");
+    }
+
+    if let Some(syntax) = syntax_set
+        .find_syntax_by_extension(file_name)
+        .or_else(|| syntax_set.find_syntax_by_extension(extension))
+    {
+        return Ok((syntax, Source::Extension));
+    }
+
+    if let Some(syntax) = syntax_set.find_syntax_by_first_line(hints.first_line) {
+        return Ok((syntax, Source::FirstLine));
+    }
+
+    Ok((syntax_set.find_syntax_plain_text(), Source::PlainText))
+}
+
+/// A `dive.syntax-for-extension` entry, `EXT=LANGUAGE`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ExtensionSyntax {
+    /// Without the `.`, or a whole file name like `Jenkinsfile`
+    extension: String,
+    syntax: String,
+}
+
+impl Parseable for ExtensionSyntax {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let (extension, syntax) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::format_err!("expected `EXT=LANGUAGE`"))?;
+        let (extension, syntax) = (extension.trim().trim_start_matches('.'), syntax.trim());
+        if extension.is_empty() || syntax.is_empty() {
+            anyhow::bail!("expected `EXT=LANGUAGE`");
+        }
+        Ok(Self {
+            extension: extension.to_owned(),
+            syntax: syntax.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for ExtensionSyntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.extension, self.syntax)
+    }
+}
+
+pub(crate) const SYNTAX_FOR_EXTENSION: MultiField<ExtensionSyntax> =
+    MultiField::<ExtensionSyntax>::new("dive.syntax-for-extension");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resolved(hints: &Hints<'_>) -> (String, Source) {
+        let syntax_set = crate::assets::load_syntaxes();
+        let (syntax, source) = resolve(&syntax_set, hints).unwrap();
+        (syntax.name.clone(), source)
+    }
+
+    fn for_path(path: &str) -> Hints<'_> {
+        Hints {
+            language: None,
+            attribute: None,
+            mappings: &[],
+            diff_driver: None,
+            path: path.as_ref(),
+            first_line: "",
+        }
+    }
+
+    fn mappings(entries: &[&str]) -> Vec<ExtensionSyntax> {
+        entries
+            .iter()
+            .map(|entry| ExtensionSyntax::parse(entry).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn language_over_config() {
+        let mappings = mappings(&["rs=Python"]);
+        let hints = Hints {
+            language: Some("ruby"),
+            mappings: &mappings,
+            ..for_path("lib.rs")
+        };
+        assert_eq!(resolved(&hints), ("Ruby".to_owned(), Source::Language));
+    }
+
+    #[test]
+    fn attribute_over_config() {
+        let mappings = mappings(&["rs=Python"]);
+        let hints = Hints {
+            attribute: Some("ruby"),
+            mappings: &mappings,
+            ..for_path("lib.rs")
+        };
+        assert_eq!(resolved(&hints), ("Ruby".to_owned(), Source::Attribute));
+    }
+
+    #[test]
+    fn config_over_diff_driver() {
+        let mappings = mappings(&["patch=Python"]);
+        let hints = Hints {
+            mappings: &mappings,
+            diff_driver: Some("ruby"),
+            ..for_path("fix.patch")
+        };
+        assert_eq!(resolved(&hints), ("Python".to_owned(), Source::Config));
+    }
+
+    #[test]
+    fn diff_driver_over_extension() {
+        let hints = Hints {
+            diff_driver: Some("python"),
+            ..for_path("fix.patch")
+        };
+        assert_eq!(resolved(&hints), ("Python".to_owned(), Source::DiffDriver));
+        let hints = Hints {
+            diff_driver: Some("golang"),
+            ..for_path("fix.patch")
+        };
+        assert_eq!(resolved(&hints), ("Go".to_owned(), Source::DiffDriver));
+    }
+
+    #[test]
+    fn extension_over_first_line() {
+        let hints = Hints {
+            first_line: "#!/usr/bin/env python",
+            ..for_path("build.rs")
+        };
+        assert_eq!(resolved(&hints), ("Rust".to_owned(), Source::Extension));
+    }
+
+    #[test]
+    fn first_line_over_plain_text() {
+        let hints = Hints {
+            first_line: "#!/usr/bin/env python",
+            ..for_path("configure")
+        };
+        assert_eq!(resolved(&hints), ("Python".to_owned(), Source::FirstLine));
+        let hints = Hints {
+            ..for_path("configure")
+        };
+        assert_eq!(
+            resolved(&hints),
+            ("Plain Text".to_owned(), Source::PlainText)
+        );
+    }
+
+    #[test]
+    fn unknown_hints_are_skipped() {
+        let mappings = mappings(&["rs=Nonsense"]);
+        let hints = Hints {
+            mappings: &mappings,
+            diff_driver: Some("astextplain"),
+            ..for_path("lib.rs")
+        };
+        assert_eq!(resolved(&hints), ("Rust".to_owned(), Source::Extension));
+
+        let syntax_set = crate::assets::load_syntaxes();
+        let hints = Hints {
+            language: Some("nonsense"),
+            ..for_path("")
+        };
+        assert!(resolve(&syntax_set, &hints).is_err());
+    }
+
+    #[test]
+    fn mappings_by_name_and_last_wins() {
+        let mappings = mappings(&["Jenkinsfile=Groovy", ".tpl=Ruby", "tpl=HTML"]);
+        let hints = Hints {
+            mappings: &mappings,
+            ..for_path("ci/Jenkinsfile")
+        };
+        assert_eq!(resolved(&hints), ("Groovy".to_owned(), Source::Config));
+        let hints = Hints {
+            mappings: &mappings,
+            ..for_path("page.tpl")
+        };
+        assert_eq!(resolved(&hints), ("HTML".to_owned(), Source::Config));
+        assert!(ExtensionSyntax::parse("tpl").is_err());
+        assert!(ExtensionSyntax::parse("=HTML").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// How much of each gutter component to show
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct GutterLayout {
+    pub(crate) author_initials: bool,
+    pub(crate) short_date: bool,
+    pub(crate) short_hash: bool,
+}
+
+impl GutterLayout {
+    /// Shrink components, least useful first, until the gutter fits in `max_width`
+    ///
+    /// `measure` is the width of the gutter with a given layout.  Components that can't shrink
+    /// any further are left as-is, even if the gutter still doesn't fit.
+    pub(crate) fn fit(max_width: usize, measure: impl Fn(&Self) -> usize) -> Self {
+        let mut layout = Self::default();
+        for step in SHRINK_ORDER {
+            let width = measure(&layout);
+            if width <= max_width {
+                break;
+            }
+            log::debug!(
+                "gutter is {width} columns, more than {max_width}; {}",
+                step.describe()
+            );
+            step.apply(&mut layout);
+        }
+        layout
+    }
+}
+
+/// The order components are shrunk in to make room for the code
+const SHRINK_ORDER: [Shrink; 3] = [Shrink::AuthorInitials, Shrink::ShortDate, Shrink::ShortHash];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Shrink {
+    AuthorInitials,
+    ShortDate,
+    ShortHash,
+}
+
+impl Shrink {
+    fn apply(self, layout: &mut GutterLayout) {
+        match self {
+            Self::AuthorInitials => layout.author_initials = true,
+            Self::ShortDate => layout.short_date = true,
+            Self::ShortHash => layout.short_hash = true,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::AuthorInitials => "abbreviating authors to initials",
+            Self::ShortDate => "abbreviating dates to `YY-MM-DD`",
+            Self::ShortHash => "abbreviating commits to 6 characters",
+        }
+    }
+}
+
+/// Commits shown with `short_hash`, never relative to `REV` as those don't truncate cleanly
+pub(crate) const SHORT_HASH_LEN: usize = 6;
+
+/// `Ada Lovelace` as `AL`
+pub(crate) fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .collect()
+}
+
+/// The fraction of the terminal the gutter may take before it is shrunk
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct GutterRatio(pub(crate) f64);
+
+impl GutterRatio {
+    /// The widest the gutter may be in a terminal `total_width` columns wide
+    pub(crate) fn max_width(self, total_width: usize) -> usize {
+        (total_width as f64 * self.0) as usize
+    }
+}
+
+impl Parseable for GutterRatio {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let ratio = s
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| anyhow::format_err!("invalid ratio `{s}`: {e}"))?;
+        if !(0.0..=1.0).contains(&ratio) {
+            anyhow::bail!("ratio `{s}` must be between 0 and 1");
+        }
+        Ok(Self(ratio))
+    }
+}
+
+impl std::fmt::Display for GutterRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub(crate) const MAX_GUTTER_RATIO: DefaultField<GutterRatio> =
+    RawField::<GutterRatio>::new("dive.max-gutter-ratio").default_value(|| GutterRatio(0.4));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn measure(layout: &GutterLayout) -> usize {
+        let author = if layout.author_initials { 2 } else { 20 };
+        let date = if layout.short_date { 8 } else { 10 };
+        let hash = if layout.short_hash { 6 } else { 8 };
+        author + date + hash
+    }
+
+    #[test]
+    fn fits_unchanged() {
+        assert_eq!(GutterLayout::fit(38, measure), GutterLayout::default());
+    }
+
+    #[test]
+    fn shrinks_in_order() {
+        assert_eq!(
+            GutterLayout::fit(20, measure),
+            GutterLayout {
+                author_initials: true,
+                short_date: false,
+                short_hash: false,
+            }
+        );
+        assert_eq!(
+            GutterLayout::fit(18, measure),
+            GutterLayout {
+                author_initials: true,
+                short_date: true,
+                short_hash: false,
+            }
+        );
+        assert_eq!(
+            GutterLayout::fit(0, measure),
+            GutterLayout {
+                author_initials: true,
+                short_date: true,
+                short_hash: true,
+            }
+        );
+    }
+
+    #[test]
+    fn initials_of_names() {
+        assert_eq!(initials("Ada Lovelace"), "AL");
+        assert_eq!(initials("  grace   brewster hopper "), "gbh");
+        assert_eq!(initials("Ada"), "A");
+    }
+
+    #[test]
+    fn parse_ratio() {
+        assert_eq!(GutterRatio::parse("0.5").unwrap(), GutterRatio(0.5));
+        assert!(GutterRatio::parse("1.5").is_err());
+        assert!(GutterRatio::parse("half").is_err());
+        assert_eq!(GutterRatio(0.4).max_width(80), 32);
+    }
+}
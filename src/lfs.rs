@@ -0,0 +1,304 @@
+//! Git LFS pointers, which stand in the tree for content kept elsewhere
+//!
+//! Blaming a pointer attributes its three lines of metadata, so a pointer is skipped with a
+//! notice naming the object instead.  With `--lfs-smudge`, each version of the pointer is run
+//! through `filter.lfs.smudge` and the smudged versions are diffed, so lines are attributed to
+//! the commit that changed the pointer to a version with them.
+
+use anyhow::Context as _;
+
+use crate::git2_config::Config;
+use crate::git2_config::ConfigSource as _;
+
+/// Pointers are at most this long, per the spec
+const MAX_POINTER_SIZE: usize = 1024;
+
+/// What a pointer file says about the object it stands in for
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Pointer {
+    /// Like `sha256:4d7a…`
+    pub(crate) oid: String,
+    pub(crate) size: u64,
+}
+
+impl Pointer {
+    /// `version` first, then `key value` lines, including `oid` and `size`
+    pub(crate) fn parse(content: &[u8]) -> Option<Self> {
+        if MAX_POINTER_SIZE < content.len() {
+            return None;
+        }
+        let content = std::str::from_utf8(content).ok()?;
+        let mut lines = content.lines();
+        let version = lines.next()?.strip_prefix("version ")?;
+        if !matches!(
+            version,
+            "https://git-lfs.github.com/spec/v1" | "https://hawser.github.com/spec/v1"
+        ) {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => {
+                    let hash = value.strip_prefix("sha256:")?;
+                    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return None;
+                    }
+                    oid = Some(value.to_owned());
+                }
+                "size" => size = Some(value.parse().ok()?),
+                _ => {}
+            }
+        }
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    /// The pointer for `tree_path` in `commit`, if it is one
+    pub(crate) fn at(
+        repo: &git2::Repository,
+        commit: &git2::Commit<'_>,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Option<Self>> {
+        let Ok(entry) = commit.tree()?.get_path(tree_path) else {
+            return Ok(None);
+        };
+        if entry.kind() != Some(git2::ObjectType::Blob) {
+            return Ok(None);
+        }
+        let (size, _) = match repo.odb()?.read_header(entry.id()) {
+            Ok(header) => header,
+            // Left for reading the file to report
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if MAX_POINTER_SIZE < size {
+            return Ok(None);
+        }
+        Ok(Self::parse(repo.find_blob(entry.id())?.content()))
+    }
+
+    /// Why `path` wasn't annotated, `failure` being why it couldn't be smudged
+    pub(crate) fn notice(&self, path: &str, failure: Option<&anyhow::Error>) -> String {
+        let object = format!(
+            "{path} is a Git LFS pointer to a {} object, {}",
+            format_size(self.size),
+            self.oid
+        );
+        match failure {
+            Some(err) => format!("{object}, and couldn't be smudged: {err:#}"),
+            None => format!("{object}; pass `--lfs-smudge` to annotate its content"),
+        }
+    }
+}
+
+/// Like `12.3 MiB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// Runs versions of a file through its smudge filter, for [`crate::engine::Engine`] to diff
+pub(crate) struct Smudge {
+    /// `filter.<driver>.smudge`, with `%f` for the path
+    command: String,
+    /// Where git runs filters
+    workdir: std::path::PathBuf,
+    /// By the blob of the pointer
+    smudged: std::collections::HashMap<git2::Oid, std::rc::Rc<[u8]>>,
+    /// Versions that couldn't be smudged, and the first error
+    failed: std::collections::HashSet<git2::Oid>,
+    first_error: Option<String>,
+}
+
+impl Smudge {
+    /// Per the `filter` gitattribute of `tree_path`, which `git lfs track` sets to `lfs`
+    pub(crate) fn new(
+        repo: &git2::Repository,
+        config: &Config,
+        attributes: &crate::attributes::Attributes,
+        tree_path: &std::path::Path,
+    ) -> anyhow::Result<Self> {
+        let driver = match attributes.get(tree_path, "filter") {
+            crate::attributes::State::Value(driver) => driver,
+            _ => "lfs".to_owned(),
+        };
+        let name = format!("filter.{driver}.smudge");
+        let command = config
+            .get_string(&name)
+            .ok()
+            .filter(|command| !command.is_empty())
+            .ok_or_else(|| {
+                anyhow::format_err!("`{name}` isn't set; run `git lfs install` to set it")
+            })?;
+        let workdir = repo
+            .workdir()
+            .context("smudging needs a worktree")?
+            .to_owned();
+        Ok(Self {
+            command,
+            workdir,
+            smudged: Default::default(),
+            failed: Default::default(),
+            first_error: None,
+        })
+    }
+
+    /// The content of `blob`, smudged if it's a pointer, as of when it was at `path`
+    pub(crate) fn content(
+        &mut self,
+        backend: &dyn crate::backend::Backend,
+        blob: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<std::rc::Rc<[u8]>> {
+        if let Some(content) = self.smudged.get(&blob) {
+            return Ok(content.clone());
+        }
+        let pointer = backend.read_blob(blob)?;
+        let content: std::rc::Rc<[u8]> = if Pointer::parse(&pointer).is_some() {
+            self.run(&pointer, path)?.into()
+        } else {
+            // From before the file was moved to LFS
+            pointer.into()
+        };
+        self.smudged.insert(blob, content.clone());
+        Ok(content)
+    }
+
+    /// Like [`Self::content`], remembering rather than returning the error, for older versions
+    pub(crate) fn try_content(
+        &mut self,
+        backend: &dyn crate::backend::Backend,
+        blob: git2::Oid,
+        path: &std::path::Path,
+    ) -> Option<std::rc::Rc<[u8]>> {
+        if self.failed.contains(&blob) {
+            return None;
+        }
+        match self.content(backend, blob, path) {
+            Ok(content) => Some(content),
+            Err(err) => {
+                log::debug!("couldn't smudge {blob}: {err:#}");
+                self.failed.insert(blob);
+                self.first_error.get_or_insert_with(|| format!("{err:#}"));
+                None
+            }
+        }
+    }
+
+    fn run(&self, pointer: &[u8], path: &std::path::Path) -> anyhow::Result<Vec<u8>> {
+        let path = path.to_str().context("path isn't UTF-8")?;
+        let quoted = shlex::try_quote(path).context("path can't be quoted for the shell")?;
+        let command = self.command.replace("%f", &quoted);
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(&command)
+            .current_dir(&self.workdir)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        log::trace!("running {cmd:?}");
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("could not run `{command}`"))?;
+        {
+            use std::io::Write as _;
+
+            // Small enough to not fill the pipe before the filter reads it
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+            stdin
+                .write_all(pointer)
+                .with_context(|| format!("could not send the pointer to `{command}`"))?;
+        }
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("could not run `{command}`"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().next().unwrap_or_default().trim();
+            if reason.is_empty() {
+                anyhow::bail!("`{command}` failed ({})", output.status);
+            }
+            anyhow::bail!("`{command}` failed ({}): {reason}", output.status);
+        }
+        Ok(output.stdout)
+    }
+
+    /// Warn about older versions that couldn't be smudged, forgetting them
+    pub(crate) fn report(&mut self, path: &std::path::Path) {
+        let Some(error) = self.first_error.take() else {
+            return;
+        };
+        let versions = if self.failed.len() == 1 {
+            "1 older version".to_owned()
+        } else {
+            format!("{} older versions", self.failed.len())
+        };
+        self.failed.clear();
+        log::warn!(
+            "{versions} of {} couldn't be smudged, so their lines are attributed to the versions after them: {error}",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POINTER: &str = "\
+version https://git-lfs.github.com/spec/v1
+oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+size 12345
+";
+
+    #[test]
+    fn parse_pointer() {
+        assert_eq!(
+            Pointer::parse(POINTER.as_bytes()),
+            Some(Pointer {
+                oid: "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
+                    .to_owned(),
+                size: 12345,
+            })
+        );
+        let extended = POINTER.replace("oid", "ext-0-foo sha256:00\noid");
+        assert!(Pointer::parse(extended.as_bytes()).is_some());
+
+        assert_eq!(Pointer::parse(b"one\ntwo\n"), None);
+        assert_eq!(
+            Pointer::parse(POINTER.replace("size 12345\n", "").as_bytes()),
+            None
+        );
+        assert_eq!(
+            Pointer::parse(POINTER.replace("sha256:4d", "sha256:").as_bytes()),
+            None
+        );
+        let long = format!("{POINTER}{}", "x".repeat(MAX_POINTER_SIZE));
+        assert_eq!(Pointer::parse(long.as_bytes()), None);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(12), "12 B");
+        assert_eq!(format_size(12345), "12.1 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 << 40), "3.0 TiB");
+    }
+}
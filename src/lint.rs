@@ -0,0 +1,312 @@
+//! `--lint-whitespace`, marking whitespace problems in the code and who they came from
+//!
+//! Problems are marked by layering `dive.lint-color` over the syntax highlighting, so a mark's
+//! background and attributes are added to a region's colors rather than replacing them.
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+use crate::git_color::GitColor;
+
+/// A kind of whitespace problem, in the order they are reported
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Issue {
+    /// Spaces or tabs before the end of the line
+    Trailing,
+    /// A tab after a space in the indentation, which renders differently per tab width
+    TabAfterSpace,
+    /// Past `dive.max-line-length` columns
+    TooLong,
+}
+
+/// Where on a line a problem is, in bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Mark {
+    pub(crate) issue: Issue,
+    pub(crate) range: std::ops::Range<usize>,
+}
+
+/// What to look for and how to mark it
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Lint {
+    pub(crate) style: anstyle::Style,
+    /// 0 being no limit
+    pub(crate) max_line_length: usize,
+}
+
+impl Lint {
+    pub(crate) fn resolve(config: &Config) -> Self {
+        Self {
+            style: config.get(&LINT_COLOR).style(),
+            max_line_length: usize::try_from(config.get(&MAX_LINE_LENGTH)).unwrap_or(0),
+        }
+    }
+
+    /// The problems on `line`, which is without its line ending
+    pub(crate) fn find(&self, line: &str) -> Vec<Mark> {
+        let mut marks = Vec::new();
+
+        let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+        let indent = &line[..indent_len];
+        if let Some(space) = indent.find(' ') {
+            if let Some(tab) = indent[space..].find('\t') {
+                // From the first tab after a space to the end of the indentation
+                marks.push(Mark {
+                    issue: Issue::TabAfterSpace,
+                    range: space + tab..indent_len,
+                });
+            }
+        }
+
+        let content_len = line.trim_end_matches([' ', '\t']).len();
+        if content_len < line.len() {
+            marks.push(Mark {
+                issue: Issue::Trailing,
+                range: content_len..line.len(),
+            });
+        }
+
+        if self.max_line_length != 0 {
+            let mut columns = 0;
+            for (i, c) in line.char_indices() {
+                columns += if c == '\t' {
+                    TAB_WIDTH - columns % TAB_WIDTH
+                } else {
+                    crate::unicode::display_width(c.encode_utf8(&mut [0; 4]))
+                };
+                if self.max_line_length < columns {
+                    marks.push(Mark {
+                        issue: Issue::TooLong,
+                        range: i..line.len(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        marks.sort_by_key(|mark| mark.issue);
+        marks
+    }
+}
+
+/// Columns between tab stops when measuring line length
+const TAB_WIDTH: usize = 8;
+
+/// Split the styled `regions` of a line where `marks` start and end, layering `style` over
+/// the marked parts
+pub(crate) fn overlay<'s>(
+    regions: Vec<(anstyle::Style, &'s str)>,
+    marks: &[Mark],
+    style: anstyle::Style,
+) -> Vec<(anstyle::Style, &'s str)> {
+    if marks.is_empty() {
+        return regions;
+    }
+    let mut overlaid = Vec::with_capacity(regions.len() + 2 * marks.len());
+    let mut offset = 0;
+    for (region_style, region) in regions {
+        let start = offset;
+        let end = start + region.len();
+        offset = end;
+
+        let mut cuts = marks
+            .iter()
+            .flat_map(|mark| [mark.range.start, mark.range.end])
+            .filter(|cut| start < *cut && *cut < end)
+            .collect::<Vec<_>>();
+        cuts.sort_unstable();
+        cuts.dedup();
+        cuts.push(end);
+
+        let mut piece_start = start;
+        for cut in cuts {
+            let marked = marks.iter().any(|mark| mark.range.contains(&piece_start));
+            let piece_style = if marked {
+                compose(region_style, style)
+            } else {
+                region_style
+            };
+            overlaid.push((piece_style, &region[piece_start - start..cut - start]));
+            piece_start = cut;
+        }
+    }
+    overlaid
+}
+
+/// `over` layered on `base`, its colors taking precedence where set and attributes combined
+pub(crate) fn compose(base: anstyle::Style, over: anstyle::Style) -> anstyle::Style {
+    base.fg_color(over.get_fg_color().or(base.get_fg_color()))
+        .bg_color(over.get_bg_color().or(base.get_bg_color()))
+        .effects(base.get_effects() | over.get_effects())
+}
+
+/// Lines with each kind of problem
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct Counts {
+    trailing: usize,
+    tab_after_space: usize,
+    too_long: usize,
+}
+
+impl Counts {
+    pub(crate) fn add(&mut self, marks: &[Mark]) {
+        for issue in [Issue::Trailing, Issue::TabAfterSpace, Issue::TooLong] {
+            if marks.iter().any(|mark| mark.issue == issue) {
+                *self.get_mut(issue) += 1;
+            }
+        }
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.trailing + self.tab_after_space + self.too_long
+    }
+
+    fn get_mut(&mut self, issue: Issue) -> &mut usize {
+        match issue {
+            Issue::Trailing => &mut self.trailing,
+            Issue::TabAfterSpace => &mut self.tab_after_space,
+            Issue::TooLong => &mut self.too_long,
+        }
+    }
+
+    /// Like `2 trailing, 1 over 100 columns`, skipping problems there are none of
+    pub(crate) fn describe(&self, max_line_length: usize) -> String {
+        let parts = [
+            (self.trailing, "trailing".to_owned()),
+            (self.tab_after_space, "tab after spaces".to_owned()),
+            (self.too_long, format!("over {max_line_length} columns")),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count != 0)
+        .map(|(count, what)| format!("{count} {what}"))
+        .collect::<Vec<_>>();
+        if parts.is_empty() {
+            "none".to_owned()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+pub(crate) const LINT_COLOR: DefaultField<GitColor> = RawField::<GitColor>::new("dive.lint-color")
+    .default_value(|| {
+        anstyle::Style::new()
+            .bg_color(Some(anstyle::AnsiColor::Red.into()))
+            .into()
+    });
+pub(crate) const MAX_LINE_LENGTH: DefaultField<i64> =
+    RawField::<i64>::new("dive.max-line-length").default_value(|| 100);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lint(max_line_length: usize) -> Lint {
+        Lint {
+            style: anstyle::Style::new(),
+            max_line_length,
+        }
+    }
+
+    fn issues(line: &str) -> Vec<(Issue, &str)> {
+        lint(10)
+            .find(line)
+            .into_iter()
+            .map(|mark| (mark.issue, &line[mark.range]))
+            .collect()
+    }
+
+    #[test]
+    fn finds_each_issue() {
+        assert_eq!(issues("let a = 1;"), []);
+        assert_eq!(issues("let a;  "), [(Issue::Trailing, "  ")]);
+        assert_eq!(issues("  \tx;"), [(Issue::TabAfterSpace, "\t")]);
+        assert_eq!(issues("\t x"), []);
+        assert_eq!(issues("let abc = 1;"), [(Issue::TooLong, "1;")]);
+        // A tab reaches the next tab stop
+        assert_eq!(issues("\t\tx"), [(Issue::TooLong, "\tx")]);
+        assert_eq!(issues("   "), [(Issue::Trailing, "   ")]);
+        assert_eq!(lint(0).find(&"x".repeat(1000)), []);
+    }
+
+    #[test]
+    fn overlapping_issues() {
+        assert_eq!(
+            issues("let a = 123;  "),
+            [(Issue::Trailing, "  "), (Issue::TooLong, "3;  ")]
+        );
+    }
+
+    #[test]
+    fn overlay_splits_regions() {
+        let keyword = anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Blue.into()));
+        let plain = anstyle::Style::new();
+        let warn = anstyle::Style::new()
+            .bg_color(Some(anstyle::AnsiColor::Red.into()))
+            .effects(anstyle::Effects::UNDERLINE);
+        let line = "let a;  ";
+        let marks = lint(0).find(line);
+        let overlaid = overlay(vec![(keyword, "let"), (plain, " a;  ")], &marks, warn);
+        assert_eq!(overlaid, [(keyword, "let"), (plain, " a;"), (warn, "  ")]);
+    }
+
+    #[test]
+    fn overlay_keeps_syntax_colors() {
+        let comment = anstyle::Style::new()
+            .fg_color(Some(anstyle::AnsiColor::Green.into()))
+            .effects(anstyle::Effects::ITALIC);
+        let warn = anstyle::Style::new().bg_color(Some(anstyle::AnsiColor::Red.into()));
+        let marks = [Mark {
+            issue: Issue::TooLong,
+            range: 4..9,
+        }];
+        let overlaid = overlay(vec![(comment, "// a b c")], &marks, warn);
+        assert_eq!(
+            overlaid,
+            [
+                (comment, "// a"),
+                (
+                    comment.bg_color(Some(anstyle::AnsiColor::Red.into())),
+                    " b c"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlay_across_regions() {
+        let a = anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Blue.into()));
+        let b = anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Green.into()));
+        let warn = anstyle::Style::new().effects(anstyle::Effects::INVERT);
+        let marks = [
+            Mark {
+                issue: Issue::TooLong,
+                range: 2..6,
+            },
+            Mark {
+                issue: Issue::Trailing,
+                range: 5..6,
+            },
+        ];
+        let overlaid = overlay(vec![(a, "abc"), (b, "def")], &marks, warn);
+        assert_eq!(
+            overlaid,
+            [
+                (a, "ab"),
+                (compose(a, warn), "c"),
+                (compose(b, warn), "de"),
+                (compose(b, warn), "f"),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_lines_not_marks() {
+        let mut counts = Counts::default();
+        counts.add(&lint(10).find("let a = 1;  \t"));
+        counts.add(&lint(10).find("x "));
+        assert_eq!(counts.describe(10), "2 trailing, 1 over 10 columns");
+        assert_eq!(counts.total(), 3);
+        assert_eq!(Counts::default().describe(10), "none");
+    }
+}
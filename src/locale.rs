@@ -0,0 +1,341 @@
+//! `dive.locale`, the language relative dates and month names are written in
+//!
+//! Each language is a row of [`LOCALES`], so adding one is adding a row and a test.  Locales
+//! without a row, like `C`, are written in English.
+
+use crate::git2_config::Config;
+use crate::git2_config::RawField;
+
+/// A unit of relative dates, in the order of [`Locale::units`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Locale {
+    /// Like `de`, which `de_DE.UTF-8` and `de-AT` also pick
+    language: &'static str,
+    /// Singular and plural of each [`Unit`]
+    units: [(&'static str, &'static str); 7],
+    /// Between a count and its unit
+    count_separator: &'static str,
+    /// Between the years and months of an age
+    part_separator: &'static str,
+    /// An age in the past, `{}` being where it goes
+    ago: &'static str,
+    future: &'static str,
+    /// Abbreviated, from January
+    months: [&'static str; 12],
+    /// Abbreviated, from Monday
+    weekdays: [&'static str; 7],
+}
+
+pub(crate) const LOCALES: &[Locale] = &[
+    Locale {
+        language: "en",
+        units: [
+            ("second", "seconds"),
+            ("minute", "minutes"),
+            ("hour", "hours"),
+            ("day", "days"),
+            ("week", "weeks"),
+            ("month", "months"),
+            ("year", "years"),
+        ],
+        count_separator: " ",
+        part_separator: ", ",
+        ago: "{} ago",
+        future: "in the future",
+        months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+        weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    },
+    Locale {
+        language: "de",
+        // Dative, following `vor`
+        units: [
+            ("Sekunde", "Sekunden"),
+            ("Minute", "Minuten"),
+            ("Stunde", "Stunden"),
+            ("Tag", "Tagen"),
+            ("Woche", "Wochen"),
+            ("Monat", "Monaten"),
+            ("Jahr", "Jahren"),
+        ],
+        count_separator: " ",
+        part_separator: ", ",
+        ago: "vor {}",
+        future: "in der Zukunft",
+        months: [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ],
+        weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    },
+    Locale {
+        language: "es",
+        units: [
+            ("segundo", "segundos"),
+            ("minuto", "minutos"),
+            ("hora", "horas"),
+            ("día", "días"),
+            ("semana", "semanas"),
+            ("mes", "meses"),
+            ("año", "años"),
+        ],
+        count_separator: " ",
+        part_separator: ", ",
+        ago: "hace {}",
+        future: "en el futuro",
+        months: [
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+        ],
+        weekdays: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    },
+    Locale {
+        language: "fr",
+        units: [
+            ("seconde", "secondes"),
+            ("minute", "minutes"),
+            ("heure", "heures"),
+            ("jour", "jours"),
+            ("semaine", "semaines"),
+            ("mois", "mois"),
+            ("an", "ans"),
+        ],
+        count_separator: " ",
+        part_separator: ", ",
+        ago: "il y a {}",
+        future: "dans le futur",
+        months: [
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+        weekdays: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    },
+    Locale {
+        language: "ja",
+        units: [
+            ("秒", "秒"),
+            ("分", "分"),
+            ("時間", "時間"),
+            ("日", "日"),
+            ("週間", "週間"),
+            ("か月", "か月"),
+            ("年", "年"),
+        ],
+        count_separator: "",
+        part_separator: "",
+        ago: "{}前",
+        future: "未来",
+        months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        weekdays: ["月", "火", "水", "木", "金", "土", "日"],
+    },
+    Locale {
+        language: "zh",
+        units: [
+            ("秒", "秒"),
+            ("分钟", "分钟"),
+            ("小时", "小时"),
+            ("天", "天"),
+            ("周", "周"),
+            ("个月", "个月"),
+            ("年", "年"),
+        ],
+        count_separator: "",
+        part_separator: "",
+        ago: "{}前",
+        future: "将来",
+        months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        weekdays: ["周一", "周二", "周三", "周四", "周五", "周六", "周日"],
+    },
+];
+
+pub(crate) const ENGLISH: &Locale = &LOCALES[0];
+
+impl Locale {
+    /// `dive.locale`, falling back to the environment's locale for times
+    pub(crate) fn resolve(config: &Config) -> &'static Self {
+        if let Some(name) = config.get(&LOCALE) {
+            return Self::find(&name).unwrap_or_else(|| {
+                log::debug!("no translations for `dive.locale = {name}`, using English");
+                ENGLISH
+            });
+        }
+        if crate::render_test::enabled() {
+            return ENGLISH;
+        }
+        Self::from_env(|var| std::env::var(var).ok())
+    }
+
+    /// Like `setlocale`, the first of `LC_ALL`, `LC_TIME`, and `LANG` that is set
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> &'static Self {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .and_then(|name| Self::find(&name))
+            .unwrap_or(ENGLISH)
+    }
+
+    /// The row for a locale name like `es_MX.UTF-8`, by its language
+    pub(crate) fn find(name: &str) -> Option<&'static Self> {
+        let language = name
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        LOCALES.iter().find(|locale| locale.language == language)
+    }
+
+    /// Like `3 weeks`
+    pub(crate) fn count(&self, count: i64, unit: Unit) -> String {
+        let (singular, plural) = self.units[unit as usize];
+        let unit = if count == 1 { singular } else { plural };
+        format!("{count}{}{unit}", self.count_separator)
+    }
+
+    /// Like `1 year, 3 months`
+    pub(crate) fn join(&self, years: &str, months: &str) -> String {
+        format!("{years}{}{months}", self.part_separator)
+    }
+
+    /// Like `3 weeks ago`
+    pub(crate) fn ago(&self, age: &str) -> String {
+        self.ago.replace("{}", age)
+    }
+
+    pub(crate) fn future(&self) -> &'static str {
+        self.future
+    }
+
+    /// `month` being from 1
+    pub(crate) fn month(&self, month: u32) -> &'static str {
+        self.months[month as usize - 1]
+    }
+
+    /// `weekday` being days since Monday
+    pub(crate) fn weekday(&self, weekday: usize) -> &'static str {
+        self.weekdays[weekday]
+    }
+}
+
+pub(crate) const LOCALE: RawField<String> = RawField::<String>::new("dive.locale");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Three weeks, a year and a month, the future, and March
+    fn phrases(language: &str) -> [String; 4] {
+        let locale = Locale::find(language).unwrap();
+        [
+            locale.ago(&locale.count(3, Unit::Week)),
+            locale.ago(&locale.join(&locale.count(1, Unit::Year), &locale.count(1, Unit::Month))),
+            locale.future().to_owned(),
+            locale.month(3).to_owned(),
+        ]
+    }
+
+    #[test]
+    fn english() {
+        assert_eq!(
+            phrases("en"),
+            ["3 weeks ago", "1 year, 1 month ago", "in the future", "Mar"]
+        );
+    }
+
+    #[test]
+    fn german() {
+        assert_eq!(
+            phrases("de"),
+            [
+                "vor 3 Wochen",
+                "vor 1 Jahr, 1 Monat",
+                "in der Zukunft",
+                "Mär"
+            ]
+        );
+    }
+
+    #[test]
+    fn spanish() {
+        assert_eq!(
+            phrases("es"),
+            ["hace 3 semanas", "hace 1 año, 1 mes", "en el futuro", "mar"]
+        );
+    }
+
+    #[test]
+    fn french() {
+        assert_eq!(
+            phrases("fr"),
+            [
+                "il y a 3 semaines",
+                "il y a 1 an, 1 mois",
+                "dans le futur",
+                "mars"
+            ]
+        );
+    }
+
+    #[test]
+    fn japanese() {
+        assert_eq!(phrases("ja"), ["3週間前", "1年1か月前", "未来", "3月"]);
+    }
+
+    #[test]
+    fn chinese() {
+        assert_eq!(phrases("zh"), ["3周前", "1年1个月前", "将来", "3月"]);
+    }
+
+    #[test]
+    fn every_locale_is_tested() {
+        let tested = ["en", "de", "es", "fr", "ja", "zh"];
+        let languages = LOCALES.iter().map(|l| l.language).collect::<Vec<_>>();
+        assert_eq!(languages, tested);
+    }
+
+    #[test]
+    fn finds_by_language() {
+        assert_eq!(Locale::find("de_DE.UTF-8").unwrap().language, "de");
+        assert_eq!(Locale::find("es-MX").unwrap().language, "es");
+        assert_eq!(Locale::find("FR").unwrap().language, "fr");
+        assert_eq!(Locale::find("ja_JP.eucJP@euro").unwrap().language, "ja");
+        assert_eq!(Locale::find("C"), None);
+        assert_eq!(Locale::find("pt_BR"), None);
+        assert_eq!(Locale::find(""), None);
+    }
+
+    #[test]
+    fn environment_precedence() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            Locale::from_env(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| (*value).to_owned())
+            })
+            .language
+        };
+        assert_eq!(env(&[]), "en");
+        assert_eq!(env(&[("LANG", "fr_FR.UTF-8")]), "fr");
+        assert_eq!(env(&[("LANG", "fr_FR"), ("LC_TIME", "de_DE")]), "de");
+        assert_eq!(
+            env(&[("LANG", "fr_FR"), ("LC_TIME", "de_DE"), ("LC_ALL", "ja_JP")]),
+            "ja"
+        );
+        assert_eq!(env(&[("LC_ALL", ""), ("LANG", "es_ES")]), "es");
+        // Unknown locales are English rather than falling through to `LANG`
+        assert_eq!(env(&[("LC_TIME", "C"), ("LANG", "zh_CN")]), "en");
+    }
+}
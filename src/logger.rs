@@ -1,5 +1,8 @@
 use std::io::Write;
 
+/// `env_logger` filter directives, like `git_dive::config=trace`
+const FILTER_ENV: &str = "GIT_DIVE_LOG";
+
 pub(crate) fn init_logging(
     level: clap_verbosity_flag::Verbosity<clap_verbosity_flag::InfoLevel>,
     colored: bool,
@@ -18,7 +21,13 @@ pub(crate) fn init_logging(
             env_logger::WriteStyle::Never
         });
 
-        builder.filter(None, level.to_level_filter());
+        if let Ok(filters) = std::env::var(FILTER_ENV) {
+            // Focus debug and trace output on the targets named in `GIT_DIVE_LOG`
+            builder.filter(None, level.to_level_filter().min(log::LevelFilter::Info));
+            builder.parse_filters(&filters);
+        } else {
+            builder.filter(None, level.to_level_filter());
+        }
 
         if level == log::LevelFilter::Trace || level == log::LevelFilter::Debug {
             builder.format_timestamp_secs();
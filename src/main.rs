@@ -5,13 +5,66 @@
 use clap::Parser;
 use proc_exit::prelude::*;
 
+mod acknowledgements;
+mod again;
 mod args;
 mod assets;
+mod attributes;
+mod backend;
+mod batch;
 mod blame;
+mod boundary;
+mod build_info;
+mod changed;
+mod coloring;
+mod commit_graph;
+mod compare;
 mod config;
+mod conflict;
+mod daemon;
+mod date;
+mod deleted;
+mod detail;
+mod diagnostic;
+mod encoding;
+mod engine;
+mod error;
+mod exit_code;
 mod git2_config;
+mod git_color;
 mod git_pager;
+mod glyphs;
+mod hash;
+mod ignore_markers;
+mod ignore_revs;
+mod language;
+mod layout;
+mod lfs;
+mod lint;
+mod locale;
 mod logger;
+mod mine;
+mod objects;
+mod palette;
+mod path_config;
+mod path_display;
+mod pathspec;
+mod profile;
+mod progress;
+mod promisor;
+mod release;
+mod render_test;
+mod renderer;
+mod replace;
+mod repo;
+mod resolution;
+mod rev;
+mod reverts;
+mod stat;
+mod status_hints;
+mod summary;
+mod tee;
+mod unicode;
 
 use crate::git2_config::Config;
 use crate::git_pager::Pager;
@@ -19,7 +72,15 @@ use crate::git_pager::Pager;
 fn main() {
     human_panic::setup_panic!();
     let result = run();
-    proc_exit::exit(result);
+    let code = proc_exit::report(result);
+    if code.is_err() {
+        log::debug!(
+            "exiting with {} ({})",
+            code.as_raw(),
+            exit_code::describe(code)
+        );
+    }
+    code.process_exit()
 }
 
 fn run() -> proc_exit::ExitResult {
@@ -31,8 +92,15 @@ fn run() -> proc_exit::ExitResult {
     }
     .write_global();
 
+    let launch_dir = std::env::current_dir().with_code(proc_exit::Code::FAILURE)?;
+    let mut argv = std::env::args_os().collect::<Vec<_>>();
+    // Without arguments, replay the last run if there is one
+    let bare = argv.len() == 1;
+    if bare {
+        argv.push("--again".into());
+    }
     // clap's `get_matches` uses Failure rather than Usage, so bypass it for `get_matches_safe`.
-    let args = args::Args::parse();
+    let mut args = args::Args::parse_argv(&argv);
 
     args.color.write_global();
     let colored_stderr = !matches!(
@@ -42,60 +110,97 @@ fn run() -> proc_exit::ExitResult {
 
     logger::init_logging(args.verbose.clone(), colored_stderr);
 
-    if let Some(current_dir) = args.current_dir.as_deref() {
-        let current_dir = current_dir
-            .iter()
-            .fold(std::path::PathBuf::new(), |current, next| {
-                current.join(next)
-            });
-        log::trace!("CWD={}", current_dir.display());
-        std::env::set_current_dir(current_dir).with_code(proc_exit::Code::FAILURE)?;
+    if args.render_test {
+        render_test::enable(&mut config, &mut args);
+    }
+
+    change_dir(&args)?;
+
+    // Include the repo's config, if any, for default args, profiles, and config checks
+    let repo = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| repo::open(&cwd, &args).ok());
+    if let Some(repo) = &repo {
+        config.add_repo(repo);
+    }
+
+    let mut launch_dir = launch_dir;
+    if args.again {
+        let Some((dir, replayed)) = repo.as_ref().and_then(|repo| again::replay(repo, &argv))
+        else {
+            if bare {
+                // Report what's missing like any other invocation would
+                args::Args::parse_from(&argv[..1]);
+            }
+            return Err(anyhow::format_err!("no previous run to replay"))
+                .with_code(exit_code::USAGE);
+        };
+        log::debug!("replaying {:?} from {}", replayed, dir.display());
+        std::env::set_current_dir(&dir).with_code(proc_exit::Code::FAILURE)?;
+        launch_dir = dir;
+        argv = replayed;
+        args = args::Args::parse_argv(&argv);
+        if args.render_test {
+            render_test::enable(&mut config, &mut args);
+        }
+        change_dir(&args)?;
+    }
+
+    let remembered = argv.clone();
+    if !args.profile.is_empty() || config.get(&profile::DEFAULT_ARGS).is_some() {
+        argv = profile::expand(&config, argv).with_code(exit_code::USAGE)?;
+        log::debug!("expanded arguments: {:?}", argv);
+        let current_dir = args.current_dir.take();
+        args = args::Args::parse_argv(&argv);
+        if args.render_test {
+            render_test::enable(&mut config, &mut args);
+        }
+        // Injected `-C`s come before the command line's, so start over from where we began
+        if args.current_dir != current_dir {
+            std::env::set_current_dir(&launch_dir).with_code(proc_exit::Code::FAILURE)?;
+            change_dir(&args)?;
+        }
+    }
+
+    if let Some(rev_file) = args.rev_file.as_deref() {
+        args.rev = rev::read_rev_file(rev_file)?;
+        log::debug!("rev `{}` from {}", args.rev, rev_file.display());
+    }
+
+    if !args.no_config_check && config.get(&config::STRICT) {
+        config::check_config(&config);
     }
 
     if let Some(output_path) = args.dump_config.as_deref() {
-        config::dump_config(output_path, &mut config)?;
+        config::dump_config(output_path, &mut config, &args)?;
+    } else if let Some(key) = args.explain.as_deref() {
+        config::explain(key, &config)?;
     } else if args.list_languages {
-        list_languages(&mut config)?;
+        list_languages(&mut config, &args)?;
     } else if args.list_themes {
-        list_themes(&mut config)?;
+        list_themes(&mut config, &args)?;
     } else if args.acknowledgements {
+        acknowledgements::acknowledgements(&mut config, &args)?;
+    } else if args.build_info {
         use std::io::Write;
-        let _ = writeln!(anstream::stdout(), "{}", assets::get_acknowledgements());
+        let _ = write!(anstream::stdout(), "{}", build_info::BuildInfo::get());
     } else if args.diagnostic {
-        use bugreport::{
-            bugreport,
-            collector::{
-                CommandLine, CompileTimeInformation, EnvironmentVariables, OperatingSystem,
-                SoftwareVersion,
-            },
-            format::Markdown,
-        };
-
-        let mut report = bugreport!()
-            .info(SoftwareVersion::default())
-            .info(OperatingSystem::default())
-            .info(CommandLine::default())
-            .info(EnvironmentVariables::list(&[
-                "SHELL",
-                "PAGER",
-                "LESS",
-                "LESSCHARSET",
-                "LANG",
-                "LC_ALL",
-                "GIT_PAGER",
-                // Skipping `GIT_CONFIG_COUNT`, `GIT_CONFIG_KEY_*` and `GIT_CONFIG_VALUE_*`
-                "GIT_CONFIG_PARAMETERS",
-                "COLORTERM",
-                "TERM",
-                "NO_COLOR",
-                "CLICOLOR",
-                "CLICOLOR_FORCE",
-            ]))
-            .info(CompileTimeInformation::default());
-
-        report.print::<Markdown>();
+        diagnostic::diagnostic(&config, &args)?;
+    } else if args.daemon {
+        daemon::serve(&args)?;
+    } else if args.daemon_stop {
+        daemon::stop(&args)?;
+    } else if args.stdin_paths.is_some() {
+        batch::stdin_paths(&mut config, &args)?;
     } else if let Some(file_path) = args.file.as_deref() {
-        blame::blame(file_path, &mut config, &args)?;
+        if let Some(result) = args.client.then(|| daemon::client(&argv, &args)).flatten() {
+            result?;
+        } else {
+            blame::blame(file_path, &mut config, &args)?;
+        }
+        if let Some(repo) = repo.as_ref().filter(|_| config.get(&again::REMEMBER)) {
+            again::remember(repo, &launch_dir, &remembered);
+        }
     } else {
         unreachable!("clap ensured a mode exists");
     }
@@ -103,16 +208,26 @@ fn run() -> proc_exit::ExitResult {
     Ok(())
 }
 
-fn list_languages(config: &mut Config) -> proc_exit::ExitResult {
-    let total_width = terminal_size::terminal_size()
-        .map(|(w, _h)| w.0)
-        .or_else(|| std::env::var_os("COLUMNS").and_then(|s| s.to_str()?.parse::<u16>().ok()))
-        .unwrap_or(80) as usize;
+/// Apply `-C`
+fn change_dir(args: &args::Args) -> proc_exit::ExitResult {
+    if let Some(current_dir) = args.current_dir.as_deref() {
+        let current_dir = current_dir
+            .iter()
+            .fold(std::path::PathBuf::new(), |current, next| {
+                current.join(next)
+            });
+        log::trace!("CWD={}", current_dir.display());
+        std::env::set_current_dir(current_dir).with_code(proc_exit::Code::FAILURE)?;
+    }
+    Ok(())
+}
+
+fn list_languages(config: &mut Config, args: &args::Args) -> proc_exit::ExitResult {
+    let total_width = render_test::terminal_width();
 
-    let pager = config.get(&git2_config::PAGER);
-    let mut pager = Pager::stdout(&pager);
+    let mut pager = Pager::resolve(config, args)?;
     let mut pager = pager.start();
-    let pager = pager.as_writer().with_code(proc_exit::Code::FAILURE)?;
+    let pager = pager.as_writer().with_code(exit_code::IO)?;
 
     let syntax_set = assets::load_syntaxes();
     let name_width = syntax_set
@@ -156,13 +271,12 @@ fn list_languages(config: &mut Config) -> proc_exit::ExitResult {
     Ok(())
 }
 
-fn list_themes(config: &mut Config) -> proc_exit::ExitResult {
+fn list_themes(config: &mut Config, args: &args::Args) -> proc_exit::ExitResult {
     let colored_stdout =
         anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
-    let pager = config.get(&git2_config::PAGER);
-    let mut pager = Pager::stdout(&pager);
+    let mut pager = Pager::resolve(config, args)?;
     let mut pager = pager.start();
-    let pager = pager.as_writer().with_code(proc_exit::Code::FAILURE)?;
+    let pager = pager.as_writer().with_code(exit_code::IO)?;
 
     let theme_set = assets::load_themes();
     if colored_stdout {
@@ -185,7 +299,9 @@ fn list_themes(config: &mut Config) -> proc_exit::ExitResult {
                 let _ = writeln!(
                     pager,
                     "{}{}",
-                    highlighter.highlight_line(line, &syntax_set).unwrap(),
+                    highlighter
+                        .highlight_line(line, &syntax_set, false)
+                        .unwrap(),
                     anstyle::Reset.render()
                 );
             }
@@ -0,0 +1,106 @@
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::MultiField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// The current user's identities, to accent their lines with `--highlight-mine`
+#[derive(Clone, Debug)]
+pub(crate) struct Mine {
+    /// Mailmapped and lowercased
+    emails: std::collections::BTreeSet<String>,
+}
+
+impl Mine {
+    /// `user.email` and `dive.my-emails`, when `--highlight-mine` or `dive.highlight-mine`
+    pub(crate) fn resolve(
+        repo: &git2::Repository,
+        config: &Config,
+        args: &crate::args::Args,
+    ) -> Option<Self> {
+        if !args.highlight_mine && !config.get(&HIGHLIGHT_MINE) {
+            return None;
+        }
+        let mailmap = repo.mailmap().ok();
+        let name = config.get(&USER_NAME);
+        let mut emails = std::collections::BTreeSet::new();
+        if let Some(email) = config.get(&USER_EMAIL) {
+            emails.insert(canonical(mailmap.as_ref(), name.as_deref(), &email));
+        }
+        for email in config.get(&MY_EMAILS) {
+            emails.insert(canonical(mailmap.as_ref(), None, &email.0));
+        }
+        if emails.is_empty() {
+            log::warn!("`--highlight-mine` needs `user.email` or `dive.my-emails`");
+            return None;
+        }
+        log::debug!("highlighting lines by {emails:?}");
+        Some(Self { emails })
+    }
+
+    /// Whether `email`, already mailmapped, is one of the user's
+    pub(crate) fn contains(&self, email: &str) -> bool {
+        self.emails.contains(&email.to_lowercase())
+    }
+}
+
+/// `email` as the mailmap spells it, like commit authors are
+fn canonical(mailmap: Option<&git2::Mailmap>, name: Option<&str>, email: &str) -> String {
+    mailmap
+        .and_then(|mailmap| {
+            // Signatures need a name, so match on the email alone without one
+            let signature = git2::Signature::now(name.unwrap_or(email), email).ok()?;
+            let resolved = mailmap.resolve_signature(&signature).ok()?;
+            Some(String::from_utf8_lossy(resolved.email_bytes()).into_owned())
+        })
+        .unwrap_or_else(|| email.to_owned())
+        .to_lowercase()
+}
+
+/// Another address the user commits with
+#[derive(Clone, Debug)]
+pub(crate) struct Email(String);
+
+impl Parseable for Email {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            anyhow::bail!("empty email");
+        }
+        Ok(Self(s.to_owned()))
+    }
+}
+
+impl std::fmt::Display for Email {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub(crate) const HIGHLIGHT_MINE: DefaultField<bool> =
+    RawField::<bool>::new("dive.highlight-mine").default();
+pub(crate) const MY_EMAILS: MultiField<Email> = MultiField::<Email>::new("dive.my-emails");
+const USER_EMAIL: RawField<String> = RawField::<String>::new("user.email");
+const USER_NAME: RawField<String> = RawField::<String>::new("user.name");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canonical_follows_mailmap() {
+        let mut mailmap = git2::Mailmap::new().unwrap();
+        mailmap
+            .add_entry(None, Some("me@work.example"), None, "me@old.example")
+            .unwrap();
+        assert_eq!(
+            canonical(Some(&mailmap), None, "me@old.example"),
+            "me@work.example"
+        );
+        assert_eq!(
+            canonical(Some(&mailmap), Some("Me"), "other@example.com"),
+            "other@example.com"
+        );
+        assert_eq!(canonical(None, None, "Me@Old.example"), "me@old.example");
+    }
+}
@@ -0,0 +1,215 @@
+//! Objects that can't be read, like a truncated loose object or one missing from a damaged pack
+//!
+//! libgit2 can spin forever inflating a truncated loose object, and its blame attributes lines
+//! to the wrong commit when a version of the file is missing.  So the history a blame needs is
+//! checked first, and when something can't be read the internal engine follows lines only as
+//! far as it can, attributing the rest to [`placeholder`].  `--strict-objects` fails instead.
+
+/// Where the lines that can't be followed further are attributed, drawn as an error
+pub(crate) fn placeholder() -> git2::Oid {
+    git2::Oid::from_bytes(&[0xff; 20]).expect("20 bytes is an object ID")
+}
+
+/// What reading `path` as of a commit found
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum PathCheck {
+    Readable,
+    /// The commit doesn't have `path`
+    Absent,
+    /// The first object along the way that can't be read
+    Unreadable(git2::Oid),
+}
+
+/// Checks objects before libgit2 reads them
+pub(crate) struct Objects<'r> {
+    repo: &'r git2::Repository,
+    odb: git2::Odb<'r>,
+    /// Where loose objects are kept
+    dir: std::path::PathBuf,
+    /// `--strict-objects`
+    strict: bool,
+    /// Whether each object checked so far can be read
+    checked: std::collections::HashMap<git2::Oid, bool>,
+}
+
+impl<'r> Objects<'r> {
+    pub(crate) fn new(repo: &'r git2::Repository, strict: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            repo,
+            odb: repo.odb()?,
+            dir: crate::boundary::common_dir(repo).join("objects"),
+            strict,
+            checked: Default::default(),
+        })
+    }
+
+    pub(crate) fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The first object in the way of reading `path` back along first parents from `commit`,
+    /// stopping where it was added
+    pub(crate) fn first_unreadable(
+        &mut self,
+        replacements: &crate::replace::Replacements,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Option<git2::Oid>> {
+        let start = std::time::Instant::now();
+        let mut next = Some(commit);
+        let mut commits = 0;
+        while let Some(id) = next {
+            commits += 1;
+            match self.check_path(id, path)? {
+                PathCheck::Readable => {}
+                PathCheck::Absent => break,
+                PathCheck::Unreadable(id) => return Ok(Some(id)),
+            }
+            next = replacements.first_parent(self.repo, id)?;
+        }
+        log::debug!(
+            "checked objects of {} in {commits} commits in {:?}",
+            path.display(),
+            start.elapsed()
+        );
+        Ok(None)
+    }
+
+    /// Whether `commit`, its tree down to `path`, and `path` can all be read
+    pub(crate) fn check_path(
+        &mut self,
+        commit: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<PathCheck> {
+        if !self.readable(commit) {
+            return Ok(PathCheck::Unreadable(commit));
+        }
+        let commit = self.repo.find_commit(commit)?;
+        let mut id = commit.tree_id();
+        let mut components = path.components().peekable();
+        while let Some(component) = components.next() {
+            if !self.readable(id) {
+                return Ok(PathCheck::Unreadable(id));
+            }
+            let tree = self.repo.find_tree(id)?;
+            let Some(entry) = tree.get_name_bytes(component.as_os_str().as_encoded_bytes()) else {
+                return Ok(PathCheck::Absent);
+            };
+            id = entry.id();
+            let is_tree = entry.kind() == Some(git2::ObjectType::Tree);
+            if components.peek().is_some() && !is_tree {
+                return Ok(PathCheck::Absent);
+            }
+        }
+        if !self.readable(id) {
+            return Ok(PathCheck::Unreadable(id));
+        }
+        Ok(PathCheck::Readable)
+    }
+
+    /// Present and, when loose, inflating to the size its header gives
+    fn readable(&mut self, id: git2::Oid) -> bool {
+        if let Some(readable) = self.checked.get(&id) {
+            return *readable;
+        }
+        let hex = id.to_string();
+        let loose = self.dir.join(&hex[..2]).join(&hex[2..]);
+        let readable = if loose.is_file() {
+            inflates(&loose)
+        } else {
+            self.odb.exists(id)
+        };
+        if !readable {
+            log::debug!("object {id} can't be read");
+        }
+        self.checked.insert(id, readable);
+        readable
+    }
+}
+
+/// A loose object's content is zlib-compressed after a header like `blob 12\0`
+fn inflates(path: &std::path::Path) -> bool {
+    let Ok(compressed) = std::fs::read(path) else {
+        return false;
+    };
+    let mut content = Vec::new();
+    let mut decoder = flate2::read::ZlibDecoder::new(compressed.as_slice());
+    if std::io::Read::read_to_end(&mut decoder, &mut content).is_err() {
+        return false;
+    }
+    let Some(nul) = content.iter().position(|b| *b == 0) else {
+        return false;
+    };
+    let size = std::str::from_utf8(&content[..nul])
+        .ok()
+        .and_then(|header| header.split_once(' '))
+        .and_then(|(_, size)| size.parse::<usize>().ok());
+    size == Some(content.len() - nul - 1)
+}
+
+/// For `--strict-objects`, or when even `REV`'s version can't be read
+pub(crate) fn unreadable_error(id: git2::Oid, path: &std::path::Path) -> anyhow::Error {
+    anyhow::format_err!(
+        "object {id} needed to blame {} can't be read; run `git fsck` to check the repository",
+        path.display()
+    )
+}
+
+/// After blaming, which objects kept how many lines from being followed further
+pub(crate) fn report(unreadable: &std::collections::BTreeSet<git2::Oid>, lines: usize) {
+    if unreadable.is_empty() {
+        return;
+    }
+    let lines = if lines == 1 {
+        "1 line is".to_owned()
+    } else {
+        format!("{lines} lines are")
+    };
+    let mut message = format!(
+        "{lines} attributed to `{MISSING}`, since history past these objects can't be read:"
+    );
+    for id in unreadable {
+        message.push_str(&format!("\n  {id}"));
+    }
+    message.push_str("\nrun `git fsck` to check the repository, or `--strict-objects` to fail");
+    log::warn!("{message}");
+}
+
+/// How lines attributed to [`placeholder`] are described
+pub(crate) const MISSING: &str = "unknown (object missing)";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn truncated_loose_object() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = git2::Repository::init(root_path).unwrap();
+        let id = repo.blob(b"one\ntwo\n").unwrap();
+        let mut objects = Objects::new(&repo, false).unwrap();
+        assert!(objects.readable(id));
+
+        let hex = id.to_string();
+        let path = root_path
+            .join(".git/objects")
+            .join(&hex[..2])
+            .join(&hex[2..]);
+        let compressed = std::fs::read(&path).unwrap();
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        std::fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+        assert!(!inflates(&path));
+        std::fs::write(&path, b"").unwrap();
+        assert!(!inflates(&path));
+
+        let missing = git2::Oid::hash_object(git2::ObjectType::Blob, b"never written").unwrap();
+        let mut objects = Objects::new(&repo, false).unwrap();
+        assert!(!objects.readable(missing));
+
+        root.close().unwrap();
+    }
+}
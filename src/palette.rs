@@ -0,0 +1,93 @@
+use crate::git2_config::Config;
+use crate::git2_config::MultiField;
+use crate::git_color::GitColor;
+
+/// Colors to tell authors apart with `--color-by author`
+#[derive(Clone, Debug)]
+pub(crate) struct AuthorPalette {
+    colors: Vec<anstyle::Style>,
+}
+
+impl AuthorPalette {
+    /// `dive.author-palette`, falling back to the terminal's bright and normal colors
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let colors = config
+            .get(&AUTHOR_PALETTE)
+            .into_iter()
+            .map(GitColor::style)
+            .collect::<Vec<_>>();
+        if colors.is_empty() {
+            Self::default()
+        } else {
+            Self { colors }
+        }
+    }
+
+    /// The color for an author, the same across runs and repos
+    ///
+    /// `email` should already be mailmapped so an author's aliases share a color.
+    pub(crate) fn color(&self, email: &str) -> anstyle::Style {
+        let hash = fnv1a(email.to_lowercase().as_bytes());
+        self.colors[(hash % self.colors.len() as u64) as usize]
+    }
+
+    /// The `i`th color, cycling, to tell a handful of things apart in order
+    pub(crate) fn nth(&self, i: usize) -> anstyle::Style {
+        self.colors[i % self.colors.len()]
+    }
+}
+
+impl Default for AuthorPalette {
+    fn default() -> Self {
+        let colors = DEFAULT_PALETTE
+            .iter()
+            .map(|c| anstyle::Style::new().fg_color(Some((*c).into())))
+            .collect();
+        Self { colors }
+    }
+}
+
+/// Unlike `std`'s hasher, this is stable across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(PRIME)
+    })
+}
+
+/// Leaves out black and white which are likely to be the background
+const DEFAULT_PALETTE: &[anstyle::AnsiColor] = &[
+    anstyle::AnsiColor::Red,
+    anstyle::AnsiColor::Green,
+    anstyle::AnsiColor::Yellow,
+    anstyle::AnsiColor::Blue,
+    anstyle::AnsiColor::Magenta,
+    anstyle::AnsiColor::Cyan,
+    anstyle::AnsiColor::BrightRed,
+    anstyle::AnsiColor::BrightGreen,
+    anstyle::AnsiColor::BrightYellow,
+    anstyle::AnsiColor::BrightBlue,
+    anstyle::AnsiColor::BrightMagenta,
+    anstyle::AnsiColor::BrightCyan,
+];
+
+pub(crate) const AUTHOR_PALETTE: MultiField<GitColor> =
+    MultiField::<GitColor>::new("dive.author-palette");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_is_stable() {
+        let palette = AuthorPalette::default();
+        assert_eq!(
+            palette.color("ada@example.com"),
+            palette.color("Ada@Example.com")
+        );
+        // Changing this changes every user's colors
+        assert_eq!(fnv1a(b"ada@example.com"), 0x2668_11ad_9b55_c572);
+    }
+}
@@ -0,0 +1,141 @@
+//! Settings for one file from its gitattributes, like `docs/** dive-theme=GitHub`
+//!
+//! `dive-theme`, `dive-tab-width`, and `dive-language` override `dive.theme`, `dive.tab-width`,
+//! and the picked syntax for the files they match, so parts of a repo can be shown differently.
+//! Values that aren't valid are ignored with a warning pointing at the line that set them.
+
+use crate::attributes::Attributes;
+use crate::attributes::State;
+
+/// An attribute's value and the `.gitattributes` line that set it
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Setting {
+    name: &'static str,
+    value: Option<String>,
+    source: String,
+}
+
+impl Setting {
+    fn find(
+        attributes: &Attributes,
+        tree_path: &std::path::Path,
+        name: &'static str,
+    ) -> Option<Self> {
+        let (state, source) = attributes.find(tree_path, name)?;
+        let value = match state {
+            State::Value(value) => Some(value.clone()),
+            State::Set => None,
+            // Back to the config
+            State::Unset | State::Unspecified => return None,
+        };
+        Some(Self {
+            name,
+            value,
+            source: source.to_owned(),
+        })
+    }
+
+    /// The value if `valid` accepts it, `expected` describing what it should be otherwise
+    fn validate<T>(&self, expected: &str, valid: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let Some(value) = &self.value else {
+            log::warn!(
+                "ignoring `{}` from {}, which needs a value like `{}={expected}`",
+                self.name,
+                self.source,
+                self.name
+            );
+            return None;
+        };
+        let valid = valid(value);
+        if valid.is_none() {
+            log::warn!(
+                "ignoring `{}={value}` from {}, which isn't {expected}",
+                self.name,
+                self.source
+            );
+        }
+        valid
+    }
+}
+
+/// The gitattributes overriding config for a file
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct PathConfig {
+    /// Only the attributes that are set
+    settings: Vec<Setting>,
+}
+
+impl PathConfig {
+    pub(crate) fn new(attributes: &Attributes, tree_path: &std::path::Path) -> Self {
+        let settings = ["dive-theme", "dive-tab-width", "dive-language"]
+            .into_iter()
+            .filter_map(|name| Setting::find(attributes, tree_path, name))
+            .collect();
+        Self { settings }
+    }
+
+    fn setting(&self, name: &str) -> Option<&Setting> {
+        self.settings.iter().find(|setting| setting.name == name)
+    }
+
+    /// Per `dive-theme`, when `theme_set` has it
+    ///
+    /// Attribute values can't have spaces, so `-` stands in for them, like
+    /// `dive-theme=Monokai-Extended-Light`.
+    pub(crate) fn theme(&self, theme_set: &crate::assets::LazyThemeSet) -> Option<String> {
+        self.setting("dive-theme")?
+            .validate("a theme from `--list-themes`", |theme| {
+                theme_set
+                    .themes()
+                    .find(|name| *name == theme || name.replace(' ', "-") == theme)
+                    .map(|name| name.to_owned())
+            })
+    }
+
+    /// Per `dive-tab-width`
+    pub(crate) fn tab_width(&self) -> Option<usize> {
+        self.setting("dive-tab-width")?
+            .validate("a width from 1 to 16", |width| {
+                width.parse().ok().filter(|width| (1..=16).contains(width))
+            })
+    }
+
+    /// Per `dive-language`, when `syntax_set` has it
+    pub(crate) fn language(&self, syntax_set: &syntect::parsing::SyntaxSet) -> Option<String> {
+        self.setting("dive-language")?
+            .validate("a language from `--list-languages`", |language| {
+                syntax_set
+                    .find_syntax_by_token(language)
+                    .is_some()
+                    .then(|| language.to_owned())
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tab_width(value: Option<&str>) -> Option<usize> {
+        let tab_width = Setting {
+            name: "dive-tab-width",
+            value: value.map(|value| value.to_owned()),
+            source: ".gitattributes:1".to_owned(),
+        };
+        PathConfig {
+            settings: vec![tab_width],
+        }
+        .tab_width()
+    }
+
+    #[test]
+    fn tab_widths() {
+        assert_eq!(tab_width(Some("4")), Some(4));
+        assert_eq!(tab_width(Some("16")), Some(16));
+        assert_eq!(tab_width(Some("0")), None);
+        assert_eq!(tab_width(Some("17")), None);
+        assert_eq!(tab_width(Some("wide")), None);
+        assert_eq!(tab_width(None), None);
+        assert_eq!(PathConfig::default().tab_width(), None);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::git2_config::Config;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// How to spell paths in output and messages
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum PathDisplay {
+    /// Relative to the current directory, like `git status`
+    RelativeToCwd,
+    /// Relative to the top of the repo, like `git ls-files --full-name`
+    #[default]
+    RelativeToRoot,
+    /// Absolute paths, without `\\?\` on Windows
+    Absolute,
+}
+
+impl PathDisplay {
+    /// `--path-display` or its shorthands, falling back to `dive.path-display`
+    ///
+    /// `None` when neither chose a mode.
+    pub(crate) fn resolve(config: &Config, args: &crate::args::Args) -> Option<Self> {
+        if let Some(mode) = args.path_display {
+            Some(mode)
+        } else if args.absolute_paths {
+            Some(Self::Absolute)
+        } else if args.relative_paths {
+            Some(Self::RelativeToCwd)
+        } else {
+            config.get(&PATH_DISPLAY)
+        }
+    }
+}
+
+impl Parseable for PathDisplay {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        <Self as clap::ValueEnum>::from_str(s, false)
+            .map_err(|_| anyhow::format_err!("unsupported path display `{s}`"))
+    }
+}
+
+impl std::fmt::Display for PathDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        clap::ValueEnum::to_possible_value(self)
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+pub(crate) const PATH_DISPLAY: RawField<PathDisplay> =
+    RawField::<PathDisplay>::new("dive.path-display");
+
+/// Spell repo paths with the chosen [`PathDisplay`]
+pub(crate) struct Paths {
+    /// Whether a mode was chosen, rather than defaulted
+    pub(crate) chosen: bool,
+    mode: PathDisplay,
+    cwd: std::path::PathBuf,
+    workdir: std::path::PathBuf,
+}
+
+impl Paths {
+    pub(crate) fn new(
+        mode: Option<PathDisplay>,
+        cwd: &std::path::Path,
+        workdir: &std::path::Path,
+    ) -> Self {
+        // Compare like `to_repo_relative` does, after resolving symlinks
+        let cwd = dunce::canonicalize(cwd).unwrap_or_else(|_| cwd.to_owned());
+        let workdir = dunce::canonicalize(workdir).unwrap_or_else(|_| workdir.to_owned());
+        Self {
+            chosen: mode.is_some(),
+            mode: mode.unwrap_or_default(),
+            cwd,
+            workdir,
+        }
+    }
+
+    /// Spell `tree_path`, a `/`-separated path from the top of the repo
+    pub(crate) fn show(&self, tree_path: &std::path::Path) -> String {
+        match self.mode {
+            PathDisplay::RelativeToRoot => tree_path.display().to_string(),
+            PathDisplay::RelativeToCwd => relative_to(&self.workdir.join(tree_path), &self.cwd)
+                .display()
+                .to_string(),
+            PathDisplay::Absolute => absolute(&self.workdir.join(tree_path))
+                .display()
+                .to_string(),
+        }
+    }
+}
+
+/// `path` with native separators and, on Windows, drive letters rather than verbatim prefixes
+///
+/// UNC shares stay as `\\server\share\...`.
+fn absolute(path: &std::path::Path) -> std::path::PathBuf {
+    dunce::simplified(path).components().collect()
+}
+
+/// `path` relative to `base`, both being absolute
+fn relative_to(path: &std::path::Path, base: &std::path::Path) -> std::path::PathBuf {
+    let path = absolute(path);
+    let base = absolute(base);
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+    while let (Some(p), Some(b)) = (path_components.peek(), base_components.peek()) {
+        if p != b {
+            break;
+        }
+        path_components.next();
+        base_components.next();
+    }
+    if path_components
+        .peek()
+        .is_some_and(|c| matches!(c, std::path::Component::Prefix(_)))
+    {
+        // Different drives have no relative path between them
+        return path;
+    }
+
+    let mut relative = std::path::PathBuf::new();
+    for _ in base_components {
+        relative.push("..");
+    }
+    relative.extend(path_components);
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+    relative
+}
+
+/// Fit `path` in `width` columns by dropping leading directories, never the file name, and
+/// marking the cut with `ellipsis`
+pub(crate) fn truncate_start<'p>(
+    path: &'p str,
+    width: usize,
+    ellipsis: &str,
+) -> std::borrow::Cow<'p, str> {
+    use crate::unicode::display_width;
+
+    if display_width(path) <= width {
+        return path.into();
+    }
+    let Some(name_start) = path.rfind('/').map(|i| i + 1) else {
+        return path.into();
+    };
+    let mut start = name_start;
+    for (i, _) in path[..name_start].char_indices().rev() {
+        if width < display_width(ellipsis) + display_width(&path[i..]) {
+            break;
+        }
+        start = i;
+    }
+    format!("{ellipsis}{}", &path[start..]).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn truncate_keeps_file_name() {
+        assert_eq!(truncate_start("src/old.rs", 20, "…"), "src/old.rs");
+        assert_eq!(truncate_start("src/module/old.rs", 12, "…"), "…dule/old.rs");
+        assert_eq!(
+            truncate_start("src/module/old.rs", 12, "..."),
+            "...le/old.rs"
+        );
+        assert_eq!(truncate_start("src/module/old.rs", 3, "…"), "…old.rs");
+        assert_eq!(truncate_start("a_long_name.rs", 3, "…"), "a_long_name.rs");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn relative() {
+        let rel = |p: &str, b: &str| {
+            relative_to(std::path::Path::new(p), std::path::Path::new(b))
+                .display()
+                .to_string()
+        };
+        assert_eq!(rel("/repo/src/lib.rs", "/repo"), "src/lib.rs");
+        assert_eq!(rel("/repo/src/lib.rs", "/repo/src"), "lib.rs");
+        assert_eq!(rel("/repo/README.md", "/repo/src/bin"), "../../README.md");
+        assert_eq!(rel("/repo/src", "/repo/src"), ".");
+        assert_eq!(rel("/repo/src/lib.rs", "/other"), "../repo/src/lib.rs");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn relative() {
+        let rel = |p: &str, b: &str| {
+            relative_to(std::path::Path::new(p), std::path::Path::new(b))
+                .display()
+                .to_string()
+        };
+        assert_eq!(rel(r"C:\repo\src\lib.rs", r"C:\repo"), r"src\lib.rs");
+        assert_eq!(
+            rel(r"\\?\C:\repo\README.md", r"C:\repo\src"),
+            r"..\README.md"
+        );
+        assert_eq!(rel(r"D:\repo\README.md", r"C:\repo"), r"D:\repo\README.md");
+        assert_eq!(
+            rel(r"\\server\share\repo\README.md", r"\\server\share\repo\src"),
+            r"..\README.md"
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn absolute_drops_verbatim() {
+        let abs = |p: &str| absolute(std::path::Path::new(p)).display().to_string();
+        assert_eq!(abs(r"\\?\C:\repo\src\lib.rs"), r"C:\repo\src\lib.rs");
+        assert_eq!(abs(r"C:/repo/src/lib.rs"), r"C:\repo\src\lib.rs");
+        assert_eq!(
+            abs(r"\\server\share\repo/lib.rs"),
+            r"\\server\share\repo\lib.rs"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            PathDisplay::parse("relative-to-cwd").unwrap(),
+            PathDisplay::RelativeToCwd
+        );
+        assert_eq!(
+            PathDisplay::parse("absolute").unwrap(),
+            PathDisplay::Absolute
+        );
+        assert!(PathDisplay::parse("relative").is_err());
+        assert_eq!(PathDisplay::RelativeToRoot.to_string(), "relative-to-root");
+    }
+}
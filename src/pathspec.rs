@@ -0,0 +1,328 @@
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// A path as given on the command line, with any pathspec magic removed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Pathspec {
+    pub(crate) path: std::path::PathBuf,
+    /// `:(icase)`
+    pub(crate) icase: bool,
+}
+
+impl Pathspec {
+    /// Parse the long form of pathspec magic, like `:(icase)README.md`
+    ///
+    /// Only `icase` is supported; paths with other magic are taken literally.
+    pub(crate) fn parse(path: &std::path::Path) -> Self {
+        let literal = || Self {
+            path: path.to_owned(),
+            icase: false,
+        };
+        let Some(s) = path.to_str() else {
+            return literal();
+        };
+        let Some((magic, rest)) = s.strip_prefix(":(").and_then(|s| s.split_once(')')) else {
+            return literal();
+        };
+        let mut icase = false;
+        for word in magic.split(',') {
+            if word.trim() != "icase" {
+                log::debug!("unsupported pathspec magic `{word}` in `{s}`");
+                return literal();
+            }
+            icase = true;
+        }
+        Self {
+            path: rest.into(),
+            icase,
+        }
+    }
+}
+
+/// Find the entry in `tree` spelled like `tree_path`, with its in-tree spelling
+///
+/// Names match regardless of Unicode normalization and, with `icase`, of case.  Exact matches
+/// win at each level.  When several entries match, the path is ambiguous.
+///
+/// With `precompose`, per `core.precomposeUnicode`, names differing only by normalization are
+/// the same name, as on macOS where the filesystem and git's argument handling can't tell them
+/// apart.  So an exact match doesn't win over another normalization of it.
+pub(crate) fn find_equivalent(
+    repo: &git2::Repository,
+    tree: &git2::Tree<'_>,
+    tree_path: &std::path::Path,
+    icase: bool,
+    precompose: bool,
+) -> anyhow::Result<Option<std::path::PathBuf>> {
+    let Some(tree_path) = tree_path.to_str() else {
+        return Ok(None);
+    };
+    let mut tree = tree.clone();
+    let mut found = Vec::new();
+    let mut components = tree_path.split('/').filter(|c| !c.is_empty()).peekable();
+    while let Some(component) = components.next() {
+        let key = fold(component, icase);
+        let entry = {
+            let matches = tree
+                .iter()
+                .filter(|e| e.name().is_some_and(|n| fold(n, icase) == key))
+                .collect::<Vec<_>>();
+            let exact = matches.iter().find(|e| e.name() == Some(component));
+            let (matches, differ) = match exact {
+                Some(exact) if precompose => {
+                    // Case still counts, only normalization doesn't
+                    let key = fold(component, false);
+                    let same = matches
+                        .iter()
+                        .filter(|e| e.name().is_some_and(|n| fold(n, false) == key))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if same.len() == 1 {
+                        (vec![exact.to_owned()], "")
+                    } else {
+                        (same, "Unicode normalization")
+                    }
+                }
+                Some(exact) => (vec![exact.to_owned()], ""),
+                None if icase => (matches, "case"),
+                None => (matches, "Unicode normalization"),
+            };
+            match matches.as_slice() {
+                [] => return Ok(None),
+                [entry] => entry.to_owned(),
+                _ => {
+                    let prefix = found.join("/");
+                    let candidates = matches
+                        .iter()
+                        .filter_map(|e| e.name())
+                        .map(|n| {
+                            let path = if prefix.is_empty() {
+                                n.to_owned()
+                            } else {
+                                format!("{prefix}/{n}")
+                            };
+                            if differ == "case" {
+                                format!("  {path}")
+                            } else {
+                                // Otherwise they look the same
+                                format!("  {path} ({})", spelling(&path))
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    let hint = if precompose {
+                        "\nSet `core.precomposeUnicode` to `false` to pick one by its exact spelling"
+                    } else {
+                        ""
+                    };
+                    anyhow::bail!(
+                        "`{tree_path}` matches several paths that differ only by {differ}:\n{}{hint}",
+                        candidates.join("\n")
+                    );
+                }
+            }
+        };
+        found.push(entry.name().expect("matched by name").to_owned());
+        if components.peek().is_some() {
+            let Ok(subtree) = entry.to_object(repo).and_then(|o| o.peel_to_tree()) else {
+                return Ok(None);
+            };
+            tree = subtree;
+        }
+    }
+    Ok(Some(found.join("/").into()))
+}
+
+/// How a path is spelled, to tell apart paths that look the same
+fn spelling(path: &str) -> &'static str {
+    if path.is_ascii() {
+        "ASCII"
+    } else if unicode_normalization::is_nfc(path) {
+        "composed"
+    } else if unicode_normalization::is_nfd(path) {
+        "decomposed"
+    } else {
+        "mixed normalization"
+    }
+}
+
+/// Canonical spelling of a path component for comparisons
+fn fold(name: &str, icase: bool) -> String {
+    use unicode_normalization::UnicodeNormalization as _;
+
+    let name = name.nfc().collect::<String>();
+    if icase {
+        name.to_lowercase()
+    } else {
+        name
+    }
+}
+
+pub(crate) const PRECOMPOSE_UNICODE: DefaultField<bool> =
+    RawField::<bool>::new("core.precomposeUnicode").default_value(|| false);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture(repo: &git2::Repository, files: &[&str]) -> git2::Oid {
+        let mut index = repo.index().unwrap();
+        let blob = repo.blob(b"content\n").unwrap();
+        for file in files {
+            let entry = git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100644,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: blob,
+                flags: 0,
+                flags_extended: 0,
+                path: file.as_bytes().to_vec(),
+            };
+            index.add(&entry).unwrap();
+        }
+        index.write_tree_to(repo).unwrap()
+    }
+
+    #[test]
+    fn parse_icase() {
+        assert_eq!(
+            Pathspec::parse(std::path::Path::new(":(icase)readme.MD")),
+            Pathspec {
+                path: "readme.MD".into(),
+                icase: true
+            }
+        );
+        assert_eq!(
+            Pathspec::parse(std::path::Path::new(":(glob)*.md")),
+            Pathspec {
+                path: ":(glob)*.md".into(),
+                icase: false
+            }
+        );
+        assert_eq!(
+            Pathspec::parse(std::path::Path::new("README.md")),
+            Pathspec {
+                path: "README.md".into(),
+                icase: false
+            }
+        );
+    }
+
+    #[test]
+    fn finds_canonical_case() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        let tree = fixture(&repo, &["README.md", "src/Main.rs"]);
+        let tree = repo.find_tree(tree).unwrap();
+
+        let find =
+            |p: &str| find_equivalent(&repo, &tree, std::path::Path::new(p), true, false).unwrap();
+        assert_eq!(find("readme.MD"), Some("README.md".into()));
+        assert_eq!(find("SRC/main.RS"), Some("src/Main.rs".into()));
+        assert_eq!(find("src"), Some("src".into()));
+        assert_eq!(find("missing.md"), None);
+        assert_eq!(find("readme.md/nested"), None);
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn ambiguous_case() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        let tree = fixture(&repo, &["docs/README.md", "docs/readme.md"]);
+        let tree = repo.find_tree(tree).unwrap();
+
+        let err = find_equivalent(
+            &repo,
+            &tree,
+            std::path::Path::new("docs/Readme.md"),
+            true,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`docs/Readme.md` matches several paths that differ only by case:
+  docs/README.md
+  docs/readme.md"
+        );
+        let exact = find_equivalent(
+            &repo,
+            &tree,
+            std::path::Path::new("docs/readme.md"),
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(exact, Some("docs/readme.md".into()));
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn finds_other_normalization() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        // Decomposed, as committed from macOS
+        let tree = fixture(&repo, &["cafe\u{301}/Menu.md"]);
+        let tree = repo.find_tree(tree).unwrap();
+
+        let find =
+            |p: &str, icase| find_equivalent(&repo, &tree, std::path::Path::new(p), icase, false);
+        assert_eq!(
+            find("caf\u{e9}/Menu.md", false).unwrap(),
+            Some("cafe\u{301}/Menu.md".into())
+        );
+        assert_eq!(find("caf\u{e9}/menu.md", false).unwrap(), None);
+        assert_eq!(
+            find("CAF\u{c9}/menu.md", true).unwrap(),
+            Some("cafe\u{301}/Menu.md".into())
+        );
+
+        let tree = fixture(&repo, &["caf\u{e9}.md", "cafe\u{301}.md"]);
+        let tree = repo.find_tree(tree).unwrap();
+        let find = |p: &str| find_equivalent(&repo, &tree, std::path::Path::new(p), false, false);
+        assert_eq!(find("caf\u{e9}.md").unwrap(), Some("caf\u{e9}.md".into()));
+        assert_eq!(
+            find("cafe\u{301}.md").unwrap(),
+            Some("cafe\u{301}.md".into())
+        );
+
+        root.close().unwrap();
+    }
+
+    #[test]
+    fn precomposed_normalizations_are_one_name() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        let tree = fixture(&repo, &["caf\u{e9}.md", "cafe\u{301}.md", "Caf\u{e9}.md"]);
+        let tree = repo.find_tree(tree).unwrap();
+
+        let find = |p: &str| find_equivalent(&repo, &tree, std::path::Path::new(p), false, true);
+        let err = find("caf\u{e9}.md").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`caf\u{e9}.md` matches several paths that differ only by Unicode normalization:
+  cafe\u{301}.md (decomposed)
+  caf\u{e9}.md (composed)
+Set `core.precomposeUnicode` to `false` to pick one by its exact spelling"
+        );
+        // Only normalization is ignored, not case
+        assert_eq!(find("Caf\u{e9}.md").unwrap(), Some("Caf\u{e9}.md".into()));
+
+        let tree = fixture(&repo, &["cafe\u{301}/Menu.md"]);
+        let tree = repo.find_tree(tree).unwrap();
+        let find = |p: &str| find_equivalent(&repo, &tree, std::path::Path::new(p), false, true);
+        assert_eq!(
+            find("caf\u{e9}/Menu.md").unwrap(),
+            Some("cafe\u{301}/Menu.md".into())
+        );
+
+        root.close().unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+use crate::git2_config::Config;
+use crate::git2_config::ConfigSource as _;
+use crate::git2_config::Field;
+use crate::git2_config::RawField;
+
+/// Prepend `dive.default-args` and expand `--profile <name>` into the flags stored in
+/// `dive.profile.<name>.args`
+///
+/// The default flags come first, then each profile's, then the ones given on the command line,
+/// so the explicit flags take precedence, per [`merge`].  Profiles may use other profiles but
+/// not themselves.
+pub(crate) fn expand(
+    config: &Config,
+    argv: impl IntoIterator<Item = std::ffi::OsString>,
+) -> anyhow::Result<Vec<std::ffi::OsString>> {
+    let mut argv = argv.into_iter();
+    let bin = argv.next();
+    let rest = argv.collect::<Vec<_>>();
+
+    let defaults = default_args(config)?;
+    let mut layers = Vec::new();
+    let mut stack = Vec::new();
+    for name in profiles_named(&defaults)
+        .into_iter()
+        .chain(profiles_named(&rest))
+    {
+        expand_profile(config, &name, &mut stack, &mut layers)?;
+    }
+    layers.insert(0, defaults);
+    layers.push(rest);
+
+    Ok(bin.into_iter().chain(merge(&layers)).collect())
+}
+
+/// Join layers of flags, lowest priority first
+///
+/// Each layer must parse on its own; clap still rejects a flag repeated within one.  Repeatable
+/// options, like `--ignore-rev` and `-C`, accumulate across layers.  Any other
+/// option is dropped from a layer when a later layer sets it or something it conflicts with,
+/// rather than leaving clap to reject the combination.  Which options repeat comes from clap.
+pub(crate) fn merge(layers: &[Vec<std::ffi::OsString>]) -> Vec<std::ffi::OsString> {
+    let mut command = <crate::args::Args as clap::CommandFactory>::command();
+    command.build();
+    let parsed = layers
+        .iter()
+        .map(|layer| occurrences(&command, layer))
+        .collect::<Vec<_>>();
+
+    let mut merged = Vec::new();
+    for (i, (layer, occurrences)) in layers.iter().zip(&parsed).enumerate() {
+        let later = parsed[i + 1..]
+            .iter()
+            .flatten()
+            .filter_map(|(arg, _)| *arg)
+            .collect::<Vec<_>>();
+        for (arg, range) in occurrences {
+            let overridden = arg.is_some_and(|arg| {
+                later.iter().any(|later| {
+                    (later.get_id() == arg.get_id() && !is_repeatable(arg))
+                        || conflicts(&command, arg, later)
+                })
+            });
+            if overridden {
+                log::debug!("dropping {:?} for a later flag", &layer[range.clone()]);
+            } else {
+                merged.extend_from_slice(&layer[range.clone()]);
+            }
+        }
+    }
+    merged
+}
+
+/// Each option in `args` with the range of arguments it and its values take up
+///
+/// Positional arguments, unknown options, and bundled short flags aren't matched to an option.
+fn occurrences<'c>(
+    command: &'c clap::Command,
+    args: &[std::ffi::OsString],
+) -> Vec<(Option<&'c clap::Arg>, std::ops::Range<usize>)> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let start = i;
+        let arg = args[i].to_string_lossy();
+        i += 1;
+        if arg == "--" {
+            found.push((None, start..args.len()));
+            break;
+        }
+        let (option, inline_value) = if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline_value) = match long.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (long, false),
+            };
+            let option = command.get_arguments().find(|a| a.get_long() == Some(name));
+            (option, inline_value)
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            let mut chars = shorts.chars();
+            let short = chars.next();
+            let option = command
+                .get_arguments()
+                .find(|a| a.get_short() == short)
+                .filter(|a| chars.as_str().is_empty() || a.get_action().takes_values());
+            (option, !chars.as_str().is_empty())
+        } else {
+            (None, false)
+        };
+        if let Some(option) = option {
+            if !inline_value && option.get_action().takes_values() {
+                let values = option.get_num_args().map(|n| n.min_values()).unwrap_or(1);
+                i = (i + values).min(args.len());
+            }
+        }
+        found.push((option, start..i));
+    }
+    found
+}
+
+fn is_repeatable(arg: &clap::Arg) -> bool {
+    matches!(
+        arg.get_action(),
+        clap::ArgAction::Append | clap::ArgAction::Count
+    )
+}
+
+fn conflicts(command: &clap::Command, a: &clap::Arg, b: &clap::Arg) -> bool {
+    let conflicts_with = |a: &clap::Arg, b: &clap::Arg| {
+        command
+            .get_arg_conflicts_with(a)
+            .iter()
+            .any(|c| c.get_id() == b.get_id())
+    };
+    conflicts_with(a, b) || conflicts_with(b, a)
+}
+
+/// `dive.default-args`, split like a shell would
+///
+/// Modes like `--dump-config` are rejected since they'd conflict with the one on the command
+/// line.
+fn default_args(config: &Config) -> anyhow::Result<Vec<std::ffi::OsString>> {
+    let Some(raw) = config.get(&DEFAULT_ARGS) else {
+        return Ok(Vec::new());
+    };
+    let args = shlex::split(&raw).ok_or_else(|| {
+        anyhow::format_err!("could not parse `{}`: {}", Field::name(&DEFAULT_ARGS), raw)
+    })?;
+    log::debug!("default args: {:?}", args);
+
+    let mut command = <crate::args::Args as clap::CommandFactory>::command();
+    // Fills in the group's args from each arg's `group`
+    command.build();
+    let modes = command
+        .get_groups()
+        .filter(|group| group.get_id() == "mode")
+        .flat_map(|group| group.get_args())
+        .filter_map(|id| command.get_arguments().find(|arg| arg.get_id() == id))
+        .filter_map(|arg| arg.get_long())
+        .collect::<Vec<_>>();
+    for arg in &args {
+        let Some(long) = arg.strip_prefix("--") else {
+            continue;
+        };
+        let long = long.split('=').next().unwrap_or(long);
+        if modes.contains(&long) {
+            anyhow::bail!(
+                "`{}` can't include `--{long}`; pass it on the command line",
+                Field::name(&DEFAULT_ARGS)
+            );
+        }
+    }
+
+    Ok(args.into_iter().map(std::ffi::OsString::from).collect())
+}
+
+fn expand_profile(
+    config: &Config,
+    name: &str,
+    stack: &mut Vec<String>,
+    layers: &mut Vec<Vec<std::ffi::OsString>>,
+) -> anyhow::Result<()> {
+    if stack.iter().any(|n| n == name) {
+        stack.push(name.to_owned());
+        anyhow::bail!(
+            "profile `{}` refers to itself: {}",
+            name,
+            stack.join(" -> ")
+        );
+    }
+    let args = config.get_string(&args_key(name)).map_err(|_| {
+        let available = available(config);
+        if available.is_empty() {
+            anyhow::format_err!("unknown profile `{}`; no profiles are defined", name)
+        } else {
+            anyhow::format_err!(
+                "unknown profile `{}`; available profiles: {}",
+                name,
+                available.join(", ")
+            )
+        }
+    })?;
+    let args = shlex::split(&args)
+        .ok_or_else(|| anyhow::format_err!("could not parse `{}`: {}", args_key(name), args))?;
+    log::debug!("profile `{}`: {:?}", name, args);
+
+    let args = args
+        .into_iter()
+        .map(std::ffi::OsString::from)
+        .collect::<Vec<_>>();
+    stack.push(name.to_owned());
+    for nested in profiles_named(&args) {
+        expand_profile(config, &nested, stack, layers)?;
+    }
+    stack.pop();
+    layers.push(args);
+    Ok(())
+}
+
+/// The values given to `--profile`, stopping at `--`
+fn profiles_named(args: &[std::ffi::OsString]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut args = args.iter().map(|a| a.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        } else if arg == "--profile" {
+            if let Some(name) = args.next() {
+                names.push(name.into_owned());
+            }
+        } else if let Some(name) = arg.strip_prefix("--profile=") {
+            names.push(name.to_owned());
+        }
+    }
+    names
+}
+
+/// Defined profiles, with the source of each
+pub(crate) fn defined(config: &Config) -> Vec<(String, String, String)> {
+    let mut profiles = Vec::new();
+    let Ok(entries) = config.entries("dive") else {
+        return profiles;
+    };
+    for (key, _) in entries {
+        let Some(name) = key
+            .strip_prefix("dive.profile.")
+            .and_then(|k| k.strip_suffix(".args"))
+        else {
+            continue;
+        };
+        if profiles.iter().any(|(n, _, _)| n == name) {
+            continue;
+        }
+        let key = args_key(name);
+        let Ok(args) = config.get_string(&key) else {
+            continue;
+        };
+        let source = config.get_source(&key).unwrap_or("unknown").to_owned();
+        profiles.push((name.to_owned(), args, source));
+    }
+    profiles.sort();
+    profiles
+}
+
+/// Whether `key` is a profile's `args`, which can't be a regular field since profiles are named
+pub(crate) fn is_profile_key(key: &str) -> bool {
+    key.strip_prefix("dive.profile.")
+        .and_then(|k| k.strip_suffix(".args"))
+        .is_some_and(|name| !name.is_empty())
+}
+
+fn available(config: &Config) -> Vec<String> {
+    defined(config)
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect()
+}
+
+fn args_key(name: &str) -> String {
+    format!("dive.profile.{name}.args")
+}
+
+pub(crate) const DEFAULT_ARGS: RawField<String> = RawField::<String>::new("dive.default-args");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn merged(layers: &[&[&str]]) -> Vec<String> {
+        let layers = layers
+            .iter()
+            .map(|layer| layer.iter().map(std::ffi::OsString::from).collect())
+            .collect::<Vec<_>>();
+        merge(&layers)
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn repeatable_options_accumulate() {
+        assert_eq!(
+            merged(&[
+                &["--ignore-rev", "a", "-C", "x"],
+                &["--ignore-rev=b", "-Cy", "FILE"]
+            ]),
+            [
+                "--ignore-rev",
+                "a",
+                "-C",
+                "x",
+                "--ignore-rev=b",
+                "-Cy",
+                "FILE"
+            ]
+        );
+        assert_eq!(merged(&[&["-v"], &["-vv"]]), ["-v", "-vv"]);
+    }
+
+    #[test]
+    fn later_scalars_win() {
+        assert_eq!(
+            merged(&[
+                &["--date", "relative", "--first-parent"],
+                &["--format=json"],
+                &["--date=short", "--first-parent", "FILE"]
+            ]),
+            ["--format=json", "--date=short", "--first-parent", "FILE"]
+        );
+        // Two values, both dropped
+        assert_eq!(
+            merged(&[&["--compare", "a", "b"], &["--compare", "c", "d"]]),
+            ["--compare", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn later_conflicts_win() {
+        assert_eq!(
+            merged(&[&["--absolute-paths", "--stat"], &["--relative-paths"]]),
+            ["--stat", "--relative-paths"]
+        );
+        assert_eq!(
+            merged(&[&["--stat"], &["--compare", "a", "b", "FILE"]]),
+            ["--compare", "a", "b", "FILE"]
+        );
+        // Positionals and anything after `--` are kept
+        assert_eq!(
+            merged(&[&["--", "--stat"], &["--stat"]]),
+            ["--", "--stat", "--stat"]
+        );
+    }
+
+    #[test]
+    fn finds_profile_names() {
+        let args = ["--profile", "a", "--profile=b", "FILE", "--", "--profile=c"]
+            .map(std::ffi::OsString::from);
+        assert_eq!(profiles_named(&args), ["a", "b"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Progress for slow work, as a line on stderr rewritten in place
+//!
+//! Lines rewritten with `\r` are only safe on a terminal nothing else is drawing on.  A pager
+//! shown on the terminal stderr goes to owns the screen, and progress written under it is
+//! interleaved with its display and left behind once cleared, so progress is suppressed for
+//! as long as one is running there.
+
+use std::io::Write as _;
+
+/// How long work runs before progress is shown, so quick runs don't flash
+const DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+/// How often the line is rewritten
+const INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// What stdout and stderr are connected to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Terminals {
+    pub(crate) stdout: bool,
+    pub(crate) stderr: bool,
+    /// Whether stdout and stderr are the same terminal
+    pub(crate) shared: bool,
+}
+
+impl Terminals {
+    fn query() -> Self {
+        use std::io::IsTerminal as _;
+        let stdout = std::io::stdout().is_terminal();
+        let stderr = std::io::stderr().is_terminal();
+        Self {
+            stdout,
+            stderr,
+            shared: stdout && stderr && same_terminal(),
+        }
+    }
+}
+
+/// Which pager, if any, is drawing on the terminal
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PagerState {
+    None,
+    /// One we started, which draws on our stdout
+    Ours,
+    /// One git started, per `GIT_PAGER_IN_USE`, with stdout being its pipe so we can't tell
+    /// which terminal it draws on
+    Git,
+}
+
+impl PagerState {
+    fn query() -> Self {
+        if crate::git_pager::pager_running() {
+            Self::Ours
+        } else if crate::git_pager::pager_in_use() {
+            Self::Git
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Whether progress can be drawn in place on stderr
+pub(crate) fn allowed(terminals: Terminals, pager: PagerState) -> bool {
+    if !terminals.stderr {
+        return false;
+    }
+    match pager {
+        PagerState::None => true,
+        // Only when stderr is a different terminal than the one being paged
+        PagerState::Ours => !(terminals.stdout && terminals.shared),
+        PagerState::Git => false,
+    }
+}
+
+pub(crate) struct Progress {
+    /// Unless quieted or stderr isn't a terminal
+    enabled: bool,
+    start: std::time::Instant,
+    last: Option<std::time::Instant>,
+    /// Columns of the line currently shown, to blank it out
+    shown: usize,
+}
+
+impl Progress {
+    pub(crate) fn new(args: &crate::args::Args) -> Self {
+        let quiet = args.verbose.log_level() < Some(log::Level::Info);
+        Self {
+            enabled: !quiet && Terminals::query().stderr,
+            start: std::time::Instant::now(),
+            last: None,
+            shown: 0,
+        }
+    }
+
+    /// Show `message`, if it's time to
+    pub(crate) fn update(&mut self, message: impl FnOnce() -> String) {
+        if !self.enabled {
+            return;
+        }
+        let now = std::time::Instant::now();
+        let due = match self.last {
+            Some(last) => INTERVAL <= now - last,
+            None => DELAY <= now - self.start,
+        };
+        if !due {
+            return;
+        }
+        self.last = Some(now);
+        if !allowed(Terminals::query(), PagerState::query()) {
+            // Whatever was shown is the pager's screen now, so leave it be
+            self.shown = 0;
+            return;
+        }
+        let message = crate::unicode::sanitize(&message());
+        let line = redraw(&message, self.shown);
+        self.shown = crate::unicode::display_width(&message);
+        let mut stderr = std::io::stderr().lock();
+        let _ = stderr.write_all(line.as_bytes());
+        let _ = stderr.flush();
+    }
+
+    /// Remove the line, before writing anything else to the terminal
+    pub(crate) fn clear(&mut self) {
+        if self.shown == 0 {
+            return;
+        }
+        if allowed(Terminals::query(), PagerState::query()) {
+            let mut stderr = std::io::stderr().lock();
+            let _ = stderr.write_all(redraw("", self.shown).as_bytes());
+            let _ = stderr.flush();
+        }
+        self.shown = 0;
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// `message` over a line `shown` columns wide, blanking what it doesn't cover
+fn redraw(message: &str, shown: usize) -> String {
+    let width = crate::unicode::display_width(message);
+    let blank = shown.saturating_sub(width);
+    let mut line = format!("\r{message}{:blank$}", "");
+    if blank != 0 {
+        line.push('\r');
+        line.push_str(message);
+    }
+    line
+}
+
+/// Whether stdout and stderr are the same device, like the same pseudo-terminal
+#[cfg(unix)]
+fn same_terminal() -> bool {
+    use std::os::fd::AsFd as _;
+    use std::os::unix::fs::MetadataExt as _;
+
+    fn metadata(fd: std::os::fd::BorrowedFd<'_>) -> std::io::Result<std::fs::Metadata> {
+        std::fs::File::from(fd.try_clone_to_owned()?).metadata()
+    }
+    match (
+        metadata(std::io::stdout().as_fd()),
+        metadata(std::io::stderr().as_fd()),
+    ) {
+        (Ok(stdout), Ok(stderr)) => {
+            (stdout.dev(), stdout.ino(), stdout.rdev())
+                == (stderr.dev(), stderr.ino(), stderr.rdev())
+        }
+        // Assume the worst, which only costs progress
+        _ => true,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_terminal() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SHARED: Terminals = Terminals {
+        stdout: true,
+        stderr: true,
+        shared: true,
+    };
+
+    #[test]
+    fn needs_a_terminal() {
+        let piped = Terminals {
+            stdout: true,
+            stderr: false,
+            shared: false,
+        };
+        assert!(!allowed(piped, PagerState::None));
+        assert!(allowed(SHARED, PagerState::None));
+    }
+
+    #[test]
+    fn hidden_under_a_pager() {
+        assert!(!allowed(SHARED, PagerState::Ours));
+        assert!(!allowed(SHARED, PagerState::Git));
+        // Git's pager has our stdout, so we can't tell it's elsewhere
+        let piped_stdout = Terminals {
+            stdout: false,
+            stderr: true,
+            shared: false,
+        };
+        assert!(!allowed(piped_stdout, PagerState::Git));
+    }
+
+    #[test]
+    fn shown_beside_a_pager() {
+        let separate = Terminals {
+            stdout: true,
+            stderr: true,
+            shared: false,
+        };
+        assert!(allowed(separate, PagerState::Ours));
+    }
+
+    #[test]
+    fn redraw_blanks_leftovers() {
+        assert_eq!(redraw("3 of 10", 0), "\r3 of 10");
+        assert_eq!(redraw("9 of 10", 7), "\r9 of 10");
+        assert_eq!(redraw("1 of 2", 8), "\r1 of 2  \r1 of 2");
+        assert_eq!(redraw("", 3), "\r   \r");
+    }
+}
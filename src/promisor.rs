@@ -0,0 +1,148 @@
+//! Partial clones, like `git clone --filter=blob:none`, leave out blobs until they are needed
+//!
+//! libgit2 can't fetch missing objects on demand, so blame fails partway through history.
+//! Instead, find every blob the file's history needs up front and fetch them in one go.
+
+use anyhow::Context as _;
+
+/// The remote missing objects can be fetched from, if this is a partial clone
+pub(crate) fn promisor_remote(repo: &git2::Repository) -> Option<String> {
+    let config = repo.config().ok()?.snapshot().ok()?;
+    if let Ok(remote) = config.get_string("extensions.partialClone") {
+        return Some(remote);
+    }
+    let remotes = repo.remotes().ok()?;
+    let remote = remotes.iter().flatten().find(|name| {
+        config
+            .get_bool(&format!("remote.{name}.promisor"))
+            .unwrap_or(false)
+    });
+    remote.map(ToOwned::to_owned)
+}
+
+/// Make sure every version of `tree_path` reachable from `rev_commit` is present
+///
+/// With `lazy_fetch`, missing blobs are fetched from the promisor remote in a single request;
+/// otherwise they are reported as an error.
+pub(crate) fn ensure_history(
+    repo: &git2::Repository,
+    rev_commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+    lazy_fetch: bool,
+) -> anyhow::Result<()> {
+    let Some(remote) = promisor_remote(repo) else {
+        return Ok(());
+    };
+    log::debug!("partial clone of `{remote}`");
+
+    let start = std::time::Instant::now();
+    let missing = missing_blobs(repo, rev_commit, tree_path)?;
+    log::debug!(
+        "found {} missing blobs for {} in {:?}",
+        missing.len(),
+        tree_path.display(),
+        start.elapsed()
+    );
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !lazy_fetch {
+        let mut message = format!(
+            "{} versions of {} are missing from this partial clone:",
+            missing.len(),
+            tree_path.display()
+        );
+        for (blob, commit) in &missing {
+            message.push_str(&format!("\n  {blob} (from {commit})"));
+        }
+        message.push_str("\nrun without `--no-lazy-fetch` to fetch them");
+        anyhow::bail!(message);
+    }
+
+    log::info!(
+        "note: fetching {} versions of {} from `{}` for this partial clone",
+        missing.len(),
+        tree_path.display(),
+        remote
+    );
+    fetch(repo, &remote, missing.iter().map(|(blob, _)| *blob))
+}
+
+/// Blobs of `tree_path` that aren't in the object database, with the commit introducing them
+fn missing_blobs(
+    repo: &git2::Repository,
+    rev_commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+) -> anyhow::Result<Vec<(git2::Oid, git2::Oid)>> {
+    let odb = repo.odb()?;
+    let mut walk = repo.revwalk()?;
+    walk.push(rev_commit.id())?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut missing = Vec::new();
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        let Some(blob) = entry_id(&commit, tree_path)? else {
+            continue;
+        };
+        if !seen.insert(blob) {
+            continue;
+        }
+        if !odb.exists(blob) {
+            missing.push((blob, commit.id()));
+        }
+    }
+    Ok(missing)
+}
+
+fn entry_id(
+    commit: &git2::Commit<'_>,
+    tree_path: &std::path::Path,
+) -> anyhow::Result<Option<git2::Oid>> {
+    match commit.tree()?.get_path(tree_path) {
+        Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => Ok(Some(entry.id())),
+        Ok(_) => Ok(None),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Fetch `ids` in one negotiation, like git does when it needs several promised objects
+fn fetch(
+    repo: &git2::Repository,
+    remote: &str,
+    ids: impl Iterator<Item = git2::Oid>,
+) -> anyhow::Result<()> {
+    let mut cmd = std::process::Command::new("git");
+    cmd.arg("--git-dir")
+        .arg(repo.path())
+        .args(["-c", "fetch.negotiationAlgorithm=noop"])
+        .args([
+            "fetch",
+            "--quiet",
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+            "--stdin",
+        ])
+        .arg(remote)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null());
+    log::trace!("running {cmd:?}");
+    let mut child = cmd.spawn().context("could not run `git fetch`")?;
+    {
+        use std::io::Write as _;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        for id in ids {
+            writeln!(stdin, "{id}").context("could not send objects to `git fetch`")?;
+        }
+    }
+    let status = child.wait().context("could not run `git fetch`")?;
+    if !status.success() {
+        anyhow::bail!("could not fetch missing objects from `{remote}` ({status})");
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! The earliest release tag containing each commit, for `--show-release`
+//!
+//! Finding it walks history for every tag, so answers are cached in the cache dir, in a file per
+//! tag glob and set of matching tags.  Moving or adding a tag starts a new file rather than
+//! serving stale answers, replacing the old one, so each repository and glob keeps one file.
+
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// Shown for commits that no matching tag contains
+pub(crate) const UNRELEASED: &str = "unreleased";
+
+/// What release, if any, first shipped each blamed commit
+#[derive(Default, Debug)]
+pub(crate) struct Releases {
+    /// `None` being unreleased
+    tags: std::collections::HashMap<git2::Oid, Option<String>>,
+}
+
+impl Releases {
+    /// Look up `commits`, each distinct commit once, among tags matching `glob`
+    ///
+    /// With `refresh`, every commit is looked up again and the cache rewritten.
+    pub(crate) fn resolve(
+        repo: &git2::Repository,
+        glob: &str,
+        refresh: bool,
+        commits: impl IntoIterator<Item = git2::Oid>,
+    ) -> Self {
+        let start = std::time::Instant::now();
+        let tags = release_tags(repo, glob);
+        let cache = crate::git_pager::cache_dir().map(|dir| {
+            dir.join("releases")
+                .join(glob_key(repo, glob).to_string())
+                .join(state_key(glob, &tags).to_string())
+        });
+        let mut cached = cache
+            .as_deref()
+            .filter(|_| !refresh)
+            .and_then(|cache| std::fs::read_to_string(cache).ok())
+            .map(|cached| read_cache(&cached))
+            .unwrap_or_default();
+
+        let mut misses = 0;
+        let mut releases = Self::default();
+        for commit in commits {
+            if commit.is_zero() || releases.tags.contains_key(&commit) {
+                continue;
+            }
+            let release = if let Some(release) = cached.get(&commit) {
+                release.clone()
+            } else {
+                misses += 1;
+                let release = earliest_containing(repo, &tags, commit);
+                cached.insert(commit, release.clone());
+                release
+            };
+            releases.tags.insert(commit, release);
+        }
+        log::debug!(
+            "found releases for {} commits among {} tags in {:?}, {misses} uncached",
+            releases.tags.len(),
+            tags.len(),
+            start.elapsed()
+        );
+
+        if let Some(cache) = cache.filter(|_| misses != 0) {
+            if let Err(err) = store(&cache, &write_cache(&cached)) {
+                log::debug!("could not cache releases in {}: {}", cache.display(), err);
+            }
+        }
+        releases
+    }
+
+    /// The tag, or [`UNRELEASED`], for a commit passed to [`Releases::resolve`]
+    pub(crate) fn get(&self, commit: git2::Oid) -> Option<&str> {
+        let release = self.tags.get(&commit)?;
+        Some(release.as_deref().unwrap_or(UNRELEASED))
+    }
+
+    /// The widest of what [`Releases::get`] returns
+    pub(crate) fn width(&self) -> usize {
+        self.tags
+            .values()
+            .map(|release| crate::unicode::display_width(release.as_deref().unwrap_or(UNRELEASED)))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A matching tag and the commit it points to
+struct ReleaseTag {
+    name: String,
+    commit: git2::Oid,
+    time: i64,
+}
+
+/// Tags matching `glob` that point to commits, oldest first
+fn release_tags(repo: &git2::Repository, glob: &str) -> Vec<ReleaseTag> {
+    let names = match repo.tag_names(Some(glob)) {
+        Ok(names) => names,
+        Err(err) => {
+            log::debug!("could not list tags matching `{glob}`: {err}");
+            return Vec::new();
+        }
+    };
+    let mut tags = names
+        .iter()
+        .flatten()
+        .filter_map(|name| {
+            let commit = repo
+                .revparse_single(&format!("refs/tags/{name}"))
+                .and_then(|object| object.peel_to_commit())
+                .ok()?;
+            Some(ReleaseTag {
+                name: name.to_owned(),
+                commit: commit.id(),
+                time: commit.time().seconds(),
+            })
+        })
+        .collect::<Vec<_>>();
+    tags.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.name.cmp(&b.name)));
+    tags
+}
+
+/// Identifies the repository and `glob`, for the files superseded as its tags move
+fn glob_key(repo: &git2::Repository, glob: &str) -> git2::Oid {
+    let common_dir = crate::boundary::common_dir(repo);
+    let state = format!("{}\n{glob}\n", common_dir.display());
+    git2::Oid::hash_object(git2::ObjectType::Blob, state.as_bytes()).unwrap_or(git2::Oid::zero())
+}
+
+/// Identifies `glob` and what its tags point to, so moving a tag misses the cache
+fn state_key(glob: &str, tags: &[ReleaseTag]) -> git2::Oid {
+    let mut state = format!("{glob}\n");
+    for tag in tags {
+        state.push_str(&format!("{}\t{}\n", tag.commit, tag.name));
+    }
+    git2::Oid::hash_object(git2::ObjectType::Blob, state.as_bytes()).unwrap_or(git2::Oid::zero())
+}
+
+fn earliest_containing(
+    repo: &git2::Repository,
+    tags: &[ReleaseTag],
+    commit: git2::Oid,
+) -> Option<String> {
+    tags.iter()
+        .find(|tag| {
+            tag.commit == commit
+                || repo
+                    .graph_descendant_of(tag.commit, commit)
+                    .unwrap_or(false)
+        })
+        .map(|tag| tag.name.clone())
+}
+
+/// Replace `cache` whole, so a run at the same time never reads part of it, then remove the
+/// files for what the glob's tags used to point to
+fn store(cache: &std::path::Path, content: &str) -> std::io::Result<()> {
+    let dir = cache.parent().expect("cache file is in a dir");
+    std::fs::create_dir_all(dir)?;
+    let name = cache.file_name().expect("cache file has a name");
+    // Dotted, so they aren't removed as superseded while being written
+    let temp = dir.join(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    std::fs::write(&temp, content)?;
+    if let Err(err) = std::fs::rename(&temp, cache) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let superseded =
+            entry.file_name() != name && !entry.file_name().to_string_lossy().starts_with('.');
+        if superseded {
+            log::trace!("removing superseded {}", entry.path().display());
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// `COMMIT\tTAG` lines, the tag being empty when unreleased
+fn read_cache(cached: &str) -> std::collections::HashMap<git2::Oid, Option<String>> {
+    cached
+        .lines()
+        .filter_map(|line| {
+            let (commit, tag) = line.split_once('\t')?;
+            let commit = git2::Oid::from_str(commit).ok()?;
+            Some((commit, (!tag.is_empty()).then(|| tag.to_owned())))
+        })
+        .collect()
+}
+
+fn write_cache(releases: &std::collections::HashMap<git2::Oid, Option<String>>) -> String {
+    let mut lines = releases
+        .iter()
+        .map(|(commit, tag)| format!("{commit}\t{}\n", tag.as_deref().unwrap_or("")))
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.concat()
+}
+
+pub(crate) const RELEASE_TAG_GLOB: DefaultField<String> =
+    RawField::<String>::new("dive.release-tag-glob").default_value(|| "v*".to_owned());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cache_round_trips() {
+        let released = git2::Oid::from_str("4b3c39f").unwrap();
+        let unreleased = git2::Oid::from_str("5908209").unwrap();
+        let releases = [(released, Some("v1.0.0".to_owned())), (unreleased, None)]
+            .into_iter()
+            .collect();
+        let written = write_cache(&releases);
+        assert_eq!(read_cache(&written), releases);
+        assert_eq!(read_cache("not a commit\tv1.0.0\n"), Default::default());
+    }
+}
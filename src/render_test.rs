@@ -0,0 +1,36 @@
+//! `--render-test`: output that is byte-for-byte the same on every machine, for snapshot tests
+
+use crate::git2_config::Config;
+
+static ENABLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Terminal width, wide enough that the default gutter isn't shrunk
+pub(crate) const WIDTH: usize = 80;
+
+/// 2024-01-01T00:00:00Z, for relative dates and `highlightRecent`
+pub(crate) const NOW: i64 = 1_704_067_200;
+
+/// Fix the width and clock, force colors on, disable the pager, and drop config that isn't
+/// from the repo or the environment, like `~/.gitconfig`
+pub(crate) fn enable(config: &mut Config, args: &mut crate::args::Args) {
+    log::debug!("rendering for tests");
+    ENABLED.store(true, std::sync::atomic::Ordering::Relaxed);
+    config.isolate();
+    args.no_pager = true;
+    anstream::ColorChoice::Always.write_global();
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// The terminal's width, falling back to `COLUMNS`, then 80
+pub(crate) fn terminal_width() -> usize {
+    if enabled() {
+        return WIDTH;
+    }
+    terminal_size::terminal_size()
+        .map(|(w, _h)| w.0)
+        .or_else(|| std::env::var_os("COLUMNS").and_then(|s| s.to_str()?.parse::<u16>().ok()))
+        .unwrap_or(80) as usize
+}
@@ -0,0 +1,405 @@
+use crate::blame::Annotation;
+use crate::blame::Annotations;
+use crate::blame::Attribution;
+
+/// A way to write blame output, fed one line at a time
+pub(crate) trait Renderer {
+    fn begin_file(&mut self, path: &str) -> anyhow::Result<()>;
+    fn line(&mut self, line: &AnnotatedLine<'_>, commit: &Annotation) -> anyhow::Result<()>;
+    fn end_file(&mut self) -> anyhow::Result<()>;
+    /// After the last file
+    fn finish(&mut self) -> anyhow::Result<()>;
+}
+
+/// A line of the file with what blame found for it
+pub(crate) struct AnnotatedLine<'a> {
+    /// 1-based
+    pub(crate) number: usize,
+    /// Without the line ending or a leading BOM
+    pub(crate) text: &'a str,
+    pub(crate) attribution: &'a Attribution,
+    /// The first line, or a different commit than the line before it
+    pub(crate) hunk_start: bool,
+    /// A line a merge introduced itself, with `--detect-resolution`
+    pub(crate) resolution: bool,
+}
+
+/// Feed a blamed file through `renderer`
+pub(crate) fn render(
+    renderer: &mut dyn Renderer,
+    path: &str,
+    file: &str,
+    attributions: &[Attribution],
+    annotations: &Annotations,
+    resolutions: &[bool],
+) -> anyhow::Result<()> {
+    let start = std::time::Instant::now();
+    renderer.begin_file(path)?;
+    let mut prev_commit = None;
+    let mut line_count = 0;
+    for (i, text) in file.lines().enumerate() {
+        let number = i + 1;
+        let text = if number == 1 {
+            text.strip_prefix('\u{feff}').unwrap_or(text)
+        } else {
+            text
+        };
+        let attribution = attributions
+            .get(i)
+            .unwrap_or_else(|| panic!("Mismatch in line numbers between dive ({number}) and git2"));
+        let line = AnnotatedLine {
+            number,
+            text,
+            attribution,
+            hunk_start: prev_commit != Some(attribution.commit),
+            resolution: resolutions.get(i).copied().unwrap_or(false),
+        };
+        prev_commit = Some(attribution.commit);
+        let commit = annotations
+            .notes
+            .get(&attribution.commit)
+            .expect("all blame hunks are annotated");
+        renderer.line(&line, commit)?;
+        line_count = number;
+    }
+    renderer.end_file()?;
+    renderer.finish()?;
+    log::debug!("rendered {} lines in {:?}", line_count, start.elapsed());
+    Ok(())
+}
+
+/// One `PATH:LINE: HASH AUTHOR DATE SUMMARY` entry per line, for editors' quickfix lists
+pub(crate) struct QuickfixRenderer<W> {
+    out: W,
+    hunks_only: bool,
+    path: String,
+}
+
+impl<W: std::io::Write> QuickfixRenderer<W> {
+    pub(crate) fn new(out: W, hunks_only: bool) -> Self {
+        Self {
+            out,
+            hunks_only,
+            path: String::new(),
+        }
+    }
+}
+
+impl<W: std::io::Write> Renderer for QuickfixRenderer<W> {
+    fn begin_file(&mut self, path: &str) -> anyhow::Result<()> {
+        path.clone_into(&mut self.path);
+        Ok(())
+    }
+
+    fn line(&mut self, line: &AnnotatedLine<'_>, commit: &Annotation) -> anyhow::Result<()> {
+        if self.hunks_only && !line.hunk_start {
+            return Ok(());
+        }
+        let _ = writeln!(
+            self.out,
+            "{}:{}: {} {} {} {}",
+            self.path,
+            line.number,
+            commit.short,
+            commit.author,
+            crate::date::format_short(commit.time),
+            commit.subject
+        );
+        Ok(())
+    }
+
+    fn end_file(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let _ = self.out.flush();
+        Ok(())
+    }
+}
+
+/// An array of files, each with every line and the commit it came from
+pub(crate) struct JsonRenderer<W> {
+    out: W,
+    /// Indented over many lines, rather than all on one
+    pretty: bool,
+    /// Each file as an object on its own line, rather than an array
+    records: bool,
+    files: Vec<JsonFile>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct JsonFile {
+    path: String,
+    /// Why the file wasn't annotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<String>,
+    lines: Vec<JsonLine>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonLine {
+    line: usize,
+    commit: String,
+    /// The line's number as of `commit`
+    orig_line: usize,
+    /// Relative to `REV` when possible, like the text output's gutter
+    origin: String,
+    /// First-parent commits back from `REV`, counting merged-in commits as their merge
+    first_parent_distance: Option<usize>,
+    author: String,
+    date: String,
+    time: i64,
+    summary: String,
+    /// Ignored commits that were looked past, newest first
+    ignored_via: Vec<String>,
+    resolution: bool,
+    /// The file before `commit`, `null` when `commit` added it
+    previous: Option<JsonPrevious>,
+    /// What `commit` reverts or cherry-picks, with `--resolve-reverts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    via: Option<JsonVia>,
+    /// `root` or `shallow` when `commit` is where history stops
+    #[serde(default)]
+    boundary: Option<String>,
+    content: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonVia {
+    /// `revert` or `cherry-pick`
+    kind: String,
+    /// The original commit
+    commit: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonPrevious {
+    commit: String,
+    /// From the top of the repo, like `git blame --porcelain`
+    path: String,
+}
+
+impl<W: std::io::Write> JsonRenderer<W> {
+    pub(crate) fn new(out: W) -> Self {
+        Self {
+            out,
+            pretty: true,
+            records: false,
+            files: Vec::new(),
+        }
+    }
+
+    /// Each file on its own line, for JSON lines like `--stdin-paths`
+    pub(crate) fn records(out: W) -> Self {
+        Self {
+            pretty: false,
+            records: true,
+            ..Self::new(out)
+        }
+    }
+
+    /// On one line, for line-delimited protocols
+    pub(crate) fn compact(out: W) -> Self {
+        Self {
+            pretty: false,
+            ..Self::new(out)
+        }
+    }
+
+    /// Write out files that were already rendered, like by the daemon
+    pub(crate) fn from_files(out: W, files: Vec<JsonFile>) -> Self {
+        Self {
+            files,
+            ..Self::new(out)
+        }
+    }
+
+    /// A file without any lines, saying why
+    pub(crate) fn skip_file(&mut self, path: &str, reason: &str) {
+        self.files.push(JsonFile {
+            path: path.to_owned(),
+            skipped: Some(reason.to_owned()),
+            lines: Vec::new(),
+        });
+    }
+}
+
+impl<W: std::io::Write> Renderer for JsonRenderer<W> {
+    fn begin_file(&mut self, path: &str) -> anyhow::Result<()> {
+        self.files.push(JsonFile {
+            path: path.to_owned(),
+            skipped: None,
+            lines: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn line(&mut self, line: &AnnotatedLine<'_>, commit: &Annotation) -> anyhow::Result<()> {
+        let file = self
+            .files
+            .last_mut()
+            .expect("`begin_file` is called before `line`");
+        file.lines.push(JsonLine {
+            line: line.number,
+            commit: line.attribution.commit.to_string(),
+            orig_line: line.attribution.line,
+            origin: commit.origin().to_owned(),
+            first_parent_distance: commit.distance,
+            author: commit.author.clone(),
+            date: crate::date::format_short(commit.time),
+            time: commit.time.seconds(),
+            summary: commit.summary.clone(),
+            ignored_via: line
+                .attribution
+                .ignored_via
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            resolution: line.resolution,
+            previous: line
+                .attribution
+                .previous
+                .as_ref()
+                .map(|previous| JsonPrevious {
+                    commit: previous.commit.to_string(),
+                    path: previous.path.display().to_string(),
+                }),
+            via: commit.via.map(|via| JsonVia {
+                kind: via.kind.to_string(),
+                commit: via.commit.to_string(),
+            }),
+            boundary: commit.boundary.map(|boundary| boundary.to_string()),
+            content: line.text.to_owned(),
+        });
+        Ok(())
+    }
+
+    fn end_file(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let output = if self.records {
+            let mut records = String::new();
+            for file in &self.files {
+                records.push_str(&serde_json::to_string(file)?);
+                records.push('\n');
+            }
+            records
+        } else {
+            let mut output = if self.pretty {
+                serde_json::to_string_pretty(&self.files)?
+            } else {
+                serde_json::to_string(&self.files)?
+            };
+            output.push('\n');
+            output
+        };
+        let _ = self.out.write_all(output.as_bytes());
+        let _ = self.out.flush();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture(repo: &git2::Repository) -> (Vec<Attribution>, Annotations) {
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let first = repo.commit(None, &sig, &sig, "First", &tree, &[]).unwrap();
+        let parent = repo.find_commit(first).unwrap();
+        let second = repo
+            .commit(None, &sig, &sig, "Second", &tree, &[&parent])
+            .unwrap();
+        let attributions = [first, first, second, first]
+            .into_iter()
+            .enumerate()
+            .map(|(i, commit)| Attribution {
+                commit,
+                path: None,
+                line: i + 1,
+                ignored_via: Vec::new(),
+                previous: None,
+            })
+            .collect::<Vec<_>>();
+        let annotations = Annotations::new(
+            repo,
+            crate::date::DateKind::Author,
+            attributions.iter().map(|a| a.commit),
+        );
+        (attributions, annotations)
+    }
+
+    #[test]
+    fn json_and_quickfix_agree() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let repo = git2::Repository::init(root.path().unwrap()).unwrap();
+        let (attributions, annotations) = fixture(&repo);
+        let file = "\u{feff}one\ntwo\nthree\nfour";
+        let resolutions = vec![false; attributions.len()];
+
+        let mut json = Vec::new();
+        render(
+            &mut JsonRenderer::new(&mut json),
+            "basic.txt",
+            file,
+            &attributions,
+            &annotations,
+            &resolutions,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let json_lines = json[0]["lines"].as_array().unwrap();
+
+        let mut quickfix = Vec::new();
+        render(
+            &mut QuickfixRenderer::new(&mut quickfix, false),
+            "basic.txt",
+            file,
+            &attributions,
+            &annotations,
+            &resolutions,
+        )
+        .unwrap();
+        let quickfix = String::from_utf8(quickfix).unwrap();
+        let quickfix_lines = quickfix.lines().collect::<Vec<_>>();
+
+        assert_eq!(json[0]["path"], "basic.txt");
+        assert_eq!(json_lines.len(), attributions.len());
+        assert_eq!(quickfix_lines.len(), attributions.len());
+        for ((json_line, quickfix_line), attribution) in
+            json_lines.iter().zip(&quickfix_lines).zip(&attributions)
+        {
+            let commit = json_line["commit"].as_str().unwrap();
+            assert_eq!(commit, attribution.commit.to_string());
+            let short = quickfix_line.split(' ').nth(1).unwrap();
+            assert!(commit.starts_with(short), "{commit} {short}");
+        }
+        assert_eq!(json_lines[0]["content"], "one");
+        assert_eq!(json_lines[3]["summary"], "First");
+
+        let mut hunks = Vec::new();
+        render(
+            &mut QuickfixRenderer::new(&mut hunks, true),
+            "basic.txt",
+            file,
+            &attributions,
+            &annotations,
+            &resolutions,
+        )
+        .unwrap();
+        let hunks = String::from_utf8(hunks).unwrap();
+        let numbers = hunks
+            .lines()
+            .map(|l| l.split(':').nth(1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, ["1", "3", "4"]);
+
+        root.close().unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// Commits swapped out by `git replace`, like grafts stitching together histories
+///
+/// libgit2 doesn't honor replace refs, so history is walked through these instead.
+#[derive(Default, Debug)]
+pub(crate) struct Replacements {
+    commits: std::collections::HashMap<git2::Oid, git2::Oid>,
+}
+
+impl Replacements {
+    /// Load `refs/replace/*` unless disabled by `--no-replace-objects`, `GIT_NO_REPLACE_OBJECTS`,
+    /// or `core.useReplaceRefs`
+    pub(crate) fn load(
+        repo: &git2::Repository,
+        config: &Config,
+        args: &crate::args::Args,
+    ) -> anyhow::Result<Self> {
+        let mut replacements = Self::default();
+        if args.no_replace_objects
+            || std::env::var_os("GIT_NO_REPLACE_OBJECTS").is_some()
+            || !config.get(&USE_REPLACE_REFS)
+        {
+            return Ok(replacements);
+        }
+
+        for reference in repo.references_glob(&format!("{REPLACE_REF_BASE}*"))? {
+            let reference = reference?;
+            let Some(original) = reference
+                .name()
+                .and_then(|n| n.strip_prefix(REPLACE_REF_BASE))
+                .and_then(|n| git2::Oid::from_str(n).ok())
+            else {
+                continue;
+            };
+            let Ok(replacement) = reference.peel_to_commit() else {
+                // Only commit replacements affect history
+                continue;
+            };
+            log::debug!("{} is replaced by {}", original, replacement.id());
+            replacements.commits.insert(original, replacement.id());
+        }
+        Ok(replacements)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+    }
+
+    pub(crate) fn is_replaced(&self, id: git2::Oid) -> bool {
+        self.commits.contains_key(&id)
+    }
+
+    /// The first parent of `id`, as seen through any replacement
+    pub(crate) fn first_parent(
+        &self,
+        repo: &git2::Repository,
+        id: git2::Oid,
+    ) -> anyhow::Result<Option<git2::Oid>> {
+        let id = self.commits.get(&id).copied().unwrap_or(id);
+        Ok(repo.find_commit(id)?.parent_ids().next())
+    }
+
+    /// Every parent of `id`, as seen through any replacement
+    pub(crate) fn parents(
+        &self,
+        repo: &git2::Repository,
+        id: git2::Oid,
+    ) -> anyhow::Result<Vec<git2::Oid>> {
+        let id = self.commits.get(&id).copied().unwrap_or(id);
+        Ok(repo.find_commit(id)?.parent_ids().collect())
+    }
+}
+
+/// History newest first as seen through a [`crate::backend::Backend`], like a [`git2::Revwalk`]
+/// by time
+pub(crate) struct Walk<'a> {
+    backend: &'a dyn crate::backend::Backend,
+    queue: std::collections::BinaryHeap<(i64, git2::Oid)>,
+    /// Whether each commit seen so far is reachable from a hidden one
+    hidden: std::collections::HashMap<git2::Oid, bool>,
+    done: std::collections::HashSet<git2::Oid>,
+    /// Queued commits that aren't hidden, since the walk is over once there are none
+    shown: usize,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(backend: &'a dyn crate::backend::Backend) -> Self {
+        Self {
+            backend,
+            queue: Default::default(),
+            hidden: Default::default(),
+            done: Default::default(),
+            shown: 0,
+        }
+    }
+
+    /// Walk `id` and its ancestors
+    pub(crate) fn push(&mut self, id: git2::Oid) -> anyhow::Result<()> {
+        self.queue_commit(id, false)
+    }
+
+    /// Leave out `id` and its ancestors, like `git log ^id`
+    pub(crate) fn hide(&mut self, id: git2::Oid) -> anyhow::Result<()> {
+        self.queue_commit(id, true)
+    }
+
+    fn queue_commit(&mut self, id: git2::Oid, hidden: bool) -> anyhow::Result<()> {
+        match self.hidden.get_mut(&id) {
+            None => {
+                let time = self.backend.commit_time(id)?;
+                self.hidden.insert(id, hidden);
+                self.queue.push((time, id));
+                if !hidden {
+                    self.shown += 1;
+                }
+            }
+            Some(was_hidden) if hidden && !*was_hidden => {
+                *was_hidden = true;
+                if !self.done.contains(&id) {
+                    self.shown -= 1;
+                }
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
+    fn step(&mut self) -> anyhow::Result<Option<git2::Oid>> {
+        while self.shown != 0 {
+            let Some((_, id)) = self.queue.pop() else {
+                break;
+            };
+            self.done.insert(id);
+            let hidden = self.hidden[&id];
+            if !hidden {
+                self.shown -= 1;
+            }
+            for parent in self.backend.parents(id)? {
+                self.queue_commit(parent, hidden)?;
+            }
+            if !hidden {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = anyhow::Result<git2::Oid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step().transpose()
+    }
+}
+
+const REPLACE_REF_BASE: &str = "refs/replace/";
+
+pub(crate) const USE_REPLACE_REFS: DefaultField<bool> =
+    RawField::<bool>::new("core.useReplaceRefs").default_value(|| true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn walk_through_graft() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = git2::Repository::init(root_path).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let mut time = 0;
+        let mut commit = |message: &str, parents: &[git2::Oid]| {
+            time += 1;
+            let sig =
+                git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                    .unwrap();
+            let parents = parents
+                .iter()
+                .map(|id| repo.find_commit(*id).unwrap())
+                .collect::<Vec<_>>();
+            let parents = parents.iter().collect::<Vec<_>>();
+            repo.commit(None, &sig, &sig, message, &tree, &parents)
+                .unwrap()
+        };
+        let a = commit("A", &[]);
+        let b = commit("B", &[a]);
+        let c = commit("C", &[a]);
+        let x = commit("X", &[]);
+        let grafted = commit("X grafted onto C", &[c]);
+
+        let walk = |replacements: &Replacements| {
+            let backend = crate::backend::Git2::new(&repo, replacements);
+            let mut walk = Walk::new(&backend);
+            walk.push(x).unwrap();
+            walk.hide(b).unwrap();
+            walk.collect::<anyhow::Result<Vec<_>>>().unwrap()
+        };
+        assert_eq!(walk(&Replacements::default()), [x]);
+        let replacements = Replacements {
+            commits: [(x, grafted)].into_iter().collect(),
+        };
+        // `A` is reachable from `B`, so it's left out
+        assert_eq!(walk(&replacements), [x, c]);
+
+        root.close().unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::Error;
+
+/// Open the repo for `cwd` the way git would
+///
+/// `--git-dir` or `GIT_DIR` name the repo directly, in which case `cwd` is the top of the
+/// worktree unless `--work-tree`, `GIT_WORK_TREE`, or `core.worktree` say otherwise.
+/// Otherwise the repo is discovered from `cwd`, stopping at `GIT_CEILING_DIRECTORIES` and,
+/// unless `GIT_DISCOVERY_ACROSS_FILESYSTEM` is set, at filesystem boundaries.
+pub(crate) fn open(
+    cwd: &std::path::Path,
+    args: &crate::args::Args,
+) -> Result<git2::Repository, Error> {
+    let git_dir = args.git_dir.clone().or_else(|| env_path("GIT_DIR"));
+    let work_tree = args.work_tree.clone().or_else(|| env_path("GIT_WORK_TREE"));
+
+    let repo = if let Some(git_dir) = &git_dir {
+        let git_dir = cwd.join(git_dir);
+        log::debug!("opening repo at {}", git_dir.display());
+        git2::Repository::open_ext(
+            &git_dir,
+            git2::RepositoryOpenFlags::NO_SEARCH,
+            std::iter::empty::<&std::ffi::OsStr>(),
+        )
+        .map_err(|source| Error::RepoNotFound {
+            git_dir: Some(git_dir.clone()),
+            source,
+        })?
+    } else {
+        let mut flags = git2::RepositoryOpenFlags::empty();
+        if env_bool("GIT_DISCOVERY_ACROSS_FILESYSTEM") {
+            flags |= git2::RepositoryOpenFlags::CROSS_FS;
+        }
+        let ceilings = std::env::var_os("GIT_CEILING_DIRECTORIES")
+            .map(|dirs| {
+                // An empty entry only changes how git resolves symlinks in the others
+                std::env::split_paths(&dirs)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        git2::Repository::open_ext(cwd, flags, &ceilings).map_err(|source| Error::RepoNotFound {
+            git_dir: None,
+            source,
+        })?
+    };
+    // Reported as git2 words it, the repo itself having been found
+    let bad_worktree = |source| Error::RepoNotFound {
+        git_dir: None,
+        source,
+    };
+
+    if let Some(work_tree) = work_tree {
+        let work_tree = cwd.join(work_tree);
+        log::debug!("using worktree {}", work_tree.display());
+        repo.set_workdir(&work_tree, false).map_err(bad_worktree)?;
+    } else if git_dir.is_some() && !repo.is_bare() && !has_core_worktree(&repo) {
+        log::debug!("using {} as the worktree, like git", cwd.display());
+        repo.set_workdir(cwd, false).map_err(bad_worktree)?;
+    }
+    Ok(repo)
+}
+
+fn has_core_worktree(repo: &git2::Repository) -> bool {
+    repo.config()
+        .and_then(|config| config.open_level(git2::ConfigLevel::Local))
+        .and_then(|config| config.get_path("core.worktree"))
+        .is_ok()
+}
+
+fn env_path(name: &str) -> Option<std::path::PathBuf> {
+    std::env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(std::path::PathBuf::from)
+}
+
+fn env_bool(name: &str) -> bool {
+    std::env::var(name)
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("yes"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,111 @@
+use crate::git2_config::DefaultField;
+use crate::git2_config::Field as _;
+use crate::git2_config::RawField;
+
+/// Find lines a merge introduced itself, like when resolving a conflict
+///
+/// A line blamed on a merge is a resolution when it isn't in any parent's version of the file.
+pub(crate) struct Resolutions<'r> {
+    repo: &'r git2::Repository,
+    /// Every line in any parent's version, by merge and path
+    parent_lines: std::collections::HashMap<
+        (git2::Oid, std::path::PathBuf),
+        std::collections::HashSet<String>,
+    >,
+}
+
+impl<'r> Resolutions<'r> {
+    pub(crate) fn new(repo: &'r git2::Repository) -> Self {
+        Self {
+            repo,
+            parent_lines: Default::default(),
+        }
+    }
+
+    /// Which `lines` are resolutions, skipping the check when there are more than `max_merges`
+    /// merges to look at
+    pub(crate) fn detect(
+        &mut self,
+        attributions: &[crate::blame::Attribution],
+        lines: &[&str],
+        max_merges: usize,
+    ) -> anyhow::Result<Vec<bool>> {
+        let commits = attributions
+            .iter()
+            .filter(|a| {
+                a.path.is_some() && !a.commit.is_zero() && a.commit != crate::objects::placeholder()
+            })
+            .map(|a| a.commit)
+            .collect::<std::collections::HashSet<_>>();
+        let mut merges = std::collections::HashSet::new();
+        for id in commits {
+            if 1 < self.repo.find_commit(id)?.parent_count() {
+                merges.insert(id);
+            }
+        }
+        if max_merges < merges.len() {
+            log::warn!(
+                "not detecting conflict resolutions: {} merges is more than `{}` ({})",
+                merges.len(),
+                MAX_RESOLUTION_MERGES.name(),
+                max_merges
+            );
+            return Ok(vec![false; attributions.len()]);
+        }
+        log::debug!("checking {} merges for conflict resolutions", merges.len());
+
+        let mut resolutions = Vec::with_capacity(attributions.len());
+        for (attribution, line) in attributions.iter().zip(lines) {
+            let resolution = match attribution.path.as_deref() {
+                Some(path) if merges.contains(&attribution.commit) => {
+                    !self.in_parent(attribution.commit, path, line)?
+                }
+                _ => false,
+            };
+            resolutions.push(resolution);
+        }
+        Ok(resolutions)
+    }
+
+    fn in_parent(
+        &mut self,
+        merge: git2::Oid,
+        path: &std::path::Path,
+        line: &str,
+    ) -> anyhow::Result<bool> {
+        let key = (merge, path.to_owned());
+        if !self.parent_lines.contains_key(&key) {
+            let lines = self.load_parent_lines(merge, path)?;
+            self.parent_lines.insert(key.clone(), lines);
+        }
+        Ok(self.parent_lines[&key].contains(normalize(line)))
+    }
+
+    fn load_parent_lines(
+        &self,
+        merge: git2::Oid,
+        path: &std::path::Path,
+    ) -> anyhow::Result<std::collections::HashSet<String>> {
+        let merge = self.repo.find_commit(merge)?;
+        let mut lines = std::collections::HashSet::new();
+        for parent in merge.parents() {
+            let Ok(entry) = parent.tree()?.get_path(path) else {
+                continue;
+            };
+            let Ok(blob) = self.repo.find_blob(entry.id()) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(blob.content());
+            lines.extend(content.lines().map(|l| normalize(l).to_owned()));
+        }
+        Ok(lines)
+    }
+}
+
+/// Like blame, changes in whitespace alone don't count
+fn normalize(line: &str) -> &str {
+    line.trim()
+}
+
+pub(crate) const MAX_RESOLUTION_MERGES: DefaultField<i64> =
+    RawField::<i64>::new("dive.max-resolution-merges").default_value(|| 100);
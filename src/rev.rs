@@ -0,0 +1,195 @@
+use crate::error::Error;
+
+/// The rev `path` records, for `--rev-file`
+///
+/// This is the first line that isn't blank or a `#` comment, trimmed.
+pub(crate) fn read_rev_file(path: &std::path::Path) -> Result<String, Error> {
+    let content = std::fs::read_to_string(path).map_err(|source| Error::Io {
+        context: format!("could not read a rev from {}", path.display()),
+        source,
+    })?;
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| Error::EmptyRevFile {
+            path: path.to_owned(),
+        })
+}
+
+/// How to refer to `args.rev` in messages
+pub(crate) fn rev_name(args: &crate::args::Args) -> String {
+    match &args.rev_file {
+        Some(path) => format!("{} (rev from {})", args.rev, path.display()),
+        None => args.rev.clone(),
+    }
+}
+
+/// The last commit on `rev`'s first-parent history committed at or before `at`, as `rev~N`
+///
+/// History is scanned newest first rather than searched, since clock skew can leave dates out
+/// of order; the first commit old enough wins.  Parents are seen through `replacements`, and
+/// past a graft the commit is named by its hash, as libgit2 would parse `rev~N` without it.
+pub(crate) fn at_date<'r>(
+    repo: &'r git2::Repository,
+    replacements: &crate::replace::Replacements,
+    rev: &str,
+    at: i64,
+) -> Result<(String, git2::Commit<'r>), Error> {
+    let mut commit = peel_to_commit(&revparse_single(repo, rev)?, rev)?;
+    let mut steps = 0;
+    let mut grafted = false;
+    loop {
+        if commit.committer().when().seconds() <= at {
+            break;
+        }
+        grafted |= replacements.is_replaced(commit.id());
+        let parent = replacements
+            .first_parent(repo, commit.id())
+            .ok()
+            .flatten()
+            .and_then(|parent| repo.find_commit(parent).ok());
+        let Some(parent) = parent else {
+            return Err(Error::NoCommitBefore {
+                rev: rev.to_owned(),
+                at: crate::date::format_short(git2::Time::new(at, 0)),
+            });
+        };
+        commit = parent;
+        steps += 1;
+    }
+    let rev = if steps == 0 {
+        rev.to_owned()
+    } else if grafted {
+        commit.id().to_string()
+    } else {
+        format!("{rev}~{steps}")
+    };
+    Ok((rev, commit))
+}
+
+/// Resolve `rev` like `git rev-parse`, preferring the commit when a short hash is ambiguous
+pub(crate) fn revparse_single<'r>(
+    repo: &'r git2::Repository,
+    rev: &str,
+) -> Result<git2::Object<'r>, Error> {
+    let not_found = |source| Error::RevNotFound { source };
+    match repo.revparse_single(rev) {
+        Ok(obj) => Ok(obj),
+        Err(err) if err.code() == git2::ErrorCode::Ambiguous => {
+            disambiguate(repo, rev, err, not_found)
+        }
+        Err(err) => Err(not_found(err)),
+    }
+}
+
+/// The commit `obj`, resolved from the rev shown as `rev_name`, points to
+pub(crate) fn peel_to_commit<'r>(
+    obj: &git2::Object<'r>,
+    rev_name: &str,
+) -> Result<git2::Commit<'r>, Error> {
+    obj.peel_to_commit().map_err(|_| Error::NotACommit {
+        rev: rev_name.to_owned(),
+        kind: obj.kind().map(|k| k.str()).unwrap_or("unknown"),
+    })
+}
+
+fn disambiguate<'r>(
+    repo: &'r git2::Repository,
+    rev: &str,
+    err: git2::Error,
+    not_found: impl Fn(git2::Error) -> Error,
+) -> Result<git2::Object<'r>, Error> {
+    let prefix = rev
+        .split(|c: char| !c.is_ascii_hexdigit())
+        .next()
+        .unwrap_or_default();
+    if prefix.len() < MIN_PREFIX_LEN {
+        return Err(not_found(err));
+    }
+
+    let candidates = find_candidates(repo, prefix).map_err(&not_found)?;
+    let commits = candidates
+        .iter()
+        .filter(|obj| obj.peel_to_commit().is_ok())
+        .collect::<Vec<_>>();
+    if let [commit] = commits.as_slice() {
+        log::debug!(
+            "`{}` is ambiguous, preferring commit {}",
+            prefix,
+            commit.id()
+        );
+        let rev = format!("{}{}", commit.id(), &rev[prefix.len()..]);
+        return repo.revparse_single(&rev).map_err(&not_found);
+    }
+
+    Err(Error::AmbiguousRev {
+        prefix: prefix.to_owned(),
+        candidates: candidates
+            .iter()
+            .take(MAX_CANDIDATES)
+            .map(describe)
+            .collect(),
+        more: candidates.len().saturating_sub(MAX_CANDIDATES),
+    })
+}
+
+/// Every object whose id starts with `prefix`, commits first
+fn find_candidates<'r>(
+    repo: &'r git2::Repository,
+    prefix: &str,
+) -> Result<Vec<git2::Object<'r>>, git2::Error> {
+    let prefix = prefix.to_ascii_lowercase();
+    let mut ids = Vec::new();
+    repo.odb()?.foreach(|id| {
+        if id.to_string().starts_with(&prefix) {
+            ids.push(*id);
+        }
+        true
+    })?;
+    ids.sort();
+    ids.dedup();
+
+    let mut candidates = ids
+        .into_iter()
+        .map(|id| repo.find_object(id, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    candidates.sort_by_key(|obj| (kind_order(obj.kind()), obj.id()));
+    Ok(candidates)
+}
+
+fn kind_order(kind: Option<git2::ObjectType>) -> usize {
+    match kind {
+        Some(git2::ObjectType::Commit) => 0,
+        Some(git2::ObjectType::Tag) => 1,
+        Some(git2::ObjectType::Tree) => 2,
+        Some(git2::ObjectType::Blob) => 3,
+        _ => 4,
+    }
+}
+
+/// Like git's hints: `abc1234 commit 2023-03-16 - Subject`
+fn describe(obj: &git2::Object<'_>) -> String {
+    let short = obj
+        .short_id()
+        .ok()
+        .and_then(|s| s.as_str().map(ToOwned::to_owned))
+        .unwrap_or_else(|| obj.id().to_string());
+    let kind = obj.kind().map(|k| k.str()).unwrap_or("unknown");
+    if let Some(commit) = obj.as_commit() {
+        let date = crate::date::format_short(commit.author().when());
+        let subject = crate::encoding::subject(commit);
+        format!("{short} {kind} {date} - {subject}")
+    } else if let Some(tag) = obj.as_tag() {
+        let name = String::from_utf8_lossy(tag.name_bytes()).into_owned();
+        format!("{short} {kind} {name}")
+    } else {
+        format!("{short} {kind}")
+    }
+}
+
+/// git doesn't accept abbreviations shorter than this
+const MIN_PREFIX_LEN: usize = 4;
+
+const MAX_CANDIDATES: usize = 10;
@@ -0,0 +1,152 @@
+//! `--resolve-reverts`, seeing through reverts and cherry-picks to the commit they came from
+//!
+//! `git revert` writes "This reverts commit SHA." and `git cherry-pick -x` writes "(cherry
+//! picked from commit SHA)".  Rather than matching those templates exactly, which tools and
+//! translations reword, a line naming a commit ID along with a word like "revert" or "picked"
+//! is enough.
+
+/// How a commit relates to the one its message names
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ViaKind {
+    Revert,
+    CherryPick,
+}
+
+impl ViaKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Revert => "revert",
+            Self::CherryPick => "cherry-pick",
+        }
+    }
+}
+
+impl std::fmt::Display for ViaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The original commit that a revert or cherry-pick names
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Via {
+    pub(crate) kind: ViaKind,
+    pub(crate) commit: git2::Oid,
+}
+
+impl Via {
+    /// What `commit`'s message names, when it's in `repo`
+    pub(crate) fn of(repo: &git2::Repository, commit: &git2::Commit<'_>) -> Option<Self> {
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        let via = candidates(&message).find_map(|(kind, id)| {
+            let original = match repo
+                .revparse_single(id)
+                .and_then(|object| object.peel_to_commit())
+            {
+                Ok(original) => original,
+                Err(err) => {
+                    log::debug!("{} names {id}, which isn't a commit: {err}", commit.id());
+                    return None;
+                }
+            };
+            // Guard against a message naming its own commit, like after an amend
+            (original.id() != commit.id()).then_some(Self {
+                kind,
+                commit: original.id(),
+            })
+        });
+        via
+    }
+}
+
+/// Words hinting that a line names the commit being reverted
+const REVERT_WORDS: &[&str] = &["revert", "rückgängig", "annul", "undo"];
+/// Words hinting that a line names the commit being cherry-picked
+const CHERRY_PICK_WORDS: &[&str] = &["cherry", "picked"];
+
+/// Each line of `message` naming a commit ID with words saying how, and that ID
+///
+/// More than one may match, like a revert's subject quoting a version number.
+pub(crate) fn candidates(message: &str) -> impl Iterator<Item = (ViaKind, &str)> {
+    message.lines().filter_map(|line| {
+        let lower = line.to_lowercase();
+        let kind = if CHERRY_PICK_WORDS.iter().any(|word| lower.contains(word)) {
+            ViaKind::CherryPick
+        } else if REVERT_WORDS.iter().any(|word| lower.contains(word)) {
+            ViaKind::Revert
+        } else {
+            return None;
+        };
+        let id = line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find(|word| is_commit_id(word))?;
+        Some((kind, id))
+    })
+}
+
+/// Abbreviated or full, with a digit so words like `defaced` aren't mistaken for one
+fn is_commit_id(word: &str) -> bool {
+    (7..=40).contains(&word.len())
+        && word.bytes().all(|b| b.is_ascii_hexdigit())
+        && word.bytes().any(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(message: &str) -> Option<(ViaKind, &str)> {
+        candidates(message).next()
+    }
+
+    #[test]
+    fn git_templates() {
+        let revert = "Revert \"Add a thing\"\n\nThis reverts commit 0123456789abcdef0123456789abcdef01234567.\n";
+        assert_eq!(
+            parse(revert),
+            Some((ViaKind::Revert, "0123456789abcdef0123456789abcdef01234567"))
+        );
+        let pick =
+            "Add a thing\n\n(cherry picked from commit 0123456789abcdef0123456789abcdef01234567)\n";
+        assert_eq!(
+            parse(pick),
+            Some((
+                ViaKind::CherryPick,
+                "0123456789abcdef0123456789abcdef01234567"
+            ))
+        );
+    }
+
+    #[test]
+    fn reworded_templates() {
+        assert_eq!(
+            parse("Fix it\n\nReverts a1b2c3d, which broke the build"),
+            Some((ViaKind::Revert, "a1b2c3d"))
+        );
+        assert_eq!(
+            parse("Fix it\n\nCherry-picked from: a1b2c3d4e5"),
+            Some((ViaKind::CherryPick, "a1b2c3d4e5"))
+        );
+        assert_eq!(
+            parse("Macht Commit a1b2c3d rückgängig"),
+            Some((ViaKind::Revert, "a1b2c3d"))
+        );
+    }
+
+    #[test]
+    fn needs_an_id_and_a_word() {
+        assert_eq!(parse("Revert the decade-old defaced facade"), None);
+        assert_eq!(parse("Fix a1b2c3d's typo"), None);
+        assert_eq!(parse("Revert a1b2c3"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn every_matching_line() {
+        assert_eq!(
+            candidates("Revert \"Bump to 2024010\"\n\nThis reverts commit a1b2c3d.")
+                .collect::<Vec<_>>(),
+            [(ViaKind::Revert, "2024010"), (ViaKind::Revert, "a1b2c3d")]
+        );
+    }
+}
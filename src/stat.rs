@@ -0,0 +1,516 @@
+use proc_exit::WithCodeResultExt;
+
+use crate::args::OutputFormat;
+use crate::blame::Annotation;
+use crate::blame::Annotations;
+use crate::blame::Attribution;
+use crate::blame::GutterStyles;
+use crate::boundary::Boundary;
+use crate::git2_config::Config;
+use crate::git2_config::MultiField;
+use crate::git2_config::Parseable;
+
+/// Count a file's lines by author and, when grouping, by team
+///
+/// Lines that are `excluded`, indexed by line, aren't counted, nor are lines from boundary
+/// commits with `--no-boundary`.  Boundary commits are listed either way.
+pub(crate) fn stat(
+    path: &str,
+    attributions: &[Attribution],
+    excluded: &[bool],
+    annotations: &Annotations,
+    config: &Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    let colored_stdout =
+        anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
+
+    let team_map = config.get(&TEAM_MAP);
+    let teams = Teams {
+        rules: team_map,
+        by_domain: args.group_by_domain,
+    };
+    let counted = attributions
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !excluded.get(*i).copied().unwrap_or(false))
+        .map(|(_, attribution)| attribution)
+        .collect::<Vec<_>>();
+    let unfiltered = counted.len();
+    let since = args
+        .touched_since
+        .as_ref()
+        .map(|since| since.seconds(crate::date::now()));
+    let counted = if let Some(since) = since {
+        counted
+            .into_iter()
+            .filter(|a| crate::blame::touched_since(annotations, a, since))
+            .collect()
+    } else {
+        counted
+    };
+    let boundaries = count_boundaries(&counted, annotations);
+    let counted = if args.no_boundary {
+        counted
+            .into_iter()
+            .filter(|a| annotation(annotations, a).boundary.is_none())
+            .collect()
+    } else {
+        counted
+    };
+    let authors = count_authors(&counted, annotations);
+    let by_team = count_teams(&authors, &teams);
+
+    let output = match args.format {
+        OutputFormat::Text => {
+            let show_teams = teams.by_domain || !teams.rules.is_empty();
+            let styles = GutterStyles::for_stdout(config, colored_stdout);
+            let mut output =
+                render_text(&authors, show_teams.then_some(by_team.as_slice()), styles);
+            if !boundaries.is_empty() {
+                output.push('\n');
+                output.push_str(&render_boundaries(
+                    &boundaries,
+                    !args.no_boundary,
+                    styles,
+                    crate::render_test::terminal_width(),
+                    crate::glyphs::Glyphs::resolve(config).ellipsis,
+                ));
+            }
+            if let Some(since) = &args.touched_since {
+                output.push_str(&format!(
+                    "\nOnly the {} of {unfiltered} lines from {since} or later are counted.\n",
+                    counted.len()
+                ));
+            }
+            output
+        }
+        OutputFormat::Json => render_json(
+            path,
+            counted.len(),
+            &authors,
+            &by_team,
+            &boundaries,
+            !args.no_boundary,
+            since,
+        )
+        .with_code(proc_exit::Code::FAILURE)?,
+        OutputFormat::Quickfix => {
+            return Err(anyhow::format_err!(
+                "`--format quickfix` is not supported with `--stat`"
+            ))
+            .with_code(crate::exit_code::USAGE);
+        }
+    };
+    crate::summary::page(&output, config, args)
+}
+
+struct AuthorLines {
+    name: String,
+    /// Mailmapped, identifying the author
+    email: String,
+    lines: usize,
+}
+
+fn annotation<'a>(annotations: &'a Annotations, attribution: &Attribution) -> &'a Annotation {
+    annotations
+        .notes
+        .get(&attribution.commit)
+        .expect("all blame hunks are annotated")
+}
+
+/// Authors by their share of lines, most first
+fn count_authors(attributions: &[&Attribution], annotations: &Annotations) -> Vec<AuthorLines> {
+    let mut authors = std::collections::BTreeMap::<String, AuthorLines>::new();
+    for attribution in attributions {
+        let commit = annotation(annotations, attribution);
+        authors
+            .entry(commit.email.to_lowercase())
+            .or_insert_with(|| AuthorLines {
+                name: commit.author.clone(),
+                email: commit.email.clone(),
+                lines: 0,
+            })
+            .lines += 1;
+    }
+    let mut authors = authors.into_values().collect::<Vec<_>>();
+    authors.sort_by(|a, b| {
+        b.lines
+            .cmp(&a.lines)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.email.cmp(&b.email))
+    });
+    authors
+}
+
+struct BoundaryLines {
+    commit: git2::Oid,
+    /// For people, per `dive.hash-style`
+    hash: String,
+    boundary: Boundary,
+    subject: String,
+    lines: usize,
+}
+
+/// Root commits and shallow boundaries, most lines first
+fn count_boundaries(
+    attributions: &[&Attribution],
+    annotations: &Annotations,
+) -> Vec<BoundaryLines> {
+    let mut boundaries = std::collections::BTreeMap::<git2::Oid, BoundaryLines>::new();
+    for attribution in attributions {
+        let commit = annotation(annotations, attribution);
+        let Some(boundary) = commit.boundary else {
+            continue;
+        };
+        boundaries
+            .entry(attribution.commit)
+            .or_insert_with(|| BoundaryLines {
+                commit: attribution.commit,
+                hash: commit.hash.clone(),
+                boundary,
+                subject: commit.subject.clone(),
+                lines: 0,
+            })
+            .lines += 1;
+    }
+    let mut boundaries = boundaries.into_values().collect::<Vec<_>>();
+    // By id rather than `hash`, which can be abbreviated to the same prefix
+    boundaries.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.commit.cmp(&b.commit)));
+    boundaries
+}
+
+/// Lines by team, most first, with `(other)` last
+fn count_teams(authors: &[AuthorLines], teams: &Teams) -> Vec<(String, usize)> {
+    let mut by_team = std::collections::BTreeMap::<String, usize>::new();
+    for author in authors {
+        *by_team.entry(teams.team(&author.email)).or_default() += author.lines;
+    }
+    let mut by_team = by_team.into_iter().collect::<Vec<_>>();
+    by_team.sort_by(|(a_team, a_lines), (b_team, b_lines)| {
+        (a_team == OTHER_TEAM)
+            .cmp(&(b_team == OTHER_TEAM))
+            .then_with(|| b_lines.cmp(a_lines))
+            .then_with(|| a_team.cmp(b_team))
+    });
+    by_team
+}
+
+const OTHER_TEAM: &str = "(other)";
+
+/// How authors are grouped into teams
+struct Teams {
+    rules: Vec<TeamRule>,
+    /// Fall back to the email's domain, with `--group-by-domain`
+    by_domain: bool,
+}
+
+impl Teams {
+    /// The first rule matching `email`, then its domain when grouping by domain
+    fn team(&self, email: &str) -> String {
+        let email = email.to_lowercase();
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| crate::attributes::wildmatch(rule.pattern.as_bytes(), email.as_bytes()))
+        {
+            return rule.team.clone();
+        }
+        if self.by_domain {
+            if let Some((_, domain)) = email.rsplit_once('@').filter(|(_, d)| !d.is_empty()) {
+                return domain.to_owned();
+            }
+        }
+        OTHER_TEAM.to_owned()
+    }
+}
+
+/// A `dive.team-map` entry, `PATTERN=TEAM`
+#[derive(Clone, Debug)]
+pub(crate) struct TeamRule {
+    /// A glob, lowercased, matched against mailmapped emails
+    pattern: String,
+    team: String,
+}
+
+impl Parseable for TeamRule {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let (pattern, team) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::format_err!("expected `PATTERN=TEAM`"))?;
+        let (pattern, team) = (pattern.trim(), team.trim());
+        if pattern.is_empty() || team.is_empty() {
+            anyhow::bail!("expected `PATTERN=TEAM`");
+        }
+        Ok(Self {
+            pattern: pattern.to_lowercase(),
+            team: team.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for TeamRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.pattern, self.team)
+    }
+}
+
+/// Whole percentages of the total for each count, summing to 100
+///
+/// Each is rounded down, then the remaining points go to those with the largest
+/// remainders, earlier counts winning ties.
+fn percentages(counts: &[usize]) -> Vec<usize> {
+    let total = counts.iter().sum::<usize>();
+    if total == 0 {
+        return vec![0; counts.len()];
+    }
+    let mut percentages = counts.iter().map(|c| c * 100 / total).collect::<Vec<_>>();
+    let mut by_remainder = (0..counts.len()).collect::<Vec<_>>();
+    by_remainder.sort_by_key(|i| std::cmp::Reverse(counts[*i] * 100 % total));
+    let missing = 100 - percentages.iter().sum::<usize>();
+    for i in by_remainder.into_iter().take(missing) {
+        percentages[i] += 1;
+    }
+    percentages
+}
+
+fn render_text(
+    authors: &[AuthorLines],
+    teams: Option<&[(String, usize)]>,
+    styles: GutterStyles,
+) -> String {
+    let authors = authors
+        .iter()
+        .map(|a| (a.name.as_str(), a.lines))
+        .collect::<Vec<_>>();
+    let mut output = render_table("Authors", &authors, styles);
+    if let Some(teams) = teams {
+        let teams = teams
+            .iter()
+            .map(|(team, lines)| (team.as_str(), *lines))
+            .collect::<Vec<_>>();
+        output.push('\n');
+        output.push_str(&render_table("Teams", &teams, styles));
+    }
+    output
+}
+
+fn render_table(title: &str, rows: &[(&str, usize)], styles: GutterStyles) -> String {
+    use std::fmt::Write as _;
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| crate::unicode::display_width(name))
+        .max()
+        .unwrap_or(0);
+    let lines_width = rows
+        .iter()
+        .map(|(_, lines)| lines.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let percentages = percentages(&rows.iter().map(|(_, lines)| *lines).collect::<Vec<_>>());
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "{}{title}:{}",
+        styles.hash.render(),
+        styles.hash.render_reset()
+    );
+    for ((name, lines), percent) in rows.iter().zip(percentages) {
+        let name = crate::unicode::pad(name, name_width);
+        let _ = writeln!(
+            output,
+            "  {}{name}{} {lines:>lines_width$} {percent:>3}%",
+            styles.author.render(),
+            styles.author.render_reset(),
+        );
+    }
+    output
+}
+
+/// Listed after the counts, saying whether they're included, with subjects cut to fit `width`
+fn render_boundaries(
+    boundaries: &[BoundaryLines],
+    counted: bool,
+    styles: GutterStyles,
+    width: usize,
+    ellipsis: &str,
+) -> String {
+    use std::fmt::Write as _;
+
+    let hash_width = boundaries
+        .iter()
+        .map(|b| crate::unicode::display_width(&b.hash))
+        .max()
+        .unwrap_or(0);
+    let kind_width = boundaries
+        .iter()
+        .map(|b| b.boundary.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let lines_width = boundaries
+        .iter()
+        .map(|b| b.lines.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    let title = if counted {
+        "Boundary commits"
+    } else {
+        "Boundary commits, not counted"
+    };
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "{}{title}:{}",
+        styles.hash.render(),
+        styles.hash.render_reset()
+    );
+    for boundary in boundaries {
+        let hash = crate::unicode::pad(&boundary.hash, hash_width);
+        let kind = boundary.boundary.to_string();
+        let used = 2 + hash_width + 1 + kind_width + 1 + lines_width + 1;
+        let subject =
+            crate::unicode::truncate_end(&boundary.subject, width.saturating_sub(used), ellipsis);
+        let _ = writeln!(
+            output,
+            "  {}{hash}{} {kind:kind_width$} {:>lines_width$} {}",
+            styles.hash.render(),
+            styles.hash.render_reset(),
+            boundary.lines,
+            subject,
+        );
+    }
+    output
+}
+
+#[derive(serde::Serialize)]
+struct JsonStat<'s> {
+    path: &'s str,
+    /// Those counted, so without boundary commits' with `--no-boundary`
+    lines: usize,
+    /// With `--touched-since`, in seconds since the epoch, lines from before not being counted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    touched_since: Option<i64>,
+    authors: Vec<JsonAuthor<'s>>,
+    teams: Vec<JsonTeam<'s>>,
+    boundaries: Vec<JsonBoundary>,
+    /// Whether `boundaries`' lines are in `authors` and `teams`
+    boundaries_counted: bool,
+}
+
+#[derive(serde::Serialize)]
+struct JsonBoundary {
+    commit: String,
+    /// `root` or `shallow`
+    boundary: String,
+    lines: usize,
+}
+
+#[derive(serde::Serialize)]
+struct JsonAuthor<'s> {
+    author: &'s str,
+    email: &'s str,
+    lines: usize,
+    percent: usize,
+}
+
+#[derive(serde::Serialize)]
+struct JsonTeam<'s> {
+    team: &'s str,
+    lines: usize,
+    percent: usize,
+}
+
+fn render_json(
+    path: &str,
+    lines: usize,
+    authors: &[AuthorLines],
+    teams: &[(String, usize)],
+    boundaries: &[BoundaryLines],
+    boundaries_counted: bool,
+    touched_since: Option<i64>,
+) -> anyhow::Result<String> {
+    let author_percentages = percentages(&authors.iter().map(|a| a.lines).collect::<Vec<_>>());
+    let team_percentages = percentages(&teams.iter().map(|(_, lines)| *lines).collect::<Vec<_>>());
+    let stat = JsonStat {
+        path,
+        lines,
+        touched_since,
+        authors: authors
+            .iter()
+            .zip(author_percentages)
+            .map(|(a, percent)| JsonAuthor {
+                author: &a.name,
+                email: &a.email,
+                lines: a.lines,
+                percent,
+            })
+            .collect(),
+        teams: teams
+            .iter()
+            .zip(team_percentages)
+            .map(|((team, lines), percent)| JsonTeam {
+                team,
+                lines: *lines,
+                percent,
+            })
+            .collect(),
+        boundaries: boundaries
+            .iter()
+            .map(|b| JsonBoundary {
+                commit: b.commit.to_string(),
+                boundary: b.boundary.to_string(),
+                lines: b.lines,
+            })
+            .collect(),
+        boundaries_counted,
+    };
+    let mut output = serde_json::to_string_pretty(&stat)?;
+    output.push('\n');
+    Ok(output)
+}
+
+pub(crate) const TEAM_MAP: MultiField<TeamRule> = MultiField::<TeamRule>::new("dive.team-map");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn percentages_sum_to_100() {
+        assert_eq!(percentages(&[1, 1, 1]), [34, 33, 33]);
+        assert_eq!(percentages(&[2, 1]), [67, 33]);
+        assert_eq!(percentages(&[1, 0]), [100, 0]);
+        assert_eq!(percentages(&[6, 3, 1]), [60, 30, 10]);
+        assert_eq!(percentages(&[10, 1, 1, 1]), [77, 8, 8, 7]);
+        assert_eq!(percentages(&[]), Vec::<usize>::new());
+        assert_eq!(percentages(&[0, 0]), [0, 0]);
+    }
+
+    #[test]
+    fn teams_from_rules_then_domain() {
+        let rules = ["*@infra.example.com=Infra", "alice@*=Web"]
+            .into_iter()
+            .map(|r| TeamRule::parse(r).unwrap())
+            .collect::<Vec<_>>();
+        let teams = Teams {
+            rules,
+            by_domain: false,
+        };
+        assert_eq!(teams.team("Bob@Infra.example.com"), "Infra");
+        assert_eq!(teams.team("alice@infra.example.com"), "Infra");
+        assert_eq!(teams.team("alice@example.com"), "Web");
+        assert_eq!(teams.team("carol@example.com"), OTHER_TEAM);
+
+        let teams = Teams {
+            by_domain: true,
+            ..teams
+        };
+        assert_eq!(teams.team("carol@Example.com"), "example.com");
+        assert_eq!(teams.team("carol"), OTHER_TEAM);
+        assert!(TeamRule::parse("Web").is_err());
+        assert!(TeamRule::parse("=Web").is_err());
+    }
+}
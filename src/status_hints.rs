@@ -0,0 +1,99 @@
+//! Why a file isn't at `REV`, per its status in the worktree, for a more helpful error
+//!
+//! Only the one path is checked, with `git_status_file`, as scanning a large worktree can take
+//! a while.  `dive.status-hints = false` skips even that.
+
+use crate::git2_config::Config;
+use crate::git2_config::DefaultField;
+use crate::git2_config::RawField;
+
+/// What the worktree and index say about a file missing from `REV`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PathStatus {
+    Untracked,
+    Ignored,
+    /// Added to the index without being committed
+    Staged,
+}
+
+/// The status of `tree_path`, from the top of the repo, when it explains why it isn't at `REV`
+pub(crate) fn probe(
+    repo: &git2::Repository,
+    config: &Config,
+    args: &crate::args::Args,
+    tree_path: &std::path::Path,
+) -> Option<PathStatus> {
+    if !args
+        .status_hints
+        .unwrap_or_else(|| config.get(&STATUS_HINTS))
+    {
+        log::debug!("not checking the status of {}", tree_path.display());
+        return None;
+    }
+    if repo.is_bare() {
+        return None;
+    }
+    let status = match repo.status_file(tree_path) {
+        Ok(status) => status,
+        Err(err) => {
+            log::debug!(
+                "could not check the status of {}: {err}",
+                tree_path.display()
+            );
+            return None;
+        }
+    };
+    log::trace!("{} has status {status:?}", tree_path.display());
+    if status.is_index_new() {
+        Some(PathStatus::Staged)
+    } else if status.is_wt_new() {
+        Some(PathStatus::Untracked)
+    } else if status.is_ignored() {
+        Some(PathStatus::Ignored)
+    } else {
+        None
+    }
+}
+
+pub(crate) const STATUS_HINTS: DefaultField<bool> =
+    RawField::<bool>::new("dive.status-hints").default_value(|| true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(argv: &[&str]) -> crate::args::Args {
+        clap::Parser::parse_from(std::iter::once("git-dive").chain(argv.iter().copied()))
+    }
+
+    #[test]
+    fn explains_missing_files() {
+        let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+        let root_path = root.path().unwrap();
+        let repo = git2::Repository::init(root_path).unwrap();
+        std::fs::write(root_path.join(".gitignore"), "*.log\n").unwrap();
+        for file in ["new.txt", "staged.txt", "debug.log"] {
+            std::fs::write(root_path.join(file), "one\n").unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_path("staged.txt".as_ref()).unwrap();
+        index.write().unwrap();
+
+        let mut config = Config::system();
+        config.isolate();
+        let probe = |path: &str, argv: &[&str]| probe(&repo, &config, &args(argv), path.as_ref());
+        assert_eq!(probe("new.txt", &["new.txt"]), Some(PathStatus::Untracked));
+        assert_eq!(
+            probe("staged.txt", &["staged.txt"]),
+            Some(PathStatus::Staged)
+        );
+        assert_eq!(
+            probe("debug.log", &["debug.log"]),
+            Some(PathStatus::Ignored)
+        );
+        assert_eq!(probe("missing.txt", &["missing.txt"]), None);
+        assert_eq!(probe("new.txt", &["--status-hints=false", "new.txt"]), None);
+
+        root.close().unwrap();
+    }
+}
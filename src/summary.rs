@@ -0,0 +1,334 @@
+use proc_exit::WithCodeResultExt;
+
+use crate::args::OutputFormat;
+use crate::blame::GutterStyles;
+use crate::date::Dates;
+use crate::git2_config::Config;
+use crate::git_pager::Pager;
+
+/// The most recent change to each file in a directory
+pub(crate) fn summarize(
+    repo: &git2::Repository,
+    rev_commit: &git2::Commit<'_>,
+    dir_path: &std::path::Path,
+    paths: &crate::path_display::Paths,
+    config: &Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    if args.stat {
+        return Err(anyhow::format_err!("`--stat` is only supported for files"))
+            .with_code(crate::exit_code::USAGE);
+    }
+    let colored_stdout =
+        anstream::AutoStream::choice(&std::io::stdout()) != anstream::ColorChoice::Never;
+
+    let start = std::time::Instant::now();
+    let files = list_files(repo, rev_commit, dir_path, args.recursive)
+        .with_code(proc_exit::Code::FAILURE)?;
+    let date = crate::date::DateKind::resolve(config, args);
+    let replacements = crate::replace::Replacements::load(repo, config, args)
+        .with_code(proc_exit::Code::FAILURE)?;
+    let mut progress = crate::progress::Progress::new(args);
+    let mut entries = last_changes(
+        repo,
+        &replacements,
+        rev_commit,
+        dir_path,
+        files,
+        date,
+        &mut progress,
+    )
+    .with_code(proc_exit::Code::FAILURE)?;
+    progress.clear();
+    if paths.chosen {
+        for entry in &mut entries {
+            let tree_path = if dir_path.as_os_str().is_empty() {
+                entry.path.clone()
+            } else {
+                format!("{}/{}", dir_path.display(), entry.path)
+            };
+            entry.path = paths.show(std::path::Path::new(&tree_path));
+        }
+    }
+    log::debug!(
+        "found last changes for {} files in {:?}",
+        entries.len(),
+        start.elapsed()
+    );
+    entries.sort_by(|a, b| {
+        b.time
+            .seconds()
+            .cmp(&a.time.seconds())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    let output = match args.format {
+        OutputFormat::Text => render_text(
+            &entries,
+            &crate::hash::HashFormat::resolve(config),
+            Dates::resolve(config, args),
+            GutterStyles::for_stdout(config, colored_stdout),
+        ),
+        OutputFormat::Json => render_json(&entries).with_code(proc_exit::Code::FAILURE)?,
+        OutputFormat::Quickfix => {
+            return Err(anyhow::format_err!(
+                "`--format quickfix` is not supported for directories"
+            ))
+            .with_code(crate::exit_code::USAGE);
+        }
+    };
+
+    page(&output, config, args)
+}
+
+/// Write already rendered `output` through the pager
+pub(crate) fn page(
+    output: &str,
+    config: &Config,
+    args: &crate::args::Args,
+) -> proc_exit::ExitResult {
+    let mut pager = Pager::resolve(config, args)?;
+    let mut pager = pager.start();
+    let pager = pager.as_writer().with_code(crate::exit_code::IO)?;
+    match crate::encoding::output_encoding(config) {
+        // JSON is always UTF-8
+        Some(encoding) if args.format == OutputFormat::Text => {
+            let mut pager = crate::encoding::Transcoder::new(pager, encoding);
+            let _ = std::io::Write::write_all(&mut pager, output.as_bytes());
+        }
+        _ => {
+            let _ = pager.write_all(output.as_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+struct LastChange {
+    /// Relative to the summarized directory, unless a `PathDisplay` was chosen
+    path: String,
+    commit: git2::Oid,
+    short: String,
+    author: String,
+    /// The date being shown
+    time: git2::Time,
+    author_time: git2::Time,
+    committer_time: git2::Time,
+}
+
+/// Files in `dir_path` at `rev_commit`, with their blob ids, keyed by their path within it
+fn list_files(
+    repo: &git2::Repository,
+    rev_commit: &git2::Commit<'_>,
+    dir_path: &std::path::Path,
+    recursive: bool,
+) -> anyhow::Result<std::collections::BTreeMap<String, git2::Oid>> {
+    let dir = subtree(repo, &rev_commit.tree()?, dir_path)?
+        .ok_or_else(|| anyhow::format_err!("{} is not a directory", dir_path.display()))?;
+    let mut files = std::collections::BTreeMap::new();
+    dir.walk(git2::TreeWalkMode::PreOrder, |parent, entry| {
+        if entry.kind() == Some(git2::ObjectType::Tree) {
+            return if recursive {
+                git2::TreeWalkResult::Ok
+            } else {
+                git2::TreeWalkResult::Skip
+            };
+        }
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            if let Some(name) = entry.name() {
+                files.insert(format!("{parent}{name}"), entry.id());
+            }
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+    Ok(files)
+}
+
+/// Walk history once, resolving each file as the commit that last changed it is found
+///
+/// A commit changed a file if it has the version at `rev_commit` and none of its parents do,
+/// so changes later reverted or discarded by a merge aren't reported.
+fn last_changes(
+    repo: &git2::Repository,
+    replacements: &crate::replace::Replacements,
+    rev_commit: &git2::Commit<'_>,
+    dir_path: &std::path::Path,
+    mut pending: std::collections::BTreeMap<String, git2::Oid>,
+    date: crate::date::DateKind,
+    progress: &mut crate::progress::Progress,
+) -> anyhow::Result<Vec<LastChange>> {
+    let mut changes = Vec::new();
+    let total = pending.len();
+
+    // Through replacements, so grafted history is searched too
+    let backend = crate::backend::Git2::new(repo, replacements);
+    let mut walk = crate::replace::Walk::new(&backend);
+    walk.push(rev_commit.id())?;
+    for id in walk {
+        if pending.is_empty() {
+            break;
+        }
+        progress.update(|| format!("Finding last changes: {} of {total} files", changes.len()));
+        let commit = repo.find_commit(id?)?;
+        let Some(dir) = subtree(repo, &commit.tree()?, dir_path)? else {
+            continue;
+        };
+        let parent_dirs = replacements
+            .parents(repo, commit.id())?
+            .into_iter()
+            .map(|p| subtree(repo, &repo.find_commit(p)?.tree()?, dir_path))
+            .collect::<Result<Vec<_>, _>>()?;
+        if parent_dirs
+            .iter()
+            .any(|p| p.as_ref().map(|p| p.id()) == Some(dir.id()))
+        {
+            // Nothing in the directory changed relative to this parent
+            continue;
+        }
+
+        let mut resolved = Vec::new();
+        for (path, blob) in &pending {
+            if entry_id(&dir, path) != Some(*blob) {
+                continue;
+            }
+            let in_parent = parent_dirs
+                .iter()
+                .any(|p| p.as_ref().and_then(|p| entry_id(p, path)) == Some(*blob));
+            if !in_parent {
+                resolved.push(path.clone());
+            }
+        }
+        if resolved.is_empty() {
+            continue;
+        }
+        let short = commit
+            .as_object()
+            .short_id()?
+            .as_str()
+            .expect("short_id is always valid UTF-8")
+            .to_owned();
+        let author = crate::unicode::sanitize(&crate::encoding::author_name(&commit));
+        for path in resolved {
+            pending.remove(&path);
+            changes.push(LastChange {
+                path,
+                commit: commit.id(),
+                short: short.clone(),
+                author: author.clone(),
+                time: date.of(&commit),
+                author_time: commit.author().when(),
+                committer_time: commit.committer().when(),
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+/// The tree at `path`, with an empty path being the root
+fn subtree<'r>(
+    repo: &'r git2::Repository,
+    root: &git2::Tree<'r>,
+    path: &std::path::Path,
+) -> anyhow::Result<Option<git2::Tree<'r>>> {
+    if path.as_os_str().is_empty() {
+        return Ok(Some(root.clone()));
+    }
+    let entry = match root.get_path(path) {
+        Ok(entry) => entry,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    if entry.kind() != Some(git2::ObjectType::Tree) {
+        return Ok(None);
+    }
+    Ok(Some(repo.find_tree(entry.id())?))
+}
+
+fn entry_id(tree: &git2::Tree<'_>, path: &str) -> Option<git2::Oid> {
+    tree.get_path(std::path::Path::new(path))
+        .ok()
+        .map(|e| e.id())
+}
+
+fn render_text(
+    entries: &[LastChange],
+    hashes: &crate::hash::HashFormat,
+    date_format: Dates,
+    styles: GutterStyles,
+) -> String {
+    use std::fmt::Write as _;
+
+    let hashes = entries
+        .iter()
+        .map(|e| hashes.show(e.commit, &e.short))
+        .collect::<Vec<_>>();
+    let hash_width = hashes
+        .iter()
+        .map(|h| crate::unicode::display_width(h))
+        .max()
+        .unwrap_or(0);
+    let author_width = entries
+        .iter()
+        .map(|e| crate::unicode::display_width(&e.author))
+        .max()
+        .unwrap_or(0);
+
+    let dates = entries
+        .iter()
+        .map(|e| date_format.format(e.time))
+        .collect::<Vec<_>>();
+    let date_width = dates
+        .iter()
+        .map(|d| crate::unicode::display_width(d))
+        .max()
+        .unwrap_or(0);
+
+    let mut output = String::new();
+    for ((entry, date), hash) in entries.iter().zip(dates).zip(hashes) {
+        let author = crate::unicode::pad(&entry.author, author_width);
+        let date = crate::unicode::pad(&date, date_width);
+        let _ = writeln!(
+            output,
+            "{}{date}{} {}{hash:hash_width$}{} {}{author}{} {}",
+            styles.date.render(),
+            styles.date.render_reset(),
+            styles.hash.render(),
+            styles.hash.render_reset(),
+            styles.author.render(),
+            styles.author.render_reset(),
+            entry.path,
+        );
+    }
+    output
+}
+
+#[derive(serde::Serialize)]
+struct JsonEntry<'e> {
+    path: &'e str,
+    commit: String,
+    author: &'e str,
+    date: String,
+    time: i64,
+    /// Regardless of which date is shown
+    author_time: i64,
+    committer_time: i64,
+}
+
+fn render_json(entries: &[LastChange]) -> anyhow::Result<String> {
+    let entries = entries
+        .iter()
+        .map(|e| JsonEntry {
+            path: &e.path,
+            commit: e.commit.to_string(),
+            author: &e.author,
+            date: crate::date::format_short(e.time),
+            time: e.time.seconds(),
+            author_time: e.author_time.seconds(),
+            committer_time: e.committer_time.seconds(),
+        })
+        .collect::<Vec<_>>();
+    let mut output = serde_json::to_string_pretty(&entries)?;
+    output.push('\n');
+    Ok(output)
+}
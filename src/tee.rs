@@ -0,0 +1,136 @@
+/// A copy of the output, for keeping a record of what was paged
+///
+/// The file is truncated when created, so an interrupted run leaves behind what was written so
+/// far.
+pub(crate) struct Tee {
+    file: std::io::BufWriter<std::fs::File>,
+    strip: Option<AnsiStripper>,
+}
+
+impl Tee {
+    /// `--tee`, stripping colors unless `--tee-raw`
+    pub(crate) fn from_args(args: &crate::args::Args) -> anyhow::Result<Option<Self>> {
+        let Some(path) = args.tee.as_deref() else {
+            return Ok(None);
+        };
+        let tee = Self::create(path, !args.tee_raw).map_err(|e| {
+            anyhow::format_err!("could not create tee file {}: {}", path.display(), e)
+        })?;
+        log::debug!("copying output to {}", path.display());
+        Ok(Some(tee))
+    }
+
+    pub(crate) fn create(path: &std::path::Path, strip_ansi: bool) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self {
+            file: std::io::BufWriter::new(file),
+            strip: strip_ansi.then(AnsiStripper::default),
+        })
+    }
+}
+
+impl std::io::Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(strip) = &mut self.strip {
+            let mut plain = Vec::with_capacity(buf.len());
+            strip.strip(buf, &mut plain);
+            self.file.write_all(&plain)?;
+        } else {
+            self.file.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Remove ANSI escape sequences from a stream of bytes
+///
+/// State carries across calls so sequences split between writes are still removed.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct AnsiStripper {
+    state: State,
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+enum State {
+    /// Plain text
+    #[default]
+    Ground,
+    /// After `ESC`
+    Escape,
+    /// In a control sequence, like colors, ended by a byte in `@`..=`~`
+    Csi,
+    /// In an operating system command, like hyperlinks, ended by `BEL` or `ESC \`
+    Osc,
+    /// After `ESC` in an operating system command
+    OscEscape,
+}
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+impl AnsiStripper {
+    pub(crate) fn strip(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        for &b in input {
+            self.state = match (self.state, b) {
+                (State::Ground, ESC) => State::Escape,
+                (State::Ground, _) => {
+                    output.push(b);
+                    State::Ground
+                }
+                (State::Escape, b'[') => State::Csi,
+                (State::Escape, b']') => State::Osc,
+                // Other two-byte sequences end here
+                (State::Escape, _) => State::Ground,
+                (State::Csi, 0x40..=0x7e) => State::Ground,
+                (State::Csi, _) => State::Csi,
+                (State::Osc, BEL) => State::Ground,
+                (State::Osc, ESC) => State::OscEscape,
+                (State::Osc, _) => State::Osc,
+                (State::OscEscape, b'\\') => State::Ground,
+                (State::OscEscape, _) => State::Osc,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strip(chunks: &[&[u8]]) -> String {
+        let mut stripper = AnsiStripper::default();
+        let mut output = Vec::new();
+        for chunk in chunks {
+            stripper.strip(chunk, &mut output);
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn plain_unchanged() {
+        assert_eq!(strip(&[b"one\ntwo\n"]), "one\ntwo\n");
+    }
+
+    #[test]
+    fn strips_colors() {
+        assert_eq!(strip(&[b"\x1b[1;31mred\x1b[0m text"]), "red text");
+    }
+
+    #[test]
+    fn strips_split_sequences() {
+        assert_eq!(strip(&[b"a\x1b", b"[38;5", b";1mb\x1b[", b"0m"]), "ab");
+    }
+
+    #[test]
+    fn strips_hyperlinks() {
+        assert_eq!(
+            strip(&[b"\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07!"]),
+            "link!"
+        );
+        assert_eq!(strip(&[b"\x1b]8;;url\x1b", b"\\link"]), "link");
+    }
+}
@@ -0,0 +1,257 @@
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::git2_config::DefaultField;
+use crate::git2_config::Parseable;
+use crate::git2_config::RawField;
+
+/// The number of terminal columns `s` takes up
+///
+/// Characters joined to the previous one with a zero-width joiner, like in emoji sequences,
+/// are assumed to render as part of it.
+pub(crate) fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut joined = false;
+    for c in s.chars() {
+        if !joined {
+            width += c.width().unwrap_or(0);
+        }
+        joined = c == ZERO_WIDTH_JOINER;
+    }
+    width
+}
+
+/// Left-align `s` within `width` columns
+pub(crate) fn pad(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    let mut padded = isolate(s);
+    padded.extend(std::iter::repeat(' ').take(padding));
+    padded
+}
+
+/// Fit `s` in `width` columns, marking a cut with `ellipsis`
+pub(crate) fn truncate_end<'s>(
+    s: &'s str,
+    width: usize,
+    ellipsis: &str,
+) -> std::borrow::Cow<'s, str> {
+    if display_width(s) <= width {
+        return s.into();
+    }
+    let budget = width.saturating_sub(display_width(ellipsis));
+    let mut used = 0;
+    let mut end = 0;
+    for (i, c) in s.char_indices() {
+        let c_width = c.width().unwrap_or(0);
+        if budget < used + c_width {
+            break;
+        }
+        used += c_width;
+        end = i + c.len_utf8();
+    }
+    format!("{}{ellipsis}", s[..end].trim_end()).into()
+}
+
+/// Remove characters that could reorder or hide the text around them, like bidi overrides
+pub(crate) fn sanitize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !is_bidi_control(*c) && !c.is_control())
+        .collect()
+}
+
+/// How to write control characters in file content, which could retitle or reset the terminal
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub(crate) enum ControlChars {
+    /// Like `␛` for C0 controls and `M-^[` for C1 controls
+    #[default]
+    Escape,
+    /// Remove them
+    Strip,
+    /// Pass them through to the terminal
+    Raw,
+}
+
+impl Parseable for ControlChars {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        <Self as clap::ValueEnum>::from_str(s, false)
+            .map_err(|_| anyhow::format_err!("unsupported control character mode `{s}`"))
+    }
+}
+
+impl std::fmt::Display for ControlChars {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        clap::ValueEnum::to_possible_value(self)
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+pub(crate) const SHOW_CONTROL_CHARS: DefaultField<ControlChars> =
+    RawField::<ControlChars>::new("dive.show-control-chars").default();
+
+/// `s` with tabs expanded to every `tab_width`th column, starting at `column`
+///
+/// `column` is moved past `s`, so a line styled in pieces can be expanded a piece at a time.
+pub(crate) fn expand_tabs<'s>(
+    s: &'s str,
+    tab_width: usize,
+    column: &mut usize,
+) -> std::borrow::Cow<'s, str> {
+    if !s.contains('\t') {
+        *column += display_width(s);
+        return s.into();
+    }
+    let mut expanded = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\t' {
+            let spaces = tab_width - *column % tab_width;
+            expanded.extend(std::iter::repeat(' ').take(spaces));
+            *column += spaces;
+        } else {
+            expanded.push(c);
+            *column += c.width().unwrap_or(0);
+        }
+    }
+    expanded.into()
+}
+
+/// `s` with control characters other than tab written per `mode`
+///
+/// Escaped characters are passed through `wrap`, to style them apart from the text.
+pub(crate) fn show_controls<'s>(
+    s: &'s str,
+    mode: ControlChars,
+    wrap: impl Fn(&str) -> String,
+) -> std::borrow::Cow<'s, str> {
+    let is_shown = |c: char| c.is_control() && c != '\t';
+    if mode == ControlChars::Raw || !s.chars().any(is_shown) {
+        return s.into();
+    }
+    let mut shown = String::with_capacity(s.len());
+    for c in s.chars() {
+        if !is_shown(c) {
+            shown.push(c);
+        } else if mode == ControlChars::Escape {
+            shown.push_str(&wrap(&escape_control(c)));
+        }
+    }
+    shown.into()
+}
+
+/// A control picture for C0 controls and DEL, `cat -v`'s notation for C1 controls
+fn escape_control(c: char) -> String {
+    let code = u32::from(c);
+    let picture = match code {
+        0x00..=0x1F => char::from_u32(0x2400 + code),
+        0x7F => Some('\u{2421}'),
+        _ => None,
+    };
+    if let Some(picture) = picture {
+        picture.to_string()
+    } else {
+        let caret = char::from_u32(code - 0x80 + 0x40).unwrap_or('?');
+        format!("M-^{caret}")
+    }
+}
+
+/// Keep right-to-left text from visually reordering the rest of the line
+fn isolate(s: &str) -> String {
+    if s.chars().any(is_rtl) {
+        format!("{FIRST_STRONG_ISOLATE}{s}{POP_DIRECTIONAL_ISOLATE}")
+    } else {
+        s.to_owned()
+    }
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Whether `c` is in a block of right-to-left scripts, like Hebrew and Arabic
+fn is_rtl(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn width_of_tricky_strings() {
+        assert_eq!(display_width("Ada"), 3);
+        // Combining acute accent
+        assert_eq!(display_width("Jose\u{301}"), 4);
+        assert_eq!(display_width("李小龍"), 6);
+        // Woman technologist: woman, ZWJ, laptop
+        assert_eq!(display_width("\u{1F469}\u{200D}\u{1F4BB}"), 2);
+        assert_eq!(display_width("שלום"), 4);
+    }
+
+    #[test]
+    fn truncate_by_width() {
+        assert_eq!(truncate_end("Fix parser", 10, "…"), "Fix parser");
+        assert_eq!(truncate_end("Fix parser crash", 10, "…"), "Fix parse…");
+        assert_eq!(truncate_end("Fix parser crash", 10, "..."), "Fix par...");
+        // Never splitting a wide character
+        assert_eq!(truncate_end("修正李小龍", 6, "…"), "修正…");
+        assert_eq!(truncate_end("Fix parser", 4, "…"), "Fix…");
+        assert_eq!(truncate_end("Fix", 0, "…"), "…");
+    }
+
+    #[test]
+    fn pad_isolates_rtl() {
+        assert_eq!(pad("Ada", 5), "Ada  ");
+        assert_eq!(pad("李", 3), "李 ");
+        assert_eq!(pad("שלום", 6), "\u{2068}שלום\u{2069}  ");
+    }
+
+    #[test]
+    fn tabs_to_the_next_stop() {
+        assert_eq!(expand_tabs("\tx", 4, &mut 0), "    x");
+        assert_eq!(expand_tabs("ab\tx", 4, &mut 0), "ab  x");
+        assert_eq!(expand_tabs("abcd\tx", 4, &mut 0), "abcd    x");
+        assert_eq!(expand_tabs("李\tx", 8, &mut 0), "李      x");
+        let mut column = 0;
+        let pieces = ["fn", "\t", "x\ty"].map(|piece| expand_tabs(piece, 4, &mut column));
+        assert_eq!(pieces.concat(), "fn  x   y");
+        assert_eq!(column, 9);
+    }
+
+    #[test]
+    fn show_control_characters() {
+        let osc = "a\u{1b}]0;owned\u{7}\tb\u{9b}c\u{7f}";
+        assert_eq!(
+            show_controls(osc, ControlChars::Escape, |c| format!("<{c}>")),
+            "a<␛>]0;owned<␇>\tb<M-^[>c<␡>"
+        );
+        assert_eq!(
+            show_controls(osc, ControlChars::Strip, |c| c.to_owned()),
+            "a]0;owned\tbc"
+        );
+        assert_eq!(show_controls(osc, ControlChars::Raw, |c| c.to_owned()), osc);
+    }
+
+    #[test]
+    fn sanitize_strips_controls() {
+        assert_eq!(sanitize("evil\u{202E}gnp.exe"), "evilgnp.exe");
+        assert_eq!(sanitize("a\u{2067}b\u{2069}\tc"), "abc");
+        assert_eq!(
+            sanitize("\u{1F469}\u{200D}\u{1F4BB}"),
+            "\u{1F469}\u{200D}\u{1F4BB}"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Initial", &[("file.txt", "one\ntwo\n")]);
+    std::fs::create_dir_all(root_path.join("sub")).unwrap();
+}
+
+#[test]
+fn replays_last_run() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let quickfix = "\
+file.txt:1: e63acb4 Test User 1970-01-01 Initial
+file.txt:2: e63acb4 Test User 1970-01-01 Initial
+";
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format", "quickfix", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(quickfix.raw());
+
+    // From where it was first run, even without arguments
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .current_dir(root_path.join("sub"))
+        .assert()
+        .success()
+        .stdout_eq(quickfix.raw())
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--again", "--hunks-only"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.txt:1: e63acb4 Test User 1970-01-01 Initial
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn nothing_to_replay() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format", "quickfix", "file.txt"])
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.remember")
+        .env("GIT_CONFIG_VALUE_0", "false")
+        .assert()
+        .success();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--again")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+no previous run to replay
+"
+            .raw(),
+        );
+
+    // Unreadable state is ignored rather than misread
+    std::fs::write(
+        root_path.join(".git/dive-last"),
+        r#"{"version": 99, "argv": ["file.txt"]}"#,
+    )
+    .unwrap();
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--again")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+no previous run to replay
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,133 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\n\ntwo\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\n\ntwo\n  \nthree\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("B".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+#[test]
+fn annotated_by_default() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ 
+⋮      3 │ two
+HEAD   4 │ 
+⋮      5 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn not_annotated() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    // The hunk starting on a blank line is shown on its next line
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.annotate-blank-lines")
+        .env("GIT_CONFIG_VALUE_0", "false")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+       2 │ 
+⋮      3 │ two
+       4 │ 
+HEAD   5 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--annotate-blank-lines")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.annotate-blank-lines")
+        .env("GIT_CONFIG_VALUE_0", "false")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ 
+⋮      3 │ two
+HEAD   4 │ 
+⋮      5 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn stat_unaffected() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    for annotate in ["true", "false"] {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .arg("--stat")
+            .arg(format!("--annotate-blank-lines={annotate}"))
+            .arg("basic.txt")
+            .current_dir(root_path)
+            .assert()
+            .success()
+            .stdout_eq(
+                "\
+Authors:
+  Test User 5 100%
+
+Boundary commits:
+  [..] root 3 A
+",
+            )
+            .stderr_eq("");
+    }
+
+    root.close().unwrap();
+}
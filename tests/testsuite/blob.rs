@@ -0,0 +1,96 @@
+use snapbox::prelude::*;
+
+/// `basic.txt` in two commits, returning the blob from each
+fn fixture(root_path: &std::path::Path) -> (git2::Oid, git2::Oid) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let mut blobs = Vec::new();
+    for (message, content) in [("A", "one\ntwo\n"), ("B", "one\ntwo\nthree\n")] {
+        fixture.commit(message, &[("basic.txt", content)]);
+        blobs.push(git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes()).unwrap());
+    }
+    fixture.checkout();
+    (blobs[0], blobs[1])
+}
+
+#[test]
+fn without_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (old, _) = fixture(root_path);
+
+    // The path is only a name, so it needn't exist
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--blob")
+        .arg(old.to_string())
+        .arg("elsewhere.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+??????? 1 │ one
+⋮       2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn with_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (old, _) = fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--blob")
+        .arg(old.to_string())
+        .arg("HEAD~1")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn mismatch() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (old, new) = fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--blob")
+        .arg(old.to_string())
+        .arg("HEAD")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("")
+        .stderr_eq(format!(
+            "basic.txt at HEAD is blob {new}, not {old}; drop `REV` to show the blob without attribution\n"
+        ));
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--blob=HEAD")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("")
+        .stderr_eq("`HEAD` is a commit, not a blob\n");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,174 @@
+/// An import, then a line each from someone else, returning the commits oldest first
+fn fixture(root_path: &std::path::Path) -> Vec<git2::Oid> {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let versions = [
+        ("Ada", "Import", "a\nb\nc\n"),
+        ("Grace", "Add d", "a\nb\nc\nd\n"),
+        ("Grace", "Add e", "a\nb\nc\nd\ne\n"),
+    ];
+    let mut ids = Vec::new();
+    for (i, (author, message, content)) in versions.into_iter().enumerate() {
+        let email = format!("{}@example.com", author.to_lowercase());
+        fixture.author(author, &email, 86_400 * i as i64);
+        ids.push(fixture.commit(message, &[("basic.txt", content)]));
+    }
+    fixture.checkout();
+    ids
+}
+
+#[test]
+fn hide_boundary() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--hide-boundary")
+        .arg("--style=hash,numbers,grid")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "         ⋮ … 3 lines from [..], a root commit …
+HEAD~1 4 │ d
+HEAD   5 │ e
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn boundary_style_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=hash,numbers,grid")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.boundary-style")
+        .env("GIT_CONFIG_VALUE_0", "hide")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "         ⋮ … 3 lines from [..], a root commit …
+HEAD~1 4 │ d
+HEAD   5 │ e
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn stat_lists_boundaries() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   3  60%
+  Grace 2  40%
+
+Boundary commits:
+  [..] root 3 Import
+",
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("--no-boundary")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Grace 2 100%
+
+Boundary commits, not counted:
+  [..] root 3 Import
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn stat_json_boundaries() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let ids = fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--stat", "--no-boundary", "--format=json", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["lines"], 2);
+    assert_eq!(json["boundaries_counted"], false);
+    assert_eq!(
+        json["boundaries"],
+        serde_json::json!([{"commit": ids[0].to_string(), "boundary": "root", "lines": 3}])
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn shallow_boundary() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let ids = fixture(root_path);
+    // As if cloned with `--depth 2`
+    std::fs::write(root_path.join(".git/shallow"), format!("{}\n", ids[1])).unwrap();
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=json", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let boundaries = json[0]["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line["boundary"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        boundaries,
+        ["shallow", "shallow", "shallow", "shallow"]
+            .map(serde_json::Value::from)
+            .into_iter()
+            .chain([serde_json::Value::Null])
+            .collect::<Vec<_>>()
+    );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,102 @@
+use snapbox::prelude::*;
+
+/// `one` through `four` from a first commit, `five` from a second, with `two` changed and a
+/// line inserted after `four` in the worktree
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for (content, message) in [
+        ("one\ntwo\nthree\nfour\n", "First"),
+        ("one\ntwo\nthree\nfour\nfive\n", "Second"),
+    ] {
+        fixture.commit(message, &[("file.txt", content)]);
+    }
+    std::fs::write(
+        root_path.join("file.txt"),
+        "one\nTWO\nthree\nfour\nnew\nfive\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn worktree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--changed-only", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.txt:2: 2c43e0a Test User 1970-01-01 First
+file.txt:4: 2c43e0a Test User 1970-01-01 First
+file.txt:5: 1d22e24 Test User 1970-01-01 Second
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn staged() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    // Only the inserted line is staged, the worktree still also changing `two`
+    let worktree = std::fs::read(root_path.join("file.txt")).unwrap();
+    std::fs::write(root_path.join("file.txt"), "one\ntwo\nthree\nfour\nnew\nfive\n").unwrap();
+    let repo = git2::Repository::open(root_path).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    std::fs::write(root_path.join("file.txt"), worktree).unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--changed-only", "--staged", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.txt:4: 2c43e0a Test User 1970-01-01 First
+file.txt:5: 1d22e24 Test User 1970-01-01 Second
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn fail_older_than() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--changed-only", "--fail-older-than", "2y", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(1)
+        .stdout_eq(
+            "\
+file.txt:2: 2c43e0a Test User 1970-01-01 First
+file.txt:4: 2c43e0a Test User 1970-01-01 First
+file.txt:5: 1d22e24 Test User 1970-01-01 Second
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+3 touched lines older than 2y
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
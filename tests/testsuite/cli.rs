@@ -39,6 +39,39 @@ HEAD 1 │ test('arg1');
     root.close().unwrap();
 }
 
+#[test]
+fn no_output() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.js", "test('arg1');")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+
+    for format in ["text", "json", "quickfix"] {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--no-output", "--format", format, "basic.js"])
+            .current_dir(root_path)
+            .assert()
+            .success()
+            .stdout_eq("".raw())
+            .stderr_eq("".raw());
+    }
+
+    root.close().unwrap();
+}
+
 #[test]
 fn js_highlight_panics() {
     let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
@@ -72,3 +105,295 @@ fn js_highlight_panics() {
 
     root.close().unwrap();
 }
+
+#[test]
+fn pager_sections_only_for_their_pager() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.js", "test('arg1');")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+
+    // Without a terminal, `moar` isn't run, so nothing is marked for it
+    for sections in ["none", "ov", "moar"] {
+        let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--pager=moar", "--color=always", "basic.js"])
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.pager-sections")
+            .env("GIT_CONFIG_VALUE_0", sections)
+            .current_dir(root_path)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("\x1b]133;"), "{sections}: {output:?}");
+        assert!(output.contains("test"), "{sections}: {output:?}");
+    }
+
+    root.close().unwrap();
+}
+
+#[test]
+fn pager_conflicts_with_no_pager() {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--pager=less")
+        .arg("--no-pager")
+        .arg("basic.js")
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: the argument '--pager <COMMAND>' cannot be used with '--no-pager'
+
+Usage: git-dive --pager <COMMAND> [..]
+
+For more information, try '--help'.
+",
+        );
+}
+
+#[test]
+fn diagnostic_reports_pager() {
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--diagnostic")
+        .arg("--pager=most -s")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("`most -s` (from --pager)"),
+        "pager missing from:\n{output}"
+    );
+}
+
+#[test]
+fn pager_by_output_height() {
+    let diagnostic = |args: &[&str]| {
+        let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .arg("--diagnostic")
+            .args(args)
+            .env("GIT_PAGER", "more")
+            .env("GIT_CONFIG_COUNT", "2")
+            .env("GIT_CONFIG_KEY_0", "dive.pager-long")
+            .env("GIT_CONFIG_VALUE_0", "most -s")
+            .env("GIT_CONFIG_KEY_1", "dive.pager-short")
+            .env("GIT_CONFIG_VALUE_1", "bat --paging=never")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let output = diagnostic(&[]);
+    assert!(
+        output.contains("`most -s` (from ") && output.contains(", `bat --paging=never` for short"),
+        "pagers missing from:\n{output}"
+    );
+
+    let output = diagnostic(&["--pager=less"]);
+    assert!(
+        output.contains("`less` (from --pager)") && !output.contains("for short output"),
+        "pager missing from:\n{output}"
+    );
+
+    let output = diagnostic(&["--no-pager"]);
+    assert!(
+        output.contains("`cat` (from --no-pager)") && !output.contains("for short output"),
+        "pager missing from:\n{output}"
+    );
+}
+
+#[test]
+fn already_paged() {
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--diagnostic")
+        .env("GIT_PAGER", "more")
+        .env("GIT_PAGER_IN_USE", "true")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("`more` (from GIT_PAGER), unused since `GIT_PAGER_IN_USE` is set"),
+        "pager missing from:\n{output}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn diagnostic_reports_less_version() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let less = root_path.join("less");
+    std::fs::write(&less, "#!/bin/sh\necho 'less 487 (POSIX regular expressions)'\n").unwrap();
+    std::fs::set_permissions(&less, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let cache = root_path.join("cache");
+
+    let diagnostic = |probe: &str| {
+        let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .arg("--diagnostic")
+            .arg(format!("--pager={}", less.display()))
+            .env("XDG_CACHE_HOME", &cache)
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.pager-probe")
+            .env("GIT_CONFIG_VALUE_0", probe)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let output = diagnostic("true");
+    assert!(
+        output.contains("less version 487, which needs `-X` with `-F`"),
+        "less version missing from:\n{output}"
+    );
+    let cached = std::fs::read_to_string(cache.join("git-dive").join("less-version")).unwrap();
+    assert!(cached.starts_with("487\t"), "{cached}");
+
+    let output = diagnostic("false");
+    assert!(
+        output.contains("less version not probed, per `dive.pager-probe`"),
+        "probe setting missing from:\n{output}"
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn log_filter_focuses_targets() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.js", "test('arg1');")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("-vvv")
+        .arg("--pager=less")
+        .arg("basic.js")
+        .current_dir(root_path)
+        .env("GIT_DIVE_LOG", "git_dive::git_pager=debug")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ test('arg1');
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+[..] pager `less` (from --pager)
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn build_info() {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--build-info")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+git-dive [..]
+commit-hash: [..]
+build-date: [..]-[..]-[..]
+rustc: rustc [..]
+target: [..]
+features: [..]
+libgit2: [..].[..].[..] ([..])
+syntaxes: [..]
+themes: [..]
+",
+        )
+        .stderr_eq("");
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--diagnostic")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("#### Build\n\n- commit-hash: "),
+        "build info missing from:\n{output}"
+    );
+}
+
+#[test]
+fn acknowledgements() {
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--acknowledgements")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("# Syntaxes and themes\n\n"), "{output}");
+    assert!(output.contains("\n# Crates\n\n"), "{output}");
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--acknowledgements")
+        .arg("--format=json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let crates: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    for field in ["name", "version", "license"] {
+        assert!(
+            crates
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|c| c.get(field).is_some()),
+            "`{field}` missing from {crates}"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use snapbox::prelude::*;
+
+/// Lines from several authors, one of whom committed under a second email
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut content = String::new();
+    for (name, email, line) in [
+        ("Ada", "ada@example.com", "one"),
+        ("Grace", "grace@example.com", "two"),
+        ("Ada", "ada@old.example.com", "three"),
+        ("Linus", "linus@example.com", "four"),
+        ("Margaret", "margaret@example.com", "five"),
+    ] {
+        content.push_str(line);
+        content.push('\n');
+        fixture.author(name, email, 0).commit(
+            line,
+            &[
+                ("basic.txt", &content),
+                (".mailmap", "Ada <ada@example.com> <ada@old.example.com>\n"),
+            ],
+        );
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn author() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--color-by=author")
+        .arg("--style=stripe,author,numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "3")
+        .env("GIT_CONFIG_KEY_0", "dive.author-palette")
+        .env("GIT_CONFIG_VALUE_0", "red")
+        .env("GIT_CONFIG_KEY_1", "dive.author-palette")
+        .env("GIT_CONFIG_VALUE_1", "blue")
+        .env("GIT_CONFIG_KEY_2", "dive.theme")
+        .env("GIT_CONFIG_VALUE_2", "ansi")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+\x1b[31m▎\x1b[0m \x1b[31mAda     \x1b[0m 1 \x1b[0mone\x1b[0m
+\x1b[0m\x1b[31m▎\x1b[0m \x1b[31mGrace   \x1b[0m 2 \x1b[0mtwo\x1b[0m
+\x1b[0m\x1b[31m▎\x1b[0m \x1b[31mAda     \x1b[0m 3 \x1b[0mthree\x1b[0m
+\x1b[0m\x1b[34m▎\x1b[0m \x1b[34mLinus   \x1b[0m 4 \x1b[0mfour\x1b[0m
+\x1b[0m\x1b[34m▎\x1b[0m \x1b[34mMargaret\x1b[0m 5 \x1b[0mfive\x1b[0m
+\x1b[0m
+Authors:
+  \x1b[31m▎\x1b[0m Ada
+  \x1b[31m▎\x1b[0m Grace
+  \x1b[34m▎\x1b[0m Linus
+  \x1b[34m▎\x1b[0m Margaret
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_color() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color-by=author")
+        .arg("--style=stripe,numbers")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+▎ 1 one
+▎ 2 two
+▎ 3 three
+▎ 4 four
+▎ 5 five
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
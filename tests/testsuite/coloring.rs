@@ -0,0 +1,157 @@
+use snapbox::prelude::*;
+
+/// An old commit followed by one made just now
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    for (content, time) in [("one\ntwo\n", 0), ("one\ntwo\nthree\n", now)] {
+        fixture
+            .author("Ada", "ada@example.com", time)
+            .commit("change", &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn blame_coloring_highlight_recent() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--style=hash")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", "blame.coloring")
+        .env("GIT_CONFIG_VALUE_0", "highlightRecent")
+        .env("GIT_CONFIG_KEY_1", "dive.theme")
+        .env("GIT_CONFIG_VALUE_1", "ansi")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+\x1b[34mHEAD~1\x1b[0m \x1b[0mone\x1b[0m
+\x1b[0m\x1b[34m⋮     \x1b[0m \x1b[0mtwo\x1b[0m
+\x1b[0m\x1b[31mHEAD  \x1b[0m \x1b[0mthree\x1b[0m
+\x1b[0m"
+                .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn repeated_lines_flag_overrides_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--coloring=repeated-lines")
+        .arg("--style=hash")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "3")
+        .env("GIT_CONFIG_KEY_0", "blame.coloring")
+        .env("GIT_CONFIG_VALUE_0", "highlightRecent")
+        .env("GIT_CONFIG_KEY_1", "dive.theme")
+        .env("GIT_CONFIG_VALUE_1", "ansi")
+        .env("GIT_CONFIG_KEY_2", "dive.hash-color")
+        .env("GIT_CONFIG_VALUE_2", "green")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+\x1b[32mHEAD~1\x1b[0m \x1b[0mone\x1b[0m
+\x1b[0m\x1b[2m\x1b[32m⋮     \x1b[0m \x1b[0mtwo\x1b[0m
+\x1b[0m\x1b[32mHEAD  \x1b[0m \x1b[0mthree\x1b[0m
+\x1b[0m"
+                .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_color_ignores_coloring() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--coloring=highlight-recent")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+HEAD   3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn fade_older_than() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    for (content, time) in [("fn one() {}\n", 0), ("fn one() {}\nfn two() {}\n", now)] {
+        fixture
+            .author("Ada", "ada@example.com", time)
+            .commit("change", &[("lib.rs", content)]);
+    }
+
+    // Every highlighted region of the old line stays dim
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--color=always", "--style=hash", "--fade-older-than=1y", "lib.rs"])
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.theme")
+        .env("GIT_CONFIG_VALUE_0", "ansi")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+\x1b[2mHEAD~1\x1b[0m \x1b[0m\x1b[2m\x1b[35mfn\x1b[0m\x1b[2m \x1b[0m\x1b[2m\x1b[34mone\x1b[0m\x1b[2m(\x1b[0m\x1b[2m)\x1b[0m\x1b[2m \x1b[0m\x1b[2m{\x1b[0m\x1b[2m}\x1b[0m
+\x1b[0mHEAD   \x1b[0m\x1b[35mfn\x1b[0m \x1b[0m\x1b[34mtwo\x1b[0m(\x1b[0m)\x1b[0m \x1b[0m{\x1b[0m}\x1b[0m
+\x1b[0m"
+                .raw(),
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--fade-older-than=1x", "lib.rs"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: invalid value '1x' for '--fade-older-than <DURATION>': invalid duration `1x`; expected counts with units like `90d`, where the units are `y`, `m`, `w`, `d`, and `h`
+
+For more information, try '--help'.
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,90 @@
+use snapbox::prelude::*;
+
+/// `two` changed and `four` added in the second commit
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for content in ["one\ntwo\nthree\n", "one\nTWO\nthree\nfour\n"] {
+        fixture.commit("Commit", &[("file.txt", content)]);
+    }
+}
+
+#[test]
+fn side_by_side() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--compare", "HEAD~1", "HEAD", "file.txt"])
+        .current_dir(root_path)
+        .env("COLUMNS", "40")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.compare-split-width")
+        .env("GIT_CONFIG_VALUE_0", "40")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1             ┃ HEAD
+4b3c39f 1 │ one    ┃ 4b3c39f 1 │ one
+4b3c39f 2 │ two    ┃ 5908209 2 │ TWO
+4b3c39f 3 │ three  ┃ 4b3c39f 3 │ three
+                   ┃ 5908209 4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unified_when_narrow() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--compare", "HEAD~1", "HEAD", "file.txt"])
+        .current_dir(root_path)
+        .env("COLUMNS", "40")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+--- HEAD~1
++++ HEAD
+  4b3c39f 1 │ one
+- 4b3c39f 2 │ two
++ 5908209 2 │ TWO
+  4b3c39f 3 │ three
++ 5908209 4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn compare_needs_text() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--compare", "HEAD~1", "HEAD", "--format=json", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--compare` only supports `--format text`
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
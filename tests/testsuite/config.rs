@@ -0,0 +1,285 @@
+use snapbox::prelude::*;
+
+#[test]
+fn global_and_system_files() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path.join("repo")).unwrap();
+    std::fs::write(root_path.join("global"), "[dive]\n\ttheme = GitHub\n").unwrap();
+    std::fs::write(root_path.join("system"), "[core]\n\tpager = most\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("global"))
+        .env("GIT_CONFIG_SYSTEM", root_path.join("system"))
+        .env_remove("GIT_CONFIG_NOSYSTEM")
+        .env_remove("GIT_PAGER")
+        .env_remove("PAGER")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+[color]
+	ui = auto  # default
+[core]
+	pager = most  # [..]/system
+[blame]
+	date = short  # default
+	coloring = none  # default
+[dive]
+	theme = GitHub  # [..]/global
+	strict = true  # default
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_system_and_missing_global() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path.join("repo")).unwrap();
+    std::fs::write(root_path.join("system"), "[core]\n\tpager = most\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("missing"))
+        .env("GIT_CONFIG_SYSTEM", root_path.join("system"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env_remove("GIT_PAGER")
+        .env_remove("PAGER")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+[color]
+	ui = auto  # default
+[core]
+	pager = less  # default
+[blame]
+	date = short  # default
+	coloring = none  # default
+[dive]
+	theme = Monokai Extended  # default
+	strict = true  # default
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unknown_key_warns() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path.join("repo")).unwrap();
+    std::fs::write(
+        root_path.join("global"),
+        "[dive]\n\tthme = GitHub\n\tfrobnicate = true\n",
+    )
+    .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("global"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_PARAMETERS", "'dive.line-numbr-color'='red'")
+        .env_remove("GIT_PAGER")
+        .env_remove("PAGER")
+        .assert()
+        .success()
+        .stderr_eq(
+            "\
+WARN: unknown config `dive.line-numbr-color` in git-cli; did you mean `dive.line-number-color`?
+WARN: unknown config `dive.thme` in [..]/global; did you mean `dive.theme`?
+WARN: unknown config `dive.frobnicate` in [..]/global
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unknown_key_check_disabled() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path.join("repo")).unwrap();
+    std::fs::write(
+        root_path.join("global"),
+        "[dive]\n\tthme = GitHub\n\tstrict = false\n",
+    )
+    .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("global"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .assert()
+        .success()
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .arg("--no-config-check")
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_PARAMETERS", "'dive.thme'='GitHub'")
+        .assert()
+        .success()
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn explain_layers() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path.join("repo")).unwrap();
+    std::fs::write(
+        root_path.join("global"),
+        "[dive]\n\tmove-badge-threshold = 10\n",
+    )
+    .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--explain", "dive.move-badge-threshold"])
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("global"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.move-badge-threshold")
+        .env("GIT_CONFIG_VALUE_0", "many")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+dive.move-badge-threshold
+	type: i64
+	default: `50`
+	git-config-env = many  # ignored, failed to read `dive.move-badge-threshold`: invalid digit found in string
+	[..]/global = 10  # used
+",
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--explain", "dive.theme"])
+        .current_dir(root_path.join("repo"))
+        .env("GIT_CONFIG_GLOBAL", root_path.join("global"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+dive.theme
+	type: String
+	default: `Monokai Extended`
+	not set, so the default is used
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn explain_unknown_key() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path).unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--explain", "dive.pager-hort"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+unknown config `dive.pager-hort`; did you mean `dive.pager-short`, `dive.pager-long`?
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn include_if_onbranch() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let repo = git2::Repository::init(root_path).unwrap();
+    let git_dir = repo.path();
+    std::fs::write(
+        git_dir.join("config"),
+        "\
+[includeIf \"onbranch:release/*\"]
+\tpath = star.inc
+[includeIf \"onbranch:release/**\"]
+\tpath = double-star.inc
+[includeIf \"onbranch:topic/\"]
+\tpath = prefix.inc
+",
+    )
+    .unwrap();
+    for (file, key) in [
+        ("star.inc", "move-badge-threshold"),
+        ("double-star.inc", "pager-min-lines"),
+        ("prefix.inc", "max-resolution-merges"),
+    ] {
+        std::fs::write(git_dir.join(file), format!("[dive]\n\t{key} = 7\n")).unwrap();
+    }
+
+    let explain = |key: &str| {
+        let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--explain", key])
+            .current_dir(root_path)
+            .env("GIT_CONFIG_GLOBAL", root_path.join("missing"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap().contains("= 7  # used")
+    };
+    let set_head = |target: &str| {
+        std::fs::write(git_dir.join("HEAD"), format!("{target}\n")).unwrap();
+    };
+
+    // Before the first commit, the unborn branch still counts
+    set_head("ref: refs/heads/release/1.x");
+    assert!(explain("dive.move-badge-threshold"));
+    assert!(explain("dive.pager-min-lines"));
+    assert!(!explain("dive.max-resolution-merges"));
+
+    // `*` doesn't cross a `/`, unlike `**` and a trailing `/`
+    set_head("ref: refs/heads/release/1.x/hotfix");
+    assert!(!explain("dive.move-badge-threshold"));
+    assert!(explain("dive.pager-min-lines"));
+
+    set_head("ref: refs/heads/topic/a/b");
+    assert!(explain("dive.max-resolution-merges"));
+    assert!(!explain("dive.pager-min-lines"));
+
+    // Detached matches nothing
+    let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+    let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap()).unwrap();
+    let commit = repo.commit(None, &sig, &sig, "A", &tree, &[]).unwrap();
+    set_head(&commit.to_string());
+    assert!(!explain("dive.move-badge-threshold"));
+    assert!(!explain("dive.pager-min-lines"));
+    assert!(!explain("dive.max-resolution-merges"));
+
+    root.close().unwrap();
+}
@@ -0,0 +1,133 @@
+use snapbox::prelude::*;
+
+/// `main` and `topic` both change `two` from a common base, with `topic` being merged into
+/// `main` and conflicting
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let base = fixture.commit_onto("Base", &[("file.txt", "one\ntwo\nthree\n")], &[]);
+    let ours = fixture.commit_onto("Ours", &[("file.txt", "one\ntwo, ours\nthree\n")], &[base]);
+    let theirs = fixture.commit_onto(
+        "Theirs",
+        &[("file.txt", "one\ntwo, theirs\nthree\nfour\n")],
+        &[base],
+    );
+    fixture
+        .repo
+        .reference("refs/heads/topic", theirs, true, "fixture")
+        .unwrap();
+    fixture.branch("main", ours);
+    fixture.checkout();
+    let topic = fixture.repo.find_annotated_commit(theirs).unwrap();
+    fixture.repo.merge(&[&topic], None, None).unwrap();
+    assert!(fixture.repo.index().unwrap().has_conflicts());
+}
+
+#[test]
+fn ours() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--ours", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+HEAD   2 │ two, ours
+HEAD~1 3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn theirs() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args([":3:file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+MERGE_HEAD~1 1 │ one
+MERGE_HEAD   2 │ two, theirs
+MERGE_HEAD~1 3 │ three
+MERGE_HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn base() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--base", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+032f3f 1 │ one
+⋮      2 │ two
+⋮      3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn not_conflicted() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Commit", &[("file.txt", "one\n")]);
+    fixture.checkout();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--ours", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+file.txt isn't conflicted
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--theirs", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+no merge, cherry-pick, rebase, or revert is in progress to take `--theirs` from
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,123 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("retitle.sh", "echo '\u{1b}]0;owned\u{7}'\n\tdone\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+fn dive(root_path: &std::path::Path, mode: Option<&str>) -> snapbox::cmd::Command {
+    let mut cmd = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=numbers")
+        .arg("retitle.sh")
+        .current_dir(root_path);
+    if let Some(mode) = mode {
+        cmd = cmd
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.show-control-chars")
+            .env("GIT_CONFIG_VALUE_0", mode);
+    }
+    cmd
+}
+
+#[test]
+fn escaped_by_default() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, None)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 echo '␛]0;owned␇'
+2 \tdone
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    let output = dive(root_path, None)
+        .arg("--color=always")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(!output.contains("\u{1b}]"), "OSC leaked into:\n{output:?}");
+    assert!(!output.contains('\u{7}'), "BEL leaked into:\n{output:?}");
+    assert!(output.contains("␛"), "escape missing from:\n{output:?}");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn strip_and_raw() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, Some("strip"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 echo ']0;owned'
+2 \tdone
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Without color, escape sequences are stripped on the way out regardless
+    let output = dive(root_path, Some("raw"))
+        .arg("--color=always")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("\u{1b}]0;owned\u{7}"),
+        "OSC missing from:\n{output:?}"
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_keeps_raw_content() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = dive(root_path, Some("strip"))
+        .arg("--format=json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains(r#""content": "echo '\u001b]0;owned\u0007'""#),
+        "raw content missing from:\n{output}"
+    );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,122 @@
+use snapbox::prelude::*;
+
+/// Three commits to `basic.txt`, the first one's version left as `damage` makes it
+fn fixture(root_path: &std::path::Path, damage: impl FnOnce(&std::path::Path)) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    fixture.author("Alice", "alice@example.com", 0);
+    for (message, content) in [("A", "1\n2\n"), ("B", "1\n2\n3\n"), ("C", "1\n2\n3\n4\n")] {
+        fixture.commit(message, &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+
+    let hex = git2::Oid::hash_object(git2::ObjectType::Blob, b"1\n2\n")
+        .unwrap()
+        .to_string();
+    let loose = root_path
+        .join(".git/objects")
+        .join(&hex[..2])
+        .join(&hex[2..]);
+    let mut permissions = std::fs::metadata(&loose).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    std::fs::set_permissions(&loose, permissions).unwrap();
+    damage(&loose);
+}
+
+fn truncate(loose: &std::path::Path) {
+    let compressed = std::fs::read(loose).unwrap();
+    std::fs::write(loose, &compressed[..compressed.len() / 2]).unwrap();
+}
+
+fn remove(loose: &std::path::Path) {
+    std::fs::remove_file(loose).unwrap();
+}
+
+fn dive(root_path: &std::path::Path) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=hash,numbers")
+        .current_dir(root_path)
+}
+
+#[test]
+fn truncated_gives_partial_results() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, truncate);
+
+    for engine in ["libgit2", "internal"] {
+        dive(root_path)
+            .arg("basic.txt")
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.blame-engine")
+            .env("GIT_CONFIG_VALUE_0", engine)
+            .assert()
+            .success()
+            .stdout_eq(
+                "\
+!!!!!!! 1 1
+⋮       2 2
+⋮       3 3
+HEAD    4 4
+"
+                .raw(),
+            )
+            .stderr_eq(
+                "\
+WARN: 3 lines are attributed to `unknown (object missing)`, since history past these objects can't be read:
+  [..]
+run `git fsck` to check the repository, or `--strict-objects` to fail
+",
+            );
+    }
+
+    root.close().unwrap();
+}
+
+#[test]
+fn missing_gives_partial_results() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, remove);
+
+    dive(root_path)
+        .args(["--stat", "basic.txt"])
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  unknown (object missing) 3  75%
+  Alice                    1  25%
+",
+        )
+        .stderr_eq(
+            "\
+WARN: 3 lines are attributed to `unknown (object missing)`, since history past these objects can't be read:
+  [..]
+run `git fsck` to check the repository, or `--strict-objects` to fail
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn strict_fails() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, truncate);
+
+    dive(root_path)
+        .args(["--strict-objects", "basic.txt"])
+        .assert()
+        .code(1)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+object [..] needed to blame basic.txt can't be read; run `git fsck` to check the repository
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,102 @@
+#![cfg(unix)]
+
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Initial", &[("basic.txt", "one\ntwo\n")]);
+    fixture.checkout();
+}
+
+fn dive(root_path: &std::path::Path, runtime: &std::path::Path) -> std::process::Command {
+    let mut cmd = std::process::Command::new(snapbox::cmd::cargo_bin!("git-dive"));
+    cmd.current_dir(root_path)
+        .env("XDG_RUNTIME_DIR", runtime)
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE");
+    cmd
+}
+
+#[test]
+fn client_matches_local() {
+    use std::io::BufRead as _;
+    use std::io::Write as _;
+
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let repo_path = root_path.join("repo");
+    std::fs::create_dir_all(&repo_path).unwrap();
+    fixture(&repo_path);
+    let runtime = root_path.join("run");
+    std::fs::create_dir_all(&runtime).unwrap();
+    let socket = runtime.join("git-dive").join("daemon.sock");
+
+    let mut daemon = dive(&repo_path, &runtime)
+        .arg("--daemon")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let start = std::time::Instant::now();
+    while !socket.exists() {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(30),
+            "daemon didn't start"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+
+    let local = dive(&repo_path, &runtime)
+        .args(["--format=json", "basic.txt"])
+        .output()
+        .unwrap();
+    assert!(local.status.success());
+    let client = dive(&repo_path, &runtime)
+        .args(["--client", "-vv", "--format=json", "basic.txt"])
+        .output()
+        .unwrap();
+    assert!(client.status.success());
+    assert_eq!(
+        String::from_utf8(client.stdout).unwrap(),
+        String::from_utf8(local.stdout).unwrap()
+    );
+    let stderr = String::from_utf8(client.stderr).unwrap();
+    assert!(stderr.contains("answered by the daemon"), "{stderr}");
+
+    let missing = dive(&repo_path, &runtime)
+        .args(["--client", "--format=json", "missing.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(missing.status.code(), Some(5));
+    let stderr = String::from_utf8(missing.stderr).unwrap();
+    assert!(stderr.contains("missing.txt"), "{stderr}");
+
+    let stream = std::os::unix::net::UnixStream::connect(&socket).unwrap();
+    let request = serde_json::json!({
+        "cwd": repo_path,
+        "args": ["--format=json"],
+        "rev": "HEAD",
+        "path": "basic.txt",
+    });
+    writeln!(&stream, "{request}").unwrap();
+    let unsupported = serde_json::json!({"cwd": repo_path, "args": ["--stat", "basic.txt"]});
+    writeln!(&stream, "{unsupported}").unwrap();
+    let mut lines = std::io::BufReader::new(&stream).lines();
+    let response: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(response[0]["path"], "basic.txt");
+    assert_eq!(response[0]["lines"][1]["content"], "two");
+    let response: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(response["code"], 2);
+    drop(lines);
+    drop(stream);
+
+    let stop = dive(&repo_path, &runtime).arg("--daemon-stop").output().unwrap();
+    assert!(stop.status.success());
+    assert!(daemon.wait().unwrap().success());
+    assert!(!socket.exists());
+
+    let stop = dive(&repo_path, &runtime).arg("--daemon-stop").output().unwrap();
+    assert_eq!(stop.status.code(), Some(1));
+    let stderr = String::from_utf8(stop.stderr).unwrap();
+    assert!(stderr.contains("no daemon is listening"), "{stderr}");
+
+    root.close().unwrap();
+}
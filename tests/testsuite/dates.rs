@@ -0,0 +1,267 @@
+use snapbox::prelude::*;
+
+/// A change written before the one it was rebased onto
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let day = 24 * 60 * 60;
+    for (message, content, authored, committed) in [
+        ("A", "one\n", 18262, 18262),      // 2020-01-01
+        ("B", "one\ntwo\n", 18048, 18628), // 2019-06-01, landed 2021-01-01
+    ] {
+        fixture
+            .author("Ada", "ada@example.com", authored * day)
+            .committer("Grace", "grace@example.com", committed * day)
+            .commit(message, &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn author_date_by_default() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+2020-01-01 1 one
+2019-06-01 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn committer_date_flag() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--committer-date")
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+2020-01-01 1 one
+2021-01-01 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn committer_date_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.use-committer-date")
+        .env("GIT_CONFIG_VALUE_0", "true")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] Ada 2020-01-01 A
+basic.txt:2: [..] Ada 2021-01-01 B
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn summary_json_has_both_dates() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--committer-date")
+        .arg("--format=json")
+        .arg(".")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "basic.txt",
+    "commit": "[..]",
+    "author": "Ada",
+    "date": "2021-01-01",
+    "time": 1609459200,
+    "author_time": 1559347200,
+    "committer_time": 1609459200
+  }
+]
+"#,
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn blame_date_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "blame.date")
+        .env("GIT_CONFIG_VALUE_0", "iso8601")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+2020-01-01 00:00:00 +0000 1 one
+2019-06-01 00:00:00 +0000 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn date_flag_overrides_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--date=unix")
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", "blame.date")
+        .env("GIT_CONFIG_VALUE_0", "iso")
+        .env("GIT_CONFIG_KEY_1", "dive.date")
+        .env("GIT_CONFIG_VALUE_1", "rfc")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1577836800 1 one
+1559347200 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn locale_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--date=rfc")
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .env("LANG", "es_ES.UTF-8")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.locale")
+        .env("GIT_CONFIG_VALUE_0", "de")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Mi, 1 Jan 2020 00:00:00 +0000 1 one
+Sa, 1 Jun 2019 00:00:00 +0000 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn locale_from_environment() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--date=rfc")
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .env("COLUMNS", "120")
+        .env_remove("LC_ALL")
+        .env("LC_TIME", "es_ES.UTF-8")
+        .env("LANG", "fr_FR.UTF-8")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+mié, 1 ene 2020 00:00:00 +0000 1 one
+sáb, 1 jun 2019 00:00:00 +0000 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unknown_locale_is_english() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--date=rfc")
+        .arg("--style=date,numbers")
+        .arg("basic.txt")
+        .env("COLUMNS", "120")
+        .env("LC_ALL", "pt_BR.UTF-8")
+        .env("LANG", "de_DE.UTF-8")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Wed, 1 Jan 2020 00:00:00 +0000 1 one
+Sat, 1 Jun 2019 00:00:00 +0000 2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,292 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\n"), ("other.txt", "other\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\nthree\n"), ("other.txt", "other\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("B".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("other.txt", "other\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Remove basic.txt".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("other.txt", "other\nmore\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("D".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+#[test]
+fn deleted_requires_flag() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+basic.txt does not exist at HEAD; pass `--find-deleted` to search history
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn find_deleted() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--find-deleted")
+        .arg("--first-parent")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~3 1 │ one
+⋮      2 │ two
+HEAD~2 3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: file was deleted in [..] (Remove basic.txt); showing it as of [..]
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn find_deleted_gives_up() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--find-deleted")
+        .arg("--max-commits=1")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+basic.txt was not found in the last 1 commits of HEAD
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+/// Write a commit-graph for everything but the latest commit, returning whether `git` could
+fn write_commit_graph(root_path: &std::path::Path) -> bool {
+    let Ok(status) = std::process::Command::new("git")
+        .args(["commit-graph", "write", "--reachable"])
+        .current_dir(root_path)
+        .status()
+    else {
+        return false;
+    };
+    assert!(status.success());
+
+    let repo = git2::Repository::open(root_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "E", &head.tree().unwrap(), &[&head])
+        .unwrap();
+    true
+}
+
+#[test]
+fn find_deleted_with_commit_graph() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    if !write_commit_graph(root_path) {
+        // `git` isn't available
+        root.close().unwrap();
+        return;
+    }
+
+    for no_commit_graph in [false, true] {
+        let mut cmd = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"));
+        if no_commit_graph {
+            cmd = cmd.arg("--no-commit-graph");
+        }
+        cmd.arg("--find-deleted")
+            .arg("--first-parent")
+            .arg("basic.txt")
+            .current_dir(root_path)
+            .assert()
+            .success()
+            .stdout_eq(
+                "\
+HEAD~4 1 │ one
+⋮      2 │ two
+HEAD~3 3 │ three
+"
+                .raw(),
+            )
+            .stderr_eq(
+                "\
+WARN: file was deleted in [..] (Remove basic.txt); showing it as of [..]
+",
+            );
+    }
+
+    root.close().unwrap();
+}
+
+#[test]
+fn diagnostic_commit_graph() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    if !write_commit_graph(root_path) {
+        // `git` isn't available
+        root.close().unwrap();
+        return;
+    }
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--diagnostic")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+...
+#### Commit graph
+
+used, 4 commits at `[..]commit-graph`
+...
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--diagnostic")
+        .arg("--no-commit-graph")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+...
+#### Commit graph
+
+disabled by `--no-commit-graph`
+...
+",
+        );
+
+    root.close().unwrap();
+}
+
+/// `basic.txt` is deleted in an old history that a new one's root is grafted onto
+fn graft_fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let old = fixture.commit_onto(
+        "A",
+        &[("basic.txt", "one\ntwo\n"), ("other.txt", "other\n")],
+        &[],
+    );
+    let removed = fixture.commit_onto("Remove basic.txt", &[("other.txt", "other\n")], &[old]);
+    let new_root = fixture.commit_onto("Import", &[("other.txt", "other\n")], &[]);
+    let head = fixture.commit_onto("D", &[("other.txt", "other\nmore\n")], &[new_root]);
+    fixture.branch("main", head);
+    fixture.checkout();
+
+    for args in [
+        &["replace", "--graft", &new_root.to_string(), &removed.to_string()][..],
+        // Recorded with the ungrafted parents, so it must not be used
+        &["commit-graph", "write", "--reachable"][..],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .env("GIT_NO_REPLACE_OBJECTS", "1")
+            .current_dir(root_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
+
+#[test]
+fn find_deleted_behind_graft() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    graft_fixture(root_path);
+
+    for first_parent in [false, true] {
+        let mut cmd = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"));
+        if first_parent {
+            cmd = cmd.arg("--first-parent");
+        }
+        cmd.args(["--find-deleted", "--style=numbers", "basic.txt"])
+            .current_dir(root_path)
+            .env_remove("GIT_NO_REPLACE_OBJECTS")
+            .assert()
+            .success()
+            .stdout_eq(
+                "\
+1 one
+2 two
+"
+                .raw(),
+            )
+            .stderr_eq(
+                "\
+WARN: file was deleted in [..] (Remove basic.txt); showing it as of [..]
+",
+            );
+    }
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--find-deleted", "--no-replace-objects", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("");
+
+    root.close().unwrap();
+}
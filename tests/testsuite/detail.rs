@@ -0,0 +1,145 @@
+/// A signature line renamed, then given a parameter, each by someone else
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let versions = [
+        ("Alice", "Add load", "fn load(path: &Path) {\n\tread(path)\n}\n"),
+        ("Bob", "Rename to open", "fn open(path: &Path) {\n\tread(path)\n}\n"),
+        (
+            "Carol",
+            "Add mode",
+            "// Files\nfn open(path: &Path, mode: Mode) {\n\tread(path)\n}\n",
+        ),
+    ];
+    for (i, (author, message, content)) in versions.into_iter().enumerate() {
+        let email = format!("{}@example.com", author.to_lowercase());
+        fixture
+            .author(author, &email, 86_400 * i as i64)
+            .commit(message, &[("file.rs", content)]);
+    }
+}
+
+#[test]
+fn breaks_down_by_commit() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "2", "--detail", "file.rs"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.rs:2
+fn open(path: &Path, mode: Mode) {
+33 2222333333 333331 11111 11113 3
+
+1 [..] Carol 1970-01-03 Add mode
+2 [..] Bob   1970-01-02 Rename to open
+3 [..] Alice 1970-01-01 Add load
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unchanged_line() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "3", "--detail", "file.rs"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.rs:3
+\tread(path)
+\t1111111111
+
+1 [..] Alice 1970-01-01 Add load
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn depth_cap() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "2", "--detail", "file.rs"])
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.detail-depth")
+        .env("GIT_CONFIG_VALUE_0", "2")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.rs:2
+fn open(path: &Path, mode: Mode) {
+22 2222222222 222221 11111 11112 2
+
+1 [..] Carol 1970-01-03 Add mode
+2 [..] Bob   1970-01-02 Rename to open (or earlier)
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn past_the_end() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "9", "--detail", "file.rs"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--line 9` is past the end of file.rs, which has 4 lines
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn needs_detail() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "2", "file.rs"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: the following required arguments were not provided:
+  --detail
+
+...
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,101 @@
+use snapbox::prelude::*;
+
+/// A fix cherry-picked from a side branch that's later merged in, then a commit on top
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let commit = |message: &str, content: &str, parents: &[git2::Oid]| {
+        fixture.commit_onto(message, &[("basic.txt", content)], parents)
+    };
+    let base = commit("base", "one\ntwo\nthree\n", &[]);
+    let fix = commit("fix", "one\nfixed\nthree\n", &[base]);
+    let pick = commit(
+        &format!("fix\n\n(cherry picked from commit {fix})\n"),
+        "one\nfixed\nthree\n",
+        &[base],
+    );
+    let merge = commit("merge", "one\nfixed\nthree\n", &[pick, fix]);
+    let after = commit("after", "one\nfixed\nthree\nfour\n", &[merge]);
+    fixture.branch("main", after);
+    fixture.checkout();
+}
+
+#[test]
+fn merged_in_counts_as_the_merge() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--resolve-reverts=replace")
+        .arg("--style=distance,numbers,grid")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+3 1 │ one
+1 2 │ fixed  ⇢ [..] Test User, 1970-01-01
+3 3 │ three
+0 4 │ four
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn capped() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=distance,grid")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.distance-cap")
+        .env("GIT_CONFIG_VALUE_0", "1")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1+ │ one
+1+ │ fixed
+1+ │ three
+ 0 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_from_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=json")
+        .arg("HEAD~1")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let files: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let distances = files[0]["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line["first_parent_distance"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(distances, [2, 1, 2].map(serde_json::Value::from));
+
+    root.close().unwrap();
+}
@@ -0,0 +1,92 @@
+/// A commit whose metadata is in ISO-8859-1, labeled with an `encoding` header
+fn fixture(root_path: &std::path::Path, encoding: &str) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let tree = fixture.tree(&[("basic.txt", "one\ntwo\n")]);
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(format!("tree {tree}\n").as_bytes());
+    buffer.extend_from_slice(b"author Zo\xeb <zoe@example.com> 0 +0000\n");
+    buffer.extend_from_slice(b"committer Zo\xeb <zoe@example.com> 0 +0000\n");
+    buffer.extend_from_slice(format!("encoding {encoding}\n").as_bytes());
+    buffer.extend_from_slice(b"\nCaf\xe9\n");
+    let commit = fixture
+        .repo
+        .odb()
+        .unwrap()
+        .write(git2::ObjectType::Commit, &buffer)
+        .unwrap();
+
+    fixture.branch("main", commit);
+    fixture.checkout();
+}
+
+#[test]
+fn decodes_commit_encoding() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, "ISO-8859-1");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=author,numbers,grid")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Zoë 1 │ one
+    2 │ two
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn log_output_encoding() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, "ISO-8859-1");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=author,numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "i18n.logOutputEncoding")
+        .env("GIT_CONFIG_VALUE_0", "latin1")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(b"Zo\xeb 1 one\n    2 two\n".as_slice())
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unknown_encoding() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, "x-klingon");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=author,numbers")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Zo� 1 one
+    2 two
+",
+        )
+        .stderr_eq(
+            "\
+WARN: commit [..] has unknown encoding `x-klingon`; assuming UTF-8
+",
+        );
+
+    root.close().unwrap();
+}
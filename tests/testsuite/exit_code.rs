@@ -0,0 +1,150 @@
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+#[test]
+fn usage() {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--no-such-flag")
+        .arg("basic.txt")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn not_a_repo() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .env("GIT_CEILING_DIRECTORIES", root_path.parent().unwrap())
+        .current_dir(root_path)
+        .assert()
+        .code(3)
+        .stdout_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn bad_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("no-such-branch")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn path_not_in_tree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("missing.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn untracked_hint() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    std::fs::write(root_path.join("new.txt"), "one\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+new.txt is untracked; commit it or pass `--contents new.txt` to annotate local content
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.txt")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_PARAMETERS", "'dive.status-hints'='false'")
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+new.txt does not exist at HEAD; pass `--find-deleted` to search history
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn io() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config")
+        .arg("no-such-dir/config")
+        .current_dir(root_path)
+        .assert()
+        .code(6)
+        .stdout_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn verbose_describes_code() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("-v")
+        .arg("missing.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stderr_eq(
+            "\
+...
+[..] exiting with 5 (path is not in the tree)
+...
+",
+        );
+
+    root.close().unwrap();
+}
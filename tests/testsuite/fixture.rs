@@ -0,0 +1,110 @@
+//! Repositories built with libgit2, for fixtures that need fixed authors, dates, or history shapes
+//! that `git_fixture::TodoList` can't express
+
+pub(crate) struct Repo {
+    pub(crate) repo: git2::Repository,
+    author: git2::Signature<'static>,
+    /// The author, if unset
+    committer: Option<git2::Signature<'static>>,
+}
+
+impl Repo {
+    /// Committing as "Test User" at the epoch
+    pub(crate) fn init(root_path: &std::path::Path) -> Self {
+        let repo = git2::Repository::init(root_path).unwrap();
+        Self {
+            repo,
+            author: signature("Test User", "test@example.com", 0),
+            committer: None,
+        }
+    }
+
+    /// Who the next commits are by, at `time` in seconds since the epoch
+    pub(crate) fn author(&mut self, name: &str, email: &str, time: i64) -> &mut Self {
+        self.author = signature(name, email, time);
+        self
+    }
+
+    pub(crate) fn committer(&mut self, name: &str, email: &str, time: i64) -> &mut Self {
+        self.committer = Some(signature(name, email, time));
+        self
+    }
+
+    /// Commit `files` as the whole tree onto `HEAD`, moving it
+    pub(crate) fn commit(&self, message: &str, files: &[(&str, &str)]) -> git2::Oid {
+        let parent = self.repo.head().ok().and_then(|head| head.target());
+        self.write_commit(Some("HEAD"), message, files, parent.as_slice())
+    }
+
+    /// Commit `files` as the whole tree onto `parents`, leaving `HEAD` alone
+    pub(crate) fn commit_onto(
+        &self,
+        message: &str,
+        files: &[(&str, &str)],
+        parents: &[git2::Oid],
+    ) -> git2::Oid {
+        self.write_commit(None, message, files, parents)
+    }
+
+    /// Point `refs/heads/<name>` at `commit`, and `HEAD` at it
+    pub(crate) fn branch(&self, name: &str, commit: git2::Oid) {
+        let name = format!("refs/heads/{name}");
+        self.repo.reference(&name, commit, true, "fixture").unwrap();
+        self.repo.set_head(&name).unwrap();
+    }
+
+    /// Make the worktree match `HEAD`
+    pub(crate) fn checkout(&self) {
+        self.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+
+    /// `files` may be in directories, like `src/lib.rs`
+    pub(crate) fn tree(&self, files: &[(&str, &str)]) -> git2::Oid {
+        let mut index = git2::Index::new().unwrap();
+        for (path, content) in files {
+            let id = self.repo.blob(content.as_bytes()).unwrap();
+            index
+                .add(&git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: 0o100644,
+                    uid: 0,
+                    gid: 0,
+                    file_size: content.len() as u32,
+                    id,
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.as_bytes().to_vec(),
+                })
+                .unwrap();
+        }
+        index.write_tree_to(&self.repo).unwrap()
+    }
+
+    fn write_commit(
+        &self,
+        update_ref: Option<&str>,
+        message: &str,
+        files: &[(&str, &str)],
+        parents: &[git2::Oid],
+    ) -> git2::Oid {
+        let tree = self.repo.find_tree(self.tree(files)).unwrap();
+        let parents = parents
+            .iter()
+            .map(|id| self.repo.find_commit(*id).unwrap())
+            .collect::<Vec<_>>();
+        let parents = parents.iter().collect::<Vec<_>>();
+        let committer = self.committer.as_ref().unwrap_or(&self.author);
+        self.repo
+            .commit(update_ref, &self.author, committer, message, &tree, &parents)
+            .unwrap()
+    }
+}
+
+fn signature(name: &str, email: &str, time: i64) -> git2::Signature<'static> {
+    git2::Signature::new(name, email, &git2::Time::new(time, 0)).unwrap()
+}
@@ -0,0 +1,161 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [
+                    ("package-lock.json", "{}\n"),
+                    ("app.min.js", "x()\n"),
+                    ("main.js", "x()\n"),
+                ]
+                .into_iter()
+                .map(|(p, c)| (p.into(), c.into()))
+                .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Add files".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [
+                    ("package-lock.json", "{}\n"),
+                    ("app.min.js", "x()\n"),
+                    ("main.js", "x()\n"),
+                    (
+                        ".gitattributes",
+                        "package-lock.json linguist-generated\n*.min.js -diff\n",
+                    ),
+                ]
+                .into_iter()
+                .map(|(p, c)| (p.into(), c.into()))
+                .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Mark generated".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+#[test]
+fn generated_is_skipped() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("package-lock.json")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+WARN: package-lock.json is marked generated; pass `--force-generated` to annotate
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("app.min.js")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+WARN: app.min.js is marked `-diff`; pass `--force-generated` to annotate
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("main.js")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+main.js:1: [..] Add files
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn force_generated() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--force-generated")
+        .arg("--format=quickfix")
+        .arg("package-lock.json")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+package-lock.json:1: [..] Add files
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn attributes_as_of_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("HEAD~")
+        .arg("package-lock.json")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+package-lock.json:1: [..] Add files
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn generated_json() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=json")
+        .arg("package-lock.json")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "package-lock.json",
+    "skipped": "package-lock.json is marked generated; pass `--force-generated` to annotate",
+    "lines": []
+  }
+]
+"#
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,146 @@
+fn fixture(root_path: &std::path::Path, content: &str, message: &str) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", content)]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some(message.to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+/// Two repos, `here` being the current directory
+fn two_repos(root_path: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let here = root_path.join("here");
+    let there = root_path.join("there");
+    std::fs::create_dir_all(&here).unwrap();
+    std::fs::create_dir_all(&there).unwrap();
+    fixture(&here, "here\n", "Add here");
+    fixture(&there, "there\n", "Add there");
+    (here, there)
+}
+
+#[test]
+fn git_dir_env() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (here, there) = two_repos(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("basic.txt")
+        .current_dir(&here)
+        .env("GIT_DIR", there.join(".git"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] Add there
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn git_dir_flag_over_env() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (here, there) = two_repos(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("--git-dir=.git")
+        .arg("basic.txt")
+        .current_dir(&here)
+        .env("GIT_DIR", there.join(".git"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] Add here
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn git_dir_with_work_tree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (_here, there) = two_repos(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("there/basic.txt")
+        .current_dir(root_path)
+        .env("GIT_DIR", there.join(".git"))
+        .env("GIT_WORK_TREE", "there")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] Add there
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn ceiling_directories() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (here, _there) = two_repos(root_path);
+    let sub = here.join("sub");
+    std::fs::create_dir_all(&sub).unwrap();
+    std::fs::write(sub.join("new.txt"), "new\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.txt")
+        .current_dir(&sub)
+        .env("GIT_CEILING_DIRECTORIES", &here)
+        .assert()
+        .code(3);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn dump_config_git_dir() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (here, there) = two_repos(root_path);
+    let config = there.join(".git").join("config");
+    let mut content = std::fs::read_to_string(&config).unwrap();
+    content.push_str("[dive]\n\ttheme = ansi\n");
+    std::fs::write(&config, content).unwrap();
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(&here)
+        .env("GIT_DIR", there.join(".git"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("theme = ansi  # $GIT_DIR/config"),
+        "theme missing from:\n{output}"
+    );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,109 @@
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for (message, content) in [("Add one", "one\n"), ("Add two", "one\ntwo\n")] {
+        fixture.commit(message, &[("src/basic.txt", content)]);
+    }
+}
+
+fn ids(root_path: &std::path::Path) -> (String, String) {
+    let repo = git2::Repository::open(root_path).unwrap();
+    let obj = repo.revparse_single("HEAD").unwrap();
+    let short = obj.short_id().unwrap().as_str().unwrap().to_owned();
+    (short, obj.id().to_string())
+}
+
+fn dive(root_path: &std::path::Path, config: &[(&str, &str)]) -> snapbox::cmd::Command {
+    let mut cmd = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", config.len().to_string());
+    for (i, (key, value)) in config.iter().enumerate() {
+        cmd = cmd
+            .env(format!("GIT_CONFIG_KEY_{i}"), *key)
+            .env(format!("GIT_CONFIG_VALUE_{i}"), *value);
+    }
+    cmd
+}
+
+fn stdout(cmd: snapbox::cmd::Command) -> String {
+    let output = cmd.assert().success().stderr_eq("").get_output().clone();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn case_and_prefix() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let (short, _) = ids(root_path);
+
+    let output = stdout(
+        dive(
+            root_path,
+            &[("dive.hash-case", "upper"), ("dive.hash-prefix", "#")],
+        )
+        .arg("src"),
+    );
+    let fields = output.split_whitespace().collect::<Vec<_>>();
+    assert_eq!(fields[1], format!("#{}", short.to_uppercase()), "{output}");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn long_style() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let (_, long) = ids(root_path);
+
+    let output = stdout(dive(root_path, &[("dive.hash-style", "long")]).arg("src"));
+    let fields = output.split_whitespace().collect::<Vec<_>>();
+    assert_eq!(fields[1], long, "{output}");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn machine_formats_untouched() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let config = [
+        ("dive.hash-style", "long"),
+        ("dive.hash-case", "upper"),
+        ("dive.hash-prefix", "#"),
+    ];
+
+    for args in [
+        &["--format=quickfix", "src/basic.txt"][..],
+        &["--format=json", "src/basic.txt"],
+        &["--format=json", "src"],
+    ] {
+        let plain = stdout(dive(root_path, &[]).args(args));
+        let configured = stdout(dive(root_path, &config).args(args));
+        assert_eq!(plain, configured, "{args:?}");
+        assert!(!configured.contains('#'), "{args:?}: {configured}");
+    }
+
+    root.close().unwrap();
+}
+
+#[test]
+fn invalid_values_are_ignored() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let (short, _) = ids(root_path);
+
+    let output = stdout(
+        dive(
+            root_path,
+            &[("dive.hash-style", "full"), ("dive.hash-case", "UPPER")],
+        )
+        .arg("src"),
+    );
+    let fields = output.split_whitespace().collect::<Vec<_>>();
+    assert_eq!(fields[1], short, "{output}");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,104 @@
+use snapbox::prelude::*;
+
+/// Lines from several authors, one of whom committed under a second email
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut content = String::new();
+    for (name, email, line) in [
+        ("Ada", "ada@example.com", "one"),
+        ("Grace", "grace@example.com", "two"),
+        ("Ada", "ada@old.example.com", "three"),
+        ("Linus", "linus@example.com", "four"),
+        ("Margaret", "margaret@example.com", "five"),
+    ] {
+        content.push_str(line);
+        content.push('\n');
+        fixture.author(name, email, 0).commit(
+            line,
+            &[
+                ("basic.txt", &content),
+                (".mailmap", "Ada <ada@example.com> <ada@old.example.com>\n"),
+            ],
+        );
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn highlight_mine() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    // The mailmap maps the old address to the one on the commits, and vice versa
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--highlight-mine")
+        .arg("--style=author,numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "4")
+        .env("GIT_CONFIG_KEY_0", "user.email")
+        .env("GIT_CONFIG_VALUE_0", "ada@old.example.com")
+        .env("GIT_CONFIG_KEY_1", "user.name")
+        .env("GIT_CONFIG_VALUE_1", "Ada")
+        .env("GIT_CONFIG_KEY_2", "dive.mine-color")
+        .env("GIT_CONFIG_VALUE_2", "magenta bold")
+        .env("GIT_CONFIG_KEY_3", "dive.theme")
+        .env("GIT_CONFIG_VALUE_3", "ansi")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+\x1b[1m\x1b[35mAda     \x1b[0m \x1b[1m\x1b[35m1\x1b[0m \x1b[0mone\x1b[0m
+\x1b[0m\x1b[32mGrace   \x1b[0m 2 \x1b[0mtwo\x1b[0m
+\x1b[0m\x1b[1m\x1b[35mAda     \x1b[0m \x1b[1m\x1b[35m3\x1b[0m \x1b[0mthree\x1b[0m
+\x1b[0m\x1b[32mLinus   \x1b[0m 4 \x1b[0mfour\x1b[0m
+\x1b[0m\x1b[32mMargaret\x1b[0m 5 \x1b[0mfive\x1b[0m
+\x1b[0m
+You authored 2/5 lines, 40%
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn my_emails() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=author")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "4")
+        .env("GIT_CONFIG_KEY_0", "user.email")
+        .env("GIT_CONFIG_VALUE_0", "grace@example.com")
+        .env("GIT_CONFIG_KEY_1", "dive.my-emails")
+        .env("GIT_CONFIG_VALUE_1", "LINUS@example.com")
+        .env("GIT_CONFIG_KEY_2", "dive.my-emails")
+        .env("GIT_CONFIG_VALUE_2", "margaret@example.com")
+        .env("GIT_CONFIG_KEY_3", "dive.highlight-mine")
+        .env("GIT_CONFIG_VALUE_3", "true")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Ada      one
+Grace    two
+Ada      three
+Linus    four
+Margaret five
+
+You authored 3/5 lines, 60%
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,123 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path, files: &[&str]) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let contents = files
+        .iter()
+        .map(|file| format!("# {file}\n"))
+        .collect::<Vec<_>>();
+    let files = files
+        .iter()
+        .zip(&contents)
+        .map(|(file, content)| (*file, content.as_str()))
+        .collect::<Vec<_>>();
+    fixture.commit("Initial", &files);
+    fixture.checkout();
+}
+
+#[test]
+fn icase_flag() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["README.md"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--icase-paths")
+        .arg("readme.MD")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ # README.md
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+showing README.md for readme.MD
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn icase_pathspec_magic() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["README.md"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg(":(icase)readme.MD")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ # README.md
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+showing README.md for readme.MD
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn exact_case_required_by_default() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["README.md"]);
+    if root_path.join("readme.MD").exists() {
+        // Case-insensitive filesystems look up the wrong case on their own
+        root.close().unwrap();
+        return;
+    }
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("readme.MD")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+readme.MD does not exist at HEAD; pass `--find-deleted` to search history
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn ambiguous_case() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit(
+        "Initial",
+        &[("README.md", "README.md"), ("readme.md", "readme.md")],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--icase-paths")
+        .arg("Readme.md")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`Readme.md` matches several paths that differ only by case:
+  README.md
+  readme.md
+",
+        );
+
+    root.close().unwrap();
+}
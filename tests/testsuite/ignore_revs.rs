@@ -0,0 +1,348 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\nthree\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\nTWO\nthree\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Reformat".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\nTWO\nthree\nfour\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("C".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+#[test]
+fn ignore_rev() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--ignore-rev=HEAD~")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+⋮      2 │ TWO
+⋮      3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn show_ignored() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--ignore-rev=HEAD~")
+        .arg("--show-ignored")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2  1 │ one
+⋮     * 2 │ TWO
+⋮       3 │ three
+HEAD    4 │ four
+
+Ignored revisions:
+  HEAD~1 1 line
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn ignore_revs_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let repo = git2::Repository::open(root_path).unwrap();
+    let reformat = repo.revparse_single("HEAD~").unwrap().id();
+    let missing = "1111111111111111111111111111111111111111";
+    std::fs::write(
+        root_path.join(".git-blame-ignore-revs"),
+        format!("# Formatting\n{reformat}\n{missing}\n"),
+    )
+    .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env(
+            "GIT_CONFIG_PARAMETERS",
+            "'blame.ignoreRevsFile'='.git-blame-ignore-revs'",
+        )
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+⋮      2 │ TWO
+⋮      3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: [..]/.git-blame-ignore-revs:3: skipping `1111111111111111111111111111111111111111`: [..]
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn global_ignore_revs_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let repo = git2::Repository::open(root_path).unwrap();
+    let reformat = repo.revparse_single("HEAD~").unwrap().id();
+    let other_repo = "1111111111111111111111111111111111111111";
+    let global = root_path.join("global-ignore-revs");
+    std::fs::write(&global, format!("{other_repo}\n{reformat}\n")).unwrap();
+    std::fs::write(
+        root_path.join("repo-ignore-revs"),
+        format!("{reformat}\n{other_repo}\n"),
+    )
+    .unwrap();
+
+    // Unknown revisions in the global file are expected, so nothing is reported
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env(
+            "GIT_CONFIG_PARAMETERS",
+            format!("'dive.global-ignore-revs-file'='{}'", global.display()),
+        )
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+⋮      2 │ TWO
+⋮      3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Duplicates across files are merged and only the repo's file is held to account
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--show-ignored")
+        .arg("--ignore-rev=HEAD~")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env(
+            "GIT_CONFIG_PARAMETERS",
+            format!(
+                "'dive.global-ignore-revs-file'='{}' 'blame.ignoreRevsFile'='repo-ignore-revs'",
+                global.display()
+            ),
+        )
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2  1 │ one
+⋮     * 2 │ TWO
+⋮       3 │ three
+HEAD    4 │ four
+
+Ignored revisions:
+  HEAD~1 1 line
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: [..]/repo-ignore-revs:2: skipping `1111111111111111111111111111111111111111`: [..]
+",
+        );
+
+    // An empty `--ignore-revs-file` clears the global file too
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--ignore-revs-file=")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env(
+            "GIT_CONFIG_PARAMETERS",
+            format!("'dive.global-ignore-revs-file'='{}'", global.display()),
+        )
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+HEAD~1 2 │ TWO
+HEAD~2 3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+const IGNORING_REFORMAT: &str = "\
+HEAD~2 1 │ one
+⋮      2 │ TWO
+⋮      3 │ three
+HEAD   4 │ four
+";
+
+const NOT_IGNORING: &str = "\
+HEAD~2 1 │ one
+HEAD~1 2 │ TWO
+HEAD~2 3 │ three
+HEAD   4 │ four
+";
+
+fn dive(root_path: &std::path::Path, config: &[(&str, &str)]) -> snapbox::cmd::Command {
+    let mut cmd = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env_remove("GIT_CONFIG_PARAMETERS")
+        .env("GIT_CONFIG_COUNT", config.len().to_string());
+    for (i, (key, value)) in config.iter().enumerate() {
+        cmd = cmd
+            .env(format!("GIT_CONFIG_KEY_{i}"), key)
+            .env(format!("GIT_CONFIG_VALUE_{i}"), value);
+    }
+    cmd
+}
+
+#[test]
+fn conventional_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let repo = git2::Repository::open(root_path).unwrap();
+    let reformat = repo.revparse_single("HEAD~").unwrap().id();
+    std::fs::write(
+        root_path.join(".git-blame-ignore-revs"),
+        format!("{reformat}\n"),
+    )
+    .unwrap();
+
+    dive(root_path, &[])
+        .assert()
+        .success()
+        .stdout_eq(IGNORING_REFORMAT.raw())
+        .stderr_eq("");
+
+    dive(root_path, &[("dive.auto-ignore-revs", "false")])
+        .assert()
+        .success()
+        .stdout_eq(NOT_IGNORING.raw())
+        .stderr_eq("");
+
+    // `--ignore-revs-file ""` clears it like any other file
+    dive(root_path, &[])
+        .arg("--ignore-revs-file=")
+        .assert()
+        .success()
+        .stdout_eq(NOT_IGNORING.raw())
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn configured_file_wins() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let repo = git2::Repository::open(root_path).unwrap();
+    let reformat = repo.revparse_single("HEAD~").unwrap().id();
+    let missing = "1111111111111111111111111111111111111111";
+    std::fs::write(
+        root_path.join(".git-blame-ignore-revs"),
+        format!("{reformat}\n{missing}\n"),
+    )
+    .unwrap();
+    std::fs::write(root_path.join("other-revs"), "# nothing yet\n").unwrap();
+
+    dive(root_path, &[("blame.ignoreRevsFile", "other-revs")])
+        .assert()
+        .success()
+        .stdout_eq(NOT_IGNORING.raw())
+        .stderr_eq("");
+
+    dive(
+        root_path,
+        &[
+            ("blame.ignoreRevsFile", "other-revs"),
+            ("dive.merge-ignore-revs", "true"),
+        ],
+    )
+    .assert()
+    .success()
+    .stdout_eq(IGNORING_REFORMAT.raw())
+    .stderr_eq(
+        "\
+WARN: [..]/.git-blame-ignore-revs:2: skipping `1111111111111111111111111111111111111111`: [..]
+",
+    );
+
+    // Configuring the conventional file itself doesn't read it twice
+    dive(
+        root_path,
+        &[
+            ("blame.ignoreRevsFile", ".git-blame-ignore-revs"),
+            ("dive.merge-ignore-revs", "true"),
+        ],
+    )
+    .assert()
+    .success()
+    .stdout_eq(IGNORING_REFORMAT.raw())
+    .stderr_eq(
+        "\
+WARN: [..]/.git-blame-ignore-revs:2: skipping `1111111111111111111111111111111111111111`: [..]
+",
+    );
+
+    root.close().unwrap();
+}
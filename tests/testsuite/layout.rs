@@ -0,0 +1,115 @@
+use snapbox::prelude::*;
+
+/// Commit each version of `basic.txt` in turn
+fn fixture(root_path: &std::path::Path, versions: &[&str]) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    fixture.author("Ada Lovelace", "ada@example.com", 0);
+    for (i, content) in versions.iter().enumerate() {
+        fixture.commit(&i.to_string(), &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn wide_terminal_unchanged() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\n", "one\ntwo\n"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .env("COLUMNS", "80")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+HEAD   2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn narrow_terminal_shortens_hash() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\n", "one\ntwo\n"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .env("COLUMNS", "20")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+12aac9 1 │ one
+3fdf81 2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn explicit_style_warns() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\n", "one\ntwo\n"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=hash,author,date,numbers")
+        .arg("basic.txt")
+        .env("COLUMNS", "40")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 Ada Lovelace 1970-01-01 1 one
+HEAD   Ada Lovelace 1970-01-01 2 two
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: gutter takes 33 of 40 columns; remove components from `--style` to make room for the code
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn max_gutter_ratio() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\n", "one\ntwo\n"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .env("COLUMNS", "20")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.max-gutter-ratio")
+        .env("GIT_CONFIG_VALUE_0", "1")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+HEAD   2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,154 @@
+use snapbox::prelude::*;
+
+/// Ada, Grace, then Linus change `big.txt`, which is kept in Git LFS, with a store for a fake
+/// smudge filter to read objects from
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let store = root_path.join(".git/lfs-store");
+    std::fs::create_dir_all(&store).unwrap();
+    for (i, (name, content)) in [
+        ("Ada", "one\ntwo\nthree\n"),
+        ("Grace", "one\n2\nthree\n"),
+        ("Linus", "one\n2\nthree\nfour\n"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let oid = format!("{i:064x}");
+        std::fs::write(store.join(&oid), content).unwrap();
+        let pointer = format!(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize {}\n",
+            content.len()
+        );
+        let email = format!("{}@example.com", name.to_lowercase());
+        fixture.author(name, &email, 1_700_000_000 + i as i64).commit(
+            name,
+            &[
+                (".gitattributes", "*.txt filter=lfs diff=lfs merge=lfs -text\n"),
+                ("big.txt", &pointer),
+            ],
+        );
+    }
+}
+
+/// Copies the object named by the pointer on stdin out of the store
+const SMUDGE: &str = "cat .git/lfs-store/$(sed -n 's/^oid sha256://p')";
+
+fn dive(root_path: &std::path::Path, smudge: &str) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "filter.lfs.smudge")
+        .env("GIT_CONFIG_VALUE_0", smudge)
+        .current_dir(root_path)
+}
+
+#[test]
+fn pointer_skipped() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, SMUDGE)
+        .arg("big.txt")
+        .assert()
+        .success()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+WARN: big.txt is a Git LFS pointer to a 17 B object, sha256:0000000000000000000000000000000000000000000000000000000000000002; pass `--lfs-smudge` to annotate its content
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn smudged_content_blamed() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, SMUDGE)
+        .args(["--lfs-smudge", "--style=author,numbers", "big.txt"])
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Ada   1 one
+Grace 2 2
+Ada   3 three
+Linus 4 four
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+note: big.txt is stored in Git LFS, so its lines are attributed to the commits that changed its pointer
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn older_versions_missing() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    std::fs::remove_file(root_path.join(format!(".git/lfs-store/{:064x}", 0))).unwrap();
+
+    dive(root_path, SMUDGE)
+        .args(["--lfs-smudge", "--style=author,numbers", "big.txt"])
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Grace 1 one
+      2 2
+      3 three
+Linus 4 four
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+note: big.txt is stored in Git LFS, so its lines are attributed to the commits that changed its pointer
+WARN: 1 older version of big.txt couldn't be smudged, so their lines are attributed to the versions after them: `cat .git/lfs-store/$(sed -n 's/^oid sha256://p')` failed (exit status: 1): cat: [..]
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn smudge_failure_falls_back() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, "")
+        .args(["--lfs-smudge", "big.txt"])
+        .assert()
+        .success()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+WARN: big.txt is a Git LFS pointer to a 17 B object, sha256:0000000000000000000000000000000000000000000000000000000000000002, and couldn't be smudged: `filter.lfs.smudge` isn't set; run `git lfs install` to set it
+",
+        );
+
+    dive(root_path, "git-lfs-not-installed smudge -- %f")
+        .args(["--lfs-smudge", "big.txt"])
+        .assert()
+        .success()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+WARN: big.txt is a Git LFS pointer to a 17 B object, sha256:0000000000000000000000000000000000000000000000000000000000000002, and couldn't be smudged: `git-lfs-not-installed smudge -- big.txt` failed (exit status: 127): [..]
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,89 @@
+use snapbox::prelude::*;
+
+/// Whitespace problems from two authors
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut content = String::new();
+    for (name, email, line) in [
+        ("Ada", "ada@example.com", "clean"),
+        ("Grace", "grace@example.com", "trailing  "),
+        ("Ada", "ada@example.com", " \tmixed"),
+        ("Grace", "grace@example.com", "a longer line "),
+    ] {
+        content.push_str(line);
+        content.push('\n');
+        fixture
+            .author(name, email, 0)
+            .commit(line, &[("basic.txt", &content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn footer_by_author() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--lint-whitespace")
+        .arg("--style=numbers,grid")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.max-line-length")
+        .env("GIT_CONFIG_VALUE_0", "13")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 │ clean
+2 │ trailing
+3 │  \tmixed
+4 │ a longer line
+
+Whitespace problems: 2 trailing, 1 tab after spaces, 1 over 13 columns
+  Grace 2 trailing, 1 over 13 columns
+  Ada   1 tab after spaces
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn marks_over_highlighting() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--lint-whitespace")
+        .arg("--style=numbers")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", "dive.lint-color")
+        .env("GIT_CONFIG_VALUE_0", "normal yellow")
+        .env("GIT_CONFIG_KEY_1", "dive.max-line-length")
+        .env("GIT_CONFIG_VALUE_1", "0")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let trailing = output.lines().nth(1).unwrap();
+    // The mark keeps the code's color, adding a background
+    let fg = "\u{1b}[38;2;248;248;242m";
+    assert!(
+        trailing.ends_with(&format!("{fg}trailing\u{1b}[0m{fg}\u{1b}[43m  \u{1b}[0m")),
+        "{trailing:?}"
+    );
+    assert!(output.contains("Whitespace problems: 2 trailing, 1 tab after spaces"));
+
+    root.close().unwrap();
+}
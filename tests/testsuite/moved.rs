@@ -0,0 +1,85 @@
+use snapbox::prelude::*;
+
+/// `first` moved from the top of the file to the bottom
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for content in [
+        "fn first() {}\n\nfn second() {\n    todo!()\n}\n",
+        "fn second() {\n    todo!()\n}\n\nfn first() {}\n",
+    ] {
+        fixture.commit("Commit", &[("lib.rs", content)]);
+    }
+}
+
+#[test]
+fn move_badge() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("lib.rs")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.move-badge-threshold")
+        .env("GIT_CONFIG_VALUE_0", "2")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ fn second() {  ↷ moved
+⋮      2 │     todo!()
+⋮      3 │ }
+HEAD   4 │ 
+⋮      5 │ fn first() {}
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Not for the few lines the default allows
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("lib.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ fn second() {
+⋮      2 │     todo!()
+⋮      3 │ }
+HEAD   4 │ 
+⋮      5 │ fn first() {}
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_orig_line() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=json", "lib.rs"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let lines = json[0]["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| (line["line"].as_u64().unwrap(), line["orig_line"].as_u64().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [(1, 3), (2, 4), (3, 5), (4, 4), (5, 5)]);
+
+    root.close().unwrap();
+}
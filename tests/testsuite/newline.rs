@@ -0,0 +1,107 @@
+use snapbox::prelude::*;
+
+/// Commit each version of `basic.txt` in turn
+fn fixture(root_path: &std::path::Path, versions: &[&str]) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for (i, content) in versions.iter().enumerate() {
+        fixture.commit(&i.to_string(), &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn no_final_newline() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\ntwo", "one\ntwo\nthree"]);
+
+    // Like other whitespace, adding the missing newline doesn't take the blame for `two`
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+HEAD   3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn empty_final_line() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\ntwo\n", "one\ntwo\n\n"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--show-no-newline")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+HEAD   3 │ 
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn show_no_newline() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\ntwo"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--show-no-newline")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ one
+⋮    2 │ two%
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn quickfix_counts_last_line() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &["one\ntwo"]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] Test User 1970-01-01 0
+basic.txt:2: [..] Test User 1970-01-01 0
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
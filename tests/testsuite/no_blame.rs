@@ -0,0 +1,130 @@
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    fixture.author("Alice", "alice@example.com", 0);
+    for (message, content) in [("A", "one\ntwo\n"), ("B", "one\ntwo\nthree\n")] {
+        fixture.commit(message, &[("basic.txt", content)]);
+    }
+}
+
+#[test]
+fn numbers_and_grid() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "HEAD~", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 │ one
+2 │ two
+",
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "--style=grid", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+│ one
+│ two
+│ three
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn bare_repo() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let work = root_path.join("work");
+    fixture(&work);
+    let bare = root_path.join("bare.git");
+    git2::build::RepoBuilder::new()
+        .bare(true)
+        .clone(work.to_str().unwrap(), &bare)
+        .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "HEAD", "basic.txt"])
+        .current_dir(&bare)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 │ one
+2 │ two
+3 │ three
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn refuses_blame_only_flags() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "--stat", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: the argument '--no-blame' cannot be used with '--stat'
+...
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "--style=hash,numbers", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--style hash` needs blame, which `--no-blame` skips; only `numbers` and `grid` can be shown
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "--format=json", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--no-blame` only supports `--format text`
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "."])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--no-blame` is only supported for files
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,92 @@
+/// A repo with history and a blobless clone of it, without any files checked out
+fn fixture(root_path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let upstream = root_path.join("upstream");
+    let fixture = crate::fixture::Repo::init(&upstream);
+    fixture
+        .repo
+        .config()
+        .unwrap()
+        .set_bool("uploadpack.allowFilter", true)
+        .unwrap();
+    for content in ["one\n", "one\ntwo\n", "one\ntwo\nthree\n"] {
+        fixture.commit(content, &[("basic.txt", content)]);
+    }
+
+    let clone = root_path.join("clone");
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "--no-checkout", "--filter=blob:none"])
+        .arg(format!("file://{}", upstream.display()))
+        .arg(&clone)
+        .status()
+        .ok()?;
+    status.success().then_some(clone)
+}
+
+#[test]
+fn no_lazy_fetch_lists_missing() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let Some(clone) = fixture(root_path) else {
+        // `git` isn't available
+        root.close().unwrap();
+        return;
+    };
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--no-lazy-fetch")
+        .arg("basic.txt")
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+3 versions of basic.txt are missing from this partial clone:
+  [..] (from [..])
+  [..] (from [..])
+  [..] (from [..])
+run without `--no-lazy-fetch` to fetch them
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn fetches_missing_at_once() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let Some(clone) = fixture(root_path) else {
+        root.close().unwrap();
+        return;
+    };
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(&clone)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+HEAD~1 2 │ two
+HEAD   3 │ three
+",
+        )
+        .stderr_eq(
+            "\
+note: fetching 3 versions of basic.txt from `origin` for this partial clone
+",
+        );
+
+    // Everything is local now
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--no-lazy-fetch")
+        .arg("basic.txt")
+        .current_dir(&clone)
+        .assert()
+        .success()
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
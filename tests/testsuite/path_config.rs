@@ -0,0 +1,296 @@
+use snapbox::prelude::*;
+
+/// Go at the top with tabs 4 wide, overridden to 2 in `docs/`, which also has bad values
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    fixture.author("Alice", "alice@example.com", 0).commit(
+        "Add files",
+        &[
+            (".gitattributes", "*.go dive-tab-width=4\n"),
+            (
+                "docs/.gitattributes",
+                "\
+# Narrow tabs for screenshots
+*.go dive-tab-width=2
+bad.go dive-tab-width=wide
+*.md dive-theme=Nonsense dive-language=Nonsense
+",
+            ),
+            ("main.go", "func main() {\n\trun()\n}\n"),
+            ("docs/example.go", "func main() {\n\trun()\n}\n"),
+            ("docs/bad.go", "func main() {\n\trun()\n}\n"),
+            ("docs/guide.md", "# Guide\n"),
+            ("notes.txt", "fn main() {}\n"),
+        ],
+    );
+    fixture.checkout();
+}
+
+fn dive(root_path: &std::path::Path, path: &str) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=numbers")
+        .arg(path)
+        .current_dir(root_path)
+}
+
+#[test]
+fn tab_width_per_directory() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, "main.go")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2     run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    dive(root_path, "docs/example.go")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2   run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn attribute_over_config() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, "main.go")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.tab-width")
+        .env("GIT_CONFIG_VALUE_0", "8")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2     run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Files without the attribute use the config
+    std::fs::write(root_path.join("docs/.gitattributes"), "").unwrap();
+    std::fs::write(root_path.join(".gitattributes"), "").unwrap();
+    dive(root_path, "docs/example.go")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.tab-width")
+        .env("GIT_CONFIG_VALUE_0", "8")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2         run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn read_from_the_worktree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    // Uncommitted, like trying a setting out
+    std::fs::write(root_path.join(".gitattributes"), "*.go dive-tab-width=3\n").unwrap();
+    dive(root_path, "main.go")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2    run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn invalid_values_warn() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path, "docs/bad.go")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2 \trun()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: ignoring `dive-tab-width=wide` from docs/.gitattributes:3, which isn't a width from 1 to 16
+"
+            .raw(),
+        );
+
+    dive(root_path, "docs/guide.md")
+        .arg("--color=always")
+        .assert()
+        .success()
+        .stderr_eq(
+            "\
+WARN: ignoring `dive-theme=Nonsense` from docs/.gitattributes:4, which isn't a theme from `--list-themes`
+WARN: ignoring `dive-language=Nonsense` from docs/.gitattributes:4, which isn't a language from `--list-languages`
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn theme_and_language() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let stdout = |cmd: snapbox::cmd::Command| {
+        let output = cmd
+            .arg("--color=always")
+            .assert()
+            .success()
+            .stderr_eq("")
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let configured = stdout(
+        dive(root_path, "notes.txt")
+            .arg("--language=rust")
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.theme")
+            .env("GIT_CONFIG_VALUE_0", "Monokai Extended Light"),
+    );
+    let plain = stdout(dive(root_path, "notes.txt"));
+    assert_ne!(configured, plain);
+
+    std::fs::write(
+        root_path.join(".gitattributes"),
+        "*.txt dive-language=rust dive-theme=Monokai-Extended-Light\n",
+    )
+    .unwrap();
+    let attributed = stdout(dive(root_path, "notes.txt"));
+    assert_eq!(attributed, configured);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn info_attributes_over_the_tree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    std::fs::create_dir_all(root_path.join(".git/info")).unwrap();
+    std::fs::write(
+        root_path.join(".git/info/attributes"),
+        "*.go dive-tab-width=3\n",
+    )
+    .unwrap();
+    // Over `docs/.gitattributes` too, though it's deeper
+    dive(root_path, "docs/example.go")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2    run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Under `core.attributesFile`'s rules, and over the system file's
+    let global = root_path.join("global-attributes");
+    std::fs::write(&global, "*.go dive-tab-width=6\n").unwrap();
+    std::fs::write(root_path.join(".gitattributes"), "").unwrap();
+    std::fs::write(root_path.join("docs/.gitattributes"), "").unwrap();
+    std::fs::write(root_path.join(".git/info/attributes"), "").unwrap();
+    dive(root_path, "docs/example.go")
+        .env("GIT_ATTR_NOSYSTEM", "1")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "core.attributesFile")
+        .env("GIT_CONFIG_VALUE_0", &global)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2       run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn bare_repo_reads_the_tree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let work = root_path.join("work");
+    fixture(&work);
+    let bare = root_path.join("bare.git");
+    git2::build::RepoBuilder::new()
+        .bare(true)
+        .clone(work.to_str().unwrap(), &bare)
+        .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--no-blame", "--style=numbers", "HEAD", "docs/example.go"])
+        .current_dir(&bare)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 func main() {
+2   run()
+3 }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,150 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit(
+        "Add files",
+        &[("src/lib.rs", "one\n"), ("docs/README.md", "# Read\n")],
+    );
+    fixture.checkout();
+}
+
+#[test]
+fn quickfix_relative_to_root_by_default() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("lib.rs")
+        .current_dir(root_path.join("src"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+src/lib.rs:1: [..] Add files
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn quickfix_relative_paths() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=quickfix")
+        .arg("--relative-paths")
+        .arg(root_path.join("src/lib.rs"))
+        .current_dir(root_path.join("docs"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+../src/lib.rs:1: [..] Add files
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn config_applies_to_errors() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.path-display")
+        .env("GIT_CONFIG_VALUE_0", "relative-to-cwd")
+        .arg("../src/main.rs")
+        .current_dir(root_path.join("docs"))
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+../src/main.rs does not exist at HEAD; pass `--find-deleted` to search history
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--absolute-paths")
+        .arg("../src/main.rs")
+        .current_dir(root_path.join("docs"))
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+[..]main.rs does not exist at HEAD; pass `--find-deleted` to search history
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn directory_listing() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=json")
+        .arg("src")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "lib.rs",
+...
+"#,
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=json")
+        .arg("--path-display=relative-to-root")
+        .arg("src")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "src/lib.rs",
+...
+"#,
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn conflicting_flags() {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--relative-paths")
+        .arg("--absolute-paths")
+        .arg("lib.rs")
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: the argument '--relative-paths' cannot be used with '--absolute-paths'
+...
+",
+        );
+}
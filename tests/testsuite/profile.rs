@@ -0,0 +1,319 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("A".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+
+    let repo = git2::Repository::open(root_path).unwrap();
+    let mut config = repo.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap();
+    config
+        .set_str("dive.profile.team.args", "--style numbers --no-pager")
+        .unwrap();
+    config
+        .set_str("dive.profile.outer.args", "--profile=team --style=hash")
+        .unwrap();
+    config
+        .set_str("dive.profile.loop.args", "--profile=again")
+        .unwrap();
+    config
+        .set_str("dive.profile.again.args", "--profile loop")
+        .unwrap();
+}
+
+#[test]
+fn profile_args() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=team")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 one
+2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn cli_overrides_profile() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=team")
+        .arg("--style=hash,grid")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD │ one
+⋮    │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Profiles using profiles apply the outer profile's flags last
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=outer")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD one
+⋮    two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn unknown_profile() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=nope")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+unknown profile `nope`; available profiles: again, loop, outer, team
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn recursive_profile() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=loop")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+profile `loop` refers to itself: loop -> again -> loop
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn dump_config_lists_profiles() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--dump-config=-")
+        .current_dir(root_path)
+        .env_remove("GIT_PAGER")
+        .env_remove("PAGER")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+...
+[dive \"profile.again\"]
+	args = --profile loop  # $GIT_DIR/config
+[dive \"profile.loop\"]
+	args = --profile=again  # $GIT_DIR/config
+[dive \"profile.outer\"]
+	args = --profile=team --style=hash  # $GIT_DIR/config
+[dive \"profile.team\"]
+	args = --style numbers --no-pager  # $GIT_DIR/config
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn default_args() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.default-args")
+        .env("GIT_CONFIG_VALUE_0", "--style 'numbers' --no-pager")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 one
+2 two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Profiles and the command line override the defaults
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--profile=outer")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.default-args")
+        .env("GIT_CONFIG_VALUE_0", "--style=numbers")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD one
+⋮    two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("-v")
+        .arg("--style=hash")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.default-args")
+        .env("GIT_CONFIG_VALUE_0", "--style=numbers")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD one
+⋮    two
+"
+            .raw(),
+        )
+        .stderr_eq(
+            r#"...
+[..]dropping ["--style=numbers"] for a later flag
+[..]expanded arguments: [[..], "-v", "--style=hash", "basic.txt"]
+...
+"#,
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn default_args_reject_modes() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.default-args")
+        .env("GIT_CONFIG_VALUE_0", "--no-pager --dump-config=-")
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`dive.default-args` can't include `--dump-config`; pass it on the command line
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn conflicting_default_args() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=quickfix", "--relative-paths", "basic.txt"])
+        .current_dir(root_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.default-args")
+        .env("GIT_CONFIG_VALUE_0", "--absolute-paths --format=json")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+basic.txt:1: [..] A
+basic.txt:2: [..] A
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn cli_pager_overrides_profile_no_pager() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--profile=team", "--pager=less", "--diagnostic"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("`less` (from --pager)"),
+        "pager missing from:\n{output}"
+    );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,212 @@
+use snapbox::prelude::*;
+
+/// A line per commit, the first two tagged as releases and the last not yet released
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+    let mut content = String::new();
+    for (line, tag) in [("one", Some("v1.0.0")), ("two", Some("v1.1.0")), ("three", None)] {
+        content.push_str(line);
+        content.push('\n');
+        let id = fixture.commit(line, &[("file.txt", &content)]);
+        if let Some(tag) = tag {
+            let commit = fixture.repo.find_object(id, None).unwrap();
+            fixture.repo.tag(tag, &commit, &sig, tag, false).unwrap();
+            fixture
+                .repo
+                .tag_lightweight(&format!("nightly-{line}"), &commit, false)
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+fn show_release() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--show-release", "file.txt"])
+        .current_dir(root_path)
+        .env("XDG_CACHE_HOME", root_path.join("cache"))
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 v1.0.0     1 │ one
+HEAD~1 v1.1.0     2 │ two
+HEAD   unreleased 3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn release_tag_glob() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let cache = root_path.join("cache");
+
+    for _ in 0..2 {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--show-release", "file.txt"])
+            .current_dir(root_path)
+            .env("XDG_CACHE_HOME", &cache)
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.release-tag-glob")
+            .env("GIT_CONFIG_VALUE_0", "nightly-*")
+            .assert()
+            .success()
+            .stdout_eq(
+                "\
+HEAD~2 nightly-one 1 │ one
+HEAD~1 nightly-two 2 │ two
+HEAD   unreleased  3 │ three
+"
+                .raw(),
+            )
+            .stderr_eq("");
+    }
+    let cached = std::fs::read_dir(cache.join("git-dive").join("releases"))
+        .unwrap()
+        .count();
+    assert_eq!(cached, 1);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_output_warms_the_cache() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let cache = root_path.join("cache");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--show-release", "--no-output", "file.txt"])
+        .current_dir(root_path)
+        .env("XDG_CACHE_HOME", &cache)
+        .assert()
+        .success()
+        .stdout_eq("".raw())
+        .stderr_eq("");
+    let cached = std::fs::read_dir(cache.join("git-dive").join("releases"))
+        .unwrap()
+        .count();
+    assert_eq!(cached, 1);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn moved_tag_replaces_the_cache() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let cache = root_path.join("cache");
+    let show_release = |expected: &'static str| {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--show-release", "file.txt"])
+            .current_dir(root_path)
+            .env("XDG_CACHE_HOME", &cache)
+            .assert()
+            .success()
+            .stdout_eq(expected.raw())
+            .stderr_eq("");
+    };
+    let cached = || {
+        let releases = cache.join("git-dive").join("releases");
+        std::fs::read_dir(releases)
+            .unwrap()
+            .flat_map(|glob| std::fs::read_dir(glob.unwrap().path()).unwrap())
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>()
+    };
+
+    show_release(
+        "\
+HEAD~2 v1.0.0     1 │ one
+HEAD~1 v1.1.0     2 │ two
+HEAD   unreleased 3 │ three
+",
+    );
+    let before = cached();
+    assert_eq!(before.len(), 1);
+
+    let repo = git2::Repository::open(root_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.tag_lightweight("v1.2.0", head.as_object(), false)
+        .unwrap();
+    show_release(
+        "\
+HEAD~2 v1.0.0 1 │ one
+HEAD~1 v1.1.0 2 │ two
+HEAD   v1.2.0 3 │ three
+",
+    );
+    let after = cached();
+    assert_eq!(after.len(), 1);
+    assert_ne!(before, after);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn refresh_releases_rewrites_the_cache() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let cache = root_path.join("cache");
+    let show_release = |args: &[&str], expected: &'static str| {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .arg("--show-release")
+            .args(args)
+            .arg("file.txt")
+            .current_dir(root_path)
+            .env("XDG_CACHE_HOME", &cache)
+            .assert()
+            .success()
+            .stdout_eq(expected.raw())
+            .stderr_eq("");
+    };
+    let expected = "\
+HEAD~2 v1.0.0     1 │ one
+HEAD~1 v1.1.0     2 │ two
+HEAD   unreleased 3 │ three
+";
+    show_release(&[], expected);
+
+    let glob = std::fs::read_dir(cache.join("git-dive").join("releases"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let file = std::fs::read_dir(glob)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let cached = std::fs::read_to_string(&file).unwrap();
+    std::fs::write(&file, cached.replace("v1.0.0", "v0.0.0-stale")).unwrap();
+    show_release(
+        &[],
+        "\
+HEAD~2 v0.0.0-stale 1 │ one
+HEAD~1 v1.1.0       2 │ two
+HEAD   unreleased   3 │ three
+",
+    );
+
+    show_release(&["--refresh-releases"], expected);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), cached);
+    show_release(&[], expected);
+
+    root.close().unwrap();
+}
@@ -0,0 +1,309 @@
+use snapbox::prelude::*;
+
+/// Commit each `(path, content)` in turn, replacing the previous commit's only file
+fn fixture(root_path: &std::path::Path, versions: &[(&str, &str)]) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for (path, content) in versions {
+        fixture.commit(path, &[(path, content)]);
+    }
+}
+
+#[test]
+fn case_only_rename() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(
+        root_path,
+        &[
+            ("Utils.rs", "one\ntwo\n"),
+            ("utils.rs", "one\ntwo\n"),
+            ("utils.rs", "one\ntwo\nthree\n"),
+        ],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("utils.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one  ← Utils.rs
+⋮      2 │ two
+HEAD   3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn normalization_rename() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    // Committed decomposed from macOS, then composed from Linux
+    fixture(
+        root_path,
+        &[
+            ("cafe\u{301}.rs", "one\ntwo\n"),
+            ("caf\u{e9}.rs", "one\ntwo\n"),
+            ("caf\u{e9}.rs", "one\ntwo\nthree\n"),
+        ],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("caf\u{e9}.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one  ← cafe\u{301}.rs
+⋮      2 │ two
+HEAD   3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn other_normalization_finds_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(
+        root_path,
+        &[
+            ("cafe\u{301}.rs", "one\ntwo\n"),
+            ("caf\u{e9}.rs", "one\ntwo\nthree\n"),
+        ],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("cafe\u{301}.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one  ← cafe\u{301}.rs
+⋮      2 │ two
+HEAD   3 │ three
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+showing caf\u{e9}.rs for cafe\u{301}.rs
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn case_still_matters_without_icase() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &[("Utils.rs", "one\n"), ("utils.rs", "one\ntwo\n")]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("Utils.rs")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+Utils.rs does not exist at HEAD; pass `--find-deleted` to search history
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn previous_follows_renames() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(
+        root_path,
+        &[
+            ("old.txt", "one\ntwo\nthree\nfour\nfive\n"),
+            ("new.txt", "one\ntwo\nthree\nfour\nfive\nsix\n"),
+        ],
+    );
+    let repo = git2::Repository::open(root_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let root_commit = head.parent_id(0).unwrap();
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=json", "new.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let lines = json[0]["lines"].as_array().unwrap();
+    // Added by the root commit
+    assert_eq!(lines[0]["commit"], root_commit.to_string());
+    assert_eq!(lines[0]["previous"], serde_json::Value::Null);
+    // Added by the rename
+    assert_eq!(lines[5]["commit"], head.id().to_string());
+    assert_eq!(
+        lines[5]["previous"],
+        serde_json::json!({"commit": root_commit.to_string(), "path": "old.txt"})
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn lines_from_renamed_files() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let fixture = crate::fixture::Repo::init(root_path);
+    let moved = "fn moved() {\n    let answer = 42;\n    println!(\"{answer}\");\n}\n";
+    fixture.commit("Commit", &[("src/very/deeply/nested/old.rs", moved)]);
+    fixture.commit("Commit", &[("new.rs", &format!("fn new() {{}}\n{moved}"))]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.rs")
+        .current_dir(root_path)
+        .env("COLUMNS", "80")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD   1 │ fn new() {}
+HEAD~1 2 │ fn moved() {  ← src/very/deeply/nested/old.rs
+⋮      3 │     let answer = 42;
+⋮      4 │     println!(\"{answer}\");
+⋮      5 │ }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Long paths lose leading directories to fit
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.rs")
+        .current_dir(root_path)
+        .env("COLUMNS", "40")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD   1 │ fn new() {}
+HEAD~1 2 │ fn moved() {  ← …ested/old.rs
+⋮      3 │     let answer = 42;
+⋮      4 │     println!(\"{answer}\");
+⋮      5 │ }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Only when asked for with `--style`
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--style=hash")
+        .arg("new.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD   fn new() {}
+HEAD~1 fn moved() {
+⋮          let answer = 42;
+⋮          println!(\"{answer}\");
+⋮      }
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn precompose_finds_decomposed_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path, &[("cafe\u{301}.rs", "one\ntwo\n")]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("caf\u{e9}.rs")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "core.precomposeUnicode")
+        .env("GIT_CONFIG_VALUE_0", "true")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ one
+⋮    2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+showing cafe\u{301}.rs for caf\u{e9}.rs
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn precompose_both_normalizations_is_ambiguous() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit(
+        "Initial",
+        &[("caf\u{e9}.rs", "caf\u{e9}.rs"), ("cafe\u{301}.rs", "cafe\u{301}.rs")],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("caf\u{e9}.rs")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "core.precomposeUnicode")
+        .env("GIT_CONFIG_VALUE_0", "true")
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`caf\u{e9}.rs` matches several paths that differ only by Unicode normalization:
+  cafe\u{301}.rs (decomposed)
+  caf\u{e9}.rs (composed)
+Set `core.precomposeUnicode` to `false` to pick one by its exact spelling
+",
+        );
+
+    // Without it, the exact spelling wins
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("caf\u{e9}.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD 1 │ caf\u{e9}.rs
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,282 @@
+//! Byte-for-byte snapshots of `--render-test` output
+//!
+//! Fixtures use fixed identities and dates so their commit ids never change.  Update the
+//! snapshots with `SNAPSHOTS=overwrite cargo test`.
+
+/// One commit in a fixture, replacing every file it lists
+struct Change<'c> {
+    author: (&'static str, &'static str),
+    /// Days since the unix epoch, also used for the committer date
+    day: i64,
+    message: &'static str,
+    files: &'c [(&'static str, &'static str)],
+}
+
+const ADA: (&str, &str) = ("Ada Lovelace", "ada@example.com");
+const GRACE: (&str, &str) = ("Grace Hopper", "grace@example.com");
+
+fn fixture(root_path: &std::path::Path, changes: &[Change<'_>]) -> git2::Oid {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut head = None;
+    let mut files = std::collections::BTreeMap::new();
+    for change in changes {
+        files.extend(change.files.iter().copied());
+        let files = files.clone().into_iter().collect::<Vec<_>>();
+        let (name, email) = change.author;
+        head = Some(
+            fixture
+                .author(name, email, change.day * 24 * 60 * 60)
+                .commit(change.message, &files),
+        );
+    }
+    fixture.checkout();
+    head.expect("at least one change")
+}
+
+/// A small Rust project with history from two authors
+fn history(root_path: &std::path::Path) -> git2::Oid {
+    fixture(
+        root_path,
+        &[
+            Change {
+                author: ADA,
+                day: 18_262, // 2020-01-01
+                message: "Initial commit",
+                files: &[
+                    ("main.rs", "fn main() {\n    println!(\"Hello\");\n}\n"),
+                    ("README.md", "# Example\n"),
+                ],
+            },
+            Change {
+                author: GRACE,
+                day: 19_700, // 2023-12-09
+                message: "Greet the world",
+                files: &[(
+                    "main.rs",
+                    "fn main() {\n    // Be friendly\n    println!(\"Hello, world!\");\n}\n",
+                )],
+            },
+        ],
+    )
+}
+
+fn render_test(root_path: &std::path::Path) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--render-test")
+        .current_dir(root_path)
+        // Leave nothing up to the tester's environment
+        .env_remove("COLUMNS")
+        .env_remove("GIT_CONFIG_PARAMETERS")
+        .env_remove("GIT_CONFIG_COUNT")
+}
+
+#[test]
+fn fixture_ids_are_stable() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+
+    let head = history(root_path);
+    assert_eq!(head.to_string(), "dabfd80124857c2cd89c5deb28b7cc4fa30d3a6b");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    render_test(root_path)
+        .arg("main.rs")
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["render/file.stdout": Text].raw())
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn full_gutter() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    render_test(root_path)
+        .arg("--style=stripe,hash,author,date,numbers,grid")
+        .arg("--color-by=author")
+        .arg("--date=relative")
+        .arg("main.rs")
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["render/full_gutter.stdout": Text].raw())
+        .stderr_eq(
+            "\
+WARN: gutter takes 38 of 80 columns; remove components from `--style` to make room for the code
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn highlight_recent() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    render_test(root_path)
+        .arg("--coloring=highlight-recent")
+        .arg("main.rs")
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["render/highlight_recent.stdout": Text].raw())
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn directory() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    render_test(root_path)
+        .arg(".")
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["render/directory.stdout": Text].raw())
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn ascii() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    let render = |ascii: &str| {
+        let output = render_test(root_path)
+            .arg("--style=stripe,hash,numbers,grid,separator")
+            .arg("--color-by=author")
+            .arg("main.rs")
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.ascii")
+            .env("GIT_CONFIG_VALUE_0", ascii)
+            .assert()
+            .success()
+            .stderr_eq("")
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let utf8 = render("false");
+    let ascii = render("true");
+
+    snapbox::assert_data_eq!(
+        ascii.clone(),
+        snapbox::file!["render/ascii.stdout": Text].raw()
+    );
+    assert!(ascii.is_ascii(), "{ascii}");
+    // Nothing but the characters differs, down to the escape sequences
+    let swapped = utf8
+        .replace('│', "|")
+        .replace('┆', ":")
+        .replace('▎', "|")
+        .replace('⋮', ":");
+    assert_eq!(swapped, ascii);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_syntax() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    let render = |args: &[&str], config: Option<(&str, &str)>| {
+        let mut cmd = render_test(root_path).args(args).arg("main.rs");
+        if let Some((key, value)) = config {
+            cmd = cmd
+                .env("GIT_CONFIG_COUNT", "1")
+                .env("GIT_CONFIG_KEY_0", key)
+                .env("GIT_CONFIG_VALUE_0", value);
+        }
+        let output = cmd.assert().success().get_output().clone();
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (highlighted, _) = render(&[], None);
+    let (plain, stderr) = render(&["--no-syntax"], None);
+
+    snapbox::assert_data_eq!(
+        plain.clone(),
+        snapbox::file!["render/no_syntax.stdout": Text].raw()
+    );
+    assert_eq!(stderr, "");
+    // Only the escape sequences differ
+    assert_eq!(
+        anstream::adapter::strip_str(&plain).to_string(),
+        anstream::adapter::strip_str(&highlighted).to_string()
+    );
+
+    assert_eq!(render(&[], Some(("dive.syntax", "false"))), (plain.clone(), String::new()));
+    assert_eq!(
+        render(&[], Some(("dive.syntax-max-lines", "3"))),
+        (
+            plain.clone(),
+            "WARN: not highlighting syntax: 4 lines is more than `dive.syntax-max-lines` (3)\n"
+                .to_owned()
+        )
+    );
+    assert_eq!(render(&[], Some(("dive.syntax-max-lines", "0"))).0, highlighted);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn language() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history(root_path);
+
+    let render = |args: &[&str]| {
+        let output = render_test(root_path)
+            .args(args)
+            .arg("README.md")
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let markdown = render(&[]);
+    let as_rust = render(&["--language", "rust"]);
+    assert_ne!(as_rust, markdown);
+    assert_eq!(render(&["-l", "rs"]), as_rust);
+    assert_eq!(
+        anstream::adapter::strip_str(&as_rust).to_string(),
+        anstream::adapter::strip_str(&markdown).to_string()
+    );
+
+    render_test(root_path)
+        .args(["--language", "nonsense", "README.md"])
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+unknown language `nonsense`; see `--list-languages`
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,144 @@
+fn fixture(root_path: &std::path::Path) {
+    let plan = git_fixture::TodoList {
+        commands: vec![
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Add basic".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Tree(git_fixture::Tree {
+                files: [("basic.txt", "one\ntwo\nthree\n")]
+                    .into_iter()
+                    .map(|(p, c)| (p.into(), c.into()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+                message: Some("Add three".to_owned()),
+                author: None,
+            }),
+            git_fixture::Command::Branch("main".into()),
+        ],
+        ..Default::default()
+    };
+    plan.run(root_path).unwrap();
+}
+
+fn run(root_path: &std::path::Path, format: &str) -> String {
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg(format!("--format={format}"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn json_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--format=json")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "basic.txt",
+    "lines": [
+      {
+        "line": 1,
+        "commit": "[..]",
+        "orig_line": 1,
+        "origin": "HEAD~1",
+        "first_parent_distance": 1,
+        "author": "[..]",
+        "date": "[..]",
+        "time": [..],
+        "summary": "Add basic",
+        "ignored_via": [],
+        "resolution": false,
+        "previous": null,
+        "boundary": "root",
+        "content": "one"
+      },
+      {
+        "line": 2,
+        "commit": "[..]",
+        "orig_line": 2,
+        "origin": "HEAD~1",
+        "first_parent_distance": 1,
+        "author": "[..]",
+        "date": "[..]",
+        "time": [..],
+        "summary": "Add basic",
+        "ignored_via": [],
+        "resolution": false,
+        "previous": null,
+        "boundary": "root",
+        "content": "two"
+      },
+      {
+        "line": 3,
+        "commit": "[..]",
+        "orig_line": 3,
+        "origin": "HEAD",
+        "first_parent_distance": 0,
+        "author": "[..]",
+        "date": "[..]",
+        "time": [..],
+        "summary": "Add three",
+        "ignored_via": [],
+        "resolution": false,
+        "previous": {
+          "commit": "[..]",
+          "path": "basic.txt"
+        },
+        "boundary": null,
+        "content": "three"
+      }
+    ]
+  }
+]
+"#,
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_and_quickfix_agree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let json: serde_json::Value = serde_json::from_str(&run(root_path, "json")).unwrap();
+    let json_lines = json[0]["lines"].as_array().unwrap();
+    let quickfix = run(root_path, "quickfix");
+    let quickfix_lines = quickfix.lines().collect::<Vec<_>>();
+
+    assert_eq!(json_lines.len(), 3);
+    assert_eq!(json_lines.len(), quickfix_lines.len());
+    for (json_line, quickfix_line) in json_lines.iter().zip(quickfix_lines) {
+        let (location, details) = quickfix_line.split_once(": ").unwrap();
+        let number = json_line["line"].as_u64().unwrap();
+        assert_eq!(location, format!("basic.txt:{number}"));
+        let short = details.split(' ').next().unwrap();
+        let commit = json_line["commit"].as_str().unwrap();
+        assert!(commit.starts_with(short), "{commit} vs {short}");
+        let summary = json_line["summary"].as_str().unwrap();
+        assert!(details.ends_with(summary), "{details} vs {summary}");
+    }
+
+    root.close().unwrap();
+}
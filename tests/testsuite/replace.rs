@@ -0,0 +1,174 @@
+use snapbox::prelude::*;
+
+/// An old history and a new one whose root is grafted onto it
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let commit = |content: &str, message: &str, parents: &[git2::Oid]| {
+        fixture.commit_onto(message, &[("basic.txt", content)], parents)
+    };
+    let old = commit("one\ntwo\n", "Old", &[]);
+    let new_root = commit("one\ntwo\nthree\n", "Import", &[]);
+    let head = commit("one\ntwo\nthree\nfour\n", "D", &[new_root]);
+    fixture.branch("main", head);
+    fixture.checkout();
+
+    graft(root_path, new_root, old);
+}
+
+/// Make `commit` a child of `parent` with `git replace`
+fn graft(root_path: &std::path::Path, commit: git2::Oid, parent: git2::Oid) {
+    let status = std::process::Command::new("git")
+        .arg("replace")
+        .arg("--graft")
+        .arg(commit.to_string())
+        .arg(parent.to_string())
+        .current_dir(root_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn follows_graft() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env_remove("GIT_NO_REPLACE_OBJECTS")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2 1 │ one
+⋮      2 │ two
+HEAD~1 3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_replace_objects() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--no-replace-objects")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+⋮      3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .env("GIT_NO_REPLACE_OBJECTS", "1")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~1 1 │ one
+⋮      2 │ two
+⋮      3 │ three
+HEAD   4 │ four
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+/// Like [`fixture`], with an `other.txt` only changed in the old history and dated commits,
+/// returning the old commit
+fn dated_fixture(root_path: &std::path::Path) -> git2::Oid {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut commit = |time: i64, content: &str, message: &str, parents: &[git2::Oid]| {
+        fixture.author("Test User", "test@example.com", time).commit_onto(
+            message,
+            &[("basic.txt", content), ("other.txt", "other\n")],
+            parents,
+        )
+    };
+    let old = commit(1_000_000_000, "one\ntwo\n", "Old", &[]);
+    let new_root = commit(1_300_000_000, "one\ntwo\nthree\n", "Import", &[]);
+    let head = commit(1_400_000_000, "one\ntwo\nthree\nfour\n", "D", &[new_root]);
+    fixture.branch("main", head);
+    fixture.checkout();
+
+    graft(root_path, new_root, old);
+    old
+}
+
+#[test]
+fn at_date_through_graft() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let old = dated_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--at=@1100000000", "--style=numbers", "basic.txt"])
+        .current_dir(root_path)
+        .env_remove("GIT_NO_REPLACE_OBJECTS")
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 one
+2 two
+"
+            .raw(),
+        )
+        .stderr_eq(format!(
+            "\
+as of [..], {old} is [..] (Old), committed 2001-09-09
+note: basic.txt differs from HEAD; showing it as of {old}
+"
+        ));
+
+    root.close().unwrap();
+}
+
+#[test]
+fn summary_through_graft() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let old = dated_fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg(".")
+        .current_dir(root_path)
+        .env_remove("GIT_NO_REPLACE_OBJECTS")
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let other = output
+        .lines()
+        .find(|l| l.ends_with("other.txt"))
+        .unwrap();
+    assert!(other.contains(&old.to_string()[..7]), "{output}");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,127 @@
+/// Authors with the same number of lines and commits at the same time, so only tie-breaks order
+/// them
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut files = std::collections::BTreeMap::new();
+    let mut shared = String::new();
+    for (name, added) in [
+        ("Zoe", &[("b.txt", "zoe\n"), ("src/z.txt", "z\n")][..]),
+        ("Amy", &[("a.txt", "amy\n"), ("src/a.txt", "a\n")][..]),
+        ("Max", &[("B.txt", "max\n"), ("src/m.txt", "m\n")][..]),
+    ] {
+        files.extend(added.iter().copied());
+        // Everyone also adds a line to the same file
+        shared.push_str(&format!("{name}\n"));
+        let mut tree = files.clone().into_iter().collect::<Vec<_>>();
+        tree.push(("shared.txt", &shared));
+
+        let email = format!("{name}@example.com");
+        fixture
+            .author(name, &email, 1_700_000_000)
+            .commit(name, &tree);
+    }
+    fixture.checkout();
+}
+
+fn dive(root_path: &std::path::Path) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive")).current_dir(root_path)
+}
+
+/// Runs `command` twice, failing unless both wrote the same bytes, like a CI job diffing reports
+fn twice(command: impl Fn() -> snapbox::cmd::Command) -> String {
+    let run = || command().assert().get_output().stdout.clone();
+    let first = run();
+    let second = run();
+    assert_eq!(first, second);
+    String::from_utf8(first).unwrap()
+}
+
+#[test]
+fn stat_ties_by_name() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let stdout = twice(|| {
+        dive(root_path)
+            .args(["--stat", "--group-by-domain", "shared.txt"])
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "dive.team-map")
+            .env("GIT_CONFIG_VALUE_0", "amy@example.com=Blue")
+    });
+    snapbox::assert_data_eq!(
+        stdout,
+        "\
+Authors:
+  Amy 1  34%
+  Max 1  33%
+  Zoe 1  33%
+
+Teams:
+  example.com 2  67%
+  Blue        1  33%
+
+Boundary commits:
+  [..] root 1 Zoe
+"
+    );
+
+    twice(|| {
+        dive(root_path).args(["--stat", "--format=json", "shared.txt"])
+    });
+
+    root.close().unwrap();
+}
+
+#[test]
+fn directory_ties_by_path() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let stdout = twice(|| dive(root_path).arg("."));
+    // All changed at the same time, so bytewise by path with uppercase first
+    let paths = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "B.txt",
+            "a.txt",
+            "b.txt",
+            "shared.txt",
+        ]
+    );
+    let stdout = twice(|| dive(root_path).arg("src"));
+    let paths = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["a.txt", "m.txt", "z.txt"]);
+
+    twice(|| dive(root_path).args(["--format=json", "."]));
+
+    root.close().unwrap();
+}
+
+#[test]
+fn batch_keys_in_order() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let stdout = twice(|| {
+        dive(root_path)
+            .args(["--stdin-paths", "--format=json"])
+            .stdin("shared.txt\nmissing.txt\n")
+    });
+    let error = stdout.lines().last().unwrap();
+    assert!(
+        error.starts_with(r#"{"path":"missing.txt","error":"#),
+        "{error}"
+    );
+
+    root.close().unwrap();
+}
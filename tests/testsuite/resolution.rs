@@ -0,0 +1,81 @@
+use snapbox::prelude::*;
+
+/// Two branches changing the same line, merged with a line neither had
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let commit = |message: &str, content: &str, parents: &[git2::Oid]| {
+        fixture.commit_onto(message, &[("basic.txt", content)], parents)
+    };
+    let base = commit("base", "one\ntwo\nthree\n", &[]);
+    let ours = commit("ours", "one\nours\nthree\n", &[base]);
+    let theirs = commit("theirs", "one\ntheirs\nthree\n", &[base]);
+    let merge = commit(
+        "merge",
+        "one\nours\ntheirs\nresolved\nthree\n",
+        &[ours, theirs],
+    );
+    fixture.branch("main", merge);
+    fixture.checkout();
+}
+
+#[test]
+fn marks_resolution() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    // `theirs` is blamed on the merge, not the second parent, but isn't new to the merge
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--detect-resolution")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2  1 │ one
+HEAD~1  2 │ ours
+HEAD    3 │ theirs
+⋮     ! 4 │ resolved
+HEAD~2  5 │ three
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn too_many_merges() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--detect-resolution")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.max-resolution-merges")
+        .env("GIT_CONFIG_VALUE_0", "0")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~2  1 │ one
+HEAD~1  2 │ ours
+HEAD    3 │ theirs
+⋮       4 │ resolved
+HEAD~2  5 │ three
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: not detecting conflict resolutions: 1 merges is more than `dive.max-resolution-merges` (0)
+",
+        );
+
+    root.close().unwrap();
+}
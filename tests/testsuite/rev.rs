@@ -0,0 +1,324 @@
+use snapbox::prelude::*;
+
+/// A repo where a 4-character prefix of a dangling commit is shared with a blob
+///
+/// Returns the shared prefix
+fn ambiguous_commit_fixture(root_path: &std::path::Path) -> String {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let files = [("basic.txt", "one\ntwo\n")];
+    let head = fixture.commit_onto("A", &files, &[]);
+    fixture.branch("main", head);
+    fixture.checkout();
+
+    let mut commits = std::collections::HashMap::new();
+    let mut blobs = std::collections::HashMap::new();
+    for i in 0.. {
+        let commit = fixture.commit_onto(&format!("Fuzz {i}"), &files, &[]);
+        let prefix = commit.to_string()[..4].to_owned();
+        if blobs.contains_key(&prefix) {
+            return prefix;
+        }
+        commits.insert(prefix, commit);
+
+        let blob = fixture.repo.blob(format!("fuzz {i}\n").as_bytes()).unwrap();
+        let prefix = blob.to_string()[..4].to_owned();
+        if commits.contains_key(&prefix) {
+            return prefix;
+        }
+        blobs.insert(prefix, blob);
+    }
+    unreachable!()
+}
+
+/// A repo where a 4-character prefix is shared by two blobs
+///
+/// Returns the shared prefix
+fn ambiguous_blob_fixture(root_path: &std::path::Path) -> String {
+    let repo = git2::Repository::init(root_path).unwrap();
+    let mut blobs = std::collections::HashSet::new();
+    for i in 0.. {
+        let blob = repo.blob(format!("fuzz {i}\n").as_bytes()).unwrap();
+        let prefix = blob.to_string()[..4].to_owned();
+        if !blobs.insert(prefix.clone()) {
+            return prefix;
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn prefers_only_commit() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let prefix = ambiguous_commit_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg(&prefix)
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(format!(
+            "\
+{prefix} 1 │ one
+⋮    2 │ two
+"
+        ))
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn lists_candidates() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let prefix = ambiguous_blob_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg(&prefix)
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .stderr_eq(format!(
+            "\
+short object ID {prefix} is ambiguous
+The candidates are:
+  {prefix}[..] blob
+  {prefix}[..] blob
+"
+        ));
+
+    root.close().unwrap();
+}
+
+/// Two commits, returning the first
+fn history_fixture(root_path: &std::path::Path) -> git2::Oid {
+    let fixture = crate::fixture::Repo::init(root_path);
+    let first = fixture.commit("one\n", &[("basic.txt", "one\n")]);
+    fixture.commit("one\ntwo\n", &[("basic.txt", "one\ntwo\n")]);
+    fixture.checkout();
+    first
+}
+
+#[test]
+fn rev_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let first = history_fixture(root_path);
+    std::fs::create_dir_all(root_path.join(".deploy")).unwrap();
+    std::fs::write(
+        root_path.join(".deploy/current"),
+        format!("# Deployed by CI\n\n  {first}  \n"),
+    )
+    .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--rev-file", ".deploy/current", "--style=numbers", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 one
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+note: basic.txt differs from HEAD; showing it as of [..] (rev from .deploy/current)
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--rev-file", ".deploy/current", "HEAD", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+error: the argument '--rev-file <PATH>' cannot be used with '[REV]'
+...
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn bad_rev_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    history_fixture(root_path);
+    std::fs::write(root_path.join("current"), "# Nothing deployed\n").unwrap();
+    std::fs::write(root_path.join("typo"), "nope\n").unwrap();
+    std::fs::write(root_path.join("deployed"), "HEAD~1\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--rev-file", "current", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+current has no rev
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--rev-file", "typo", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+could not resolve `nope` from typo: [..]
+",
+        );
+
+    // Messages say where the rev came from
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--rev-file", "deployed", "missing.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(5)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+missing.txt does not exist at HEAD~1 (rev from deployed); pass `--find-deleted` to search history
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn older_than_head() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let first = history_fixture(root_path);
+    let repo = git2::Repository::open(root_path).unwrap();
+    repo.tag_lightweight("v1.0", &repo.find_object(first, None).unwrap(), false)
+        .unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.tag_lightweight("v2.0", head.as_object(), false)
+        .unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--style=numbers", "v1.0", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+1 one
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+note: basic.txt differs from HEAD; showing it as of v1.0
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--quiet", "--style=numbers", "v1.0", "basic.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("");
+
+    // The same version as HEAD, or relative to HEAD, is what was asked for
+    for rev in ["v2.0", "HEAD~1"] {
+        snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+            .args(["--style=numbers", rev, "basic.txt"])
+            .current_dir(root_path)
+            .assert()
+            .success()
+            .stderr_eq("");
+    }
+
+    root.close().unwrap();
+}
+
+/// A line added each day from 2023-06-01, the third commit's clock a day behind
+fn dated_fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    // 2023-06-01T12:00:00Z
+    let noon = 1_685_620_800;
+    let day = 24 * 60 * 60;
+    let mut content = String::new();
+    for (i, time) in [noon, noon + day, noon, noon + 3 * day].into_iter().enumerate() {
+        content.push_str(&format!("line {i}\n"));
+        fixture
+            .author("Test User", "test@example.com", time)
+            .commit(&format!("Day {i}"), &[("file.txt", &content)]);
+    }
+}
+
+#[test]
+fn at_date() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    dated_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--at", "2023-06-03", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~3 1 │ line 0
+HEAD~2 2 │ line 1
+HEAD~1 3 │ line 2
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+as of 2023-06-03, HEAD~1 is [..] (Day 2), committed 2023-06-01
+",
+        );
+
+    // The skewed commit is the first old enough, though its parent is newer
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--at", "2023-06-01 18:00", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD~3 1 │ line 0
+HEAD~2 2 │ line 1
+HEAD~1 3 │ line 2
+"
+            .raw(),
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--at", "2023-05-31", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(4)
+        .stderr_eq(
+            "\
+HEAD has no commits from 2023-05-31 or before
+",
+        );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--at", "someday", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .code(2);
+
+    root.close().unwrap();
+}
@@ -0,0 +1,131 @@
+use snapbox::prelude::*;
+
+/// `two` removed then restored by a revert, and `three` cherry-picked from `topic`
+///
+/// Returns the removal and the picked commit, the originals
+fn fixture(root_path: &std::path::Path) -> (git2::Oid, git2::Oid) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let add = fixture.commit("Add", &[("file.txt", "one\ntwo\n")]);
+    fixture.author("Other Person", "other@example.com", 0);
+    let picked = fixture.commit_onto("Add three", &[("file.txt", "one\ntwo\nthree\n")], &[add]);
+    fixture
+        .repo
+        .reference("refs/heads/topic", picked, true, "fixture")
+        .unwrap();
+    let removal = fixture.commit("Remove two", &[("file.txt", "one\n")]);
+    fixture.author("Test User", "test@example.com", 0);
+    fixture.commit(
+        &format!("Revert \"Remove two\"\n\nThis reverts commit {removal}.\n"),
+        &[("file.txt", "one\ntwo\n")],
+    );
+    fixture.commit(
+        &format!("Add three\n\n(cherry picked from commit {picked})\n"),
+        &[("file.txt", "one\ntwo\nthree\n")],
+    );
+    (removal, picked)
+}
+
+fn short(root_path: &std::path::Path, id: git2::Oid) -> String {
+    let repo = git2::Repository::open(root_path).unwrap();
+    let object = repo.find_object(id, None).unwrap();
+    object.short_id().unwrap().as_str().unwrap().to_owned()
+}
+
+#[test]
+fn alongside() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (removal, picked) = fixture(root_path);
+    let (removal, picked) = (short(root_path, removal), short(root_path, picked));
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--resolve-reverts", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            format!(
+                "\
+HEAD~3  1 │ one
+HEAD~1  2 │ two  ↩ {removal} Other Person, 1970-01-01
+HEAD    3 │ three  ⇢ {picked} Other Person, 1970-01-01
+"
+            )
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn replace() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (_, picked) = fixture(root_path);
+    let picked = short(root_path, picked);
+    let repo = git2::Repository::open(root_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let revert = short(root_path, head.parent_id(0).unwrap());
+    let pick = short(root_path, head.id());
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--resolve-reverts=replace", "--style=hash,author,numbers", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            format!(
+                "\
+HEAD~3  Test User    1 one
+HEAD~2  Other Person 2 two  ↩ {revert} Test User, 1970-01-01
+{picked} Other Person 3 three  ⇢ {pick} Test User, 1970-01-01
+"
+            )
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_via() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let (removal, picked) = fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--resolve-reverts", "--format=json", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let files: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let lines = &files[0]["lines"];
+    assert!(lines[0].get("via").is_none());
+    assert_eq!(
+        lines[1]["via"],
+        serde_json::json!({"kind": "revert", "commit": removal.to_string()})
+    );
+    assert_eq!(
+        lines[2]["via"],
+        serde_json::json!({"kind": "cherry-pick", "commit": picked.to_string()})
+    );
+
+    // Left out without `--resolve-reverts`
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=json", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let files: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(files[0]["lines"][1].get("via").is_none());
+
+    root.close().unwrap();
+}
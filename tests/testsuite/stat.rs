@@ -0,0 +1,289 @@
+/// Lines from authors at a few domains, one of whom committed under a second email
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let mut content = String::new();
+    for (name, email, lines) in [
+        ("Ada", "ada@example.com", "one\ntwo\nthree\n"),
+        ("Grace", "grace@navy.example.org", "four\nfive\n"),
+        ("Ada", "ada@old.example.com", "six\n"),
+        ("Linus", "linus@kernel.example.org", "seven\n"),
+    ] {
+        content.push_str(lines);
+        fixture.author(name, email, 0).commit(
+            name,
+            &[
+                ("basic.txt", &content),
+                (".mailmap", "Ada <ada@example.com> <ada@old.example.com>\n"),
+            ],
+        );
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn by_author() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   4  57%
+  Grace 2  29%
+  Linus 1  14%
+
+Boundary commits:
+  [..] root 3 Ada
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn by_domain() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("--group-by-domain")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.team-map")
+        .env("GIT_CONFIG_VALUE_0", "*@navy.*=Navy")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   4  57%
+  Grace 2  29%
+  Linus 1  14%
+
+Teams:
+  example.com        4  57%
+  Navy               2  29%
+  kernel.example.org 1  14%
+
+Boundary commits:
+  [..] root 3 Ada
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn by_team_map() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("--format=json")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", "dive.team-map")
+        .env("GIT_CONFIG_VALUE_0", "*.example.org=Labs")
+        .env("GIT_CONFIG_KEY_1", "dive.team-map")
+        .env("GIT_CONFIG_VALUE_1", "grace@*=Navy")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["path"], "basic.txt");
+    assert_eq!(json["lines"], 7);
+    assert_eq!(
+        json["authors"],
+        serde_json::json!([
+            {"author": "Ada", "email": "ada@example.com", "lines": 4, "percent": 57},
+            {"author": "Grace", "email": "grace@navy.example.org", "lines": 2, "percent": 29},
+            {"author": "Linus", "email": "linus@kernel.example.org", "lines": 1, "percent": 14},
+        ])
+    );
+    // The first matching entry wins
+    assert_eq!(
+        json["teams"],
+        serde_json::json!([
+            {"team": "Labs", "lines": 3, "percent": 43},
+            {"team": "(other)", "lines": 4, "percent": 57},
+        ])
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn malformed_team_map_warns() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", "dive.team-map")
+        .env("GIT_CONFIG_VALUE_0", "*@navy.example.org")
+        .env("GIT_CONFIG_KEY_1", "dive.team-map")
+        .env("GIT_CONFIG_VALUE_1", "*.example.org=Labs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   4  57%
+  Grace 2  29%
+  Linus 1  14%
+
+Teams:
+  Labs    3  43%
+  (other) 4  57%
+
+Boundary commits:
+  [..] root 3 Ada
+",
+        )
+        .stderr_eq(
+            "\
+WARN: ignoring `dive.team-map = *@navy.example.org`: expected `PATTERN=TEAM`
+",
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn directory_unsupported() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg(".")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--stat` is only supported for files
+",
+        );
+
+    root.close().unwrap();
+}
+
+/// Commits `basic.txt` as each author, in turn, with their version of it
+fn commit_versions(root_path: &std::path::Path, versions: &[(&str, &str)]) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    for (name, content) in versions {
+        let email = format!("{}@example.com", name.to_lowercase());
+        fixture
+            .author(name, &email, 0)
+            .commit(name, &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+#[test]
+fn ignore_markers() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    commit_versions(
+        root_path,
+        &[
+            ("Ada", "one\ntwo\n"),
+            (
+                "Grace",
+                "one\n// dive:ignore-start\ngenerated\ngenerated\n// dive:ignore-end\ntwo\n",
+            ),
+            (
+                "Linus",
+                "one\n// dive:ignore-start\ngenerated\ngenerated\n// dive:ignore-end\ntwo\nthree\n",
+            ),
+        ],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("basic.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   2  67%
+  Linus 1  33%
+
+Boundary commits:
+  [..] root 2 Ada
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn configured_ignore_markers() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    commit_versions(
+        root_path,
+        &[
+            ("Ada", "one\ntwo\n"),
+            ("Grace", "one\n# BEGIN generated\ntable\n# END generated\ntwo\n"),
+            (
+                "Linus",
+                "one\n# BEGIN generated\ntable\n# END generated\ntwo\n# BEGIN more\nthree\n",
+            ),
+        ],
+    );
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stat")
+        .arg("basic.txt")
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "dive.ignore-markers")
+        .env("GIT_CONFIG_VALUE_0", "BEGIN END")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Ada   2  50%
+  Linus 2  50%
+
+Boundary commits:
+  [..] root 2 Ada
+",
+        )
+        .stderr_eq(
+            "\
+WARN: basic.txt:6: never ended by `END`; not ignoring the lines after it
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,106 @@
+/// `one.txt` gaining a line, beside `dir/two.txt`
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    for (message, one) in [("A", "one\n"), ("B", "one\nmore\n")] {
+        fixture.commit(message, &[("one.txt", one), ("dir/two.txt", "two\n")]);
+    }
+    fixture.checkout();
+}
+
+fn records(stdout: &[u8]) -> Vec<serde_json::Value> {
+    std::str::from_utf8(stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_lines() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stdin-paths")
+        .arg("--format=json")
+        .stdin("one.txt\n\ndir/two.txt\n")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let records = records(&output);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["path"], "one.txt");
+    assert_eq!(records[0]["lines"].as_array().unwrap().len(), 2);
+    assert_eq!(records[1]["path"], "dir/two.txt");
+    assert_eq!(records[1]["lines"][0]["content"], "two");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn failures_carry_on() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stdin-paths=HEAD~")
+        .arg("--format=json")
+        .arg("-z")
+        .stdin("missing.txt\0dir\0one.txt\0")
+        .current_dir(root_path)
+        .assert()
+        .code(1)
+        .stderr_eq(
+            "\
+missing.txt does not exist at HEAD~; pass `--find-deleted` to search history
+dir is a directory; `--stdin-paths` only annotates files
+2 of 3 paths failed
+",
+        )
+        .get_output()
+        .stdout
+        .clone();
+    let records = records(&output);
+    assert_eq!(
+        records[0],
+        serde_json::json!({
+            "path": "missing.txt",
+            "error": "missing.txt does not exist at HEAD~; pass `--find-deleted` to search history",
+            "code": 5,
+        })
+    );
+    assert_eq!(records[1]["code"], 2);
+    assert_eq!(records[2]["path"], "one.txt");
+    // As of `HEAD~`
+    assert_eq!(records[2]["lines"].as_array().unwrap().len(), 1);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_only() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--stdin-paths")
+        .stdin("one.txt\n")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--stdin-paths` only supports `--format json`
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,82 @@
+/// Subjects with a tab, no blank line before the body, and one too long for the terminal
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    let versions = [
+        ("Fix\tparser  crash\nwhich leaked into the body\n", "one\n"),
+        (
+            "Rewrite the tokenizer so that nested brackets and string escapes are handled the same way everywhere\n",
+            "two\n",
+        ),
+    ];
+    fixture.author("Alice", "alice@example.com", 0);
+    for (message, content) in versions {
+        fixture.commit(message, &[("file.txt", content)]);
+    }
+}
+
+#[test]
+fn quickfix_is_one_line() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=quickfix", "HEAD~", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.txt:1: [..] Alice 1970-01-01 Fix parser crash
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn json_is_untouched() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--format=json", "HEAD~", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json[0]["lines"][0]["summary"], "Fix\tparser  crash which leaked into the body");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn detail_fits_the_terminal() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .args(["--line", "1", "--detail", "file.txt"])
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+file.txt:1
+two
+111
+
+1 [..] Alice 1970-01-01 Rewrite the tokenizer so that nested brackets and st…
+",
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
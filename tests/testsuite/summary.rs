@@ -0,0 +1,123 @@
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit(
+        "A",
+        &[
+            ("top.txt", "top\n"),
+            ("src/a.txt", "a\n"),
+            ("src/b.txt", "b\n"),
+            ("src/sub/c.txt", "c\n"),
+        ],
+    );
+    fixture.commit(
+        "B",
+        &[
+            ("top.txt", "top\n"),
+            ("src/a.txt", "a\nmore\n"),
+            ("src/b.txt", "b\n"),
+            ("src/sub/c.txt", "c\n"),
+        ],
+    );
+    fixture.commit(
+        "C",
+        &[
+            ("top.txt", "top\nmore\n"),
+            ("src/a.txt", "a\nmore\n"),
+            ("src/b.txt", "b\n"),
+            ("src/sub/c.txt", "c\nmore\n"),
+        ],
+    );
+    fixture.checkout();
+}
+
+fn short(root_path: &std::path::Path, rev: &str) -> String {
+    let repo = git2::Repository::open(root_path).unwrap();
+    let obj = repo.revparse_single(rev).unwrap();
+    obj.short_id().unwrap().as_str().unwrap().to_owned()
+}
+
+#[test]
+fn directory() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("src")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let commits = output
+        .lines()
+        .map(|l| {
+            let fields = l.split_whitespace().collect::<Vec<_>>();
+            (fields[1].to_owned(), fields[fields.len() - 1].to_owned())
+        })
+        .collect::<Vec<_>>();
+    // Commits share a timestamp, so ties are sorted by path
+    assert_eq!(
+        commits,
+        [
+            (short(root_path, "HEAD~1"), "a.txt".to_owned()),
+            (short(root_path, "HEAD~2"), "b.txt".to_owned()),
+        ],
+        "{output}"
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn directory_recursive_json() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--recursive")
+        .arg("--format=json")
+        .arg("src")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"[
+  {
+    "path": "a.txt",
+    "commit": "[..]",
+    "author": "[..]",
+    "date": "[..]",
+    "time": [..],
+    "author_time": [..],
+    "committer_time": [..]
+  },
+  {
+    "path": "b.txt",
+    "commit": "[..]",
+    "author": "[..]",
+    "date": "[..]",
+    "time": [..],
+    "author_time": [..],
+    "committer_time": [..]
+  },
+  {
+    "path": "sub/c.txt",
+    "commit": "[..]",
+    "author": "[..]",
+    "date": "[..]",
+    "time": [..],
+    "author_time": [..],
+    "committer_time": [..]
+  }
+]
+"#,
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
@@ -0,0 +1,83 @@
+use snapbox::prelude::*;
+
+fn fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Initial", &[("main.rs", "fn main() {}\n")]);
+    fixture.checkout();
+}
+
+#[test]
+fn strips_colors() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let tee_path = root_path.join("blame.log");
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--tee")
+        .arg(&tee_path)
+        .arg("main.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stderr_eq("")
+        .get_output()
+        .stdout
+        .clone();
+    assert!(output.contains(&0x1b), "stdout is colored");
+
+    snapbox::assert_data_eq!(
+        std::fs::read_to_string(&tee_path).unwrap(),
+        "\
+HEAD 1 │ fn main() {}
+"
+        .raw()
+    );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn raw_keeps_colors() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+    let tee_path = root_path.join("blame.log");
+
+    let output = snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--color=always")
+        .arg("--tee")
+        .arg(&tee_path)
+        .arg("--tee-raw")
+        .arg("main.rs")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    assert_eq!(std::fs::read(&tee_path).unwrap(), output);
+
+    root.close().unwrap();
+}
+
+#[test]
+fn uncreatable_file() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--tee")
+        .arg(root_path.join("missing").join("blame.log"))
+        .arg("main.rs")
+        .current_dir(root_path)
+        .assert()
+        .code(6)
+        .stdout_eq("")
+        .stderr_eq("could not create tee file [..]: [..]\n");
+
+    root.close().unwrap();
+}
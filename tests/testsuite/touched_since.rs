@@ -0,0 +1,143 @@
+use snapbox::prelude::*;
+
+/// Ada wrote the file in 2020 and Grace changed its middle in 2024
+fn fixture(root_path: &std::path::Path) {
+    let mut fixture = crate::fixture::Repo::init(root_path);
+    for (name, time, content) in [
+        ("Ada", 1_577_836_800, "1\n2\n3\n4\n5\n6\n"),
+        ("Grace", 1_704_067_200, "1\n2\nthree\nfour\n5\n6\n"),
+    ] {
+        let email = format!("{}@example.com", name.to_lowercase());
+        fixture
+            .author(name, &email, time)
+            .commit(name, &[("basic.txt", content)]);
+    }
+    fixture.checkout();
+}
+
+fn dive(root_path: &std::path::Path) -> snapbox::cmd::Command {
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive")).current_dir(root_path)
+}
+
+#[test]
+fn older_lines_collapse() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path)
+        .args([
+            "--style=author,numbers",
+            "--touched-since=2023-01-01",
+            "basic.txt",
+        ])
+        .assert()
+        .success()
+        .stdout_eq(
+            "        … 2 lines from before 2023-01-01 …
+Grace 3 three
+      4 four
+        … 2 lines from before 2023-01-01 …
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    // Run over everything from other commits, and other markers
+    dive(root_path)
+        .args([
+            "--style=numbers",
+            "--squeeze-unchanged=1",
+            "--touched-since=2025-01-01",
+            "basic.txt",
+        ])
+        .assert()
+        .success()
+        .stdout_eq(
+            "  … 6 lines from before 2025-01-01 …\n"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn stat_counts_touched() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path)
+        .args(["--stat", "--touched-since=2023-01-01", "basic.txt"])
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+Authors:
+  Grace 2 100%
+
+Only the 2 of 6 lines from 2023-01-01 or later are counted.
+",
+        )
+        .stderr_eq("");
+
+    dive(root_path)
+        .args([
+            "--stat",
+            "--format=json",
+            "--touched-since=@1600000000",
+            "basic.txt",
+        ])
+        .assert()
+        .success()
+        .stdout_eq(
+            r#"{
+  "path": "basic.txt",
+  "lines": 2,
+  "touched_since": 1600000000,
+  "authors": [
+    {
+      "author": "Grace",
+      "email": "grace@example.com",
+      "lines": 2,
+      "percent": 100
+    }
+  ],
+  "teams": [
+    {
+      "team": "(other)",
+      "lines": 2,
+      "percent": 100
+    }
+  ],
+  "boundaries": [],
+  "boundaries_counted": true
+}
+"#
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}
+
+#[test]
+fn text_only() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    fixture(root_path);
+
+    dive(root_path)
+        .args(["--format=json", "--touched-since=2023-01-01", "basic.txt"])
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+`--touched-since` only supports `--format text`, or `--stat`
+",
+        );
+
+    root.close().unwrap();
+}
@@ -0,0 +1,139 @@
+use snapbox::prelude::*;
+
+#[test]
+fn no_commits() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path).unwrap();
+    std::fs::write(root_path.join("new.txt"), "one\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+repository has no commits yet
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn no_commits_with_contents() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    git2::Repository::init(root_path).unwrap();
+    std::fs::write(root_path.join("new.txt"), "one\n").unwrap();
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--contents=-")
+        .arg("new.txt")
+        .stdin("one\ntwo\n")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+0000000 1 │ one
+⋮       2 │ two
+"
+            .raw(),
+        )
+        .stderr_eq(
+            "\
+WARN: repository has no commits yet; showing every line as not committed
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+fn empty_fixture(root_path: &std::path::Path) {
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Empty", &[]);
+    std::fs::write(root_path.join("new.txt"), "one\n").unwrap();
+}
+
+#[test]
+fn empty_tree() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    empty_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("new.txt")
+        .current_dir(root_path)
+        .assert()
+        .code(2)
+        .stdout_eq("")
+        .stderr_eq(
+            "\
+HEAD has no files
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn empty_tree_with_contents() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    empty_fixture(root_path);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--contents=new.txt")
+        .arg("--format=quickfix")
+        .arg("new.txt")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+new.txt:1: 0000000 Not committed [..] Not committed
+",
+        )
+        .stderr_eq(
+            "\
+WARN: new.txt does not exist at HEAD; showing every line as not committed
+"
+            .raw(),
+        );
+
+    root.close().unwrap();
+}
+
+#[test]
+fn contents_marks_uncommitted_lines() {
+    let root = snapbox::dir::DirRoot::mutable_temp().unwrap();
+    let root_path = root.path().unwrap();
+    let fixture = crate::fixture::Repo::init(root_path);
+    fixture.commit("Add basic", &[("basic.txt", "one\ntwo\n")]);
+
+    snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("git-dive"))
+        .arg("--contents=-")
+        .arg("--style=hash,author,numbers,grid")
+        .arg("basic.txt")
+        .stdin("one\nnew\ntwo\n")
+        .current_dir(root_path)
+        .assert()
+        .success()
+        .stdout_eq(
+            "\
+HEAD    Test User     1 │ one
+0000000 Not committed 2 │ new
+HEAD    Test User     3 │ two
+"
+            .raw(),
+        )
+        .stderr_eq("");
+
+    root.close().unwrap();
+}